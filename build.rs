@@ -0,0 +1,25 @@
+use flate2::{write::GzEncoder, Compression};
+use std::{env, fs, io::Write, path::Path};
+
+#[path = "src/command_tree/model.rs"]
+#[allow(dead_code)]
+mod model;
+
+/// Parses `schemas/command_tree.json` at build time and embeds it as gzipped
+/// bincode, so the binary neither carries nor re-parses the JSON.
+fn main() {
+    let source = "schemas/command_tree.json";
+    println!("cargo:rerun-if-changed={source}");
+    println!("cargo:rerun-if-changed=src/command_tree/model.rs");
+
+    let raw = fs::read_to_string(source).expect("read command_tree.json");
+    let tree: model::CommandTree = serde_json::from_str(&raw).expect("invalid command_tree.json");
+    let encoded = bincode::serialize(&tree).expect("encode command tree");
+
+    let mut gz = GzEncoder::new(Vec::new(), Compression::best());
+    gz.write_all(&encoded).expect("compress command tree");
+    let compressed = gz.finish().expect("compress command tree");
+
+    let out = Path::new(&env::var("OUT_DIR").expect("OUT_DIR")).join("command_tree.bin.gz");
+    fs::write(out, compressed).expect("write command tree");
+}
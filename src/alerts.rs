@@ -0,0 +1,466 @@
+use anyhow::{anyhow, Context, Result};
+use clap::{Arg, ArgAction, Command};
+use serde_json::{json, Value};
+use std::{collections::BTreeMap, fs, io::Write, time::Duration};
+
+use crate::error::ApiError;
+use crate::http::Body;
+use crate::interrupt;
+use crate::output::{self, OutputOptions};
+use crate::query;
+use crate::timeparse;
+use crate::watch::PollFailures;
+use crate::ApiContext;
+use notify::Notifier;
+
+mod lint;
+mod maintenance;
+mod notify;
+mod scaffold;
+
+const ALERTS_PATH: &str = "/api/v1/alerts";
+const RULES_PATH: &str = "/api/v1/rules";
+
+pub fn extend(cmd: Command) -> Command {
+    maintenance::extend(cmd)
+        .subcommand(
+            Command::new("init")
+                .about("Generate a starter alert rule YAML to edit, lint, and create")
+                .arg(
+                    Arg::new("metric")
+                        .long("metric")
+                        .value_name("NAME")
+                        .required_unless_present("signal")
+                        .help("Alert on this metric"),
+                )
+                .arg(
+                    Arg::new("signal")
+                        .long("signal")
+                        .value_name("SIGNAL")
+                        .value_parser(["logs", "traces"])
+                        .conflicts_with("metric")
+                        .help("Alert on error counts from logs or traces instead"),
+                )
+                .arg(
+                    Arg::new("agg")
+                        .long("agg")
+                        .value_name("AGG")
+                        .value_parser([
+                            "sum", "avg", "min", "max", "count", "rate", "increase", "p50", "p75",
+                            "p90", "p95", "p99",
+                        ])
+                        .requires("metric")
+                        .help("Metric aggregation (default p99 for duration/latency metrics, else avg)"),
+                )
+                .arg(
+                    Arg::new("service")
+                        .long("service")
+                        .value_name("NAME")
+                        .help("Only this service (service.name)"),
+                )
+                .arg(
+                    Arg::new("name")
+                        .long("name")
+                        .value_name("NAME")
+                        .help("Rule name (default derived from the metric and service)"),
+                )
+                .arg(
+                    Arg::new("out")
+                        .long("out")
+                        .value_name("FILE")
+                        .help("Write to FILE instead of stdout (refuses to overwrite)"),
+                ),
+        )
+        .subcommand(
+            Command::new("lint")
+                .about("Validate an alert rule file (YAML or JSON) without sending it")
+                .arg(Arg::new("file").required(true).help("Rule file")),
+        )
+        .subcommand(
+            Command::new("create")
+                .about("Validate an alert rule file and create the rule")
+                .arg(
+                    Arg::new("from-file")
+                        .long("from-file")
+                        .value_name("FILE")
+                        .required(true)
+                        .help("Rule file (YAML or JSON)"),
+                )
+                .arg(
+                    Arg::new("no-lint")
+                        .long("no-lint")
+                        .action(ArgAction::SetTrue)
+                        .help("Send the rule even if local validation finds errors"),
+                ),
+        )
+        .subcommand(
+            Command::new("firing")
+                .about("List triggered alerts filtered by severity, state, and age")
+                .arg(
+                    Arg::new("severity")
+                        .long("severity")
+                        .value_name("LIST")
+                        .help("Comma-separated severities, e.g. critical,warning"),
+                )
+                .arg(
+                    Arg::new("state")
+                        .long("state")
+                        .value_name("STATE")
+                        .value_parser(["firing", "resolved", "all"])
+                        .default_value("firing")
+                        .help("Alert state to keep"),
+                )
+                .arg(
+                    Arg::new("since")
+                        .long("since")
+                        .value_name("DURATION|TIME")
+                        .help("Only alerts that started after this (e.g. 2h, now-30m)"),
+                )
+                .arg(
+                    Arg::new("count-only")
+                        .long("count-only")
+                        .action(ArgAction::SetTrue)
+                        .help("Print only the number of matching alerts"),
+                ),
+        )
+        .subcommand(
+            Command::new("watch")
+                .about("Poll alerts and report state transitions")
+                .arg(
+                    Arg::new("interval")
+                        .long("interval")
+                        .value_name("SECS")
+                        .default_value("30")
+                        .help("Polling interval"),
+                )
+                .arg(
+                    Arg::new("notify-webhook")
+                        .long("notify-webhook")
+                        .value_name("URL")
+                        .help("POST a Slack-compatible message per transition"),
+                )
+                .arg(
+                    Arg::new("notify")
+                        .long("notify")
+                        .value_name("desktop|command:CMD")
+                        .action(ArgAction::Append)
+                        .help("Also show a desktop notification, or run CMD with the event JSON on stdin and SIGNOZ_ALERT_* set, per transition (repeatable)"),
+                )
+                .arg(
+                    Arg::new("notify-initial")
+                        .long("notify-initial")
+                        .action(ArgAction::SetTrue)
+                        .help("Also report alerts already firing at startup"),
+                ),
+        )
+}
+
+pub fn handle(
+    op: &str,
+    matches: &clap::ArgMatches,
+    ctx: &ApiContext,
+    opts: &OutputOptions,
+) -> Option<Result<()>> {
+    match op {
+        "init" => Some(handle_init(matches)),
+        "lint" => Some(handle_lint(matches, opts)),
+        "create" => Some(handle_create(matches, ctx, opts)),
+        "firing" => Some(handle_firing(matches, ctx, opts)),
+        "watch" => Some(handle_watch(matches, ctx, opts)),
+        "silence" => Some(maintenance::handle_silence(matches, ctx, opts)),
+        "maintenance" => Some(maintenance::handle_maintenance(matches, ctx, opts)),
+        _ => None,
+    }
+}
+
+fn handle_init(matches: &clap::ArgMatches) -> Result<()> {
+    let arg = |name: &str| matches.get_one::<String>(name).map(String::as_str);
+    let target = match (arg("metric"), arg("signal")) {
+        (Some(metric), _) => scaffold::Target::Metric {
+            name: metric,
+            agg: arg("agg").unwrap_or_else(|| scaffold::default_agg(metric)),
+        },
+        (None, Some(signal)) => scaffold::Target::Errors { signal },
+        (None, None) => return Err(anyhow!("pass --metric NAME or --signal logs|traces")),
+    };
+    let rule = scaffold::render(&target, arg("service"), arg("name"))?;
+    match arg("out") {
+        Some(path) => {
+            let mut file = fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(path)
+                .with_context(|| format!("create {path}"))?;
+            file.write_all(rule.as_bytes())?;
+            eprintln!("wrote {path}; edit the placeholders, then: signoz alerts lint {path}");
+        }
+        None => print!("{rule}"),
+    }
+    Ok(())
+}
+
+fn handle_lint(matches: &clap::ArgMatches, opts: &OutputOptions) -> Result<()> {
+    let file = matches
+        .get_one::<String>("file")
+        .ok_or_else(|| anyhow!("missing rule file"))?;
+    let report = lint::lint(&lint::read_rule(file)?);
+    let errors = report.errors();
+    output::print_value(
+        &json!({ "file": file, "valid": errors == 0, "issues": report.issues }),
+        opts,
+    )?;
+    if errors > 0 {
+        return Err(anyhow!("{file}: {errors} error(s)"));
+    }
+    Ok(())
+}
+
+/// Lints first so a malformed rule fails with a pointer to the field rather
+/// than the API's generic error; warnings are printed and do not block.
+fn handle_create(matches: &clap::ArgMatches, ctx: &ApiContext, opts: &OutputOptions) -> Result<()> {
+    let file = matches
+        .get_one::<String>("from-file")
+        .ok_or_else(|| anyhow!("missing --from-file"))?;
+    let rule = lint::read_rule(file)?;
+    let report = lint::lint(&rule);
+    for issue in &report.issues {
+        let field = |key: &str| issue[key].as_str().unwrap_or_default();
+        eprintln!(
+            "{}: {}: {}",
+            field("level"),
+            field("path"),
+            field("message")
+        );
+    }
+    let errors = report.errors();
+    if errors > 0 && !matches.get_flag("no-lint") {
+        return Err(anyhow!(
+            "{file}: {errors} error(s); fix them or pass --no-lint"
+        ));
+    }
+
+    let response = ctx.execute(
+        "POST",
+        RULES_PATH,
+        &[],
+        Some(Body::Json(rule)),
+        Some("application/json"),
+    )?;
+    output::print_response(&response, opts)?;
+    if response.status >= 400 {
+        return Err(ApiError::from_response(&response)).context("create rule failed");
+    }
+    Ok(())
+}
+
+/// Severity becomes an Alertmanager label matcher (`filter=severity=~"a|b"`);
+/// state and `--since` are applied to the returned alerts, since the API has
+/// no parameters for them.
+fn handle_firing(matches: &clap::ArgMatches, ctx: &ApiContext, opts: &OutputOptions) -> Result<()> {
+    let mut query = Vec::new();
+    if let Some(severity) = matches.get_one::<String>("severity") {
+        let levels: Vec<&str> = severity
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .collect();
+        query.push((
+            "filter".to_string(),
+            format!("severity=~\"{}\"", levels.join("|")),
+        ));
+    }
+    let state = matches
+        .get_one::<String>("state")
+        .map_or("firing", String::as_str);
+    if state == "firing" {
+        query.push(("active".to_string(), "true".to_string()));
+    }
+    let since = matches
+        .get_one::<String>("since")
+        .map(|raw| timeparse::range(raw, None).map(|(start, _)| start))
+        .transpose()?;
+
+    let response = ctx.execute("GET", ALERTS_PATH, &query, None, None)?;
+    if response.status >= 400 {
+        return Err(ApiError::from_response(&response)).context("list alerts failed");
+    }
+    let now = query::now_millis();
+    let time = |alert: &Value, key: &str| {
+        alert
+            .get(key)
+            .and_then(Value::as_str)
+            .and_then(|t| timeparse::parse_time_ms(t).ok())
+    };
+    let alerts: Vec<Value> = response
+        .body
+        .get("data")
+        .unwrap_or(&response.body)
+        .as_array()
+        .cloned()
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|alert| {
+            let resolved = matches!(alert_state(alert).1.state.as_str(), "resolved" | "inactive")
+                || time(alert, "endsAt").is_some_and(|end| end > 0 && end <= now);
+            let state_ok = match state {
+                "firing" => !resolved,
+                "resolved" => resolved,
+                _ => true,
+            };
+            let recent = match since {
+                Some(cutoff) => time(alert, "startsAt").is_some_and(|start| start >= cutoff),
+                None => true,
+            };
+            state_ok && recent
+        })
+        .collect();
+
+    if matches.get_flag("count-only") {
+        println!("{}", alerts.len());
+        return Ok(());
+    }
+    output::print_value(&Value::Array(alerts), opts)
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct AlertState {
+    name: String,
+    state: String,
+    severity: String,
+    summary: String,
+}
+
+fn handle_watch(matches: &clap::ArgMatches, ctx: &ApiContext, opts: &OutputOptions) -> Result<()> {
+    let interval: u64 = matches
+        .get_one::<String>("interval")
+        .map(|v| v.parse())
+        .transpose()
+        .map_err(|_| anyhow!("invalid --interval"))?
+        .unwrap_or(30);
+    let mut notifiers = matches
+        .get_many::<String>("notify")
+        .into_iter()
+        .flatten()
+        .map(|spec| Notifier::parse(spec))
+        .collect::<Result<Vec<_>>>()?;
+    if let Some(url) = matches.get_one::<String>("notify-webhook") {
+        notifiers.push(Notifier::webhook(url, ctx)?);
+    }
+    let mut previous: Option<BTreeMap<String, AlertState>> = None;
+    if matches.get_flag("notify-initial") {
+        previous = Some(BTreeMap::new());
+    }
+
+    interrupt::graceful();
+    let interval = Duration::from_secs(interval.max(1));
+    let mut failures = PollFailures::new(interval);
+    loop {
+        let current = match fetch_alerts(ctx) {
+            Ok(current) => current,
+            Err(err) => {
+                // The last good state stays, so nothing is reported twice.
+                if !interrupt::sleep(failures.failed(err)?) {
+                    return Err(interrupt::Interrupted { checkpoint: None }.into());
+                }
+                continue;
+            }
+        };
+        failures.succeeded();
+        if let Some(prev) = &previous {
+            for event in transitions(prev, &current) {
+                output::print_value(&event, opts)?;
+                for notifier in &notifiers {
+                    notifier.send(&event);
+                }
+            }
+        }
+        previous = Some(current);
+        if !interrupt::sleep(interval) {
+            return Err(interrupt::Interrupted { checkpoint: None }.into());
+        }
+    }
+}
+
+fn fetch_alerts(ctx: &ApiContext) -> Result<BTreeMap<String, AlertState>> {
+    let response = ctx.execute("GET", ALERTS_PATH, &[], None, None)?;
+    if response.status >= 400 {
+        return Err(ApiError::from_response(&response)).context("list alerts failed");
+    }
+    let items = response
+        .body
+        .get("data")
+        .unwrap_or(&response.body)
+        .as_array()
+        .cloned()
+        .unwrap_or_default();
+    Ok(items.iter().map(alert_state).collect())
+}
+
+/// Keys an alert by fingerprint (falling back to its labels) and extracts
+/// the fields used in notifications.
+fn alert_state(alert: &Value) -> (String, AlertState) {
+    let labels = alert.get("labels").cloned().unwrap_or(Value::Null);
+    let label = |name: &str| {
+        labels
+            .get(name)
+            .and_then(Value::as_str)
+            .unwrap_or("")
+            .to_string()
+    };
+    let key = alert
+        .get("fingerprint")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .unwrap_or_else(|| labels.to_string());
+    let state = alert
+        .pointer("/status/state")
+        .or_else(|| alert.get("state"))
+        .and_then(Value::as_str)
+        .unwrap_or("firing")
+        .to_string();
+    let summary = alert
+        .pointer("/annotations/summary")
+        .or_else(|| alert.pointer("/annotations/description"))
+        .and_then(Value::as_str)
+        .unwrap_or("")
+        .to_string();
+    (
+        key,
+        AlertState {
+            name: label("alertname"),
+            state,
+            severity: label("severity"),
+            summary,
+        },
+    )
+}
+
+fn transitions(
+    prev: &BTreeMap<String, AlertState>,
+    current: &BTreeMap<String, AlertState>,
+) -> Vec<Value> {
+    let mut out = Vec::new();
+    for (key, alert) in current {
+        let from = prev.get(key).map(|p| p.state.as_str());
+        if from != Some(alert.state.as_str()) {
+            out.push(event(key, alert, from.unwrap_or("inactive"), &alert.state));
+        }
+    }
+    for (key, alert) in prev {
+        if !current.contains_key(key) {
+            out.push(event(key, alert, &alert.state, "resolved"));
+        }
+    }
+    out
+}
+
+fn event(key: &str, alert: &AlertState, from: &str, to: &str) -> Value {
+    json!({
+        "fingerprint": key,
+        "alert": alert.name,
+        "severity": alert.severity,
+        "from": from,
+        "to": to,
+        "summary": alert.summary,
+    })
+}
@@ -0,0 +1,315 @@
+use anyhow::{Context, Result};
+use serde_json::{json, Value};
+use std::fs;
+
+const ALERT_TYPES: &[&str] = &[
+    "METRIC_BASED_ALERT",
+    "LOGS_BASED_ALERT",
+    "TRACES_BASED_ALERT",
+    "EXCEPTIONS_BASED_ALERT",
+];
+const RULE_TYPES: &[&str] = &["threshold_rule", "promql_rule", "anomaly_rule"];
+const QUERY_TYPES: &[&str] = &["builder", "promql", "clickhouse_sql"];
+const COMPARE_OPS: &[&str] = &["1", "2", "3", "4", "above", "below", "equal", "not_equal"];
+const MATCH_TYPES: &[&str] = &[
+    "1",
+    "2",
+    "3",
+    "4",
+    "5",
+    "at_least_once",
+    "all_the_times",
+    "on_average",
+    "in_total",
+    "last",
+];
+const SEVERITIES: &[&str] = &["info", "warning", "error", "critical"];
+
+/// Issues found in one rule: `{level, path, message}` with `level` either
+/// `error` (the API would reject or misread the rule) or `warning`.
+#[derive(Default)]
+pub struct Report {
+    pub issues: Vec<Value>,
+}
+
+impl Report {
+    fn error(&mut self, path: &str, message: impl Into<String>) {
+        self.push("error", path, message.into());
+    }
+
+    fn warning(&mut self, path: &str, message: impl Into<String>) {
+        self.push("warning", path, message.into());
+    }
+
+    fn push(&mut self, level: &str, path: &str, message: String) {
+        self.issues
+            .push(json!({ "level": level, "path": path, "message": message }));
+    }
+
+    pub fn errors(&self) -> usize {
+        self.issues
+            .iter()
+            .filter(|issue| issue["level"] == "error")
+            .count()
+    }
+}
+
+/// Reads a YAML or JSON rule file (YAML is a superset, so one parser does).
+pub fn read_rule(path: &str) -> Result<Value> {
+    let raw = fs::read_to_string(path).with_context(|| format!("read {path}"))?;
+    serde_yaml::from_str(&raw).with_context(|| format!("parse {path}"))
+}
+
+/// Checks the fields `POST /api/v1/rules` needs, their enum values and
+/// types, and that the condition's queries are usable.
+pub fn lint(rule: &Value) -> Report {
+    let mut report = Report::default();
+    if !rule.is_object() {
+        report.error("", "rule must be an object");
+        return report;
+    }
+    let text = |key: &str| rule.get(key).and_then(Value::as_str);
+
+    match text("alert") {
+        Some(name) if !name.trim().is_empty() => {}
+        _ => report.error("/alert", "rule name is required"),
+    }
+    one_of(&mut report, rule, "", "alertType", ALERT_TYPES);
+    one_of(&mut report, rule, "", "ruleType", RULE_TYPES);
+
+    let windows: Vec<Option<u64>> = ["evalWindow", "frequency"]
+        .iter()
+        .map(|key| match rule.get(*key) {
+            None => None,
+            Some(Value::String(raw)) => {
+                let parsed = parse_go_duration(raw);
+                if parsed.is_none() {
+                    report.error(
+                        &format!("/{key}"),
+                        format!("`{raw}` is not a duration like 5m0s or 1h"),
+                    );
+                }
+                parsed
+            }
+            Some(_) => {
+                report.error(&format!("/{key}"), "must be a duration string like 5m0s");
+                None
+            }
+        })
+        .collect();
+    if let [Some(window), Some(frequency)] = windows[..] {
+        if frequency > window {
+            report.warning(
+                "/frequency",
+                "evaluated less often than evalWindow; some data is never checked",
+            );
+        }
+    }
+
+    match rule.pointer("/labels/severity").and_then(Value::as_str) {
+        None => report.warning(
+            "/labels/severity",
+            "no severity label; notifications cannot be routed by severity",
+        ),
+        Some(severity) if !SEVERITIES.contains(&severity) => report.warning(
+            "/labels/severity",
+            format!(
+                "unusual severity `{severity}` (expected {})",
+                SEVERITIES.join(", ")
+            ),
+        ),
+        Some(_) => {}
+    }
+    if let Some(channels) = rule.get("preferredChannels") {
+        if !channels
+            .as_array()
+            .is_some_and(|items| items.iter().all(Value::is_string))
+        {
+            report.error("/preferredChannels", "must be a list of channel names");
+        }
+    }
+
+    match rule.get("condition") {
+        Some(condition) if condition.is_object() => lint_condition(&mut report, rule, condition),
+        _ => report.error("/condition", "condition object is required"),
+    }
+    report
+}
+
+fn lint_condition(report: &mut Report, rule: &Value, condition: &Value) {
+    match condition.get("target") {
+        Some(Value::Number(_)) => {}
+        Some(Value::String(raw)) if raw.trim().parse::<f64>().is_ok() => report.error(
+            "/condition/target",
+            format!("threshold must be a number, not the string \"{raw}\""),
+        ),
+        Some(_) => report.error("/condition/target", "threshold must be a number"),
+        None if rule.get("ruleType").and_then(Value::as_str) == Some("anomaly_rule") => {}
+        None => report.error("/condition/target", "threshold is required"),
+    }
+    one_of(report, condition, "/condition", "op", COMPARE_OPS);
+    one_of(report, condition, "/condition", "matchType", MATCH_TYPES);
+
+    let Some(composite) = condition.get("compositeQuery").filter(|c| c.is_object()) else {
+        report.error(
+            "/condition/compositeQuery",
+            "compositeQuery object is required",
+        );
+        return;
+    };
+    let prefix = "/condition/compositeQuery";
+    let query_type = composite.get("queryType").and_then(Value::as_str);
+    one_of(report, composite, prefix, "queryType", QUERY_TYPES);
+    let rule_type = rule.get("ruleType").and_then(Value::as_str);
+    if (rule_type == Some("promql_rule")) != (query_type == Some("promql")) && query_type.is_some()
+    {
+        report.warning(
+            &format!("{prefix}/queryType"),
+            format!(
+                "ruleType {} with queryType {}; promql rules use promql queries",
+                rule_type.unwrap_or("?"),
+                query_type.unwrap_or("?")
+            ),
+        );
+    }
+
+    // v5 rules list queries in `queries`; older ones use per-type maps.
+    // Entries are (path segment, query name, query).
+    let (key, queries): (&str, Vec<(String, String, &Value)>) = match composite.get("queries") {
+        Some(Value::Array(items)) => (
+            "queries",
+            items
+                .iter()
+                .enumerate()
+                .map(|(i, q)| {
+                    let name = q.pointer("/spec/name").and_then(Value::as_str);
+                    (i.to_string(), name.unwrap_or_default().to_string(), q)
+                })
+                .collect(),
+        ),
+        _ => {
+            let key = match query_type {
+                Some("promql") => "promQueries",
+                Some("clickhouse_sql") => "chQueries",
+                _ => "builderQueries",
+            };
+            let map = composite.get(key).and_then(Value::as_object);
+            (
+                key,
+                map.into_iter()
+                    .flatten()
+                    .map(|(name, q)| (name.clone(), name.clone(), q))
+                    .collect(),
+            )
+        }
+    };
+    if queries.is_empty() {
+        report.error(&format!("{prefix}/{key}"), "no queries defined");
+        return;
+    }
+
+    let signal = match rule.get("alertType").and_then(Value::as_str) {
+        Some("METRIC_BASED_ALERT") => Some("metrics"),
+        Some("LOGS_BASED_ALERT") => Some("logs"),
+        Some("TRACES_BASED_ALERT" | "EXCEPTIONS_BASED_ALERT") => Some("traces"),
+        _ => None,
+    };
+    for (segment, _, query) in &queries {
+        let path = format!("{prefix}/{key}/{segment}");
+        let spec = query.get("spec").unwrap_or(query);
+        match query_type {
+            Some("promql") | Some("clickhouse_sql") => {
+                if spec
+                    .get("query")
+                    .and_then(Value::as_str)
+                    .is_none_or(|q| q.trim().is_empty())
+                {
+                    report.error(&format!("{path}/query"), "query text is empty");
+                }
+            }
+            _ => {
+                let source = spec
+                    .get("signal")
+                    .or_else(|| spec.get("dataSource"))
+                    .and_then(Value::as_str);
+                if let (Some(source), Some(signal)) = (source, signal) {
+                    if source != signal {
+                        report.warning(
+                            &path,
+                            format!("queries {source} but alertType expects {signal}"),
+                        );
+                    }
+                }
+                let has_aggregation = spec
+                    .get("aggregations")
+                    .and_then(Value::as_array)
+                    .is_some_and(|a| !a.is_empty())
+                    || spec.get("aggregateOperator").is_some();
+                if !has_aggregation {
+                    report.error(&path, "builder query has no aggregation to compare");
+                }
+            }
+        }
+    }
+
+    if let Some(selected) = condition.get("selectedQueryName").and_then(Value::as_str) {
+        if !queries.iter().any(|(_, name, _)| name == selected) {
+            report.error(
+                "/condition/selectedQueryName",
+                format!("no query named {selected}"),
+            );
+        }
+    }
+}
+
+/// Requires `value[key]` to be one of `allowed`; `prefix` is `value`'s path
+/// in the rule. Numbers are accepted for enums the API stores as numeric
+/// strings.
+fn one_of(report: &mut Report, value: &Value, prefix: &str, key: &str, allowed: &[&str]) {
+    let path = format!("{prefix}/{key}");
+    let raw = match value.get(key) {
+        None => {
+            report.error(&path, format!("required (one of {})", allowed.join(", ")));
+            return;
+        }
+        Some(Value::String(s)) => s.clone(),
+        Some(Value::Number(n)) => n.to_string(),
+        Some(other) => other.to_string(),
+    };
+    if !allowed.contains(&raw.as_str()) {
+        report.error(
+            &path,
+            format!("`{raw}` is not one of {}", allowed.join(", ")),
+        );
+    }
+}
+
+/// Go `time.Duration` strings (`5m0s`, `1h30m`, `90s`) as seconds.
+fn parse_go_duration(raw: &str) -> Option<u64> {
+    let mut total = 0.0;
+    let mut rest = raw.trim();
+    if rest.is_empty() {
+        return None;
+    }
+    while !rest.is_empty() {
+        let split = rest.find(|c: char| !c.is_ascii_digit() && c != '.')?;
+        let (num, tail) = rest.split_at(split);
+        let value: f64 = num.parse().ok()?;
+        let unit_len = tail
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(tail.len());
+        let (unit, tail) = tail.split_at(unit_len);
+        total += value
+            * match unit {
+                "ns" => 1e-9,
+                "us" | "µs" => 1e-6,
+                "ms" => 1e-3,
+                "s" => 1.0,
+                "m" => 60.0,
+                "h" => 3600.0,
+                _ => return None,
+            };
+        rest = tail;
+    }
+    Some(total as u64)
+}
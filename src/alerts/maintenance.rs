@@ -0,0 +1,254 @@
+use anyhow::{anyhow, Context, Result};
+use clap::{Arg, ArgAction, Command};
+use serde_json::{json, Value};
+
+use crate::error::ApiError;
+use crate::http::Body;
+use crate::ids;
+use crate::output::{self, OutputOptions};
+use crate::query;
+use crate::timeparse;
+use crate::ApiContext;
+
+/// Planned maintenance (downtime) schedules; alerts for the listed rule ids,
+/// or every rule when the list is empty, are muted inside the window.
+const DOWNTIME_PATH: &str = "/api/v1/downtime_schedules";
+const RULES_PATH: &str = "/api/v1/rules";
+
+fn rule_arg() -> Arg {
+    Arg::new("rule")
+        .long("rule")
+        .value_name("ID")
+        .action(ArgAction::Append)
+        .help("Alert rule id to mute (repeatable)")
+}
+
+fn all_rules_arg() -> Arg {
+    Arg::new("all-rules")
+        .long("all-rules")
+        .action(ArgAction::SetTrue)
+        .conflicts_with("rule")
+        .help("Mute every alert rule")
+}
+
+pub fn extend(cmd: Command) -> Command {
+    cmd.subcommand(
+        Command::new("silence")
+            .about("Mute alert rules from now for a while (a planned maintenance window)")
+            .arg(rule_arg())
+            .arg(all_rules_arg())
+            .arg(
+                Arg::new("for")
+                    .long("for")
+                    .value_name("DURATION")
+                    .required(true)
+                    .help("How long to mute, e.g. 30m or 2h"),
+            )
+            .arg(
+                Arg::new("reason")
+                    .long("reason")
+                    .value_name("TEXT")
+                    .help("Why the alerts are muted (stored as the description)"),
+            ),
+    )
+    .subcommand(
+        Command::new("maintenance")
+            .about("Planned maintenance windows (downtime schedules)")
+            .subcommand_required(true)
+            .arg_required_else_help(true)
+            .subcommand(
+                Command::new("list")
+                    .about("List maintenance windows and silences")
+                    .arg(
+                        Arg::new("active")
+                            .long("active")
+                            .action(ArgAction::SetTrue)
+                            .help("Only windows in effect now"),
+                    ),
+            )
+            .subcommand(
+                Command::new("create")
+                    .about("Schedule a maintenance window")
+                    .arg(
+                        Arg::new("name")
+                            .long("name")
+                            .value_name("NAME")
+                            .required(true)
+                            .help("Window name"),
+                    )
+                    .arg(
+                        Arg::new("start")
+                            .long("start")
+                            .value_name("TIME")
+                            .required(true)
+                            .help("Start: RFC 3339, now+1h, or epoch"),
+                    )
+                    .arg(
+                        Arg::new("end")
+                            .long("end")
+                            .value_name("TIME")
+                            .required(true)
+                            .help("End: RFC 3339, now+3h, or epoch"),
+                    )
+                    .arg(rule_arg())
+                    .arg(all_rules_arg())
+                    .arg(
+                        Arg::new("reason")
+                            .long("reason")
+                            .value_name("TEXT")
+                            .help("Description"),
+                    ),
+            )
+            .subcommand(
+                Command::new("delete")
+                    .about("Delete a maintenance window or lift a silence")
+                    .arg(Arg::new("id").required(true).help("Window id")),
+            ),
+    )
+}
+
+pub fn handle_silence(
+    matches: &clap::ArgMatches,
+    ctx: &ApiContext,
+    opts: &OutputOptions,
+) -> Result<()> {
+    let duration = matches
+        .get_one::<String>("for")
+        .ok_or_else(|| anyhow!("--for is required"))?;
+    let start = query::now_millis();
+    let end = start + timeparse::parse_duration_ms(duration)?;
+    let rules = rule_ids(matches, ctx)?;
+    let name = match rules.as_slice() {
+        [] => format!("silence all rules for {duration}"),
+        ids => format!("silence {} for {duration}", ids.join(", ")),
+    };
+    create(ctx, opts, &name, matches, rules, start, end)
+}
+
+pub fn handle_maintenance(
+    matches: &clap::ArgMatches,
+    ctx: &ApiContext,
+    opts: &OutputOptions,
+) -> Result<()> {
+    match matches.subcommand() {
+        Some(("list", m)) => list(m, ctx, opts),
+        Some(("create", m)) => {
+            let time = |name: &str| {
+                let raw = m
+                    .get_one::<String>(name)
+                    .ok_or_else(|| anyhow!("--{name} is required"))?;
+                timeparse::parse_time_ms(raw).map_err(|err| anyhow!("invalid --{name}: {err}"))
+            };
+            let (start, end) = (time("start")?, time("end")?);
+            if start >= end {
+                return Err(anyhow!("--start must be before --end"));
+            }
+            let name = m.get_one::<String>("name").cloned().unwrap_or_default();
+            create(ctx, opts, &name, m, rule_ids(m, ctx)?, start, end)
+        }
+        Some(("delete", m)) => {
+            let id = m
+                .get_one::<String>("id")
+                .ok_or_else(|| anyhow!("missing id"))?;
+            let id = ids::expand(&ctx.base_url, DOWNTIME_PATH, id)?;
+            let path = format!("{DOWNTIME_PATH}/{}", urlencoding::encode(&id));
+            let response = ctx.execute("DELETE", &path, &[], None, None)?;
+            output::print_response(&response, opts)?;
+            if response.status >= 400 {
+                return Err(ApiError::from_response(&response))
+                    .context("delete maintenance window failed");
+            }
+            Ok(())
+        }
+        _ => Err(anyhow!("unknown maintenance command")),
+    }
+}
+
+/// `--rule` ids (prefixes expanded), or an empty list for `--all-rules`,
+/// which the API reads as "every rule". One of the two is required so a
+/// typo cannot mute everything.
+fn rule_ids(matches: &clap::ArgMatches, ctx: &ApiContext) -> Result<Vec<String>> {
+    if matches.get_flag("all-rules") {
+        return Ok(Vec::new());
+    }
+    let rules: Vec<String> = matches
+        .get_many::<String>("rule")
+        .ok_or_else(|| anyhow!("pass --rule ID (repeatable) or --all-rules"))?
+        .map(|id| ids::expand(&ctx.base_url, RULES_PATH, id))
+        .collect::<Result<_>>()?;
+    Ok(rules)
+}
+
+fn create(
+    ctx: &ApiContext,
+    opts: &OutputOptions,
+    name: &str,
+    matches: &clap::ArgMatches,
+    rules: Vec<String>,
+    start: u64,
+    end: u64,
+) -> Result<()> {
+    let body = json!({
+        "name": name,
+        "description": matches.get_one::<String>("reason").cloned().unwrap_or_default(),
+        "alertIds": rules,
+        "schedule": {
+            "timezone": "UTC",
+            "startTime": timeparse::format_rfc3339(start),
+            "endTime": timeparse::format_rfc3339(end),
+        },
+    });
+    let response = ctx.execute(
+        "POST",
+        DOWNTIME_PATH,
+        &[],
+        Some(Body::Json(body)),
+        Some("application/json"),
+    )?;
+    output::print_response(&response, opts)?;
+    if response.status >= 400 {
+        return Err(ApiError::from_response(&response)).context("create maintenance window failed");
+    }
+    Ok(())
+}
+
+fn list(matches: &clap::ArgMatches, ctx: &ApiContext, opts: &OutputOptions) -> Result<()> {
+    let response = ctx.execute("GET", DOWNTIME_PATH, &[], None, None)?;
+    if response.status >= 400 {
+        return Err(ApiError::from_response(&response)).context("list maintenance windows failed");
+    }
+    ids::remember(&ctx.base_url, DOWNTIME_PATH, &response.body);
+    let now = query::now_millis();
+    let windows: Vec<Value> = response
+        .body
+        .get("data")
+        .unwrap_or(&response.body)
+        .as_array()
+        .cloned()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|mut window| {
+            let time = |key: &str| {
+                window
+                    .pointer(&format!("/schedule/{key}"))
+                    .and_then(Value::as_str)
+                    .and_then(|t| timeparse::parse_time_ms(t).ok())
+            };
+            // Recurring windows are reported but not expanded.
+            let recurring = window
+                .pointer("/schedule/recurrence")
+                .is_some_and(|r| !r.is_null());
+            let active = !recurring
+                && time("startTime").is_some_and(|s| s <= now)
+                && time("endTime").is_none_or(|e| now < e);
+            if let Value::Object(map) = &mut window {
+                map.insert("active".into(), json!(active));
+            }
+            window
+        })
+        .filter(|window| {
+            !matches.get_flag("active") || window.get("active") == Some(&Value::Bool(true))
+        })
+        .collect();
+    output::print_value(&Value::Array(windows), opts)
+}
@@ -0,0 +1,158 @@
+use anyhow::{anyhow, Context, Result};
+use serde_json::{json, Value};
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+};
+
+use crate::http::{Body, HttpClient};
+use crate::ApiContext;
+
+/// Where `alerts watch` reports a transition besides stdout.
+pub enum Notifier {
+    /// Slack-compatible incoming webhook.
+    Webhook(String, Box<HttpClient>),
+    /// `osascript` on macOS, `notify-send` elsewhere.
+    Desktop,
+    /// A shell command given the event as JSON on stdin.
+    Command(String),
+}
+
+impl Notifier {
+    /// Parses `desktop` or `command:CMD`.
+    pub fn parse(spec: &str) -> Result<Self> {
+        match spec.split_once(':') {
+            _ if spec == "desktop" => Ok(Notifier::Desktop),
+            Some(("command", command)) if !command.trim().is_empty() => {
+                Ok(Notifier::Command(command.to_string()))
+            }
+            _ => Err(anyhow!(
+                "invalid --notify {spec:?} (use desktop or command:CMD)"
+            )),
+        }
+    }
+
+    pub fn webhook(url: &str, ctx: &ApiContext) -> Result<Self> {
+        let client = HttpClient::new(url.to_string(), None, None, Vec::new(), &ctx.client_opts)?;
+        Ok(Notifier::Webhook(url.to_string(), Box::new(client)))
+    }
+
+    /// Sends one transition; failures are warnings so the watch keeps going.
+    pub fn send(&self, event: &Value) {
+        let result = match self {
+            Notifier::Webhook(url, client) => webhook(client, url, event),
+            Notifier::Desktop => desktop(event),
+            Notifier::Command(command) => run_command(command, event),
+        };
+        if let Err(err) = result {
+            eprintln!("warning: {err:#}");
+        }
+    }
+}
+
+fn field<'a>(event: &'a Value, name: &str) -> &'a str {
+    event.get(name).and_then(Value::as_str).unwrap_or("")
+}
+
+/// `firing → resolved (critical)`, after the alert name.
+fn change(event: &Value) -> String {
+    let mut text = format!("{} → {}", field(event, "from"), field(event, "to"));
+    if !field(event, "severity").is_empty() {
+        text.push_str(&format!(" ({})", field(event, "severity")));
+    }
+    text
+}
+
+fn webhook(client: &HttpClient, url: &str, event: &Value) -> Result<()> {
+    let icon = if field(event, "to") == "resolved" {
+        ":white_check_mark:"
+    } else {
+        ":rotating_light:"
+    };
+    let mut text = format!("{icon} *{}* {}", field(event, "alert"), change(event));
+    if !field(event, "summary").is_empty() {
+        text.push_str(&format!("\n{}", field(event, "summary")));
+    }
+    let response = client
+        .execute(
+            "POST",
+            url,
+            &[],
+            Some(Body::Json(json!({ "text": text }))),
+            Some("application/json"),
+        )
+        .context("webhook failed")?;
+    if response.status >= 400 {
+        return Err(anyhow!("webhook returned http {}", response.status));
+    }
+    Ok(())
+}
+
+fn desktop(event: &Value) -> Result<()> {
+    let title = format!("{} {}", field(event, "alert"), change(event));
+    let body = field(event, "summary");
+    let status = if cfg!(target_os = "macos") {
+        let quote = |s: &str| format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""));
+        Command::new("osascript")
+            .arg("-e")
+            .arg(format!(
+                "display notification {} with title {}",
+                quote(body),
+                quote(&title)
+            ))
+            .status()
+    } else {
+        Command::new("notify-send")
+            // `--` so an alert named like an option is shown, not parsed.
+            .args(["--app-name", "signoz", "--", &title, body])
+            .status()
+    };
+    match status {
+        Ok(status) if status.success() => Ok(()),
+        Ok(status) => Err(anyhow!("desktop notification failed: {status}")),
+        Err(err) => Err(anyhow!(
+            "desktop notification failed: {err} (is {} installed?)",
+            if cfg!(target_os = "macos") {
+                "osascript"
+            } else {
+                "notify-send"
+            }
+        )),
+    }
+}
+
+/// Runs the command with the event on stdin and its fields in
+/// `SIGNOZ_ALERT_*` variables, for one-liners that skip parsing JSON.
+fn run_command(command: &str, event: &Value) -> Result<()> {
+    let (shell, flag) = if cfg!(windows) {
+        ("cmd", "/C")
+    } else {
+        ("sh", "-c")
+    };
+    let mut child = Command::new(shell)
+        .args([flag, command])
+        .env("SIGNOZ_ALERT_NAME", field(event, "alert"))
+        .env("SIGNOZ_ALERT_FROM", field(event, "from"))
+        .env("SIGNOZ_ALERT_TO", field(event, "to"))
+        .env("SIGNOZ_ALERT_SEVERITY", field(event, "severity"))
+        .env("SIGNOZ_ALERT_SUMMARY", field(event, "summary"))
+        .env("SIGNOZ_ALERT_FINGERPRINT", field(event, "fingerprint"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("start notify command `{command}`"))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // A command that only reads the variables may exit without reading.
+        let _ = writeln!(stdin, "{event}");
+    }
+    // Its output goes to stderr, keeping stdout to the event stream.
+    let output = child.wait_with_output().context("run notify command")?;
+    eprint!("{}", String::from_utf8_lossy(&output.stdout));
+    if !output.status.success() {
+        return Err(anyhow!(
+            "notify command `{command}` failed: {}",
+            output.status
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,135 @@
+use anyhow::Result;
+use serde_json::Value;
+
+use crate::metrics;
+
+/// What a scaffolded rule watches.
+pub enum Target<'a> {
+    /// A metric with a `--agg`-style aggregation.
+    Metric { name: &'a str, agg: &'a str },
+    /// Error counts from logs or traces.
+    Errors { signal: &'a str },
+}
+
+/// Default aggregation for a metric: latency histograms are usually alerted
+/// on a high percentile, everything else on the average.
+pub fn default_agg(metric: &str) -> &'static str {
+    let name = metric.to_lowercase();
+    if name.contains("duration") || name.contains("latency") {
+        "p99"
+    } else {
+        "avg"
+    }
+}
+
+/// A commented starter rule in the format `alerts lint`/`alerts create`
+/// read. Thresholds are placeholders to be tuned.
+pub fn render(target: &Target, service: Option<&str>, name: Option<&str>) -> Result<String> {
+    let scope = service.unwrap_or("all services");
+    let (alert_type, signal, aggregation, default_name, target_value, target_note) = match target {
+        Target::Metric { name: metric, agg } => (
+            "METRIC_BASED_ALERT",
+            "metrics",
+            metrics::aggregation(metric, agg),
+            format!("{scope}: {agg} {metric} too high"),
+            "500",
+            "in the metric's unit (check it with `signoz metrics query`)",
+        ),
+        Target::Errors { signal } => (
+            if *signal == "logs" {
+                "LOGS_BASED_ALERT"
+            } else {
+                "TRACES_BASED_ALERT"
+            },
+            *signal,
+            serde_json::json!({ "expression": "count()" }),
+            format!("{scope}: too many {signal} errors"),
+            "10",
+            "errors per evaluation window",
+        ),
+    };
+    let mut filters: Vec<String> = service
+        .map(|s| format!("service.name = '{}'", s.replace('\'', "\\'")))
+        .into_iter()
+        .collect();
+    match signal {
+        "logs" => filters.push("severity_text IN ('ERROR', 'FATAL')".to_string()),
+        "traces" => filters.push("hasError = true".to_string()),
+        _ => {}
+    }
+    let filter = filters.join(" AND ");
+    let name = name.map_or(default_name, str::to_string);
+
+    Ok(format!(
+        r#"# Alert rule scaffold from `signoz alerts init`. Tune the placeholders, then:
+#   signoz alerts lint FILE
+#   signoz alerts create --from-file FILE
+alert: {name}
+alertType: {alert_type}
+ruleType: threshold_rule
+version: v5
+# Each evaluation looks at evalWindow of data and runs every frequency.
+evalWindow: 5m0s
+frequency: 1m0s
+labels:
+  # info | warning | error | critical
+  severity: warning
+annotations:
+  summary: {summary}
+  description: "Value {{{{$value}}}} crossed threshold {{{{$threshold}}}}"
+# Channel names to notify (see `signoz channels list-channels`); empty uses
+# the default routing.
+preferredChannels: []
+condition:
+  # Threshold placeholder, {target_note}.
+  target: {target_value}
+  # above | below | equal | not_equal
+  op: above
+  # at_least_once | all_the_times | on_average | in_total | last
+  matchType: {match_type}
+  selectedQueryName: A
+  compositeQuery:
+    queryType: builder
+    panelType: graph
+    queries:
+      - type: builder_query
+        spec:
+          name: A
+          signal: {signal}
+          stepInterval: 60
+          aggregations:
+{aggregation}
+          filter:
+            expression: {filter}
+          # Add keys (e.g. service.name) to alert per group.
+          groupBy: []
+"#,
+        name = yaml_string(&name),
+        summary = yaml_string(&name),
+        match_type = match target {
+            Target::Metric { .. } => "on_average",
+            Target::Errors { .. } => "in_total",
+        },
+        aggregation = list_item(&aggregation, 12)?,
+        filter = yaml_string(&filter),
+    ))
+}
+
+/// `value` as a YAML list item indented by `indent` spaces.
+fn list_item(value: &Value, indent: usize) -> Result<String> {
+    let yaml = serde_yaml::to_string(value)?;
+    let pad = " ".repeat(indent);
+    Ok(yaml
+        .lines()
+        .enumerate()
+        .map(|(i, line)| match i {
+            0 => format!("{pad}- {line}"),
+            _ => format!("{pad}  {line}"),
+        })
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+
+fn yaml_string(text: &str) -> String {
+    serde_json::to_string(text).unwrap_or_default()
+}
@@ -0,0 +1,249 @@
+use anyhow::{anyhow, Context, Result};
+use clap::{Arg, Command};
+
+use crate::command_tree::CommandTree;
+use crate::config::{self, Config};
+use crate::error::Failure;
+
+/// Longest chain of aliases expanding into aliases.
+const MAX_EXPANSIONS: usize = 10;
+
+pub fn command() -> Command {
+    Command::new("alias")
+        .about("Manage command aliases: `signoz <alias> ...` runs the command line it stands for")
+        .subcommand_required(true)
+        .subcommand(
+            Command::new("set")
+                .about("Define or replace an alias")
+                .arg(Arg::new("name").required(true).value_name("NAME"))
+                .arg(
+                    Arg::new("command")
+                        .required(true)
+                        .value_name("COMMAND")
+                        .allow_hyphen_values(true)
+                        .help("Command line after `signoz`, quoted as one argument; $1, $2, ... take the alias's arguments in order, $@ all of them, and unused arguments are appended"),
+                ),
+        )
+        .subcommand(Command::new("list").about("Show the defined aliases"))
+        .subcommand(
+            Command::new("remove")
+                .about("Delete an alias")
+                .arg(Arg::new("name").required(true).value_name("NAME")),
+        )
+}
+
+pub fn handle(
+    matches: &clap::ArgMatches,
+    root: &Command,
+    tree: &CommandTree,
+    config: &Config,
+) -> Result<()> {
+    match matches.subcommand() {
+        Some(("set", sub)) => {
+            let name = sub.get_one::<String>("name").expect("required");
+            let command = sub.get_one::<String>("command").expect("required");
+            if name.is_empty()
+                || !name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+            {
+                return Err(anyhow!(
+                    "invalid alias name {name:?}; use letters, digits, - and _"
+                ));
+            }
+            if crate::is_command(root, tree, name) {
+                return Err(anyhow!(
+                    "{name} is a built-in command; pick another alias name"
+                ));
+            }
+            if split(command)?.is_empty() {
+                return Err(anyhow!("alias {name} needs a command"));
+            }
+            let path = config::save_alias(name, Some(command))?;
+            eprintln!("Saved alias {name} to {}.", path.display());
+            Ok(())
+        }
+        Some(("list", _)) => {
+            let mut aliases: Vec<_> = config.aliases.iter().collect();
+            aliases.sort();
+            if aliases.is_empty() {
+                eprintln!("No aliases defined; add one with `signoz alias set NAME 'COMMAND'`.");
+            }
+            for (name, command) in aliases {
+                println!("{name}\t{command}");
+            }
+            Ok(())
+        }
+        Some(("remove", sub)) => {
+            let name = sub.get_one::<String>("name").expect("required");
+            if !config.aliases.contains_key(name) {
+                return Err(anyhow!("no alias named {name}"));
+            }
+            let path = config::save_alias(name, None)?;
+            eprintln!("Removed alias {name} from {}.", path.display());
+            Ok(())
+        }
+        _ => Err(anyhow!("alias subcommand required")),
+    }
+}
+
+/// Replaces a leading alias (after any global flags) with its command line,
+/// repeatedly, so aliases may build on each other. Built-in commands and
+/// resources always win over aliases. The config is only read when the
+/// first word is not a known command.
+pub fn expand(root: &Command, tree: &CommandTree, mut args: Vec<String>) -> Result<Vec<String>> {
+    let mut seen: Vec<String> = Vec::new();
+    let mut config: Option<Config> = None;
+    loop {
+        let Some(at) = crate::first_subcommand(root, &args) else {
+            return Ok(args);
+        };
+        let name = args[at].clone();
+        if crate::is_command(root, tree, &name) {
+            return Ok(args);
+        }
+        let config = match &mut config {
+            Some(config) => config,
+            None => config.insert(config::load()?),
+        };
+        let Some(command) = config.aliases.get(&name) else {
+            return Ok(args);
+        };
+        if seen.contains(&name) {
+            seen.push(name);
+            return Err(anyhow!("alias loop: {}", seen.join(" -> ")));
+        }
+        if seen.len() >= MAX_EXPANSIONS {
+            return Err(anyhow!(
+                "alias {name}: more than {MAX_EXPANSIONS} nested aliases"
+            ));
+        }
+        let words = split(command).with_context(|| format!("alias {name}"))?;
+        let expanded = substitute(&name, &words, &args[at + 1..])?;
+        args.splice(at.., expanded);
+        seen.push(name);
+    }
+}
+
+enum Part {
+    Text(String),
+    /// `$N`, 1-based.
+    Arg(usize),
+    /// `$@`, only as a word of its own.
+    All,
+}
+
+fn substitute(name: &str, words: &[Vec<Part>], rest: &[String]) -> Result<Vec<String>> {
+    let mut used = 0;
+    let mut all = false;
+    let mut out = Vec::new();
+    for word in words {
+        if let [Part::All] = word.as_slice() {
+            out.extend(rest.iter().cloned());
+            all = true;
+            continue;
+        }
+        let mut text = String::new();
+        for part in word {
+            match part {
+                Part::Text(s) => text.push_str(s),
+                Part::Arg(n) => {
+                    let value = rest.get(n - 1).ok_or_else(|| {
+                        anyhow!("alias {name} uses ${n} but got {} argument(s)", rest.len())
+                    })?;
+                    text.push_str(value);
+                    used = used.max(*n);
+                }
+                Part::All => text.push_str(&rest.join(" ")),
+            }
+        }
+        out.push(text);
+    }
+    if !all {
+        out.extend(rest.iter().skip(used).cloned());
+    }
+    Ok(out)
+}
+
+/// Shell-style word splitting: single quotes are literal, double quotes
+/// allow `\"`, `\\`, and `\$`, and `$N`/`$@` outside single quotes are
+/// placeholders.
+fn split(line: &str) -> Result<Vec<Vec<Part>>> {
+    let mut words = Vec::new();
+    let mut word: Vec<Part> = Vec::new();
+    let mut text = String::new();
+    let mut in_word = false;
+    let mut chars = line.chars().peekable();
+    let mut quote: Option<char> = None;
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some('\''), '\'') | (Some('"'), '"') => quote = None,
+            (Some('\''), c) => text.push(c),
+            (None, '\'' | '"') => {
+                quote = Some(c);
+                in_word = true;
+            }
+            (None, c) if c.is_whitespace() => {
+                if in_word {
+                    flush(&mut text, &mut word);
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            (_, '\\') => {
+                in_word = true;
+                match chars.next() {
+                    Some(next) if quote.is_none() || matches!(next, '"' | '\\' | '$') => {
+                        text.push(next)
+                    }
+                    Some(next) => {
+                        text.push('\\');
+                        text.push(next);
+                    }
+                    None => text.push('\\'),
+                }
+            }
+            (_, '$') if chars.peek() == Some(&'@') => {
+                chars.next();
+                flush(&mut text, &mut word);
+                word.push(Part::All);
+                in_word = true;
+            }
+            (_, '$')
+                if chars
+                    .peek()
+                    .is_some_and(|c| c.is_ascii_digit() && *c != '0') =>
+            {
+                let mut digits = String::new();
+                while let Some(d) = chars.peek().filter(|d| d.is_ascii_digit()) {
+                    digits.push(*d);
+                    chars.next();
+                }
+                let index = digits.parse().map_err(|_| {
+                    Failure::Usage(format!("argument reference ${digits} is out of range"))
+                })?;
+                flush(&mut text, &mut word);
+                word.push(Part::Arg(index));
+                in_word = true;
+            }
+            (_, c) => {
+                text.push(c);
+                in_word = true;
+            }
+        }
+    }
+    if let Some(q) = quote {
+        return Err(anyhow!("unterminated {q} quote"));
+    }
+    if in_word {
+        flush(&mut text, &mut word);
+        words.push(word);
+    }
+    Ok(words)
+}
+
+fn flush(text: &mut String, word: &mut Vec<Part>) {
+    if !text.is_empty() {
+        word.push(Part::Text(std::mem::take(text)));
+    }
+}
@@ -0,0 +1,265 @@
+use anyhow::{anyhow, Context, Result};
+use clap::{Arg, Command};
+use serde_json::{json, Value};
+
+use crate::error::ApiError;
+use crate::http::Body;
+use crate::ids;
+use crate::output::{self, OutputOptions};
+use crate::timeparse;
+use crate::ApiContext;
+
+const KEYS_PATH: &str = "/api/v1/pats";
+/// Cheap authenticated request used to check a new key before switching.
+const VERIFY_PATH: &str = "/api/v1/user/me";
+
+pub fn extend(cmd: Command) -> Command {
+    let key = || Arg::new("key").required(true).help("API key id or name");
+    let expires = || {
+        Arg::new("expires-in-days")
+            .long("expires-in-days")
+            .value_name("DAYS")
+            .value_parser(clap::value_parser!(u64))
+    };
+    cmd.subcommand(
+        Command::new("create")
+            .about("Create an API key and print its token (shown once; store it now)")
+            .arg(
+                Arg::new("name")
+                    .long("name")
+                    .value_name("NAME")
+                    .required(true)
+                    .help("Key name"),
+            )
+            .arg(
+                Arg::new("role")
+                    .long("role")
+                    .value_name("ROLE")
+                    .default_value("VIEWER")
+                    .value_parser(["ADMIN", "EDITOR", "VIEWER"])
+                    .help("Role the key acts with"),
+            )
+            .arg(expires().default_value("0").help("Days until the key expires (0: never)")),
+    )
+    .subcommand(
+        Command::new("rotate")
+            .about("Replace a key: create a new one with the same role, verify it, print it, revoke the old one")
+            .arg(key())
+            .arg(
+                Arg::new("name")
+                    .long("name")
+                    .value_name("NAME")
+                    .help("Name for the new key (default: the old key's name)"),
+            )
+            .arg(expires().help("Days until the new key expires (default: the old key's lifetime)")),
+    )
+    .subcommand(
+        Command::new("revoke")
+            .about("Revoke an API key by id or name")
+            .arg(key()),
+    )
+}
+
+pub fn handle(
+    op: &str,
+    matches: &clap::ArgMatches,
+    ctx: &ApiContext,
+    opts: &OutputOptions,
+) -> Option<Result<()>> {
+    match op {
+        "create" => Some(handle_create(matches, ctx, opts)),
+        "rotate" => Some(handle_rotate(matches, ctx, opts)),
+        "revoke" => Some(handle_revoke(matches, ctx, opts)),
+        _ => None,
+    }
+}
+
+fn handle_create(matches: &clap::ArgMatches, ctx: &ApiContext, opts: &OutputOptions) -> Result<()> {
+    let name = matches
+        .get_one::<String>("name")
+        .ok_or_else(|| anyhow!("missing --name"))?;
+    let role = matches
+        .get_one::<String>("role")
+        .map_or("VIEWER", String::as_str);
+    let days = matches
+        .get_one::<u64>("expires-in-days")
+        .copied()
+        .unwrap_or(0);
+    let key = create(ctx, name, role, days)?;
+    eprintln!("Store the token now; it is not shown again.");
+    output::print_value(&summary(&key), opts)
+}
+
+/// Nothing is revoked until the new key has authenticated a request, and
+/// the new key is printed unless it was rejected and revoked again.
+fn handle_rotate(matches: &clap::ArgMatches, ctx: &ApiContext, opts: &OutputOptions) -> Result<()> {
+    let old = find(ctx, matches)?;
+    let old_id = field(&old, "id").to_string();
+    let name = matches
+        .get_one::<String>("name")
+        .map_or_else(|| field(&old, "name").to_string(), String::clone);
+    let days = match matches.get_one::<u64>("expires-in-days") {
+        Some(days) => *days,
+        None => lifetime_days(&old),
+    };
+    let new = create(ctx, &name, field(&old, "role"), days)?;
+    let new_id = field(&new, "id").to_string();
+    let token = field(&new, "token").to_string();
+    let mut out = summary(&new);
+    out["replaces"] = json!(old_id);
+
+    // A request that never got an answer says nothing about the new key, so
+    // it is kept and shown, and the old key stays as it is.
+    let verified = ctx
+        .client(Some(&token), None)
+        .and_then(|client| client.execute("GET", VERIFY_PATH, &[], None, None));
+    let verified = match verified {
+        Ok(response) => response,
+        Err(err) => {
+            eprintln!(
+                "warning: could not verify the new key {new_id}: {err:#}; the old key {old_id} \
+                 was not revoked"
+            );
+            out["old_key_revoked"] = json!(false);
+            eprintln!("Store the token now; it is not shown again.");
+            return output::print_value(&out, opts);
+        }
+    };
+    if verified.status >= 400 {
+        let cleanup = revoke(ctx, &new_id);
+        return Err(anyhow!(
+            "new key {new_id} failed verification (http {}); {}; the old key {old_id} is unchanged",
+            verified.status,
+            match cleanup {
+                Ok(()) => "the new key was revoked".to_string(),
+                Err(err) => format!("revoking the new key also failed: {err:#}"),
+            }
+        ));
+    }
+
+    let revoked = revoke(ctx, &old_id);
+    out["old_key_revoked"] = json!(revoked.is_ok());
+    eprintln!("Store the token now; it is not shown again.");
+    output::print_value(&out, opts)?;
+    revoked.map_err(|err| {
+        anyhow!("new key {new_id} is active, but revoking the old key {old_id} failed: {err:#}")
+    })
+}
+
+fn handle_revoke(matches: &clap::ArgMatches, ctx: &ApiContext, opts: &OutputOptions) -> Result<()> {
+    let key = find(ctx, matches)?;
+    let id = field(&key, "id");
+    revoke(ctx, id)?;
+    output::print_value(
+        &json!({ "id": id, "name": field(&key, "name"), "revoked": true }),
+        opts,
+    )
+}
+
+fn create(ctx: &ApiContext, name: &str, role: &str, days: u64) -> Result<Value> {
+    let response = ctx.execute(
+        "POST",
+        KEYS_PATH,
+        &[],
+        Some(Body::Json(
+            json!({ "name": name, "role": role, "expiresInDays": days }),
+        )),
+        Some("application/json"),
+    )?;
+    if response.status >= 400 {
+        return Err(ApiError::from_response(&response)).context("create api key failed");
+    }
+    let key = response.body.get("data").unwrap_or(&response.body).clone();
+    if field(&key, "token").is_empty() {
+        return Err(anyhow!(
+            "create api key returned no token: {}",
+            response.body
+        ));
+    }
+    ids::remember(&ctx.base_url, KEYS_PATH, &key);
+    Ok(key)
+}
+
+fn revoke(ctx: &ApiContext, id: &str) -> Result<()> {
+    let path = format!("{KEYS_PATH}/{}", urlencoding::encode(id));
+    let response = ctx.execute("DELETE", &path, &[], None, None)?;
+    if response.status >= 400 {
+        return Err(ApiError::from_response(&response))
+            .with_context(|| format!("revoke api key {id} failed"));
+    }
+    Ok(())
+}
+
+/// The unrevoked key whose id (or remembered id prefix) or name matches.
+fn find(ctx: &ApiContext, matches: &clap::ArgMatches) -> Result<Value> {
+    let wanted = matches
+        .get_one::<String>("key")
+        .ok_or_else(|| anyhow!("missing key id or name"))?;
+    let id = ids::expand(&ctx.base_url, KEYS_PATH, wanted)?;
+    let response = ctx.execute("GET", KEYS_PATH, &[], None, None)?;
+    if response.status >= 400 {
+        return Err(ApiError::from_response(&response)).context("list api keys failed");
+    }
+    let keys: Vec<Value> = response
+        .body
+        .get("data")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter(|k| k.get("revoked") != Some(&Value::Bool(true)))
+        .cloned()
+        .collect();
+    if let Some(key) = keys.iter().find(|k| field(k, "id") == id) {
+        return Ok(key.clone());
+    }
+    let named: Vec<&Value> = keys.iter().filter(|k| field(k, "name") == wanted).collect();
+    match named.as_slice() {
+        [key] => Ok((*key).clone()),
+        [] => Err(anyhow!("no active api key with id or name {wanted}")),
+        _ => Err(anyhow!(
+            "{} active api keys are named {wanted}; pass the id ({})",
+            named.len(),
+            named
+                .iter()
+                .map(|k| field(k, "id"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )),
+    }
+}
+
+/// The old key's full lifetime in days (0 when it never expires), so a
+/// rotated key keeps the same expiry policy.
+fn lifetime_days(key: &Value) -> u64 {
+    let expires_ms = key
+        .get("expiresAt")
+        .and_then(Value::as_u64)
+        .filter(|secs| *secs > 0)
+        .map(|secs| secs * 1000);
+    let created_ms = key
+        .get("createdAt")
+        .and_then(Value::as_str)
+        .and_then(|raw| timeparse::parse_rfc3339_ns(raw).ok())
+        .map(|ns| ns / 1_000_000);
+    match (expires_ms, created_ms) {
+        (Some(expires), Some(created)) => (expires.saturating_sub(created) as f64 / 86_400_000.0)
+            .round()
+            .max(1.0) as u64,
+        (Some(_), None) => 90,
+        _ => 0,
+    }
+}
+
+fn summary(key: &Value) -> Value {
+    json!({
+        "id": key.get("id"),
+        "name": key.get("name"),
+        "role": key.get("role"),
+        "expiresAt": key.get("expiresAt"),
+        "token": key.get("token"),
+    })
+}
+
+fn field<'a>(value: &'a Value, key: &str) -> &'a str {
+    value.get(key).and_then(Value::as_str).unwrap_or_default()
+}
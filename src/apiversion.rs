@@ -0,0 +1,100 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::config;
+use crate::ApiContext;
+
+/// How long a probe result is trusted, so a server upgrade is noticed
+/// within a day.
+const TTL_SECS: u64 = 24 * 60 * 60;
+/// A cheap v2 route every server that has v2 routes serves.
+const PROBE_PATH: &str = "/api/v2/features";
+
+#[derive(Clone, Copy, Deserialize, Serialize)]
+struct Entry {
+    v2: bool,
+    checked_at: u64,
+}
+
+/// Base URL -> what the last probe of it found.
+type Store = BTreeMap<String, Entry>;
+
+fn store_path() -> Result<PathBuf> {
+    Ok(config::config_dir()?.join("api_versions.json"))
+}
+
+fn load_store() -> Store {
+    store_path()
+        .and_then(|path| Ok(fs::read_to_string(path)?))
+        .ok()
+        .and_then(|raw| serde_json::from_str(&raw).ok())
+        .unwrap_or_default()
+}
+
+fn save_store(store: &Store) -> Result<()> {
+    let path = store_path()?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).context("create config dir")?;
+    }
+    fs::write(&path, serde_json::to_string(store)?).context("write api version cache")
+}
+
+/// Whether the server has `/api/v2/` routes: the cached answer for its base
+/// URL, else a `HEAD` of a v2 route (cached for a day). `None` when it
+/// cannot be told: while recording or replaying, or when the probe fails.
+pub fn v2_supported(ctx: &ApiContext) -> Option<bool> {
+    if ctx.client_opts.cassette.is_some() {
+        return None;
+    }
+    let now = now_secs();
+    if let Some(entry) = load_store()
+        .get(ctx.base_url.trim_end_matches('/'))
+        .filter(|entry| now.saturating_sub(entry.checked_at) < TTL_SECS)
+    {
+        return Some(entry.v2);
+    }
+    let response = ctx.execute("HEAD", PROBE_PATH, &[], None, None).ok()?;
+    if response.status >= 500 {
+        return None;
+    }
+    // Older servers send unknown routes to the UI's HTML or a 404; any
+    // other answer (even 401 or 405) means the route exists.
+    let v2 = response.status != 404 && !crate::is_html_response(&response);
+    if ctx.client_opts.verbosity > 0 {
+        eprintln!(
+            "* {PROBE_PATH}: http {}; {} v2 routes",
+            response.status,
+            if v2 { "using" } else { "no" }
+        );
+    }
+    record(&ctx.base_url, v2);
+    Some(v2)
+}
+
+/// Remembers whether the server has v2 routes, e.g. after a v2 request got
+/// the UI's HTML although the cached probe said otherwise. Best effort:
+/// cache errors are ignored.
+pub fn record(base_url: &str, v2: bool) {
+    let mut store = load_store();
+    store.insert(
+        base_url.trim_end_matches('/').to_string(),
+        Entry {
+            v2,
+            checked_at: now_secs(),
+        },
+    );
+    let _ = save_store(&store);
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
@@ -0,0 +1,428 @@
+use anyhow::{anyhow, Context, Result};
+use clap::{Arg, ArgAction, Command};
+use serde_json::{json, Map, Value};
+use std::{
+    collections::BTreeSet,
+    fs,
+    io::{self, IsTerminal},
+    path::Path,
+};
+
+use crate::http::Body;
+use crate::objects::{self, item_id, item_path, Kind, KINDS};
+use crate::output::{self, OutputOptions};
+use crate::references;
+use crate::watch;
+use crate::ApiContext;
+
+enum Action {
+    Create {
+        body: Value,
+    },
+    Update {
+        id: String,
+        body: Value,
+        changes: Vec<Value>,
+    },
+    Delete {
+        id: String,
+    },
+}
+
+struct Change {
+    kind: &'static Kind,
+    name: String,
+    action: Action,
+}
+
+fn source_args(cmd: Command) -> Command {
+    cmd.arg(
+        Arg::new("file")
+            .short('f')
+            .long("file")
+            .value_name("DIR")
+            .required(true)
+            .help("Directory with channels/, dashboards/, views/, and rules/ definitions"),
+    )
+    .arg(
+        Arg::new("prune")
+            .long("prune")
+            .action(ArgAction::SetTrue)
+            .help("Destroy live objects of a managed kind that have no local definition"),
+    )
+    .arg(
+        Arg::new("json")
+            .long("json")
+            .action(ArgAction::SetTrue)
+            .help("Emit the plan as JSON"),
+    )
+    .arg(references::skip_arg())
+}
+
+pub fn plan_command() -> Command {
+    source_args(
+        Command::new("plan")
+            .about("Show what apply would add, change, or destroy on the live instance"),
+    )
+}
+
+pub fn apply_command() -> Command {
+    source_args(
+        Command::new("apply")
+            .about("Make the live instance match local channel/dashboard/view/rule definitions"),
+    )
+    .arg(
+        Arg::new("auto-approve")
+            .long("auto-approve")
+            .action(ArgAction::SetTrue)
+            .help("Apply without asking for confirmation"),
+    )
+}
+
+pub fn handle_plan(
+    matches: &clap::ArgMatches,
+    ctx: &ApiContext,
+    opts: &OutputOptions,
+) -> Result<()> {
+    let changes = plan(matches, ctx)?;
+    print_plan(&changes, matches.get_flag("json"), opts)
+}
+
+pub fn handle_apply(
+    matches: &clap::ArgMatches,
+    ctx: &ApiContext,
+    opts: &OutputOptions,
+) -> Result<()> {
+    let changes = plan(matches, ctx)?;
+    let json = matches.get_flag("json");
+    print_plan(&changes, json, opts)?;
+    if changes.is_empty() {
+        return Ok(());
+    }
+    if !matches.get_flag("auto-approve") {
+        confirm()?;
+    }
+
+    let mut applied = Vec::new();
+    for change in &changes {
+        let kind = change.kind;
+        let (method, path, body, verb) = match &change.action {
+            Action::Create { body } => ("POST", kind.path.to_string(), Some(body), "created"),
+            Action::Update { id, body, .. } => ("PUT", item_path(kind, id), Some(body), "changed"),
+            Action::Delete { id } => ("DELETE", item_path(kind, id), None, "destroyed"),
+        };
+        let response = ctx.execute(
+            method,
+            &path,
+            &[],
+            body.cloned().map(Body::Json),
+            body.map(|_| "application/json"),
+        )?;
+        if response.status >= 400 {
+            return Err(anyhow!(
+                "{} {} \"{}\" failed after {} applied: http {}: {}",
+                method,
+                kind.singular,
+                change.name,
+                applied.len(),
+                response.status,
+                response.body
+            ));
+        }
+        if !json {
+            eprintln!("{} \"{}\": {verb}", kind.singular, change.name);
+        }
+        applied.push(json!({ "kind": kind.singular, "name": change.name, "result": verb }));
+    }
+    if json {
+        output::print_value(&json!({ "applied": applied }), opts)?;
+    } else {
+        eprintln!("Apply complete: {}", summary(&changes));
+    }
+    Ok(())
+}
+
+fn plan(matches: &clap::ArgMatches, ctx: &ApiContext) -> Result<Vec<Change>> {
+    let dir = Path::new(
+        matches
+            .get_one::<String>("file")
+            .ok_or_else(|| anyhow!("-f DIR is required"))?,
+    );
+    if !dir.is_dir() {
+        return Err(anyhow!("{} is not a directory", dir.display()));
+    }
+    let prune = matches.get_flag("prune");
+
+    let mut changes = Vec::new();
+    for kind in KINDS {
+        let kind_dir = dir.join(kind.name);
+        if !kind_dir.is_dir() {
+            continue;
+        }
+        let local = read_definitions(kind, &kind_dir)?;
+        let live = list(ctx, kind)?;
+
+        for (name, desired) in &local {
+            let found: Vec<&(String, String, Value)> =
+                live.iter().filter(|(n, ..)| n == name).collect();
+            match found.as_slice() {
+                [] => changes.push(Change {
+                    kind,
+                    name: name.clone(),
+                    action: Action::Create {
+                        body: desired.clone(),
+                    },
+                }),
+                [(_, id, current)] => {
+                    let mut diffs = Vec::new();
+                    watch::diff(
+                        &project(current, desired),
+                        desired,
+                        &mut String::new(),
+                        &mut diffs,
+                    );
+                    if !diffs.is_empty() {
+                        changes.push(Change {
+                            kind,
+                            name: name.clone(),
+                            action: Action::Update {
+                                id: id.clone(),
+                                body: desired.clone(),
+                                changes: diffs,
+                            },
+                        });
+                    }
+                }
+                _ => {
+                    return Err(anyhow!(
+                        "{} \"{name}\" matches {} live objects; rename them so names are unique",
+                        kind.singular,
+                        found.len()
+                    ))
+                }
+            }
+        }
+        if prune {
+            for (name, id, _) in live
+                .iter()
+                .filter(|(n, ..)| !local.iter().any(|(l, _)| l == n))
+            {
+                changes.push(Change {
+                    kind,
+                    name: name.clone(),
+                    action: Action::Delete { id: id.clone() },
+                });
+            }
+        }
+    }
+    if !matches.get_flag("no-ref-check") {
+        check_references(ctx, &changes)?;
+    }
+    Ok(changes)
+}
+
+/// Fails before anything is sent when a definition being created or
+/// updated points at a channel, metric, or dashboard variable that will not
+/// exist once the plan is applied.
+fn check_references(ctx: &ApiContext, changes: &[Change]) -> Result<()> {
+    let mut checker = references::Checker::new(ctx);
+    let mut channels = None;
+    for change in changes {
+        let (Action::Create { body } | Action::Update { body, .. }) = &change.action else {
+            continue;
+        };
+        let owner = format!("{} \"{}\"", change.kind.singular, change.name);
+        checker.metrics(&owner, body);
+        match change.kind.name {
+            "dashboards" => checker.dashboard_variables(&owner, body),
+            "rules" => {
+                let known = match &mut channels {
+                    Some(known) => known,
+                    None => channels.insert(channel_names(ctx, changes)?),
+                };
+                checker.channels(&owner, body, known);
+            }
+            _ => {}
+        }
+    }
+    checker.finish()
+}
+
+/// Channel names as they will be after the plan: live ones, minus pruned,
+/// plus created.
+fn channel_names(ctx: &ApiContext, changes: &[Change]) -> Result<BTreeSet<String>> {
+    let kind = KINDS
+        .iter()
+        .find(|k| k.name == "channels")
+        .ok_or_else(|| anyhow!("channels kind missing"))?;
+    let mut names: BTreeSet<String> = list(ctx, kind)?.into_iter().map(|(n, ..)| n).collect();
+    for change in changes.iter().filter(|c| c.kind.name == "channels") {
+        match change.action {
+            Action::Create { .. } => names.insert(change.name.clone()),
+            Action::Delete { .. } => names.remove(&change.name),
+            Action::Update { .. } => true,
+        };
+    }
+    Ok(names)
+}
+
+/// Local definitions by name. Dashboard files may be `dashboards export`
+/// output (`{id, data}`); only `data` is compared and sent.
+fn read_definitions(kind: &Kind, dir: &Path) -> Result<Vec<(String, Value)>> {
+    let mut entries: Vec<_> = fs::read_dir(dir)
+        .with_context(|| format!("read {}", dir.display()))?
+        .collect::<io::Result<_>>()?;
+    entries.sort_by_key(|entry| entry.file_name());
+
+    let mut out: Vec<(String, Value)> = Vec::new();
+    for entry in entries {
+        let path = entry.path();
+        let ext = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or_default();
+        if !matches!(ext, "json" | "yaml" | "yml") {
+            continue;
+        }
+        let raw = fs::read_to_string(&path).with_context(|| format!("read {}", path.display()))?;
+        let mut value: Value = if ext == "json" {
+            serde_json::from_str(&raw).with_context(|| format!("parse {}", path.display()))?
+        } else {
+            serde_yaml::from_str(&raw).with_context(|| format!("parse {}", path.display()))?
+        };
+        if kind.name == "dashboards" {
+            if let Some(data) = value.get("data").filter(|d| d.is_object()) {
+                value = data.clone();
+            }
+        }
+        let name = value
+            .pointer(kind.key)
+            .and_then(Value::as_str)
+            .ok_or_else(|| anyhow!("{} has no `{}`", path.display(), &kind.key[1..]))?
+            .to_string();
+        if out.iter().any(|(n, _)| *n == name) {
+            return Err(anyhow!("{} \"{name}\" is defined twice", kind.singular));
+        }
+        out.push((name, value));
+    }
+    Ok(out)
+}
+
+/// Live objects as `(name, id, comparable value)`, the value being the
+/// body a create would take.
+fn list(ctx: &ApiContext, kind: &Kind) -> Result<Vec<(String, String, Value)>> {
+    let items = objects::list(ctx, kind)?
+        .ok_or_else(|| anyhow!("this instance has no {} endpoint", kind.name))?;
+    Ok(items
+        .into_iter()
+        .filter_map(|item| {
+            let id = item_id(&item)?;
+            let value = objects::body(kind, &item);
+            let name = value
+                .pointer(kind.key)
+                .or_else(|| item.pointer(kind.key))?
+                .as_str()?
+                .to_string();
+            Some((name, id, value))
+        })
+        .collect())
+}
+
+/// `current` limited to the keys `desired` sets, so fields the server adds
+/// (ids, timestamps, state) do not show up as changes. Arrays are compared
+/// item by item; items beyond `desired`'s are kept whole, as a real change.
+pub fn project(current: &Value, desired: &Value) -> Value {
+    match (current, desired) {
+        (Value::Object(cur), Value::Object(want)) => Value::Object(
+            want.iter()
+                .filter_map(|(key, value)| Some((key.clone(), project(cur.get(key)?, value))))
+                .collect::<Map<_, _>>(),
+        ),
+        (Value::Array(cur), Value::Array(want)) => Value::Array(
+            cur.iter()
+                .enumerate()
+                .map(|(i, item)| match want.get(i) {
+                    Some(wanted) => project(item, wanted),
+                    None => item.clone(),
+                })
+                .collect(),
+        ),
+        _ => current.clone(),
+    }
+}
+
+fn summary(changes: &[Change]) -> String {
+    let count = |f: fn(&Action) -> bool| changes.iter().filter(|c| f(&c.action)).count();
+    format!(
+        "{} to add, {} to change, {} to destroy.",
+        count(|a| matches!(a, Action::Create { .. })),
+        count(|a| matches!(a, Action::Update { .. })),
+        count(|a| matches!(a, Action::Delete { .. })),
+    )
+}
+
+fn print_plan(changes: &[Change], json: bool, opts: &OutputOptions) -> Result<()> {
+    if json {
+        let items: Vec<Value> = changes
+            .iter()
+            .map(|change| {
+                let mut item = json!({ "kind": change.kind.singular, "name": change.name });
+                match &change.action {
+                    Action::Create { .. } => item["action"] = json!("create"),
+                    Action::Update { id, changes, .. } => {
+                        item["action"] = json!("update");
+                        item["id"] = json!(id);
+                        item["changes"] = json!(changes);
+                    }
+                    Action::Delete { id } => {
+                        item["action"] = json!("delete");
+                        item["id"] = json!(id);
+                    }
+                }
+                item
+            })
+            .collect();
+        return output::print_value(&Value::Array(items), opts);
+    }
+
+    if changes.is_empty() {
+        println!("No changes. The live instance matches the local definitions.");
+        return Ok(());
+    }
+    for change in changes {
+        let label = format!("{} \"{}\"", change.kind.singular, change.name);
+        match &change.action {
+            Action::Create { .. } => println!("  + {label}"),
+            Action::Delete { id } => println!("  - {label} ({id})"),
+            Action::Update { id, changes, .. } => {
+                println!("  ~ {label} ({id})");
+                for diff in changes {
+                    let path = diff["path"].as_str().unwrap_or_default();
+                    let show =
+                        |key: &str| diff.get(key).map_or("(none)".to_string(), Value::to_string);
+                    println!("      {path}: {} -> {}", show("from"), show("to"));
+                }
+            }
+        }
+    }
+    println!("\nPlan: {}", summary(changes));
+    Ok(())
+}
+
+pub fn confirm() -> Result<()> {
+    if !io::stdin().is_terminal() {
+        return Err(anyhow!(
+            "refusing to apply without a terminal; pass --auto-approve"
+        ));
+    }
+    eprint!("Apply these changes? [y/N] ");
+    let mut answer = String::new();
+    io::stdin()
+        .read_line(&mut answer)
+        .context("read confirmation")?;
+    if matches!(answer.trim().to_ascii_lowercase().as_str(), "y" | "yes") {
+        Ok(())
+    } else {
+        Err(anyhow!("apply cancelled"))
+    }
+}
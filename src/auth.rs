@@ -0,0 +1,402 @@
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{self, BufRead, BufReader, Write},
+    net::TcpListener,
+    path::PathBuf,
+    process::Command,
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+use url::Url;
+
+use crate::config;
+use crate::error::{ApiError, Failure};
+use crate::http::{self, Body, ClientOptions, HttpClient};
+
+/// Refresh stored tokens this many seconds before they actually expire.
+const REFRESH_SKEW_SECS: u64 = 30;
+/// How long `login --sso` waits for the browser to come back.
+const SSO_TIMEOUT: Duration = Duration::from_secs(300);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AuthMode {
+    ApiKey,
+    Token,
+    Auto,
+}
+
+pub fn parse_auth_mode(
+    raw: Option<&String>,
+    api_key: Option<&String>,
+    token: Option<&String>,
+) -> AuthMode {
+    match raw.map(|v| v.as_str()) {
+        Some("api-key") => AuthMode::ApiKey,
+        Some("token") => AuthMode::Token,
+        Some("auto") => AuthMode::Auto,
+        _ => {
+            if api_key.is_none() && token.is_some() {
+                AuthMode::Token
+            } else {
+                AuthMode::Auto
+            }
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct TokenStore {
+    #[serde(default)]
+    tokens: BTreeMap<String, StoredToken>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct StoredToken {
+    pub access_token: String,
+    pub refresh_token: Option<String>,
+    pub expires_at: Option<u64>,
+    pub email: Option<String>,
+    pub org_id: Option<String>,
+}
+
+impl StoredToken {
+    fn is_expired(&self) -> bool {
+        match self.expires_at {
+            Some(at) => now_secs() + REFRESH_SKEW_SECS >= at,
+            None => false,
+        }
+    }
+}
+
+fn store_path() -> Result<PathBuf> {
+    Ok(config::config_dir()?.join("credentials.json"))
+}
+
+fn store_key(base_url: &str) -> String {
+    base_url.trim_end_matches('/').to_string()
+}
+
+fn load_store() -> Result<TokenStore> {
+    let path = store_path()?;
+    if !path.exists() {
+        return Ok(TokenStore::default());
+    }
+    let raw = fs::read_to_string(&path).context("read credentials file")?;
+    serde_json::from_str(&raw).context("invalid credentials file")
+}
+
+fn save_store(store: &TokenStore) -> Result<()> {
+    let path = store_path()?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).context("create config dir")?;
+    }
+    fs::write(&path, serde_json::to_string_pretty(store)?).context("write credentials file")?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o600))
+            .context("restrict credentials file")?;
+    }
+    Ok(())
+}
+
+fn save_token(base_url: &str, token: StoredToken) -> Result<()> {
+    let mut store = load_store()?;
+    store.tokens.insert(store_key(base_url), token);
+    save_store(&store)
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Returns the cached login token for `base_url`, rotating it first when it
+/// is about to expire.
+pub fn stored_token(base_url: &str, opts: &ClientOptions) -> Result<Option<String>> {
+    let store = load_store()?;
+    let Some(token) = store.tokens.get(&store_key(base_url)).cloned() else {
+        return Ok(None);
+    };
+    if !token.is_expired() {
+        return Ok(Some(token.access_token));
+    }
+    let Some(refresh) = token.refresh_token.clone() else {
+        return Err(Failure::Auth(format!(
+            "stored token for {base_url} expired; run `signoz login` again"
+        ))
+        .into());
+    };
+    let client = HttpClient::new(base_url.to_string(), None, None, Vec::new(), opts)?;
+    let response = client.execute(
+        "POST",
+        "/api/v2/sessions/rotate",
+        &[],
+        Some(Body::Json(json!({ "refreshToken": refresh }))),
+        Some("application/json"),
+    )?;
+    if response.status >= 400 {
+        return Err(ApiError::from_response(&response))
+            .context("token refresh failed; run `signoz login` again");
+    }
+    let refreshed = parse_token(&response.body, token.email, token.org_id)?;
+    let access = refreshed.access_token.clone();
+    save_token(base_url, refreshed)?;
+    Ok(Some(access))
+}
+
+fn parse_token(body: &Value, email: Option<String>, org_id: Option<String>) -> Result<StoredToken> {
+    let data = body.get("data").unwrap_or(body);
+    let access_token = data
+        .get("accessToken")
+        .and_then(Value::as_str)
+        .ok_or_else(|| anyhow!("login response missing accessToken"))?
+        .to_string();
+    let refresh_token = data
+        .get("refreshToken")
+        .and_then(Value::as_str)
+        .map(str::to_string);
+    let expires_at = data
+        .get("expiresIn")
+        .and_then(Value::as_u64)
+        .map(|secs| now_secs() + secs);
+    Ok(StoredToken {
+        access_token,
+        refresh_token,
+        expires_at,
+        email,
+        org_id,
+    })
+}
+
+pub fn login(
+    base_url: &str,
+    email: &str,
+    password: &str,
+    org_id: Option<String>,
+    opts: &ClientOptions,
+) -> Result<StoredToken> {
+    let client = HttpClient::new(base_url.to_string(), None, None, Vec::new(), opts)?;
+    let org_id = match org_id {
+        Some(id) => id,
+        None => resolve_org_id(&client, base_url, email)?,
+    };
+    let response = client.execute(
+        "POST",
+        "/api/v2/sessions/email_password",
+        &[],
+        Some(Body::Json(json!({
+            "email": email,
+            "password": password,
+            "orgId": org_id,
+        }))),
+        Some("application/json"),
+    )?;
+    if response.status >= 400 {
+        return Err(ApiError::from_response(&response)).context("login failed");
+    }
+    let token = parse_token(&response.body, Some(email.to_string()), Some(org_id))?;
+    save_token(base_url, token.clone())?;
+    Ok(token)
+}
+
+fn session_orgs(client: &HttpClient, email: &str, reference: &str) -> Result<Vec<Value>> {
+    let response = client.execute(
+        "GET",
+        "/api/v2/sessions/context",
+        &[
+            ("email".to_string(), email.to_string()),
+            ("ref".to_string(), reference.to_string()),
+        ],
+        None,
+        None,
+    )?;
+    if response.status >= 400 {
+        return Err(ApiError::from_response(&response))
+            .context("session context lookup failed; pass --org-id");
+    }
+    Ok(response
+        .body
+        .pointer("/data/orgs")
+        .and_then(Value::as_array)
+        .cloned()
+        .unwrap_or_default())
+}
+
+fn pick_org(orgs: Vec<Value>, org_id: Option<&str>, email: &str) -> Result<Value> {
+    if let Some(id) = org_id {
+        return orgs
+            .into_iter()
+            .find(|org| org.get("id").and_then(Value::as_str) == Some(id))
+            .ok_or_else(|| anyhow!("organization {id} not available for {email}"));
+    }
+    match orgs.len() {
+        0 => Err(anyhow!("no organization found for {email}")),
+        1 => Ok(orgs.into_iter().next().unwrap_or_default()),
+        _ => {
+            let ids: Vec<String> = orgs
+                .iter()
+                .map(|org| {
+                    format!(
+                        "{} ({})",
+                        org.get("id").and_then(Value::as_str).unwrap_or("?"),
+                        org.get("name").and_then(Value::as_str).unwrap_or("?")
+                    )
+                })
+                .collect();
+            Err(anyhow!(
+                "multiple organizations for {email}; pass --org-id: {}",
+                ids.join(", ")
+            ))
+        }
+    }
+}
+
+fn resolve_org_id(client: &HttpClient, base_url: &str, email: &str) -> Result<String> {
+    let org = pick_org(session_orgs(client, email, base_url)?, None, email)?;
+    org.get("id")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .ok_or_else(|| anyhow!("session context missing org id; pass --org-id"))
+}
+
+/// Browser-based SSO: the provider callback 303-redirects to `ref` with the
+/// token pair in the query string, so `ref` points at a loopback listener.
+/// A random state in its path keeps other local pages from handing the
+/// listener a token of their choosing.
+pub fn login_sso(
+    base_url: &str,
+    email: &str,
+    org_id: Option<String>,
+    provider: Option<&str>,
+    open_browser: bool,
+    opts: &ClientOptions,
+) -> Result<StoredToken> {
+    let listener = TcpListener::bind("127.0.0.1:0").context("bind loopback listener")?;
+    let callback_path = format!("/callback/{}", http::random_hex(16)?);
+    let redirect = format!("http://{}{callback_path}", listener.local_addr()?);
+
+    let client = HttpClient::new(base_url.to_string(), None, None, Vec::new(), opts)?;
+    let org = pick_org(
+        session_orgs(&client, email, &redirect)?,
+        org_id.as_deref(),
+        email,
+    )?;
+    let callbacks = org
+        .pointer("/authNSupport/callback")
+        .and_then(Value::as_array)
+        .cloned()
+        .unwrap_or_default();
+    let callback = match provider {
+        Some(name) => callbacks
+            .iter()
+            .find(|cb| cb.get("provider").and_then(Value::as_str) == Some(name)),
+        None => callbacks.first(),
+    }
+    .ok_or_else(|| anyhow!("no SSO provider configured for {email}"))?;
+    let url = callback
+        .get("url")
+        .and_then(Value::as_str)
+        .ok_or_else(|| anyhow!("SSO provider missing login url"))?;
+
+    eprintln!("Open this URL to sign in:\n  {url}");
+    if open_browser {
+        open_url(url);
+    }
+    eprintln!(
+        "Waiting for the SSO redirect on {} ...",
+        listener.local_addr()?
+    );
+
+    let query = accept_callback(&listener, &callback_path)?;
+    let param = |name: &str| {
+        query
+            .iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.clone())
+    };
+    if let Some(err) = param("error") {
+        return Err(anyhow!("SSO login failed: {err}"));
+    }
+    let access_token = param("accessToken")
+        .ok_or_else(|| anyhow!("SSO redirect did not include an accessToken"))?;
+    let token = StoredToken {
+        access_token,
+        refresh_token: param("refreshToken"),
+        expires_at: param("expiresIn")
+            .and_then(|v| v.parse::<u64>().ok())
+            .map(|secs| now_secs() + secs),
+        email: Some(email.to_string()),
+        org_id: org.get("id").and_then(Value::as_str).map(str::to_string),
+    };
+    save_token(base_url, token.clone())?;
+    Ok(token)
+}
+
+/// The query of the first request for `callback_path`; other requests get
+/// a 404. Gives up after [`SSO_TIMEOUT`].
+fn accept_callback(listener: &TcpListener, callback_path: &str) -> Result<Vec<(String, String)>> {
+    let deadline = Instant::now() + SSO_TIMEOUT;
+    listener
+        .set_nonblocking(true)
+        .context("set up loopback listener")?;
+    loop {
+        let mut stream = match listener.accept() {
+            Ok((stream, _)) => stream,
+            Err(err) if err.kind() == io::ErrorKind::WouldBlock => {
+                if Instant::now() >= deadline {
+                    return Err(anyhow!(
+                        "no SSO redirect within {}s; run `signoz login --sso` again",
+                        SSO_TIMEOUT.as_secs()
+                    ));
+                }
+                thread::sleep(Duration::from_millis(100));
+                continue;
+            }
+            Err(err) => return Err(err).context("accept SSO redirect"),
+        };
+        stream.set_nonblocking(false)?;
+        stream.set_read_timeout(Some(Duration::from_secs(10)))?;
+        let mut line = String::new();
+        if BufReader::new(&stream).read_line(&mut line).is_err() {
+            continue;
+        }
+        let target = line.split_whitespace().nth(1).unwrap_or("/");
+        let (path, _) = target.split_once('?').unwrap_or((target, ""));
+        if path != callback_path {
+            let _ = stream.write_all(b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n");
+            continue;
+        }
+        let url = Url::parse(&format!("http://localhost{target}")).context("parse redirect")?;
+        let page = "<html><body>signoz: login complete, you can close this tab.</body></html>";
+        let _ = write!(
+            stream,
+            "HTTP/1.1 200 OK\r\nContent-Type: text/html\r\nContent-Length: {}\r\n\r\n{page}",
+            page.len()
+        );
+        return Ok(url.query_pairs().into_owned().collect());
+    }
+}
+
+fn open_url(url: &str) {
+    let result = if cfg!(target_os = "macos") {
+        Command::new("open").arg(url).status()
+    } else if cfg!(target_os = "windows") {
+        // Not `cmd /C start`: cmd.exe would split the URL at `&` and run
+        // the rest as a command.
+        Command::new("rundll32")
+            .args(["url.dll,FileProtocolHandler", url])
+            .status()
+    } else {
+        Command::new("xdg-open").arg(url).status()
+    };
+    if result.is_err() {
+        eprintln!("(could not launch a browser; open the URL manually)");
+    }
+}
@@ -0,0 +1,552 @@
+use anyhow::{anyhow, Context, Result};
+use clap::{builder::PossibleValuesParser, Arg, ArgAction, Command};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use serde_json::{json, Value};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::{self, File},
+    io::Read,
+    path::Path,
+};
+
+use crate::error::{ApiError, Failure};
+use crate::http::Body;
+use crate::objects::{self, item_id, item_path, Kind, KINDS};
+use crate::output::{self, OutputOptions};
+use crate::progress::Progress;
+use crate::{ids, merge, query, timeparse, ApiContext};
+
+/// Layout version written to the manifest; archives from a newer layout
+/// are refused rather than half restored.
+const FORMAT: u64 = 1;
+
+const PIPELINES_PATH: &str = "/api/v1/logs/pipelines";
+const PIPELINES_LATEST_PATH: &str = "/api/v1/logs/pipelines/latest";
+
+fn only_arg() -> Arg {
+    Arg::new("only")
+        .long("only")
+        .value_name("KINDS")
+        .value_delimiter(',')
+        .value_parser(PossibleValuesParser::new([
+            "channels",
+            "dashboards",
+            "views",
+            "rules",
+            "pipelines",
+        ]))
+        .help("Limit to these comma-separated kinds")
+}
+
+pub fn backup_command() -> Command {
+    Command::new("backup")
+        .about(
+            "Save dashboards, alert rules, channels, saved views, and log pipelines to a .tar.gz",
+        )
+        .long_about(
+            "Writes one JSON file per object (dashboards/, rules/, channels/, views/, and \
+             pipelines.json) and a manifest.json listing what was captured, with ids and \
+             names, to a gzipped tar. Bodies are saved as a create takes them, so the \
+             unpacked directory also works with `signoz apply -f`, and pipelines.json with \
+             `signoz pipelines apply -f`. Channel configs include their webhook URLs and \
+             keys: keep the archive private.",
+        )
+        .arg(
+            Arg::new("out")
+                .long("out")
+                .value_name("FILE")
+                .required(true)
+                .help("Archive to write, e.g. backup.tar.gz"),
+        )
+        .arg(only_arg())
+}
+
+pub fn restore_command() -> Command {
+    Command::new("restore")
+        .about("Recreate the objects in a `signoz backup` archive on this instance")
+        .long_about(
+            "Objects are matched to live ones by title, alert name, or name: matches are \
+             updated, the rest created. Backed-up ids that appear in restored objects (a \
+             dashboard linked from a rule, say) are rewritten to the ids they got here. \
+             Log pipelines replace the deployed set. Shows the plan and asks before \
+             changing anything.",
+        )
+        .arg(
+            Arg::new("file")
+                .value_name("FILE")
+                .required(true)
+                .help("Archive written by `signoz backup`"),
+        )
+        .arg(only_arg())
+        .arg(
+            Arg::new("dry-run")
+                .long("dry-run")
+                .action(ArgAction::SetTrue)
+                .help("Show the plan without changing anything"),
+        )
+        .arg(
+            Arg::new("yes")
+                .long("yes")
+                .short('y')
+                .action(ArgAction::SetTrue)
+                .conflicts_with("dry-run")
+                .help("Restore without asking"),
+        )
+}
+
+fn selected(matches: &clap::ArgMatches, kind: &str) -> bool {
+    matches
+        .get_many::<String>("only")
+        .is_none_or(|mut only| only.any(|k| k == kind))
+}
+
+pub fn handle_backup(
+    matches: &clap::ArgMatches,
+    ctx: &ApiContext,
+    opts: &OutputOptions,
+) -> Result<()> {
+    let out = Path::new(matches.get_one::<String>("out").expect("required"));
+    let now = query::now_millis();
+    let mut files: Vec<(String, Vec<u8>)> = Vec::new();
+    let mut objects = Vec::new();
+    let mut counts = serde_json::Map::new();
+    let mut skipped = Vec::new();
+
+    let progress = Progress::start("backing up");
+    for kind in KINDS.iter().filter(|k| selected(matches, k.name)) {
+        progress.set(format!("backing up {}", kind.name));
+        let Some(items) = objects::list(ctx, kind)? else {
+            eprintln!(
+                "warning: this instance has no {} endpoint; skipped",
+                kind.name
+            );
+            skipped.push(json!(kind.name));
+            continue;
+        };
+        let mut count = 0;
+        for item in &items {
+            let Some(id) = item_id(item) else {
+                continue;
+            };
+            let body = objects::body(kind, item);
+            let file = format!("{}/{}.json", kind.name, id.replace(['/', '\\'], "_"));
+            files.push((file.clone(), pretty(&body)?));
+            objects.push(json!({
+                "kind": kind.name,
+                "id": id,
+                "name": name(kind, &body),
+                "file": file,
+            }));
+            count += 1;
+        }
+        counts.insert(kind.name.to_string(), json!(count));
+    }
+    if selected(matches, "pipelines") {
+        progress.set("backing up pipelines");
+        let pipelines = deployed_pipelines(ctx)?;
+        counts.insert("pipelines".to_string(), json!(pipelines.len()));
+        if !pipelines.is_empty() {
+            files.push(("pipelines.json".to_string(), pretty(&json!(pipelines))?));
+        }
+    }
+    drop(progress);
+
+    let manifest = json!({
+        "format": FORMAT,
+        "created_at": timeparse::format_rfc3339(now),
+        "cli_version": env!("CARGO_PKG_VERSION"),
+        "base_url": ctx.base_url,
+        "counts": counts,
+        "skipped": skipped,
+        "objects": objects,
+    });
+    files.insert(0, ("manifest.json".to_string(), pretty(&manifest)?));
+    write_archive(out, &files, now / 1000)?;
+    output::print_value(
+        &json!({ "file": out.display().to_string(), "counts": counts }),
+        opts,
+    )
+}
+
+/// Written beside the target and renamed, readable by the owner only since
+/// channel configs hold credentials.
+fn write_archive(out: &Path, files: &[(String, Vec<u8>)], mtime: u64) -> Result<()> {
+    if let Some(dir) = out.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(dir).with_context(|| format!("create {}", dir.display()))?;
+    }
+    let partial = out.with_file_name(format!(
+        ".{}.partial",
+        out.file_name().unwrap_or_default().to_string_lossy()
+    ));
+    let written = (|| -> Result<()> {
+        let file = File::create(&partial)?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&partial, fs::Permissions::from_mode(0o600))?;
+        }
+        let mut archive = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+        for (name, bytes) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(bytes.len() as u64);
+            header.set_mode(0o600);
+            header.set_mtime(mtime);
+            header.set_cksum();
+            archive.append_data(&mut header, name, bytes.as_slice())?;
+        }
+        archive.into_inner()?.finish()?;
+        fs::rename(&partial, out)?;
+        Ok(())
+    })();
+    if let Err(err) = written {
+        let _ = fs::remove_file(&partial);
+        return Err(err).with_context(|| format!("write {}", out.display()));
+    }
+    Ok(())
+}
+
+struct Step<'a> {
+    kind: &'static Kind,
+    old_id: String,
+    name: String,
+    body: Value,
+    /// The live object this one replaces; created when `None`.
+    target: Option<&'a str>,
+}
+
+pub fn handle_restore(
+    matches: &clap::ArgMatches,
+    ctx: &ApiContext,
+    opts: &OutputOptions,
+) -> Result<()> {
+    let path = matches.get_one::<String>("file").expect("required");
+    let files = read_archive(path)?;
+    let manifest: Value = files
+        .get("manifest.json")
+        .ok_or_else(|| anyhow!("{path} has no manifest.json; not a `signoz backup` archive"))
+        .and_then(|raw| Ok(serde_json::from_slice(raw)?))
+        .with_context(|| format!("read {path}"))?;
+    let format = manifest["format"].as_u64().unwrap_or(0);
+    if format > FORMAT {
+        return Err(anyhow!(
+            "{path} uses backup format {format}; this signoz reads up to {FORMAT}, upgrade it"
+        ));
+    }
+
+    let mut live: HashMap<&str, Vec<(String, String)>> = HashMap::new();
+    let mut steps = Vec::new();
+    for kind in KINDS.iter().filter(|k| selected(matches, k.name)) {
+        let entries: Vec<&Value> = manifest["objects"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter(|o| o["kind"] == kind.name)
+            .collect();
+        if entries.is_empty() {
+            continue;
+        }
+        let current = objects::list(ctx, kind)?
+            .ok_or_else(|| anyhow!("this instance has no {} endpoint to restore to", kind.name))?;
+        live.insert(
+            kind.name,
+            current
+                .iter()
+                .filter_map(|item| Some((name(kind, &objects::body(kind, item)), item_id(item)?)))
+                .collect(),
+        );
+        for entry in entries {
+            let file = entry["file"].as_str().unwrap_or_default();
+            let raw = files
+                .get(file)
+                .ok_or_else(|| anyhow!("{path}: manifest lists {file}, which is missing"))?;
+            let body: Value =
+                serde_json::from_slice(raw).with_context(|| format!("parse {path}: {file}"))?;
+            steps.push((
+                kind,
+                entry["id"].as_str().unwrap_or_default().to_string(),
+                body,
+            ));
+        }
+    }
+    let steps: Vec<Step> = steps
+        .into_iter()
+        .map(|(kind, old_id, body)| {
+            let name = name(kind, &body);
+            let found: Vec<&str> = live[kind.name]
+                .iter()
+                .filter(|(n, _)| *n == name)
+                .map(|(_, id)| id.as_str())
+                .collect();
+            match found.as_slice() {
+                [] => Ok(Step {
+                    kind,
+                    old_id,
+                    name,
+                    body,
+                    target: None,
+                }),
+                [id] => Ok(Step {
+                    kind,
+                    old_id,
+                    name,
+                    body,
+                    target: Some(id),
+                }),
+                _ => Err(anyhow!(
+                    "{} \"{name}\" matches {} live objects; rename them so names are unique",
+                    kind.singular,
+                    found.len()
+                )),
+            }
+        })
+        .collect::<Result<_>>()?;
+    let pipelines: Option<Vec<Value>> = match files.get("pipelines.json") {
+        Some(raw) if selected(matches, "pipelines") => Some(
+            serde_json::from_slice(raw).with_context(|| format!("parse {path}: pipelines.json"))?,
+        ),
+        _ => None,
+    };
+
+    let plan: Vec<Value> = steps
+        .iter()
+        .map(|step| {
+            json!({
+                "kind": step.kind.singular,
+                "name": step.name,
+                "action": if step.target.is_some() { "update" } else { "create" },
+                "backup_id": step.old_id,
+                "id": step.target,
+            })
+        })
+        .collect();
+    for step in &steps {
+        match step.target {
+            Some(id) => eprintln!("  ~ {} \"{}\" ({id})", step.kind.singular, step.name),
+            None => eprintln!("  + {} \"{}\"", step.kind.singular, step.name),
+        }
+    }
+    if let Some(pipelines) = &pipelines {
+        eprintln!(
+            "  ~ log pipelines: the deployed set is replaced with {}",
+            pipelines.len()
+        );
+    }
+    let created = steps.iter().filter(|s| s.target.is_none()).count();
+    eprintln!(
+        "Restore: {created} to create, {} to update{}.",
+        steps.len() - created,
+        if pipelines.is_some() {
+            ", pipelines replaced"
+        } else {
+            ""
+        }
+    );
+    if steps.is_empty() && pipelines.is_none() {
+        return output::print_value(&json!([]), opts);
+    }
+    if matches.get_flag("dry-run") {
+        return output::print_value(&Value::Array(plan), opts);
+    }
+    if !matches.get_flag("yes") {
+        merge::confirm("Restore these objects?")?;
+    }
+
+    let mut id_map: HashMap<String, String> = HashMap::new();
+    let mut sent: Vec<(&Step, String, Value)> = Vec::new();
+    let mut summary = Vec::new();
+    let progress = Progress::start(format!("restoring {} objects", steps.len()));
+    for (done, step) in steps.iter().enumerate() {
+        progress.set(format!("restoring {}/{}", done + 1, steps.len()));
+        let body = remap(&step.body, &id_map);
+        let (method, item_path, action) = match step.target {
+            Some(id) => ("PUT", Some(item_path(step.kind, id)), "updated"),
+            None => ("POST", None, "created"),
+        };
+        let path = item_path.as_deref().unwrap_or(step.kind.path);
+        let response = ctx.execute(
+            method,
+            path,
+            &[],
+            Some(Body::Json(body.clone())),
+            Some("application/json"),
+        )?;
+        if response.status >= 400 {
+            return Err(ApiError::from_response(&response)).with_context(|| {
+                format!(
+                    "restore {} \"{}\" failed after {done} restored",
+                    step.kind.singular, step.name
+                )
+            });
+        }
+        let id = step
+            .target
+            .map(str::to_string)
+            .or_else(|| created_id(&response.body));
+        if let Some(id) = &id {
+            if !step.old_id.is_empty() && ids::is_uuid(&step.old_id) {
+                id_map.insert(step.old_id.clone(), id.clone());
+            }
+            sent.push((step, id.clone(), body));
+        }
+        summary.push(json!({
+            "kind": step.kind.singular,
+            "name": step.name,
+            "action": action,
+            "backup_id": step.old_id,
+            "id": id,
+        }));
+    }
+
+    // Objects restored before the ones they link to still carry the old
+    // ids; send those again now that every id is known.
+    for (step, id, body) in &sent {
+        let relinked = remap(&step.body, &id_map);
+        if relinked == *body {
+            continue;
+        }
+        progress.set(format!(
+            "relinking {} \"{}\"",
+            step.kind.singular, step.name
+        ));
+        let response = ctx.execute(
+            "PUT",
+            &item_path(step.kind, id),
+            &[],
+            Some(Body::Json(relinked)),
+            Some("application/json"),
+        )?;
+        if response.status >= 400 {
+            return Err(ApiError::from_response(&response)).with_context(|| {
+                format!("relink {} \"{}\" failed", step.kind.singular, step.name)
+            });
+        }
+    }
+
+    if let Some(pipelines) = pipelines {
+        progress.set("restoring pipelines");
+        let count = pipelines.len();
+        restore_pipelines(ctx, pipelines, &id_map)?;
+        summary.push(json!({ "kind": "pipelines", "action": "replaced", "count": count }));
+    }
+    drop(progress);
+    output::print_value(&Value::Array(summary), opts)
+}
+
+/// Saving pipelines replaces the deployed set; pipelines whose name is
+/// deployed keep that id, the rest are saved as new.
+fn restore_pipelines(
+    ctx: &ApiContext,
+    pipelines: Vec<Value>,
+    id_map: &HashMap<String, String>,
+) -> Result<()> {
+    let deployed = deployed_pipelines(ctx)?;
+    let pipelines: Vec<Value> = pipelines
+        .into_iter()
+        .enumerate()
+        .map(|(i, pipeline)| {
+            let mut pipeline = remap(&pipeline, id_map);
+            let name = pipeline.get("name").cloned();
+            if let Value::Object(map) = &mut pipeline {
+                map.retain(|key, _| !objects::SERVER_FIELDS.contains(&key.as_str()));
+                map.insert("orderId".to_string(), json!(i + 1));
+                if let Some(id) = deployed
+                    .iter()
+                    .find(|p| name.is_some() && p.get("name") == name.as_ref())
+                    .and_then(|p| p.get("id"))
+                {
+                    map.insert("id".to_string(), id.clone());
+                }
+            }
+            pipeline
+        })
+        .collect();
+    let response = ctx.execute(
+        "POST",
+        PIPELINES_PATH,
+        &[],
+        Some(Body::Json(json!({ "pipelines": pipelines }))),
+        Some("application/json"),
+    )?;
+    if response.status >= 400 {
+        return Err(ApiError::from_response(&response)).context("restore pipelines failed");
+    }
+    Ok(())
+}
+
+fn read_archive(path: &str) -> Result<BTreeMap<String, Vec<u8>>> {
+    let file = File::open(path).with_context(|| format!("open {path}"))?;
+    let mut archive = tar::Archive::new(GzDecoder::new(file));
+    let mut files = BTreeMap::new();
+    let entries = archive.entries().with_context(|| format!("read {path}"))?;
+    for entry in entries {
+        let mut entry = entry.with_context(|| format!("read {path}"))?;
+        let name = entry.path()?.to_string_lossy().into_owned();
+        let mut bytes = Vec::new();
+        entry
+            .read_to_end(&mut bytes)
+            .with_context(|| format!("read {path}: {name}"))?;
+        files.insert(name, bytes);
+    }
+    if files.is_empty() {
+        return Err(Failure::Usage(format!("{path} is empty")).into());
+    }
+    Ok(files)
+}
+
+/// The deployed pipelines; none when nothing was ever saved.
+fn deployed_pipelines(ctx: &ApiContext) -> Result<Vec<Value>> {
+    let response = ctx.execute("GET", PIPELINES_LATEST_PATH, &[], None, None)?;
+    if response.status == 404 {
+        return Ok(Vec::new());
+    }
+    if response.status >= 400 {
+        return Err(ApiError::from_response(&response)).context("get pipelines failed");
+    }
+    Ok(response
+        .body
+        .pointer("/data/pipelines")
+        .and_then(Value::as_array)
+        .cloned()
+        .unwrap_or_default())
+}
+
+fn name(kind: &Kind, body: &Value) -> String {
+    body.pointer(kind.key)
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// The id of a created object: `data` itself (saved views) or its
+/// `uuid`/`id`.
+fn created_id(response: &Value) -> Option<String> {
+    match response.get("data").unwrap_or(response) {
+        Value::String(id) if !id.is_empty() => Some(id.clone()),
+        data => item_id(data),
+    }
+}
+
+/// `value` with every backed-up id in `id_map` replaced by its new id
+/// wherever it appears in a string (ids, links, and URLs alike). Only UUIDs
+/// are mapped, so short numeric ids never rewrite unrelated text.
+fn remap(value: &Value, id_map: &HashMap<String, String>) -> Value {
+    match value {
+        Value::String(s) => Value::String(
+            id_map
+                .iter()
+                .filter(|(old, new)| old != new)
+                .fold(s.clone(), |s, (old, new)| s.replace(old.as_str(), new)),
+        ),
+        Value::Array(items) => Value::Array(items.iter().map(|v| remap(v, id_map)).collect()),
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(k, v)| (k.clone(), remap(v, id_map)))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+fn pretty(value: &Value) -> Result<Vec<u8>> {
+    Ok((serde_json::to_string_pretty(value)? + "\n").into_bytes())
+}
@@ -0,0 +1,194 @@
+use anyhow::{anyhow, Context, Result};
+use clap::{Arg, Command};
+use serde_json::{json, Value};
+use std::{
+    io::{self, BufRead, Write},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+};
+
+use crate::command_tree::{CommandTree, Operation};
+use crate::error::Failure;
+use crate::output::{self, OutputOptions};
+use crate::ApiContext;
+
+pub fn command() -> Command {
+    Command::new("batch")
+        .about("Run NDJSON commands from stdin, one result line per command")
+        .long_about(
+            "Reads lines like {\"resource\":\"rules\",\"op\":\"get-rule\",\"params\":{\"id\":\"42\"}} \
+             (optional \"body\") from stdin and prints one NDJSON result per line with \
+             line, status, and body (or error).",
+        )
+        .arg(
+            Arg::new("concurrency")
+                .long("concurrency")
+                .value_name("N")
+                .default_value("1")
+                .help("Commands in flight at once (results are printed as they finish)"),
+        )
+}
+
+pub fn handle(
+    matches: &clap::ArgMatches,
+    tree: &CommandTree,
+    ctx: &ApiContext,
+    opts: &OutputOptions,
+) -> Result<()> {
+    let concurrency: usize = matches
+        .get_one::<String>("concurrency")
+        .map(|v| v.parse())
+        .transpose()
+        .map_err(|_| anyhow!("invalid --concurrency"))?
+        .unwrap_or(1)
+        .max(1);
+    let lines: Vec<(usize, String)> = io::stdin()
+        .lock()
+        .lines()
+        .enumerate()
+        .map(|(i, line)| line.map(|l| (i + 1, l)))
+        .collect::<io::Result<Vec<_>>>()
+        .context("read stdin")?
+        .into_iter()
+        .filter(|(_, l)| !l.trim().is_empty())
+        .collect();
+
+    let cli = crate::build_cli(tree);
+    let next = AtomicUsize::new(0);
+    let failed = AtomicUsize::new(0);
+    let stdout = Mutex::new(io::stdout());
+    thread::scope(|scope| {
+        for _ in 0..concurrency.min(lines.len()) {
+            scope.spawn(|| {
+                while let Some((number, line)) = lines.get(next.fetch_add(1, Ordering::SeqCst)) {
+                    let mut result = match run_line(&cli, tree, ctx, line) {
+                        Ok((status, body)) => {
+                            if status >= 400 {
+                                failed.fetch_add(1, Ordering::SeqCst);
+                            }
+                            json!({ "status": status, "body": body })
+                        }
+                        Err(err) => {
+                            failed.fetch_add(1, Ordering::SeqCst);
+                            json!({ "error": format!("{err:#}") })
+                        }
+                    };
+                    result["line"] = json!(number);
+                    if let Some(normalizer) = &opts.normalize {
+                        if let Some(body) = result.get_mut("body") {
+                            normalizer.apply(body);
+                        }
+                    }
+                    let rendered = if opts.stable {
+                        output::stabilize(&result).to_string()
+                    } else {
+                        result.to_string()
+                    };
+                    if let Ok(mut out) = stdout.lock() {
+                        let _ = writeln!(out, "{rendered}");
+                    }
+                }
+            });
+        }
+    });
+
+    match failed.into_inner() {
+        0 => Ok(()),
+        n => Err(anyhow!("{n} of {} batch commands failed", lines.len())),
+    }
+}
+
+/// Parses one NDJSON command into the operation's own flags and runs it.
+fn run_line(
+    cli: &Command,
+    tree: &CommandTree,
+    ctx: &ApiContext,
+    line: &str,
+) -> Result<(u16, Value)> {
+    let entry: Value = serde_json::from_str(line).context("invalid JSON")?;
+    let (op, op_matches) = op_matches(cli, tree, &entry)?;
+    let (response, _) = crate::run_op(ctx, op, &op_matches)?;
+    Ok((response.status, response.body))
+}
+
+/// The operation an entry like `{"resource", "op", "params", "body"}` names,
+/// with its params and body parsed as that operation's flags. Params are
+/// keyed by flag or API name; `true` turns on a switch, and arrays repeat
+/// the flag.
+pub fn op_matches<'t>(
+    cli: &Command,
+    tree: &'t CommandTree,
+    entry: &Value,
+) -> Result<(&'t Operation, clap::ArgMatches)> {
+    let field = |name: &str| {
+        entry
+            .get(name)
+            .and_then(Value::as_str)
+            .ok_or_else(|| anyhow!("missing \"{name}\""))
+    };
+    let (resource, op_name) = (field("resource")?, field("op")?);
+    let op = crate::find_op(tree, resource, op_name)
+        .ok_or_else(|| anyhow!("unknown command {resource} {op_name}"))?;
+    let op_cmd = cli
+        .find_subcommand(resource)
+        .and_then(|r| r.find_subcommand(op_name))
+        .ok_or_else(|| anyhow!("unknown command {resource} {op_name}"))?;
+
+    let mut argv: Vec<String> = Vec::new();
+    if let Some(params) = entry.get("params") {
+        let params = params
+            .as_object()
+            .ok_or_else(|| anyhow!("\"params\" must be an object"))?;
+        for (name, value) in params {
+            let arg = op_cmd
+                .get_arguments()
+                .find(|a| a.get_id() == name.as_str() || a.get_long() == Some(name.as_str()))
+                .or_else(|| {
+                    let param = op.params.iter().find(|p| &p.param_name == name)?;
+                    op_cmd
+                        .get_arguments()
+                        .find(|a| a.get_id() == param.name.as_str())
+                })
+                .ok_or_else(|| anyhow!("unknown param \"{name}\" for {resource} {op_name}"))?;
+            let flag = format!("--{}", arg.get_long().unwrap_or(arg.get_id().as_str()));
+            if !arg.get_action().takes_values() {
+                if value.as_bool() == Some(true) {
+                    argv.push(flag);
+                }
+                continue;
+            }
+            let values = match value {
+                Value::Array(items) => items.iter().collect(),
+                other => vec![other],
+            };
+            for value in values {
+                argv.push(flag.clone());
+                argv.push(match value {
+                    Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                });
+            }
+        }
+    }
+    if let Some(body) = entry.get("body") {
+        argv.push("--body".to_string());
+        argv.push(match body {
+            Value::String(s) => s.clone(),
+            other => other.to_string(),
+        });
+    }
+
+    let op_matches = op_cmd
+        .clone()
+        .no_binary_name(true)
+        .try_get_matches_from(argv)
+        .map_err(|err| {
+            let message = err.to_string();
+            let first = message.lines().next().unwrap_or_default();
+            Failure::Usage(first.trim_start_matches("error: ").to_string())
+        })?;
+    Ok((op, op_matches))
+}
@@ -0,0 +1,197 @@
+use anyhow::{anyhow, Context, Result};
+use clap::{Arg, Command};
+use serde_json::{json, Map, Value};
+use std::{
+    collections::BTreeMap,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::http::Body;
+use crate::output::{self, OutputOptions};
+use crate::progress::Progress;
+use crate::query::{self, QUERY_RANGE_PATH};
+use crate::{interrupt, timeparse, ApiContext};
+
+pub fn command() -> Command {
+    Command::new("bench")
+        .about("Replay a query_range body under load and report latency percentiles, errors, and throughput")
+        .arg(
+            Arg::new("body")
+                .long("body")
+                .value_name("JSON|@file|@-")
+                .required(true)
+                .help("query_range request body, e.g. one saved by `query build`"),
+        )
+        .arg(
+            Arg::new("concurrency")
+                .long("concurrency")
+                .value_name("N")
+                .default_value("1")
+                .help("Requests in flight at once"),
+        )
+        .arg(
+            Arg::new("duration")
+                .long("duration")
+                .value_name("DURATION")
+                .default_value("30s")
+                .help("How long to keep sending"),
+        )
+        .arg(
+            Arg::new("requests")
+                .long("requests")
+                .value_name("N")
+                .help("Stop after N requests, if before --duration"),
+        )
+        .arg(
+            Arg::new("window")
+                .long("window")
+                .value_name("DURATION")
+                .help("Move each request's range to the last DURATION (e.g. 1h) instead of the body's start/end"),
+        )
+}
+
+struct Sample {
+    latency: Duration,
+    /// `None` when the request never got a response.
+    status: Option<u16>,
+}
+
+pub fn handle(matches: &clap::ArgMatches, ctx: &ApiContext, opts: &OutputOptions) -> Result<()> {
+    let arg = |name: &str| matches.get_one::<String>(name).map(String::as_str);
+    let raw = crate::read_body_input(arg("body").expect("required"))?;
+    let body: Value = serde_json::from_str(&raw).context("invalid --body JSON")?;
+    if !body.is_object() {
+        return Err(anyhow!("--body must be a query_range JSON object"));
+    }
+    let concurrency: usize = arg("concurrency")
+        .unwrap_or("1")
+        .parse()
+        .ok()
+        .filter(|n| *n > 0)
+        .ok_or_else(|| anyhow!("invalid --concurrency"))?;
+    let duration = Duration::from_millis(timeparse::parse_duration_ms(
+        arg("duration").unwrap_or("30s"),
+    )?);
+    let max_requests: Option<usize> = arg("requests")
+        .map(|v| v.parse().map_err(|_| anyhow!("invalid --requests")))
+        .transpose()?;
+    let window = arg("window")
+        .map(timeparse::parse_duration_ms)
+        .transpose()?;
+
+    query::warn_if_heavy(&body);
+    eprintln!(
+        "Sending query_range with {concurrency} worker(s) for {}s; Ctrl-C stops early.",
+        duration.as_secs()
+    );
+    interrupt::graceful();
+    let started = Instant::now();
+    let deadline = started + duration;
+    let issued = AtomicUsize::new(0);
+    let samples = Mutex::new(Vec::new());
+    let progress = Progress::start("benchmarking");
+    thread::scope(|scope| {
+        for _ in 0..concurrency {
+            scope.spawn(|| loop {
+                if Instant::now() >= deadline || interrupt::requested() {
+                    return;
+                }
+                if max_requests.is_some_and(|max| issued.fetch_add(1, Ordering::SeqCst) >= max) {
+                    return;
+                }
+                let mut body = body.clone();
+                if let Some(window) = window {
+                    let end = query::now_millis();
+                    body["start"] = json!(end.saturating_sub(window));
+                    body["end"] = json!(end);
+                }
+                let sent = Instant::now();
+                let status = ctx
+                    .execute(
+                        "POST",
+                        QUERY_RANGE_PATH,
+                        &[],
+                        Some(Body::Json(body)),
+                        Some("application/json"),
+                    )
+                    .ok()
+                    .map(|response| response.status);
+                let sample = Sample {
+                    latency: sent.elapsed(),
+                    status,
+                };
+                if let Ok(mut samples) = samples.lock() {
+                    samples.push(sample);
+                    progress.set(format!("{} requests", samples.len()));
+                }
+            });
+        }
+    });
+    drop(progress);
+    let elapsed = started.elapsed();
+    let samples = samples.into_inner().unwrap_or_default();
+
+    let report = summarize(&samples, elapsed, concurrency);
+    output::print_value(&report, opts)?;
+    if !samples.is_empty() && report["errors"] == json!(samples.len()) {
+        return Err(anyhow!("all {} requests failed", samples.len()));
+    }
+    Ok(())
+}
+
+/// Counts per status (`error` for no response) and nearest-rank latency
+/// percentiles over the successful requests.
+fn summarize(samples: &[Sample], elapsed: Duration, concurrency: usize) -> Value {
+    let mut statuses: BTreeMap<String, usize> = BTreeMap::new();
+    let mut ok: Vec<f64> = Vec::new();
+    for sample in samples {
+        let key = sample
+            .status
+            .map_or_else(|| "error".to_string(), |s| s.to_string());
+        *statuses.entry(key).or_default() += 1;
+        if sample.status.is_some_and(|s| s < 400) {
+            ok.push(sample.latency.as_secs_f64() * 1000.0);
+        }
+    }
+    ok.sort_by(f64::total_cmp);
+    let errors = samples.len() - ok.len();
+    let round = |ms: f64| (ms * 100.0).round() / 100.0;
+    let percentile = |p: f64| {
+        let rank = ((p / 100.0) * ok.len() as f64).ceil() as usize;
+        round(ok[rank.clamp(1, ok.len()) - 1])
+    };
+    let latency = if ok.is_empty() {
+        Value::Null
+    } else {
+        let mut latency = Map::new();
+        latency.insert("min".into(), json!(round(ok[0])));
+        for p in [50.0, 90.0, 95.0, 99.0] {
+            latency.insert(format!("p{p}"), json!(percentile(p)));
+        }
+        latency.insert("max".into(), json!(round(ok[ok.len() - 1])));
+        latency.insert(
+            "mean".into(),
+            json!(round(ok.iter().sum::<f64>() / ok.len() as f64)),
+        );
+        Value::Object(latency)
+    };
+    let secs = elapsed.as_secs_f64().max(f64::EPSILON);
+    json!({
+        "concurrency": concurrency,
+        "duration_s": round(secs),
+        "requests": samples.len(),
+        "errors": errors,
+        "error_rate": match samples.len() {
+            0 => 0.0,
+            n => round(errors as f64 * 100.0 / n as f64) / 100.0,
+        },
+        "throughput_rps": round(samples.len() as f64 / secs),
+        "latency_ms": latency,
+        "status_codes": statuses,
+    })
+}
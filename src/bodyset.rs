@@ -0,0 +1,196 @@
+use anyhow::{anyhow, Context, Result};
+use clap::{Arg, ArgAction};
+use serde_json::{json, Value};
+use std::{fs, io::Read};
+
+const FLAGS: [&str; 3] = ["set", "set-json", "set-file"];
+
+/// `--set`, `--set-json`, and `--set-file` for operations with a JSON body.
+pub fn args() -> Vec<Arg> {
+    let arg = |id: &'static str, value_name: &'static str, help: &'static str| {
+        Arg::new(id)
+            .long(id)
+            .value_name(value_name)
+            .action(ArgAction::Append)
+            .help(help)
+    };
+    vec![
+        arg(
+            "set",
+            "PATH=VALUE",
+            "Set a body field; true/false/null and numbers are typed, anything else is a string (repeatable)",
+        ),
+        arg(
+            "set-json",
+            "PATH=JSON",
+            "Set a body field to a JSON value (repeatable)",
+        ),
+        arg(
+            "set-file",
+            "PATH=@FILE",
+            "Set a body field to a file's contents as a string; @- reads stdin (repeatable)",
+        ),
+    ]
+}
+
+pub fn present(matches: &clap::ArgMatches) -> bool {
+    FLAGS
+        .iter()
+        .any(|id| matches!(matches.try_get_many::<String>(id), Ok(Some(_))))
+}
+
+/// `base` (or `{}`) with every `--set*` flag applied in command-line order.
+/// Paths are dot-separated keys with `[N]` to index an array and `[]` to
+/// append; `\.` is a literal dot, as in `filters.attrs.service\.name`.
+pub fn apply(base: Option<Value>, matches: &clap::ArgMatches) -> Result<Value> {
+    let mut flags = Vec::new();
+    for id in FLAGS {
+        let (Some(indices), Some(values)) =
+            (matches.indices_of(id), matches.get_many::<String>(id))
+        else {
+            continue;
+        };
+        flags.extend(indices.zip(values).map(|(index, raw)| (index, id, raw)));
+    }
+    flags.sort_by_key(|(index, _, _)| *index);
+
+    let mut body = base.unwrap_or_else(|| json!({}));
+    for (_, id, raw) in flags {
+        let (path, raw_value) = raw
+            .split_once('=')
+            .ok_or_else(|| anyhow!("--{id} expects PATH=VALUE, got {raw}"))?;
+        let value = match id {
+            "set" => scalar(raw_value),
+            "set-json" => serde_json::from_str(raw_value)
+                .with_context(|| format!("--set-json {path}: invalid JSON"))?,
+            _ => Value::String(read_file(raw_value)?),
+        };
+        let segments = parse_path(path).with_context(|| format!("--{id} {raw}"))?;
+        set(&mut body, &segments, value, path)?;
+    }
+    Ok(body)
+}
+
+enum Segment {
+    Key(String),
+    Index(usize),
+    Append,
+}
+
+fn parse_path(raw: &str) -> Result<Vec<Segment>> {
+    let mut out = Vec::new();
+    let mut key = String::new();
+    let mut after_bracket = false;
+    let mut chars = raw.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => key.push(chars.next().unwrap_or('\\')),
+            '.' => {
+                if key.is_empty() && !after_bracket {
+                    return Err(anyhow!("empty key in path"));
+                }
+                if !key.is_empty() {
+                    out.push(Segment::Key(std::mem::take(&mut key)));
+                }
+                after_bracket = false;
+                continue;
+            }
+            '[' => {
+                if !key.is_empty() {
+                    out.push(Segment::Key(std::mem::take(&mut key)));
+                }
+                let inner: String = chars.by_ref().take_while(|c| *c != ']').collect();
+                out.push(match inner.as_str() {
+                    "" => Segment::Append,
+                    n => Segment::Index(
+                        n.parse()
+                            .map_err(|_| anyhow!("array index must be a number, got [{n}]"))?,
+                    ),
+                });
+                after_bracket = true;
+                continue;
+            }
+            c => key.push(c),
+        }
+        after_bracket = false;
+    }
+    if !key.is_empty() {
+        out.push(Segment::Key(key));
+    } else if !after_bracket {
+        return Err(anyhow!("empty key in path"));
+    }
+    Ok(out)
+}
+
+/// Creates missing objects and arrays along the way; an index may point at
+/// an existing element or one past the end.
+fn set(target: &mut Value, path: &[Segment], value: Value, full: &str) -> Result<()> {
+    let Some((segment, rest)) = path.split_first() else {
+        *target = value;
+        return Ok(());
+    };
+    match segment {
+        Segment::Key(key) => {
+            if target.is_null() {
+                *target = json!({});
+            }
+            let map = target
+                .as_object_mut()
+                .ok_or_else(|| anyhow!("{full}: cannot set {key} on a non-object"))?;
+            set(
+                map.entry(key.clone()).or_insert(Value::Null),
+                rest,
+                value,
+                full,
+            )
+        }
+        Segment::Index(_) | Segment::Append => {
+            if target.is_null() {
+                *target = json!([]);
+            }
+            let items = target
+                .as_array_mut()
+                .ok_or_else(|| anyhow!("{full}: cannot index a non-array"))?;
+            let index = match segment {
+                Segment::Index(index) => *index,
+                _ => items.len(),
+            };
+            if index > items.len() {
+                return Err(anyhow!(
+                    "{full}: index {index} is past the end of a {}-element array",
+                    items.len()
+                ));
+            }
+            if index == items.len() {
+                items.push(Value::Null);
+            }
+            set(&mut items[index], rest, value, full)
+        }
+    }
+}
+
+fn scalar(raw: &str) -> Value {
+    match raw {
+        "true" => return Value::Bool(true),
+        "false" => return Value::Bool(false),
+        "null" => return Value::Null,
+        _ => {}
+    }
+    if let Ok(n) = raw.parse::<i64>() {
+        return json!(n);
+    }
+    match raw.parse::<f64>() {
+        Ok(f) if f.is_finite() => json!(f),
+        _ => Value::String(raw.to_string()),
+    }
+}
+
+fn read_file(raw: &str) -> Result<String> {
+    let path = raw.strip_prefix('@').unwrap_or(raw);
+    if path == "-" {
+        let mut buf = String::new();
+        std::io::stdin().read_to_string(&mut buf)?;
+        return Ok(buf);
+    }
+    fs::read_to_string(path).with_context(|| format!("read {path}"))
+}
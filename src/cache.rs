@@ -0,0 +1,138 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::config;
+
+/// Caps on the cache directory; past either, the entries stored longest ago
+/// are removed.
+const MAX_BYTES: u64 = 64 * 1024 * 1024;
+const MAX_ENTRIES: usize = 2000;
+
+/// A cached GET response and the validators to revalidate it with.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Entry {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    /// When the body was fetched or last confirmed unchanged (unix secs).
+    pub stored_at: u64,
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub content_type: String,
+    pub body: String,
+}
+
+impl Entry {
+    pub fn is_fresh(&self, ttl: Duration) -> bool {
+        now_secs().saturating_sub(self.stored_at) < ttl.as_secs()
+    }
+}
+
+fn cache_dir() -> Result<PathBuf> {
+    Ok(config::config_dir()?.join("cache"))
+}
+
+/// One file per method, URL, and credential, so responses never cross
+/// API keys.
+pub fn key(method: &str, url: &str, credential: &str) -> String {
+    ring::digest::digest(
+        &ring::digest::SHA256,
+        format!("{method}\n{url}\n{credential}").as_bytes(),
+    )
+    .as_ref()
+    .iter()
+    .map(|b| format!("{b:02x}"))
+    .collect()
+}
+
+/// The entry for `key`; a missing or unreadable one is a miss.
+pub fn load(key: &str) -> Option<Entry> {
+    let raw = fs::read_to_string(cache_dir().ok()?.join(format!("{key}.json"))).ok()?;
+    serde_json::from_str(&raw).ok()
+}
+
+pub fn store(key: &str, entry: &Entry) -> Result<()> {
+    let dir = cache_dir()?;
+    // Bodies can hold anything the credential can read, so neither the
+    // directory nor the files are ever readable by others, even briefly.
+    let mut builder = fs::DirBuilder::new();
+    builder.recursive(true);
+    let mut options = OpenOptions::new();
+    options.create(true).write(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{DirBuilderExt, OpenOptionsExt};
+        builder.mode(0o700);
+        options.mode(0o600);
+    }
+    builder.create(&dir).context("create cache dir")?;
+    let path = dir.join(format!("{key}.json"));
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        // Files from before this was private too.
+        if let Ok(meta) = fs::metadata(&path) {
+            if meta.permissions().mode() & 0o077 != 0 {
+                fs::set_permissions(&path, fs::Permissions::from_mode(0o600))
+                    .context("restrict response cache")?;
+            }
+        }
+    }
+    let mut file = options.open(&path).context("write response cache")?;
+    file.write_all(serde_json::to_string(entry)?.as_bytes())
+        .context("write response cache")?;
+    drop(file);
+    evict(&dir)
+}
+
+/// Keeps the directory under [`MAX_BYTES`] and [`MAX_ENTRIES`], oldest
+/// entries first; a single body over the size cap is not kept at all.
+fn evict(dir: &Path) -> Result<()> {
+    let mut entries: Vec<(SystemTime, u64, PathBuf)> = fs::read_dir(dir)
+        .context("read cache dir")?
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let meta = entry.metadata().ok()?;
+            let path = entry.path();
+            (meta.is_file() && path.extension().is_some_and(|ext| ext == "json"))
+                .then(|| (meta.modified().unwrap_or(UNIX_EPOCH), meta.len(), path))
+        })
+        .collect();
+    let mut bytes: u64 = entries.iter().map(|(_, size, _)| size).sum();
+    let mut count = entries.len();
+    entries.sort_by_key(|(modified, _, _)| *modified);
+    for (_, size, path) in entries {
+        if bytes <= MAX_BYTES && count <= MAX_ENTRIES {
+            break;
+        }
+        // Another run evicting at the same time may have got there first.
+        match fs::remove_file(&path) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => {
+                return Err(err).with_context(|| format!("remove {}", path.display()));
+            }
+            _ => {}
+        }
+        bytes -= size;
+        count -= 1;
+    }
+    Ok(())
+}
+
+/// Marks the entry as just confirmed by a 304.
+pub fn touch(key: &str, mut entry: Entry) -> Result<Entry> {
+    entry.stored_at = now_secs();
+    store(key, &entry)?;
+    Ok(entry)
+}
+
+pub fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
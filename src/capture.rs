@@ -0,0 +1,266 @@
+use anyhow::{Context, Result};
+use serde_json::{json, Value};
+use std::{
+    env,
+    fs::{self, File},
+    io::Write,
+    path::PathBuf,
+    sync::{Arc, OnceLock},
+    time::Instant,
+};
+use url::Url;
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipWriter};
+
+use crate::config;
+use crate::http::{self, Cassette};
+use crate::query;
+use crate::timeparse;
+
+const REDACTED: &str = "<redacted>";
+
+/// Flags whose values are credentials.
+pub const SECRET_FLAGS: &[&str] = &["--api-key", "--token", "--password"];
+
+/// Field names (lowercased, `_`/`-` removed) that hold webhook URLs or
+/// routing keys in channel configs.
+const SECRET_FIELDS: &[&str] = &[
+    "apiurl",
+    "slackapiurl",
+    "webhookurl",
+    "routingkey",
+    "servicekey",
+];
+
+struct Active {
+    path: PathBuf,
+    args: Vec<String>,
+    started_at: u64,
+    started: Instant,
+    cassette: Arc<Cassette>,
+}
+
+static ACTIVE: OnceLock<Active> = OnceLock::new();
+
+/// Starts `--capture FILE`: HTTP traffic is kept in memory and written with
+/// the command line, version, and config to a zip by [`finish`].
+pub fn start(path: &str) -> Arc<Cassette> {
+    let cassette = Arc::new(Cassette::capture());
+    let _ = ACTIVE.set(Active {
+        path: path.into(),
+        args: env::args().skip(1).collect(),
+        started_at: query::now_millis(),
+        started: Instant::now(),
+        cassette: cassette.clone(),
+    });
+    cassette
+}
+
+/// Writes the bundle if `--capture` was given, recording how the command
+/// ended. Failures are reported on stderr so they never mask `result`.
+pub fn finish(result: &Result<()>) {
+    let Some(active) = ACTIVE.get() else {
+        return;
+    };
+    match write_bundle(active, result) {
+        Ok(()) => eprintln!("wrote capture bundle {}", active.path.display()),
+        Err(err) => eprintln!("error: capture bundle: {err:#}"),
+    }
+}
+
+fn write_bundle(active: &Active, result: &Result<()>) -> Result<()> {
+    let meta = json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "os": env::consts::OS,
+        "arch": env::consts::ARCH,
+        "args": redact_args(&active.args),
+        "started_at": timeparse::format_rfc3339(active.started_at),
+        "elapsed_ms": active.started.elapsed().as_millis() as u64,
+        "result": match result {
+            Ok(()) => json!({ "ok": true }),
+            Err(err) => json!({ "ok": false, "error": format!("{err:#}") }),
+        },
+    });
+    // The cassette redacts each interaction as it is recorded.
+    let transcript = active.cassette.transcript()?;
+
+    let file =
+        File::create(&active.path).with_context(|| format!("create {}", active.path.display()))?;
+    let mut zip = ZipWriter::new(file);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    let mut add = |name: &str, text: String| -> Result<()> {
+        zip.start_file(name, options)?;
+        zip.write_all(text.as_bytes())?;
+        Ok(())
+    };
+    add("meta.json", serde_json::to_string_pretty(&meta)?)?;
+    add(
+        "transcript.json",
+        serde_json::to_string_pretty(&transcript)?,
+    )?;
+    if let Some(config) = config_snapshot()? {
+        add("config.toml", config)?;
+    }
+    zip.finish()?;
+    Ok(())
+}
+
+/// Field names holding credentials. A bare `key` is left alone: query
+/// bodies use it for attribute names; `apiKey`, `secretKey` and the like
+/// are secret.
+fn is_secret(name: &str) -> bool {
+    let name = name.to_lowercase().replace(['_', '-'], "");
+    [
+        "password",
+        "secret",
+        "token",
+        "apikey",
+        "jwt",
+        "cookie",
+        "authorization",
+    ]
+    .iter()
+    .any(|part| name.contains(part))
+        || (name.ends_with("key") && name != "key")
+        || SECRET_FIELDS.contains(&name.as_str())
+}
+
+pub fn redact_args(args: &[String]) -> Vec<String> {
+    let mut out = Vec::with_capacity(args.len());
+    let mut hide_next = false;
+    for arg in args {
+        if std::mem::take(&mut hide_next) {
+            out.push(REDACTED.to_string());
+            continue;
+        }
+        if let Some((flag, _)) = arg
+            .split_once('=')
+            .filter(|(f, _)| SECRET_FLAGS.contains(f))
+        {
+            out.push(format!("{flag}={REDACTED}"));
+            continue;
+        }
+        hide_next = SECRET_FLAGS.contains(&arg.as_str());
+        out.push(redact_header_arg(arg));
+    }
+    out
+}
+
+/// `--header 'SIGNOZ-API-KEY: x'` values carry credentials too.
+fn redact_header_arg(arg: &str) -> String {
+    match arg.split_once(':') {
+        Some((name, _)) if is_secret_header(arg) => format!("{name}: {REDACTED}"),
+        _ => arg.to_string(),
+    }
+}
+
+/// Whether a `NAME: VALUE` argument is a credential header.
+pub fn is_secret_header(arg: &str) -> bool {
+    arg.split_once(':')
+        .is_some_and(|(name, _)| http::is_secret_header(name))
+}
+
+/// Masks secret query parameters, body fields, and headers of a recorded
+/// request/response pair.
+pub fn redact_interaction(interaction: &mut Value) {
+    if let Some(Value::String(raw)) = interaction.get_mut("url") {
+        if let Ok(mut url) = Url::parse(raw) {
+            let pairs: Vec<(String, String)> = url
+                .query_pairs()
+                .map(|(k, v)| {
+                    let v = if is_secret(&k) {
+                        REDACTED.into()
+                    } else {
+                        v.into_owned()
+                    };
+                    (k.into_owned(), v)
+                })
+                .collect();
+            if !pairs.is_empty() {
+                url.query_pairs_mut().clear().extend_pairs(pairs);
+            }
+            *raw = url.to_string();
+        }
+    }
+    for key in ["request_body", "body"] {
+        if let Some(Value::String(raw)) = interaction.get_mut(key) {
+            if let Ok(mut body) = serde_json::from_str::<Value>(raw) {
+                redact_json(&mut body);
+                *raw = body.to_string();
+            }
+        }
+    }
+    for header in interaction
+        .get_mut("headers")
+        .and_then(Value::as_array_mut)
+        .into_iter()
+        .flatten()
+    {
+        let secret = header[0]
+            .as_str()
+            .is_some_and(|name| is_secret(name) || http::is_secret_header(name));
+        if secret {
+            header[1] = json!(REDACTED);
+        }
+    }
+}
+
+/// A body with its secret fields masked, for debug output; text that is
+/// not JSON is returned as is.
+pub fn redact_body(raw: &str) -> String {
+    match serde_json::from_str::<Value>(raw) {
+        Ok(mut body) if body.is_object() || body.is_array() => {
+            redact_json(&mut body);
+            body.to_string()
+        }
+        _ => raw.to_string(),
+    }
+}
+
+fn redact_json(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map.iter_mut() {
+                if is_secret(key) && !child.is_object() && !child.is_array() {
+                    *child = json!(REDACTED);
+                } else {
+                    redact_json(child);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact_json),
+        // Channel configs are stored as JSON inside a string.
+        Value::String(raw) if raw.starts_with('{') => {
+            if let Ok(mut inner) = serde_json::from_str::<Value>(raw) {
+                redact_json(&mut inner);
+                *raw = inner.to_string();
+            }
+        }
+        _ => {}
+    }
+}
+
+/// The config file with credential values replaced; `None` without one.
+fn config_snapshot() -> Result<Option<String>> {
+    let path = config::config_path()?;
+    if !path.exists() {
+        return Ok(None);
+    }
+    let raw = fs::read_to_string(&path).context("read config file")?;
+    let Ok(mut config) = raw.parse::<toml::Table>() else {
+        return Ok(Some(
+            "# config file is not valid TOML; omitted\n".to_string(),
+        ));
+    };
+    redact_toml(&mut config);
+    Ok(Some(toml::to_string_pretty(&config)?))
+}
+
+fn redact_toml(table: &mut toml::Table) {
+    for (key, value) in table.iter_mut() {
+        match value {
+            toml::Value::Table(child) => redact_toml(child),
+            _ if is_secret(key) => *value = toml::Value::String(REDACTED.to_string()),
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use anyhow::{anyhow, Context, Result};
+use clap::{Arg, Command};
+use serde_json::{json, Value};
+use std::fs;
+
+use crate::error::ApiError;
+use crate::http::Body;
+use crate::ids;
+use crate::output::{self, OutputOptions};
+use crate::ApiContext;
+
+const CHANNELS_PATH: &str = "/api/v1/channels";
+/// Sends a test notification through a receiver config without saving it.
+const TEST_PATH: &str = "/api/v1/testChannel";
+
+pub fn extend(cmd: Command) -> Command {
+    cmd.subcommand(
+        Command::new("test")
+            .about("Send a test notification through a channel and report whether it was delivered")
+            .arg(
+                Arg::new("id")
+                    .required_unless_present("from-file")
+                    .help("Channel id"),
+            )
+            .arg(
+                Arg::new("from-file")
+                    .long("from-file")
+                    .value_name("FILE")
+                    .conflicts_with("id")
+                    .help("Test a channel definition (YAML or JSON) before creating it"),
+            ),
+    )
+}
+
+pub fn handle(
+    op: &str,
+    matches: &clap::ArgMatches,
+    ctx: &ApiContext,
+    opts: &OutputOptions,
+) -> Option<Result<()>> {
+    match op {
+        "test" => Some(handle_test(matches, ctx, opts)),
+        _ => None,
+    }
+}
+
+/// The API answers the test request only after Alertmanager has tried the
+/// receiver, so its status is the delivery result.
+fn handle_test(matches: &clap::ArgMatches, ctx: &ApiContext, opts: &OutputOptions) -> Result<()> {
+    let (id, receiver) = match matches.get_one::<String>("from-file") {
+        Some(file) => {
+            let raw = fs::read_to_string(file).with_context(|| format!("read {file}"))?;
+            let receiver: Value =
+                serde_yaml::from_str(&raw).with_context(|| format!("parse {file}"))?;
+            (Value::Null, receiver)
+        }
+        None => {
+            let id = matches
+                .get_one::<String>("id")
+                .ok_or_else(|| anyhow!("missing channel id"))?;
+            let id = ids::expand(&ctx.base_url, CHANNELS_PATH, id)?;
+            (json!(id), fetch_receiver(ctx, &id)?)
+        }
+    };
+    let name = receiver.get("name").cloned().unwrap_or(Value::Null);
+    let kind = receiver
+        .as_object()
+        .into_iter()
+        .flatten()
+        .find_map(|(key, _)| key.strip_suffix("_configs"))
+        .map_or(Value::Null, |kind| json!(kind));
+
+    let response = ctx.execute(
+        "POST",
+        TEST_PATH,
+        &[],
+        Some(Body::Json(receiver)),
+        Some("application/json"),
+    )?;
+    let delivered = response.status < 400;
+    let error = (!delivered).then(|| {
+        response
+            .body
+            .get("error")
+            .cloned()
+            .unwrap_or_else(|| response.body.clone())
+    });
+    output::print_value(
+        &json!({
+            "id": id,
+            "name": name,
+            "type": kind,
+            "delivered": delivered,
+            "status": response.status,
+            "error": error,
+        }),
+        opts,
+    )?;
+    if !delivered {
+        return Err(ApiError::from_response(&response)).context("test notification failed");
+    }
+    Ok(())
+}
+
+/// A saved channel keeps its receiver config (`{name, slack_configs: [..]}`
+/// and so on) as a JSON string in `data`.
+fn fetch_receiver(ctx: &ApiContext, id: &str) -> Result<Value> {
+    let path = format!("{CHANNELS_PATH}/{}", urlencoding::encode(id));
+    let response = ctx.execute("GET", &path, &[], None, None)?;
+    if response.status >= 400 {
+        return Err(ApiError::from_response(&response))
+            .with_context(|| format!("get channel {id} failed"));
+    }
+    let channel = response.body.get("data").unwrap_or(&response.body);
+    match channel.get("data") {
+        Some(Value::String(raw)) => {
+            serde_json::from_str(raw).with_context(|| format!("parse channel {id} config"))
+        }
+        Some(config @ Value::Object(_)) => Ok(config.clone()),
+        _ => Err(anyhow!("channel {id} has no receiver config")),
+    }
+}
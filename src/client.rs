@@ -0,0 +1,207 @@
+use anyhow::{anyhow, Result};
+use clap::Command;
+use serde_json::{json, Value};
+use std::time::Duration;
+
+use crate::auth::{self, AuthMode};
+use crate::command_tree::{self, CommandTree};
+use crate::config;
+use crate::hooks::Hooks;
+use crate::http::{Body, ClientOptions, HttpResponse};
+use crate::ApiContext;
+
+/// A SigNoz API client for other Rust tools: the command tree, credentials,
+/// and request handling of the `signoz` binary without shelling out to it.
+///
+/// ```no_run
+/// use serde_json::json;
+///
+/// let client = signoz_cli::Client::from_env()?;
+/// let dashboards = client.execute_op("dashboards", "list-dashboards", json!({}))?;
+/// let alert = client.execute_op("alerts", "get-alert", json!({"id": "42"}))?;
+/// println!("{} {}", dashboards.status, alert.body);
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub struct Client {
+    ctx: ApiContext,
+    tree: CommandTree,
+    cli: Command,
+}
+
+/// Settings for [`Client`]; anything left unset is resolved like the
+/// matching global flag: from the profile, then the environment.
+#[derive(Default)]
+pub struct ClientBuilder {
+    profile: Option<String>,
+    base_url: Option<String>,
+    api_key: Option<String>,
+    token: Option<String>,
+    auth: Option<AuthMode>,
+    headers: Vec<(String, String)>,
+    timeout: Option<Duration>,
+    retries: u32,
+}
+
+impl ClientBuilder {
+    /// A `[profiles.<name>]` entry of the config file.
+    pub fn profile(mut self, name: impl Into<String>) -> Self {
+        self.profile = Some(name.into());
+        self
+    }
+
+    pub fn base_url(mut self, url: impl Into<String>) -> Self {
+        self.base_url = Some(url.into());
+        self
+    }
+
+    pub fn api_key(mut self, key: impl Into<String>) -> Self {
+        self.api_key = Some(key.into());
+        self
+    }
+
+    /// A bearer token; without a token or API key, the login saved by
+    /// `signoz login` for the instance is used.
+    pub fn token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+
+    /// Which credential to send, as `--auth`; by default the API key, then
+    /// the token when the key is refused.
+    pub fn auth(mut self, mode: AuthMode) -> Self {
+        self.auth = Some(mode);
+        self
+    }
+
+    /// An extra header sent with every request (repeatable).
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Extra attempts for 429 and 5xx responses.
+    pub fn retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Reads the config file and command tree, and refreshes a stored login
+    /// if one is needed.
+    pub fn build(self) -> Result<Client> {
+        let config = config::load()?;
+        let tree = command_tree::load_command_tree(&[])?;
+        let client_opts = ClientOptions {
+            timeout_secs: self.timeout.map(|t| t.as_secs().max(1)),
+            retries: self.retries,
+            hooks: Hooks {
+                pre_request: config.hooks.pre_request.clone(),
+                post_response: config.hooks.post_response.clone(),
+            },
+            ..ClientOptions::default()
+        };
+        let flags = crate::CredentialFlags {
+            profile: self.profile,
+            base_url: self.base_url,
+            api_key: self.api_key,
+            token: self.token,
+            token_auth: self.auth == Some(AuthMode::Token),
+        };
+        let crate::Credentials {
+            base_url,
+            api_key,
+            token,
+        } = crate::resolve_credentials(&config, &tree, &flags, &client_opts)?;
+        let auth_mode = self
+            .auth
+            .unwrap_or_else(|| auth::parse_auth_mode(None, api_key.as_ref(), token.as_ref()));
+
+        let cli = crate::build_cli(&tree);
+        Ok(Client {
+            ctx: ApiContext {
+                base_url,
+                api_key,
+                token,
+                auth_mode,
+                headers: self.headers,
+                client_opts,
+                defaults: config.defaults,
+            },
+            tree,
+            cli,
+        })
+    }
+}
+
+impl Client {
+    pub fn builder() -> ClientBuilder {
+        ClientBuilder::default()
+    }
+
+    /// A client configured like `signoz` with no flags: `SIGNOZ_PROFILE`,
+    /// `SIGNOZ_API_URL`, `SIGNOZ_API_KEY`, and so on.
+    pub fn from_env() -> Result<Client> {
+        ClientBuilder::default().build()
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.ctx.base_url
+    }
+
+    /// The operations [`Client::execute_op`] can run.
+    pub fn tree(&self) -> &CommandTree {
+        &self.tree
+    }
+
+    /// Runs `signoz <resource> <op>`. `params` is an object keyed by flag or
+    /// API name, as in `signoz batch`: `true` turns on a switch (such as
+    /// `all` to follow pages), and arrays repeat the flag. Error statuses
+    /// come back as responses, not errors.
+    pub fn execute_op(&self, resource: &str, op: &str, params: Value) -> Result<HttpResponse> {
+        self.run(json!({"resource": resource, "op": op, "params": params}))
+    }
+
+    /// [`Client::execute_op`] with a request body.
+    pub fn execute_op_with_body(
+        &self,
+        resource: &str,
+        op: &str,
+        params: Value,
+        body: Value,
+    ) -> Result<HttpResponse> {
+        self.run(json!({"resource": resource, "op": op, "params": params, "body": body}))
+    }
+
+    /// Sends a request to any path on the instance, like `signoz request`.
+    pub fn request(
+        &self,
+        method: &str,
+        path: &str,
+        query: &[(String, String)],
+        body: Option<Value>,
+    ) -> Result<HttpResponse> {
+        let content_type = body.as_ref().map(|_| "application/json");
+        let response = self
+            .ctx
+            .execute(method, path, query, body.map(Body::Json), content_type)?;
+        crate::ensure_api_response(path, &response)?;
+        Ok(response)
+    }
+
+    fn run(&self, mut entry: Value) -> Result<HttpResponse> {
+        match entry.get("params") {
+            Some(Value::Null) => {
+                entry.as_object_mut().expect("object").remove("params");
+            }
+            Some(Value::Object(_)) => {}
+            _ => return Err(anyhow!("params must be a JSON object")),
+        }
+        let (op, op_matches) = crate::batch::op_matches(&self.cli, &self.tree, &entry)?;
+        let (response, _) = crate::run_op(&self.ctx, op, &op_matches)?;
+        Ok(response)
+    }
+}
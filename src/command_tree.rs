@@ -1,55 +1,85 @@
-use serde::{Deserialize, Serialize};
-
-#[derive(Debug, Deserialize, Serialize, Clone)]
-#[allow(dead_code)]
-pub struct CommandTree {
-    pub version: u32,
-    pub base_url: String,
-    pub resources: Vec<Resource>,
+use anyhow::{Context, Result};
+use flate2::read::GzDecoder;
+use std::env;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use crate::config;
+
+pub mod generate;
+mod model;
+mod overlay;
+
+pub use model::*;
+
+/// Where `signoz spec sync` writes a tree that replaces the embedded one.
+pub fn synced_path() -> Result<PathBuf> {
+    Ok(config::config_dir()?.join("command_tree.json"))
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
-#[allow(dead_code)]
-pub struct Resource {
-    pub name: String,
-    pub ops: Vec<Operation>,
+/// `--command-tree`/`SIGNOZ_COMMAND_TREE` if given, else the synced tree
+/// when there is one, else the embedded tree; then the extra endpoints from
+/// `--command-tree-overlay`/`SIGNOZ_COMMAND_TREE_OVERLAY` are merged in.
+/// Read from the raw arguments, since the tree shapes the parser.
+pub fn load_command_tree(args: &[String]) -> Result<CommandTree> {
+    let mut tree = match arg_or_env(args, "--command-tree", "SIGNOZ_COMMAND_TREE") {
+        Some(path) => read_tree(Path::new(&path))?,
+        None => synced_or_embedded(),
+    };
+    if let Some(path) = arg_or_env(
+        args,
+        "--command-tree-overlay",
+        "SIGNOZ_COMMAND_TREE_OVERLAY",
+    ) {
+        overlay::apply(&mut tree, Path::new(&path))?;
+    }
+    Ok(tree)
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
-#[allow(dead_code)]
-pub struct Operation {
-    pub name: String,
-    pub method: String,
-    pub path: String,
-    pub summary: Option<String>,
-    pub description: Option<String>,
-    pub tags: Vec<String>,
-    pub deprecated: bool,
-    pub params: Vec<ParamDef>,
-    pub request_body: Option<RequestBodyDef>,
+fn arg_or_env(args: &[String], flag: &str, var: &str) -> Option<String> {
+    let prefix = format!("{flag}=");
+    args.iter()
+        .position(|arg| arg == flag)
+        .and_then(|i| args.get(i + 1).cloned())
+        .or_else(|| {
+            args.iter()
+                .find_map(|arg| Some(arg.strip_prefix(&prefix)?.to_string()))
+        })
+        .or_else(|| env::var(var).ok().filter(|v| !v.is_empty()))
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
-#[allow(dead_code)]
-pub struct ParamDef {
-    pub param_name: String,
-    pub name: String,
-    pub flag: String,
-    pub location: String,
-    pub required: bool,
-    pub schema_type: String,
-    pub is_array: bool,
+fn read_tree(path: &Path) -> Result<CommandTree> {
+    let raw = fs::read_to_string(path).with_context(|| format!("read {}", path.display()))?;
+    serde_json::from_str(&raw).with_context(|| format!("invalid command tree {}", path.display()))
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
-#[allow(dead_code)]
-pub struct RequestBodyDef {
-    pub required: bool,
-    pub content_type: String,
-    pub schema_type: String,
+/// A synced tree that fails to parse is reported and skipped.
+fn synced_or_embedded() -> CommandTree {
+    let Ok(path) = synced_path() else {
+        return embedded();
+    };
+    if !path.exists() {
+        return embedded();
+    }
+    match read_tree(&path) {
+        Ok(tree) => tree,
+        Err(err) => {
+            eprintln!(
+                "warning: ignoring synced command tree ({err:#}); run `signoz spec sync` or `signoz spec reset`"
+            );
+            embedded()
+        }
+    }
 }
 
-pub fn load_command_tree() -> CommandTree {
-    let raw = include_str!("../schemas/command_tree.json");
-    serde_json::from_str(raw).expect("invalid command_tree.json")
+/// The tree is parsed and bincode-encoded by `build.rs`, then gzipped, so
+/// startup only inflates and decodes it.
+fn embedded() -> CommandTree {
+    let compressed = include_bytes!(concat!(env!("OUT_DIR"), "/command_tree.bin.gz"));
+    let mut raw = Vec::new();
+    GzDecoder::new(&compressed[..])
+        .read_to_end(&mut raw)
+        .expect("corrupt embedded command tree");
+    bincode::deserialize(&raw).expect("invalid embedded command tree")
 }
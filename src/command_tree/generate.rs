@@ -0,0 +1,1130 @@
+use serde_json::{json, Map, Value};
+
+use super::model::{CommandTree, Operation, ParamDef, RequestBodyDef, Resource, ResponseDef};
+use crate::patch;
+
+const METHODS: [&str; 7] = ["get", "post", "put", "patch", "delete", "head", "options"];
+
+const TIME_PARAM_NAMES: [&str; 8] = [
+    "start",
+    "end",
+    "from",
+    "to",
+    "starttime",
+    "endtime",
+    "start-time",
+    "end-time",
+];
+
+const UNIT_HINTS: [(&str, &[&str]); 4] = [
+    ("ns", &["nanosecond", "unix_nano", "unixnano", "_ns"]),
+    ("us", &["microsecond", "_us"]),
+    ("ms", &["millisecond", "epoch ms", "_ms"]),
+    ("s", &["unix seconds", "epoch seconds", "in seconds"]),
+];
+
+/// Default terminal table columns of list operations, by resource and
+/// operation; dotted names reach into nested fields.
+const COLUMN_HINTS: &[(&str, &str, &[&str])] = &[
+    (
+        "alerts",
+        "list-alerts",
+        &[
+            "fingerprint",
+            "labels.alertname",
+            "labels.severity",
+            "status.state",
+            "startsAt",
+        ],
+    ),
+    (
+        "apikeys",
+        "list-apikeys",
+        &["id", "name", "role", "expiresAt", "lastUsed", "revoked"],
+    ),
+    (
+        "authdomains",
+        "list-auth-domains",
+        &["id", "name", "ssoType", "ssoEnabled"],
+    ),
+    (
+        "channels",
+        "list-channels",
+        &["id", "name", "type", "updated_at"],
+    ),
+    (
+        "dashboards",
+        "list-dashboards",
+        &["uuid", "data.title", "createdBy", "updatedAt"],
+    ),
+    (
+        "features",
+        "get-features",
+        &["name", "kind", "stage", "resolvedValue"],
+    ),
+    (
+        "logs",
+        "list-promoted-and-indexed-paths",
+        &["path", "promote"],
+    ),
+    (
+        "preferences",
+        "list-org-preferences",
+        &["name", "valueType", "value", "defaultValue"],
+    ),
+    (
+        "preferences",
+        "list-user-preferences",
+        &["name", "valueType", "value", "defaultValue"],
+    ),
+    (
+        "users",
+        "list-apikeys",
+        &["id", "name", "role", "expiresAt", "lastUsed", "revoked"],
+    ),
+    (
+        "users",
+        "list-invite",
+        &["email", "name", "role", "createdAt"],
+    ),
+    (
+        "users",
+        "list-users",
+        &["id", "displayName", "email", "role", "createdAt"],
+    ),
+];
+
+/// Converts an OpenAPI 3 document into a command tree: one resource per
+/// first tag (or path segment), one operation per method, plus the
+/// operations SigNoz serves but does not document.
+pub fn from_openapi(
+    spec: &Value,
+    base_url: &str,
+    generated_at: String,
+    spec_sha256: String,
+) -> CommandTree {
+    let empty = Value::Object(Map::new());
+    let components = spec.get("components").unwrap_or(&empty);
+
+    let mut resources: Vec<(String, Vec<Operation>)> = Vec::new();
+    for (path, path_item) in spec
+        .get("paths")
+        .and_then(Value::as_object)
+        .into_iter()
+        .flatten()
+    {
+        if !path_item.is_object() {
+            continue;
+        }
+        for method in METHODS {
+            let Some(op) = path_item.get(method) else {
+                continue;
+            };
+            let tags: Vec<&str> = op
+                .get("tags")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+                .collect();
+            let operation = Operation {
+                name: op_name(op, method, path),
+                method: method.to_uppercase(),
+                path: path.clone(),
+                summary: op.get("summary").and_then(Value::as_str).map(String::from),
+                description: op
+                    .get("description")
+                    .and_then(Value::as_str)
+                    .map(String::from),
+                tags: tags.iter().map(|t| safe_kebab(t)).collect(),
+                deprecated: op.get("deprecated").and_then(Value::as_bool) == Some(true),
+                replaced_by: None,
+                params: build_params(path_item, op, components),
+                request_body: request_body(op, components),
+                response: response(op, components),
+                columns: None,
+                live: live(op),
+            };
+            push(&mut resources, &resource_name(path, &tags), operation);
+        }
+    }
+
+    add_query_range_ops(&mut resources, components);
+    add_undocumented_ops(&mut resources);
+    add_live_ops(&mut resources);
+    // The spec tags API key endpoints as users; expose them as their own
+    // resource too, next to the hand-written create/rotate/revoke helpers.
+    let apikeys: Vec<Operation> = resources
+        .iter()
+        .filter(|(name, _)| name == "users")
+        .flat_map(|(_, ops)| ops)
+        .filter(|op| op.path.starts_with("/api/v1/pats"))
+        .map(|op| Operation {
+            tags: vec!["apikeys".to_string()],
+            ..op.clone()
+        })
+        .collect();
+    for op in apikeys {
+        push(&mut resources, "apikeys", op);
+    }
+
+    resources.sort_by(|a, b| a.0.cmp(&b.0));
+    let mut resources: Vec<Resource> = resources
+        .into_iter()
+        .map(|(name, mut ops)| {
+            let mut seen: Vec<(String, usize)> = Vec::new();
+            for op in &mut ops {
+                match seen.iter_mut().find(|(n, _)| *n == op.name) {
+                    Some((_, count)) => {
+                        *count += 1;
+                        op.name = format!("{}-{count}", op.name);
+                    }
+                    None => seen.push((op.name.clone(), 1)),
+                }
+            }
+            ops.sort_by(|a, b| a.name.cmp(&b.name));
+            for op in &mut ops {
+                op.columns = COLUMN_HINTS
+                    .iter()
+                    .find(|(resource, op_name, _)| *resource == name && *op_name == op.name)
+                    .map(|(_, _, columns)| columns.iter().map(|c| c.to_string()).collect());
+            }
+            Resource { name, ops }
+        })
+        .collect();
+    link_replacements(&mut resources);
+
+    CommandTree {
+        version: 1,
+        base_url: base_url.to_string(),
+        generated_at: Some(generated_at),
+        spec_sha256: Some(spec_sha256),
+        resources,
+    }
+}
+
+/// Points each deprecated operation at the live operation with the same
+/// method on the same path under the newest later API version
+/// (`/api/v1/logs` -> `/api/v3/logs`).
+fn link_replacements(resources: &mut [Resource]) {
+    let live: Vec<(String, String, u32, String)> = resources
+        .iter()
+        .flat_map(|res| res.ops.iter().map(move |op| (res, op)))
+        .filter(|(_, op)| !op.deprecated)
+        .filter_map(|(res, op)| {
+            let (version, rest) = api_version(&op.path)?;
+            Some((
+                op.method.clone(),
+                rest.to_string(),
+                version,
+                format!("{} {}", res.name, op.name),
+            ))
+        })
+        .collect();
+    for op in resources
+        .iter_mut()
+        .flat_map(|res| res.ops.iter_mut())
+        .filter(|op| op.deprecated)
+    {
+        let Some((version, rest)) = api_version(&op.path) else {
+            continue;
+        };
+        op.replaced_by = live
+            .iter()
+            .filter(|(method, path, v, _)| *method == op.method && path == rest && *v > version)
+            .max_by_key(|(_, _, v, _)| *v)
+            .map(|(_, _, _, name)| name.clone());
+    }
+}
+
+/// `/api/v3/logs` -> `(3, "/logs")`.
+fn api_version(path: &str) -> Option<(u32, &str)> {
+    let rest = path.strip_prefix("/api/v")?;
+    let end = rest.find('/').unwrap_or(rest.len());
+    Some((rest[..end].parse().ok()?, &rest[end..]))
+}
+
+/// The tree as `schemas/command_tree.json` stores it: keys sorted and
+/// absent optional fields left out.
+pub fn to_json(tree: &CommandTree) -> serde_json::Result<String> {
+    let mut value = serde_json::to_value(tree)?;
+    drop_absent(&mut value);
+    serde_json::to_string_pretty(&value)
+}
+
+fn drop_absent(value: &mut Value) {
+    const OPTIONAL: [&str; 11] = [
+        "response",
+        "replaced_by",
+        "columns",
+        "live",
+        "time_unit",
+        "skeleton",
+        "schema",
+        "example",
+        "content_type",
+        "schema_type",
+        "patch_types",
+    ];
+    match value {
+        Value::Object(map) => {
+            map.retain(|key, v| !(v.is_null() && OPTIONAL.contains(&key.as_str())));
+            map.values_mut().for_each(drop_absent);
+        }
+        Value::Array(items) => items.iter_mut().for_each(drop_absent),
+        _ => {}
+    }
+}
+
+fn push(resources: &mut Vec<(String, Vec<Operation>)>, name: &str, op: Operation) {
+    match resources.iter_mut().find(|(n, _)| n == name) {
+        Some((_, ops)) => ops.push(op),
+        None => resources.push((name.to_string(), vec![op])),
+    }
+}
+
+fn camel_to_kebab(value: &str) -> String {
+    let mut out = String::with_capacity(value.len() + 4);
+    let mut prev: Option<char> = None;
+    for c in value.chars() {
+        if c.is_ascii_uppercase()
+            && prev.is_some_and(|p| p.is_ascii_lowercase() || p.is_ascii_digit())
+        {
+            out.push('-');
+        }
+        out.push(if c == '_' { '-' } else { c });
+        prev = Some(c);
+    }
+    out.to_lowercase()
+}
+
+pub(super) fn safe_kebab(value: &str) -> String {
+    camel_to_kebab(value).trim_matches('-').to_string()
+}
+
+fn resource_name(path: &str, tags: &[&str]) -> String {
+    if let Some(tag) = tags.first() {
+        return safe_kebab(tag);
+    }
+    let parts: Vec<&str> = path.split('/').filter(|p| !p.is_empty()).collect();
+    match parts.as_slice() {
+        ["api", version, name, ..] if version.starts_with('v') => safe_kebab(name),
+        [first, ..] => safe_kebab(first),
+        [] => "misc".to_string(),
+    }
+}
+
+fn op_name(op: &Value, method: &str, path: &str) -> String {
+    if let Some(id) = op
+        .get("operationId")
+        .and_then(Value::as_str)
+        .filter(|id| !id.is_empty())
+    {
+        return safe_kebab(id);
+    }
+    let cleaned = path.replace(['{', '}'], "").replace('/', "-");
+    safe_kebab(&format!("{method}-{}", cleaned.trim_matches('-')))
+}
+
+fn is_empty(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::Object(map) => map.is_empty(),
+        Value::Array(items) => items.is_empty(),
+        _ => false,
+    }
+}
+
+fn resolve_ref<'a>(reference: &str, components: &'a Value) -> Option<&'a Value> {
+    let rest = reference.strip_prefix("#/components/")?;
+    rest.split('/')
+        .try_fold(components, |current, part| current.get(part))
+        .filter(|v| !is_empty(v))
+}
+
+fn ref_name(reference: &str) -> &str {
+    reference.rsplit('/').next().unwrap_or(reference)
+}
+
+/// The type name shown for a schema, and whether it is an array.
+fn schema_info(schema: &Value, components: &Value) -> (String, bool) {
+    if is_empty(schema) {
+        return ("string".to_string(), false);
+    }
+    if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+        return match resolve_ref(reference, components) {
+            Some(resolved) => schema_info(resolved, components),
+            None => (ref_name(reference).to_string(), false),
+        };
+    }
+    for key in ["oneOf", "anyOf", "allOf"] {
+        if schema.get(key).is_some() {
+            return (key.to_string(), false);
+        }
+    }
+    match schema.get("type").and_then(Value::as_str) {
+        Some("array") => {
+            let empty = json!({});
+            let (item, _) = schema_info(schema.get("items").unwrap_or(&empty), components);
+            (format!("array<{item}>"), true)
+        }
+        Some(kind) if !kind.is_empty() => (kind.to_string(), false),
+        _ if schema.get("properties").is_some_and(|p| !is_empty(p)) => {
+            ("object".to_string(), false)
+        }
+        _ => ("string".to_string(), false),
+    }
+}
+
+/// A placeholder value shaped like `schema`, the starting point for
+/// `--edit`. Recursive references stop at the second visit.
+pub(super) fn skeleton(schema: &Value, components: &Value, seen: &[String]) -> Value {
+    if is_empty(schema) {
+        return Value::Null;
+    }
+    if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+        let name = ref_name(reference).to_string();
+        if seen.contains(&name) {
+            return json!({});
+        }
+        let resolved = resolve_ref(reference, components)
+            .cloned()
+            .unwrap_or(Value::Null);
+        return skeleton(&resolved, components, &[seen, &[name]].concat());
+    }
+    for key in ["example", "default"] {
+        if let Some(value) = schema.get(key) {
+            return value.clone();
+        }
+    }
+    if let Some(first) = schema
+        .get("enum")
+        .and_then(Value::as_array)
+        .and_then(|v| v.first())
+    {
+        return first.clone();
+    }
+    if let Some(parts) = schema.get("allOf").and_then(Value::as_array) {
+        let mut merged = Map::new();
+        for part in parts {
+            if let Value::Object(map) = skeleton(part, components, seen) {
+                merged.extend(map);
+            }
+        }
+        return Value::Object(merged);
+    }
+    for key in ["oneOf", "anyOf"] {
+        if let Some(first) = schema
+            .get(key)
+            .and_then(Value::as_array)
+            .and_then(|v| v.first())
+        {
+            return skeleton(first, components, seen);
+        }
+    }
+    let kind = schema.get("type").and_then(Value::as_str);
+    let properties = schema.get("properties").and_then(Value::as_object);
+    if kind == Some("object") || properties.is_some_and(|p| !p.is_empty()) {
+        return Value::Object(
+            properties
+                .into_iter()
+                .flatten()
+                .map(|(name, prop)| (name.clone(), skeleton(prop, components, seen)))
+                .collect(),
+        );
+    }
+    match kind {
+        Some("array") => {
+            let item = skeleton(
+                schema.get("items").unwrap_or(&Value::Null),
+                components,
+                seen,
+            );
+            match item {
+                Value::Object(ref map) if !map.is_empty() => json!([item]),
+                _ => json!([]),
+            }
+        }
+        Some("string") => json!(""),
+        Some("integer" | "number") => json!(0),
+        Some("boolean") => json!(false),
+        _ => Value::Null,
+    }
+}
+
+/// `schema` with `$ref`s replaced by what they point at, so each body
+/// carries a self-contained schema. A recursive reference is left as
+/// `$ref`, which the validator accepts as anything.
+fn inline_schema(schema: &Value, components: &Value, seen: &[String]) -> Value {
+    match schema {
+        Value::Array(items) => Value::Array(
+            items
+                .iter()
+                .map(|item| inline_schema(item, components, seen))
+                .collect(),
+        ),
+        Value::Object(map) => {
+            if let Some(reference) = map.get("$ref").and_then(Value::as_str) {
+                let name = ref_name(reference).to_string();
+                if seen.contains(&name) {
+                    return json!({ "$ref": reference });
+                }
+                let resolved = resolve_ref(reference, components)
+                    .cloned()
+                    .unwrap_or_else(|| json!({}));
+                return inline_schema(&resolved, components, &[seen, &[name]].concat());
+            }
+            let mut out = Map::new();
+            for (key, value) in map {
+                match (key.as_str(), value) {
+                    ("description" | "example" | "examples", _) => {}
+                    ("properties", Value::Object(properties)) => {
+                        out.insert(
+                            key.clone(),
+                            Value::Object(
+                                properties
+                                    .iter()
+                                    .map(|(name, prop)| {
+                                        (name.clone(), inline_schema(prop, components, seen))
+                                    })
+                                    .collect(),
+                            ),
+                        );
+                    }
+                    _ => {
+                        out.insert(key.clone(), inline_schema(value, components, seen));
+                    }
+                }
+            }
+            Value::Object(out)
+        }
+        other => other.clone(),
+    }
+}
+
+/// Unit a time parameter is sent in, or `None` for other parameters.
+fn time_unit(name: &str, schema: &Value, description: &str, schema_type: &str) -> Option<String> {
+    if !TIME_PARAM_NAMES.contains(&safe_kebab(name).as_str()) {
+        return None;
+    }
+    let format = schema.get("format").and_then(Value::as_str).unwrap_or("");
+    if format == "date-time" {
+        return Some("rfc3339".to_string());
+    }
+    let text = format!("{name} {description} {format}").to_lowercase();
+    let unit = UNIT_HINTS
+        .iter()
+        .find(|(_, hints)| hints.iter().any(|hint| text.contains(hint)))
+        .map(|(unit, _)| *unit)
+        .unwrap_or(match schema_type {
+            "integer" | "number" => "ms",
+            _ => "rfc3339",
+        });
+    Some(unit.to_string())
+}
+
+fn build_params(path_item: &Value, op: &Value, components: &Value) -> Vec<ParamDef> {
+    // Operation parameters override path-level ones with the same name and
+    // location, keeping the path-level position.
+    let mut params: Vec<((String, String), Value)> = Vec::new();
+    for raw in [path_item, op]
+        .iter()
+        .filter_map(|v| v.get("parameters").and_then(Value::as_array))
+        .flatten()
+    {
+        let param = raw
+            .get("$ref")
+            .and_then(Value::as_str)
+            .and_then(|r| resolve_ref(r, components))
+            .unwrap_or(raw);
+        let text = |key: &str, default: &str| {
+            param
+                .get(key)
+                .and_then(Value::as_str)
+                .unwrap_or(default)
+                .to_string()
+        };
+        let key = (text("name", ""), text("in", "query"));
+        match params.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, existing)) => *existing = param.clone(),
+            None => params.push((key, param.clone())),
+        }
+    }
+
+    let mut used_flags: Vec<String> = Vec::new();
+    let mut out = Vec::new();
+    for ((name, location), param) in params {
+        let empty = json!({});
+        let schema = param.get("schema").unwrap_or(&empty);
+        let (schema_type, is_array) = schema_info(schema, components);
+
+        let base_flag = safe_kebab(&name);
+        let mut flag = if location == "header" {
+            format!("header-{base_flag}")
+        } else {
+            base_flag.clone()
+        };
+        if used_flags.contains(&flag) {
+            flag = format!("{location}-{base_flag}");
+        }
+        used_flags.push(flag.clone());
+
+        let description = param
+            .get("description")
+            .and_then(Value::as_str)
+            .unwrap_or("");
+        out.push(ParamDef {
+            time_unit: time_unit(&name, schema, description, &schema_type),
+            name: format!("{location}__{base_flag}"),
+            param_name: name,
+            flag,
+            location,
+            required: param.get("required").and_then(Value::as_bool) == Some(true),
+            schema_type,
+            is_array,
+        });
+    }
+    out
+}
+
+/// Picks `application/json` when offered, else the first content type.
+fn content_schema(content: &Map<String, Value>) -> Option<(String, Value)> {
+    let content_type = if content.contains_key("application/json") {
+        "application/json".to_string()
+    } else {
+        content.keys().next()?.clone()
+    };
+    let schema = content
+        .get(&content_type)
+        .and_then(|c| c.get("schema"))
+        .cloned()
+        .unwrap_or_else(|| json!({}));
+    Some((content_type, schema))
+}
+
+fn request_body(op: &Value, components: &Value) -> Option<RequestBodyDef> {
+    let body = op.get("requestBody").filter(|b| !is_empty(b))?;
+    let content = body.get("content").and_then(Value::as_object)?;
+    let (content_type, schema) = content_schema(content)?;
+    let json = content_type.contains("json");
+    let patch_types: Vec<String> = [patch::MERGE_PATCH, patch::JSON_PATCH]
+        .into_iter()
+        .filter(|t| content.contains_key(*t) && *t != content_type)
+        .map(String::from)
+        .collect();
+    let example = skeleton(&schema, components, &[]);
+    Some(RequestBodyDef {
+        required: body.get("required").and_then(Value::as_bool) == Some(true),
+        schema_type: schema_info(&schema, components).0,
+        skeleton: (json && !is_empty(&example)).then(|| example.to_string()),
+        schema: (json && !is_empty(&schema))
+            .then(|| inline_schema(&schema, components, &[]).to_string()),
+        content_type,
+        patch_types: (!patch_types.is_empty()).then_some(patch_types),
+    })
+}
+
+/// `sse` when a success response is an event stream, `websocket` when the
+/// operation answers with 101 Switching Protocols.
+fn live(op: &Value) -> Option<String> {
+    let responses = op.get("responses").and_then(Value::as_object)?;
+    if responses.contains_key("101") {
+        return Some("websocket".to_string());
+    }
+    responses
+        .iter()
+        .filter(|(status, _)| status.starts_with('2'))
+        .filter_map(|(_, response)| response.get("content").and_then(Value::as_object))
+        .any(|content| content.contains_key("text/event-stream"))
+        .then(|| "sse".to_string())
+}
+
+/// The first success response: its status, content type, inlined schema,
+/// and a placeholder example shaped like it.
+fn response(op: &Value, components: &Value) -> Option<ResponseDef> {
+    let responses = op.get("responses").and_then(Value::as_object)?;
+    let (status, response) = responses
+        .iter()
+        .find(|(status, _)| status.starts_with('2'))?;
+    let response = response
+        .get("$ref")
+        .and_then(Value::as_str)
+        .map_or(Some(response), |r| resolve_ref(r, components));
+    let mut def = ResponseDef {
+        status: status.clone(),
+        description: response
+            .and_then(|r| r.get("description"))
+            .and_then(Value::as_str)
+            .map(String::from),
+        content_type: None,
+        schema_type: None,
+        schema: None,
+        example: None,
+    };
+    if let Some((content_type, schema)) = response
+        .and_then(|r| r.get("content"))
+        .and_then(Value::as_object)
+        .and_then(content_schema)
+    {
+        describe_response(&mut def, content_type, &schema, components);
+    }
+    Some(def)
+}
+
+fn describe_response(
+    def: &mut ResponseDef,
+    content_type: String,
+    schema: &Value,
+    components: &Value,
+) {
+    let example = skeleton(schema, components, &[]);
+    def.schema_type = Some(schema_info(schema, components).0);
+    def.schema = (!is_empty(schema)).then(|| inline_schema(schema, components, &[]).to_string());
+    def.example =
+        (content_type.contains("json") && !is_empty(&example)).then(|| example.to_string());
+    def.content_type = Some(content_type);
+}
+
+fn json_body(schema: Option<&Value>, skeleton: Option<&Value>) -> Option<RequestBodyDef> {
+    Some(RequestBodyDef {
+        required: true,
+        content_type: "application/json".to_string(),
+        schema_type: if schema.is_some() {
+            "QueryRangeRequest".to_string()
+        } else {
+            "object".to_string()
+        },
+        skeleton: skeleton.map(Value::to_string),
+        schema: schema.map(Value::to_string),
+        patch_types: None,
+    })
+}
+
+fn path_param(name: &str) -> ParamDef {
+    ParamDef {
+        param_name: name.to_string(),
+        name: format!("path__{}", safe_kebab(name)),
+        flag: safe_kebab(name),
+        location: "path".to_string(),
+        required: true,
+        schema_type: "string".to_string(),
+        is_array: false,
+        time_unit: None,
+    }
+}
+
+/// The v5 query_range envelope, which the OpenAPI spec does not describe.
+/// Query specs differ by type, so only their common fields are typed and
+/// unknown keys are allowed there.
+fn query_range_schema() -> Value {
+    let spec = json!({
+        "type": "object",
+        "additionalProperties": true,
+        "properties": {
+            "name": {"type": "string"},
+            "signal": {"type": "string", "enum": ["traces", "logs", "metrics"]},
+            "disabled": {"type": "boolean"},
+            "aggregations": {"type": "array", "items": {"type": "object", "additionalProperties": true}},
+            "filter": {"type": "object", "properties": {"expression": {"type": "string"}}},
+            "having": {"type": "object", "properties": {"expression": {"type": "string"}}},
+            "groupBy": {
+                "type": "array",
+                "items": {"type": "object", "required": ["name"], "additionalProperties": true},
+            },
+            "order": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "required": ["key"],
+                    "properties": {
+                        "key": {"type": "object", "required": ["name"], "additionalProperties": true},
+                        "direction": {"type": "string", "enum": ["asc", "desc"]},
+                    },
+                },
+            },
+            "limit": {"type": "integer", "minimum": 0},
+            "offset": {"type": "integer", "minimum": 0},
+            "legend": {"type": "string"},
+            "query": {"type": "string"},
+            "expression": {"type": "string"},
+        },
+    });
+    let query = json!({
+        "type": "object",
+        "required": ["type", "spec"],
+        "properties": {
+            "type": {
+                "type": "string",
+                "enum": [
+                    "builder_query",
+                    "builder_formula",
+                    "builder_trace_operator",
+                    "builder_join",
+                    "promql",
+                    "clickhouse_sql",
+                ],
+            },
+            "spec": spec,
+        },
+    });
+    json!({
+        "type": "object",
+        "required": ["start", "end", "requestType", "compositeQuery"],
+        "properties": {
+            "schemaVersion": {"type": "string"},
+            "start": {"type": "integer", "minimum": 0},
+            "end": {"type": "integer", "minimum": 0},
+            "requestType": {"type": "string", "enum": ["time_series", "scalar", "raw", "trace", "distribution"]},
+            "variables": {"type": "object", "additionalProperties": true},
+            "formatOptions": {"type": "object", "additionalProperties": true},
+            "noCache": {"type": "boolean"},
+            "compositeQuery": {
+                "type": "object",
+                "required": ["queries"],
+                "properties": {
+                    "queries": {"type": "array", "minItems": 1, "items": query},
+                },
+            },
+        },
+    })
+}
+
+fn add_query_range_ops(resources: &mut Vec<(String, Vec<Operation>)>, components: &Value) {
+    let mut response = ResponseDef {
+        status: "200".to_string(),
+        description: Some("OK".to_string()),
+        content_type: None,
+        schema_type: None,
+        schema: None,
+        example: None,
+    };
+    describe_response(
+        &mut response,
+        "application/json".to_string(),
+        &json!({
+            "type": "object",
+            "properties": {
+                "status": {"type": "string"},
+                "data": {"$ref": "#/components/schemas/Querybuildertypesv5QueryRangeResponse"},
+            },
+        }),
+        components,
+    );
+    let schema = query_range_schema();
+    for signal in ["logs", "traces", "metrics"] {
+        let aggregation = if signal == "metrics" {
+            json!({"metricName": "", "timeAggregation": "rate", "spaceAggregation": "sum"})
+        } else {
+            json!({"expression": "count()"})
+        };
+        let example = json!({
+            "start": 0,
+            "end": 0,
+            "requestType": "time_series",
+            "compositeQuery": {
+                "queries": [{
+                    "type": "builder_query",
+                    "spec": {
+                        "name": "A",
+                        "signal": signal,
+                        "aggregations": [aggregation],
+                        "filter": {"expression": ""},
+                    },
+                }],
+            },
+        });
+        let op = Operation {
+            name: "query-range".to_string(),
+            method: "POST".to_string(),
+            path: "/api/v5/query_range".to_string(),
+            summary: Some(format!("Query range for {signal}")),
+            description: Some("SigNoz query_range API".to_string()),
+            tags: vec![signal.to_string()],
+            deprecated: false,
+            replaced_by: None,
+            params: Vec::new(),
+            request_body: json_body(Some(&schema), Some(&example)),
+            response: Some(response.clone()),
+            columns: None,
+            live: None,
+        };
+        push(resources, signal, op);
+    }
+}
+
+/// Endpoints missing from the spec: (resource, name, method, path, has a
+/// JSON body, description).
+const UNDOCUMENTED: &[(&str, &str, &str, &str, bool, &str)] = &[
+    (
+        "channels",
+        "list-channels",
+        "GET",
+        "/api/v1/channels",
+        false,
+        "List notification channels",
+    ),
+    (
+        "channels",
+        "get-channel",
+        "GET",
+        "/api/v1/channels/{id}",
+        false,
+        "Get notification channel",
+    ),
+    (
+        "channels",
+        "create-channel",
+        "POST",
+        "/api/v1/channels",
+        true,
+        "Create notification channel",
+    ),
+    (
+        "channels",
+        "update-channel",
+        "PUT",
+        "/api/v1/channels/{id}",
+        true,
+        "Update notification channel",
+    ),
+    (
+        "channels",
+        "delete-channel",
+        "DELETE",
+        "/api/v1/channels/{id}",
+        false,
+        "Delete notification channel",
+    ),
+    (
+        "rules",
+        "list-rules",
+        "GET",
+        "/api/v1/rules",
+        false,
+        "List alert rules",
+    ),
+    (
+        "rules",
+        "get-rule",
+        "GET",
+        "/api/v1/rules/{id}",
+        false,
+        "Get alert rule",
+    ),
+    (
+        "rules",
+        "create-rule",
+        "POST",
+        "/api/v1/rules",
+        true,
+        "Create alert rule",
+    ),
+    (
+        "rules",
+        "update-rule",
+        "PUT",
+        "/api/v1/rules/{id}",
+        true,
+        "Update alert rule",
+    ),
+    (
+        "rules",
+        "delete-rule",
+        "DELETE",
+        "/api/v1/rules/{id}",
+        false,
+        "Delete alert rule",
+    ),
+    (
+        "alerts",
+        "list-alerts",
+        "GET",
+        "/api/v1/alerts",
+        false,
+        "List alerts",
+    ),
+    (
+        "alerts",
+        "get-alert",
+        "GET",
+        "/api/v1/alerts/{id}",
+        false,
+        "Get alert",
+    ),
+    (
+        "dashboards",
+        "list-dashboards",
+        "GET",
+        "/api/v1/dashboards",
+        false,
+        "List dashboards",
+    ),
+    (
+        "dashboards",
+        "get-dashboard",
+        "GET",
+        "/api/v1/dashboards/{id}",
+        false,
+        "Get dashboard",
+    ),
+    (
+        "dashboards",
+        "create-dashboard",
+        "POST",
+        "/api/v1/dashboards",
+        true,
+        "Create dashboard",
+    ),
+    (
+        "dashboards",
+        "update-dashboard",
+        "PUT",
+        "/api/v1/dashboards/{id}",
+        true,
+        "Update dashboard",
+    ),
+    (
+        "dashboards",
+        "delete-dashboard",
+        "DELETE",
+        "/api/v1/dashboards/{id}",
+        false,
+        "Delete dashboard",
+    ),
+    (
+        "pipelines",
+        "get-pipelines",
+        "GET",
+        "/api/v1/logs/pipelines/{version}",
+        false,
+        "Get log pipelines",
+    ),
+    (
+        "pipelines",
+        "save-pipelines",
+        "POST",
+        "/api/v1/logs/pipelines",
+        true,
+        "Save log pipelines",
+    ),
+    (
+        "pipelines",
+        "preview-pipelines",
+        "POST",
+        "/api/v1/logs/pipelines/preview",
+        true,
+        "Preview log pipelines",
+    ),
+];
+
+/// Longer descriptions for undocumented ops whose summary is not enough.
+const UNDOCUMENTED_DETAILS: &[(&str, &str)] = &[
+    (
+        "get-pipelines",
+        "Get a version of the log pipelines; use `latest` for the deployed one",
+    ),
+    (
+        "save-pipelines",
+        "Replace the log pipelines with a new version",
+    ),
+    (
+        "preview-pipelines",
+        "Run sample logs through pipelines without deploying them",
+    ),
+    (
+        "live-tail",
+        "Stream logs as they are ingested, one JSON object per line, until Ctrl-C; \
+         `--q` takes a composite query (JSON) whose builder filters select the logs",
+    ),
+];
+
+/// Live endpoints SigNoz serves but does not document: (resource, name,
+/// transport, path, comma-separated query params, summary).
+const LIVE_OPS: &[(&str, &str, &str, &str, &str, &str)] = &[(
+    "logs",
+    "live-tail",
+    "sse",
+    "/api/v3/logs/livetail",
+    "q",
+    "Stream logs as they are ingested",
+)];
+
+fn add_live_ops(resources: &mut Vec<(String, Vec<Operation>)>) {
+    for &(resource, name, transport, path, query, summary) in LIVE_OPS {
+        let detail = UNDOCUMENTED_DETAILS
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map_or(summary, |(_, detail)| *detail);
+        let params = query
+            .split(',')
+            .filter(|param| !param.is_empty())
+            .map(|param| ParamDef {
+                param_name: param.to_string(),
+                name: format!("query__{}", safe_kebab(param)),
+                flag: safe_kebab(param),
+                location: "query".to_string(),
+                required: false,
+                schema_type: "string".to_string(),
+                is_array: false,
+                time_unit: None,
+            })
+            .collect();
+        let op = Operation {
+            name: name.to_string(),
+            method: "GET".to_string(),
+            path: path.to_string(),
+            summary: Some(summary.to_string()),
+            description: Some(format!(
+                "{detail} (undocumented; verify against your SigNoz version)."
+            )),
+            tags: vec![resource.to_string()],
+            deprecated: false,
+            replaced_by: None,
+            params,
+            request_body: None,
+            response: None,
+            columns: None,
+            live: Some(transport.to_string()),
+        };
+        push(resources, resource, op);
+    }
+}
+
+fn add_undocumented_ops(resources: &mut Vec<(String, Vec<Operation>)>) {
+    for &(resource, name, method, path, has_body, summary) in UNDOCUMENTED {
+        let detail = UNDOCUMENTED_DETAILS
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map_or(summary, |(_, detail)| *detail);
+        let caveat = if resource == "channels" {
+            "documented in SigNoz alerting docs"
+        } else {
+            "undocumented; verify against your SigNoz version"
+        };
+        let params = path
+            .split('/')
+            .filter_map(|part| part.strip_prefix('{')?.strip_suffix('}'))
+            .map(path_param)
+            .collect();
+        let op = Operation {
+            name: name.to_string(),
+            method: method.to_string(),
+            path: path.to_string(),
+            summary: Some(summary.to_string()),
+            description: Some(format!("{detail} ({caveat}).")),
+            tags: vec![resource.to_string()],
+            deprecated: false,
+            replaced_by: None,
+            params,
+            request_body: if has_body {
+                json_body(None, None)
+            } else {
+                None
+            },
+            response: None,
+            columns: None,
+            live: None,
+        };
+        push(resources, resource, op);
+    }
+}
@@ -0,0 +1,106 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[allow(dead_code)]
+pub struct CommandTree {
+    pub version: u32,
+    pub base_url: String,
+    /// UTC time the generator ran (absent in older trees).
+    #[serde(default)]
+    pub generated_at: Option<String>,
+    /// SHA-256 of the OpenAPI spec the tree was generated from.
+    #[serde(default)]
+    pub spec_sha256: Option<String>,
+    pub resources: Vec<Resource>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[allow(dead_code)]
+pub struct Resource {
+    pub name: String,
+    pub ops: Vec<Operation>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[allow(dead_code)]
+pub struct Operation {
+    pub name: String,
+    pub method: String,
+    pub path: String,
+    pub summary: Option<String>,
+    pub description: Option<String>,
+    pub tags: Vec<String>,
+    pub deprecated: bool,
+    /// `resource op` to use instead of a deprecated operation, when known
+    /// (absent otherwise and in older trees).
+    #[serde(default)]
+    pub replaced_by: Option<String>,
+    pub params: Vec<ParamDef>,
+    pub request_body: Option<RequestBodyDef>,
+    /// First success response (absent for hand-added ops and in older
+    /// trees).
+    #[serde(default)]
+    pub response: Option<ResponseDef>,
+    /// Item fields shown as table columns when the response goes to a
+    /// terminal (absent for most operations and in older trees).
+    #[serde(default)]
+    pub columns: Option<Vec<String>>,
+    /// `sse` or `websocket` for operations that keep the connection open
+    /// and push events (absent for request/response operations and in
+    /// older trees).
+    #[serde(default)]
+    pub live: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[allow(dead_code)]
+pub struct ParamDef {
+    pub param_name: String,
+    pub name: String,
+    pub flag: String,
+    pub location: String,
+    pub required: bool,
+    pub schema_type: String,
+    pub is_array: bool,
+    /// Unit a time parameter is sent in: `s`, `ms`, `us`, `ns`, or `rfc3339`
+    /// (absent for other parameters and in older trees).
+    #[serde(default)]
+    pub time_unit: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[allow(dead_code)]
+pub struct RequestBodyDef {
+    pub required: bool,
+    pub content_type: String,
+    pub schema_type: String,
+    /// JSON placeholder shaped like the body schema, the starting point for
+    /// `--edit` (absent for untyped bodies and in older trees).
+    #[serde(default)]
+    pub skeleton: Option<String>,
+    /// JSON Schema of the body with `$ref`s inlined, checked before sending
+    /// (absent for untyped bodies and in older trees).
+    #[serde(default)]
+    pub schema: Option<String>,
+    /// Patch formats the endpoint takes besides `content_type`:
+    /// `application/merge-patch+json` and `application/json-patch+json`
+    /// (absent when none and in older trees).
+    #[serde(default)]
+    pub patch_types: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ResponseDef {
+    pub status: String,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub content_type: Option<String>,
+    #[serde(default)]
+    pub schema_type: Option<String>,
+    /// JSON Schema of the response with `$ref`s inlined.
+    #[serde(default)]
+    pub schema: Option<String>,
+    /// JSON placeholder shaped like the response.
+    #[serde(default)]
+    pub example: Option<String>,
+}
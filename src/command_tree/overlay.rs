@@ -0,0 +1,243 @@
+use anyhow::{anyhow, Context, Result};
+use serde::Deserialize;
+use serde_json::Value;
+use std::{fs, path::Path};
+
+use super::generate::{safe_kebab, skeleton};
+use super::model::{CommandTree, Operation, ParamDef, RequestBodyDef, Resource};
+
+const METHODS: [&str; 7] = ["GET", "POST", "PUT", "PATCH", "DELETE", "HEAD", "OPTIONS"];
+
+/// Extra endpoints in a shorter form than the generated tree: flags, param
+/// names, and path parameters are derived, so an entry needs little more
+/// than a method and a path.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Overlay {
+    resources: Vec<OverlayResource>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct OverlayResource {
+    name: String,
+    #[serde(default)]
+    ops: Vec<OverlayOp>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct OverlayOp {
+    name: String,
+    method: String,
+    path: String,
+    #[serde(default)]
+    summary: Option<String>,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    params: Vec<OverlayParam>,
+    #[serde(default)]
+    body: Option<OverlayBody>,
+    /// Item fields for the terminal table, as in the generated tree.
+    #[serde(default)]
+    columns: Option<Vec<String>>,
+    /// Warn when run and hide from `list`, like a deprecated spec operation.
+    #[serde(default)]
+    deprecated: bool,
+    /// `resource op` suggested instead when it is run.
+    #[serde(default)]
+    replaced_by: Option<String>,
+    /// `sse` or `websocket`: keep the connection open and print events.
+    #[serde(default)]
+    live: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct OverlayParam {
+    name: String,
+    #[serde(rename = "in", default = "default_location")]
+    location: String,
+    #[serde(default)]
+    required: bool,
+    #[serde(rename = "type", default = "default_type")]
+    schema_type: String,
+    #[serde(default)]
+    flag: Option<String>,
+    #[serde(default)]
+    time_unit: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct OverlayBody {
+    #[serde(default = "default_content_type")]
+    content_type: String,
+    #[serde(default)]
+    required: bool,
+    /// JSON Schema, used for `--edit` skeletons and validation.
+    #[serde(default)]
+    schema: Option<Value>,
+    /// Patch formats taken besides `content_type`, as in the generated tree.
+    #[serde(default)]
+    patch_types: Option<Vec<String>>,
+}
+
+fn default_location() -> String {
+    "query".to_string()
+}
+
+fn default_type() -> String {
+    "string".to_string()
+}
+
+fn default_content_type() -> String {
+    "application/json".to_string()
+}
+
+/// Adds the overlay's operations to `tree`. An op named like an existing
+/// one in the same resource replaces it.
+pub fn apply(tree: &mut CommandTree, path: &Path) -> Result<()> {
+    let raw = fs::read_to_string(path).with_context(|| format!("read {}", path.display()))?;
+    let overlay: Overlay = serde_yaml::from_str(&raw)
+        .with_context(|| format!("invalid command tree overlay {}", path.display()))?;
+    for entry in overlay.resources {
+        let name = safe_kebab(&entry.name);
+        if name.is_empty() {
+            return Err(anyhow!("{}: resource with an empty name", path.display()));
+        }
+        let index = match tree.resources.iter().position(|r| r.name == name) {
+            Some(index) => index,
+            None => {
+                tree.resources.push(Resource {
+                    name: name.clone(),
+                    ops: Vec::new(),
+                });
+                tree.resources.len() - 1
+            }
+        };
+        let resource = &mut tree.resources[index];
+        for op in entry.ops {
+            let op = operation(&name, op)
+                .with_context(|| format!("{}: resource {name}", path.display()))?;
+            match resource.ops.iter_mut().find(|o| o.name == op.name) {
+                Some(existing) => *existing = op,
+                None => resource.ops.push(op),
+            }
+        }
+        resource.ops.sort_by(|a, b| a.name.cmp(&b.name));
+    }
+    tree.resources.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(())
+}
+
+fn operation(resource: &str, op: OverlayOp) -> Result<Operation> {
+    let name = safe_kebab(&op.name);
+    let method = op.method.to_uppercase();
+    if !METHODS.contains(&method.as_str()) {
+        return Err(anyhow!("op {name}: unknown method {}", op.method));
+    }
+    if !op.path.starts_with('/') {
+        return Err(anyhow!("op {name}: path {} must start with /", op.path));
+    }
+    if let Some(live) = op.live.as_deref() {
+        if !matches!(live, "sse" | "websocket") {
+            return Err(anyhow!(
+                "op {name}: live must be sse or websocket, not {live}"
+            ));
+        }
+        if method != "GET" {
+            return Err(anyhow!("op {name}: live operations are opened with GET"));
+        }
+    }
+
+    let mut params = op.params;
+    for placeholder in op
+        .path
+        .split('/')
+        .filter_map(|part| part.strip_prefix('{')?.strip_suffix('}'))
+    {
+        if !params
+            .iter()
+            .any(|p| p.location == "path" && p.name == placeholder)
+        {
+            params.push(OverlayParam {
+                name: placeholder.to_string(),
+                location: "path".to_string(),
+                required: true,
+                schema_type: default_type(),
+                flag: None,
+                time_unit: None,
+            });
+        }
+    }
+    let mut defs: Vec<ParamDef> = Vec::new();
+    for param in params {
+        if !matches!(param.location.as_str(), "path" | "query" | "header") {
+            return Err(anyhow!(
+                "op {name}: param {} has unknown location {} (path, query, or header)",
+                param.name,
+                param.location
+            ));
+        }
+        let base_flag = safe_kebab(&param.name);
+        let flag = param.flag.unwrap_or_else(|| match param.location.as_str() {
+            "header" => format!("header-{base_flag}"),
+            _ => base_flag.clone(),
+        });
+        if defs.iter().any(|d| d.flag == flag) {
+            return Err(anyhow!("op {name}: two params use --{flag}"));
+        }
+        defs.push(ParamDef {
+            name: format!("{}__{base_flag}", param.location),
+            required: param.required || param.location == "path",
+            param_name: param.name,
+            flag,
+            location: param.location,
+            is_array: param.schema_type.starts_with("array"),
+            schema_type: param.schema_type,
+            time_unit: param.time_unit,
+        });
+    }
+
+    let request_body = op.body.map(|body| {
+        let json = body.content_type.contains("json");
+        let example = body
+            .schema
+            .as_ref()
+            .filter(|_| json)
+            .map(|schema| skeleton(schema, &Value::Null, &[]))
+            .filter(|example| !example.is_null());
+        RequestBodyDef {
+            required: body.required,
+            schema_type: body
+                .schema
+                .as_ref()
+                .and_then(|s| s.get("type"))
+                .and_then(Value::as_str)
+                .unwrap_or("object")
+                .to_string(),
+            skeleton: example.map(|e| e.to_string()),
+            schema: body.schema.filter(|_| json).map(|s| s.to_string()),
+            content_type: body.content_type,
+            patch_types: body.patch_types,
+        }
+    });
+
+    Ok(Operation {
+        name,
+        method,
+        path: op.path,
+        summary: op.summary,
+        description: op.description,
+        tags: vec![resource.to_string()],
+        deprecated: op.deprecated,
+        replaced_by: op.replaced_by,
+        params: defs,
+        request_body,
+        response: None,
+        columns: op.columns,
+        live: op.live,
+    })
+}
@@ -0,0 +1,257 @@
+use anyhow::{anyhow, Context, Result};
+use serde::Deserialize;
+use std::{
+    collections::{BTreeMap, HashMap},
+    env, fs,
+    path::PathBuf,
+};
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub export: ExportConfig,
+    pub request: RequestConfig,
+    pub hooks: HooksConfig,
+    pub output: OutputConfig,
+    /// `[defaults.<resource>]` tables for the convenience commands.
+    pub defaults: HashMap<String, CommandDefaults>,
+    /// `[profiles.<name>]` instances, selected with `--profile`.
+    pub profiles: HashMap<String, Profile>,
+    /// `[aliases]`: name to the command line it stands for.
+    pub aliases: HashMap<String, String>,
+    /// `[headers.<name>]` header sets, sent with `--header-preset`.
+    pub headers: HashMap<String, BTreeMap<String, String>>,
+}
+
+impl Config {
+    pub fn profile(&self, name: &str) -> Result<&Profile> {
+        self.profiles.get(name).ok_or_else(|| {
+            let mut known: Vec<&str> = self.profiles.keys().map(String::as_str).collect();
+            known.sort_unstable();
+            match known.as_slice() {
+                [] => anyhow!("unknown profile {name}; none configured ([profiles.<name>])"),
+                _ => anyhow!("unknown profile {name} (configured: {})", known.join(", ")),
+            }
+        })
+    }
+
+    pub fn header_preset(&self, name: &str) -> Result<&BTreeMap<String, String>> {
+        self.headers.get(name).ok_or_else(|| {
+            let mut known: Vec<&str> = self.headers.keys().map(String::as_str).collect();
+            known.sort_unstable();
+            match known.as_slice() {
+                [] => anyhow!("unknown header preset {name}; none configured ([headers.<name>])"),
+                _ => anyhow!(
+                    "unknown header preset {name} (configured: {})",
+                    known.join(", ")
+                ),
+            }
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct ExportConfig {
+    /// Volatile fields removed from exported resources.
+    pub strip: Vec<String>,
+    /// Identifier fields removed unless `--keep-ids` is given.
+    pub id_fields: Vec<String>,
+}
+
+impl Default for ExportConfig {
+    fn default() -> Self {
+        Self {
+            strip: ["createdAt", "createdBy", "updatedAt", "updatedBy"]
+                .map(String::from)
+                .to_vec(),
+            id_fields: ["id", "uuid"].map(String::from).to_vec(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct RequestConfig {
+    /// Bodies above this size (e.g. `10MB`) need confirmation or `--force`.
+    pub max_body_size: String,
+}
+
+impl Default for RequestConfig {
+    fn default() -> Self {
+        Self {
+            max_body_size: "10MB".to_string(),
+        }
+    }
+}
+
+/// Shell commands run around every HTTP request (see `hooks::Hooks`).
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct HooksConfig {
+    pub pre_request: Option<String>,
+    pub post_response: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct OutputConfig {
+    /// Print only `data` of `{"status": "success", "data": ...}` responses,
+    /// as `--unwrap` does; `--no-unwrap` turns it off again.
+    pub unwrap: bool,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct CommandDefaults {
+    /// Default `--since` window (e.g. `15m`).
+    pub since: Option<String>,
+}
+
+/// A named SigNoz instance and its credentials.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Profile {
+    pub base_url: Option<String>,
+    pub api_key: Option<String>,
+    pub token: Option<String>,
+}
+
+pub fn config_dir() -> Result<PathBuf> {
+    if let Ok(dir) = env::var("SIGNOZ_CONFIG_DIR") {
+        return Ok(PathBuf::from(dir));
+    }
+    if let Ok(dir) = env::var("XDG_CONFIG_HOME") {
+        return Ok(PathBuf::from(dir).join("signoz"));
+    }
+    let home = env::var("HOME")
+        .or_else(|_| env::var("USERPROFILE"))
+        .map_err(|_| anyhow!("cannot locate home directory; set SIGNOZ_CONFIG_DIR"))?;
+    Ok(PathBuf::from(home).join(".config").join("signoz"))
+}
+
+pub fn config_path() -> Result<PathBuf> {
+    if let Ok(path) = env::var("SIGNOZ_CONFIG") {
+        return Ok(PathBuf::from(path));
+    }
+    Ok(config_dir()?.join("config.toml"))
+}
+
+pub fn load() -> Result<Config> {
+    let path = config_path()?;
+    if !path.exists() {
+        return Ok(Config::default());
+    }
+    let raw = fs::read_to_string(&path).context("read config file")?;
+    toml::from_str(&raw).with_context(|| format!("invalid config file {}", path.display()))
+}
+
+/// Writes `[profiles.<name>]` to the config file. Returns the file's path.
+pub fn save_profile(name: &str, profile: &Profile) -> Result<PathBuf> {
+    let mut table = toml::Table::new();
+    for (key, value) in [
+        ("base_url", &profile.base_url),
+        ("api_key", &profile.api_key),
+        ("token", &profile.token),
+    ] {
+        if let Some(value) = value {
+            table.insert(key.to_string(), toml::Value::String(value.clone()));
+        }
+    }
+    set_entry("profiles", name, Some(toml::Value::Table(table)))
+}
+
+/// Sets `name` in `[aliases]`, or removes it when `command` is `None`.
+pub fn save_alias(name: &str, command: Option<&str>) -> Result<PathBuf> {
+    set_entry(
+        "aliases",
+        name,
+        command.map(|c| toml::Value::String(c.to_string())),
+    )
+}
+
+/// Sets or removes `section.name`. A new entry is appended as a table of its
+/// own or added under the section's header, so the rest of the file
+/// (comments included) is left alone; otherwise the file is rewritten.
+fn set_entry(section: &str, name: &str, value: Option<toml::Value>) -> Result<PathBuf> {
+    let path = config_path()?;
+    let raw = match fs::read_to_string(&path) {
+        Ok(raw) => raw,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(err) => return Err(err).context("read config file"),
+    };
+    let mut config: toml::Table =
+        toml::from_str(&raw).with_context(|| format!("invalid config file {}", path.display()))?;
+
+    let existing = config.get(section).and_then(toml::Value::as_table);
+    let appendable = match (&value, existing) {
+        (None, _) => false,
+        (Some(_), None) => true,
+        (Some(value), Some(entries)) => value.is_table() && !entries.contains_key(name),
+    };
+    let header = format!("[{section}]");
+    let insertable = !appendable
+        && value.is_some()
+        && existing.is_some_and(|entries| !entries.contains_key(name))
+        && raw.lines().any(|line| line.trim() == header);
+    let updated = match value {
+        Some(value) if insertable => {
+            let line = toml::to_string(&toml::Table::from_iter([(name.to_string(), value)]))?;
+            let mut out = String::with_capacity(raw.len() + line.len());
+            let mut inserted = false;
+            for current in raw.split_inclusive('\n') {
+                out.push_str(current);
+                if !inserted && current.trim() == header {
+                    if !current.ends_with('\n') {
+                        out.push('\n');
+                    }
+                    out.push_str(&line);
+                    inserted = true;
+                }
+            }
+            out
+        }
+        Some(value) if appendable => {
+            let header = toml::Table::from_iter([(
+                section.to_string(),
+                toml::Value::Table(toml::Table::from_iter([(name.to_string(), value)])),
+            )]);
+            let separator = match raw.as_str() {
+                "" => "",
+                raw if raw.ends_with("\n\n") => "",
+                raw if raw.ends_with('\n') => "\n",
+                _ => "\n\n",
+            };
+            format!("{raw}{separator}{}", toml::to_string_pretty(&header)?)
+        }
+        value => {
+            let entries = config
+                .entry(section.to_string())
+                .or_insert_with(|| toml::Value::Table(toml::Table::new()));
+            let toml::Value::Table(entries) = entries else {
+                return Err(anyhow!("{section} in {} is not a table", path.display()));
+            };
+            match value {
+                Some(value) => {
+                    entries.insert(name.to_string(), value);
+                }
+                None => {
+                    entries.remove(name);
+                }
+            }
+            toml::to_string_pretty(&config)?
+        }
+    };
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).context("create config dir")?;
+    }
+    fs::write(&path, updated).with_context(|| format!("write {}", path.display()))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o600))
+            .context("restrict config file")?;
+    }
+    Ok(path)
+}
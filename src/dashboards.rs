@@ -0,0 +1,354 @@
+use anyhow::{anyhow, Context, Result};
+use clap::{Arg, ArgAction, Command};
+use serde_json::{json, Value};
+use std::{collections::HashMap, fs, path::Path};
+
+use crate::config;
+use crate::error::ApiError;
+use crate::http::Body;
+use crate::ids;
+use crate::normalize::Normalizer;
+use crate::output::{self, OutputOptions};
+use crate::progress::Progress;
+use crate::references;
+use crate::ApiContext;
+
+const DASHBOARDS_PATH: &str = "/api/v1/dashboards";
+
+pub fn extend(cmd: Command) -> Command {
+    let dir = || {
+        Arg::new("dir")
+            .long("dir")
+            .value_name("DIR")
+            .default_value("./dashboards")
+            .help("Directory holding one JSON file per dashboard")
+    };
+    cmd.subcommand(
+        Command::new("export")
+            .about("Write every dashboard to its own JSON file")
+            .arg(dir()),
+    )
+    .subcommand(
+        Command::new("import")
+            .about("Create or update dashboards from exported JSON files")
+            .arg(dir())
+            .arg(
+                Arg::new("dry-run")
+                    .long("dry-run")
+                    .action(ArgAction::SetTrue)
+                    .help("Show what would be created or updated without sending anything"),
+            )
+            .arg(references::skip_arg()),
+    )
+    .subcommand(
+        Command::new("list")
+            .about("List dashboard ids, titles, and tags, optionally filtered by tag")
+            .arg(
+                Arg::new("tag")
+                    .long("tag")
+                    .value_name("TAG")
+                    .action(ArgAction::Append)
+                    .help("Only dashboards with this tag, e.g. team=payments (repeatable, all must match)"),
+            ),
+    )
+    .subcommand(
+        Command::new("tag")
+            .about("Add or remove dashboard tags (key=value tags replace the key's old value)")
+            .arg(Arg::new("id").required(true).help("Dashboard id"))
+            .arg(
+                Arg::new("tags")
+                    .required(true)
+                    .num_args(1..)
+                    .value_name("TAG")
+                    .help("Tags such as team=payments or prod"),
+            )
+            .arg(
+                Arg::new("remove")
+                    .long("remove")
+                    .action(ArgAction::SetTrue)
+                    .help("Remove the tags instead (a bare key removes any key=value tag)"),
+            ),
+    )
+}
+
+pub fn handle(
+    op: &str,
+    matches: &clap::ArgMatches,
+    ctx: &ApiContext,
+    opts: &OutputOptions,
+) -> Option<Result<()>> {
+    match op {
+        "export" => Some(handle_export(matches, ctx, opts)),
+        "import" => Some(handle_import(matches, ctx, opts)),
+        "list" => Some(handle_list(matches, ctx, opts)),
+        "tag" => Some(handle_tag(matches, ctx, opts)),
+        _ => None,
+    }
+}
+
+fn handle_export(matches: &clap::ArgMatches, ctx: &ApiContext, opts: &OutputOptions) -> Result<()> {
+    let dir = Path::new(dir_arg(matches));
+    fs::create_dir_all(dir).with_context(|| format!("create {}", dir.display()))?;
+    let existing = read_dir(dir)?;
+    // Fields in `[export] strip` are dropped so files only change when the
+    // dashboard itself does; ids stay, since import matches on them.
+    let normalizer = Normalizer::new(&config::load()?.export, true);
+
+    let dashboards = list(ctx)?;
+    let total = dashboards.len();
+    let progress = Progress::start(format!("exporting {total} dashboards"));
+    let mut summary = Vec::new();
+    for (done, mut dashboard) in dashboards.into_iter().enumerate() {
+        progress.set(format!("exporting {}/{total}", done + 1));
+        let Some(id) = dashboard_id(&dashboard) else {
+            continue;
+        };
+        normalizer.apply_resource(&mut dashboard);
+        let title = title(&dashboard).to_string();
+        let file = file_name(&title, &id);
+        // A renamed dashboard gets a new file name; drop the old one.
+        for (old, value) in &existing {
+            if old != &file && dashboard_id(value).as_deref() == Some(id.as_str()) {
+                fs::remove_file(dir.join(old)).with_context(|| format!("remove {old}"))?;
+            }
+        }
+        let text = serde_json::to_string_pretty(&dashboard)? + "\n";
+        fs::write(dir.join(&file), text).with_context(|| format!("write {file}"))?;
+        summary.push(json!({ "id": id, "title": title, "file": file }));
+    }
+    drop(progress);
+    output::print_value(&Value::Array(summary), opts)
+}
+
+/// Files are matched to existing dashboards by id, then by unique title;
+/// anything unmatched is created.
+fn handle_import(matches: &clap::ArgMatches, ctx: &ApiContext, opts: &OutputOptions) -> Result<()> {
+    let dir = Path::new(dir_arg(matches));
+    let files = read_dir(dir)?;
+    if files.is_empty() {
+        return Err(anyhow!("no dashboard files in {}", dir.display()));
+    }
+    let dry_run = matches.get_flag("dry-run");
+    if !matches.get_flag("no-ref-check") {
+        let mut checker = references::Checker::new(ctx);
+        for (file, value) in &files {
+            let data = value.get("data").unwrap_or(value);
+            checker.metrics(file, data);
+            checker.dashboard_variables(file, data);
+        }
+        checker.finish()?;
+    }
+
+    let remote = list(ctx)?;
+    let ids: Vec<String> = remote.iter().filter_map(dashboard_id).collect();
+    let mut titles: HashMap<&str, Vec<String>> = HashMap::new();
+    for dashboard in &remote {
+        if let Some(id) = dashboard_id(dashboard) {
+            titles.entry(title(dashboard)).or_default().push(id);
+        }
+    }
+
+    let mut summary = Vec::new();
+    let total = files.len();
+    let progress = Progress::start(format!("importing {total} dashboards"));
+    for (done, (file, value)) in files.into_iter().enumerate() {
+        progress.set(format!("importing {}/{total}: {file}", done + 1));
+        let data = value.get("data").cloned().unwrap_or_else(|| value.clone());
+        let title = data
+            .get("title")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        let target = dashboard_id(&value)
+            .filter(|id| ids.contains(id))
+            .or_else(|| match titles.get(title).map(Vec::as_slice) {
+                Some([id]) => Some(id.clone()),
+                _ => None,
+            });
+        let (action, id) = match &target {
+            Some(id) => ("update", Some(id.clone())),
+            None => ("create", None),
+        };
+        let id = if dry_run {
+            id
+        } else {
+            let (method, path) = match &target {
+                Some(id) => (
+                    "PUT",
+                    format!("{DASHBOARDS_PATH}/{}", urlencoding::encode(id)),
+                ),
+                None => ("POST", DASHBOARDS_PATH.to_string()),
+            };
+            let response = ctx.execute(
+                method,
+                &path,
+                &[],
+                Some(Body::Json(data.clone())),
+                Some("application/json"),
+            )?;
+            if response.status >= 400 {
+                return Err(ApiError::from_response(&response))
+                    .with_context(|| format!("{action} {file} failed"));
+            }
+            id.or_else(|| dashboard_id(response.body.get("data").unwrap_or(&response.body)))
+        };
+        summary.push(json!({ "file": file, "title": title, "action": action, "id": id }));
+    }
+    drop(progress);
+    output::print_value(&Value::Array(summary), opts)
+}
+
+/// SigNoz has no dashboard folders; tags (`data.tags`) are the grouping the
+/// UI filters on, so they double as folders here.
+fn handle_list(matches: &clap::ArgMatches, ctx: &ApiContext, opts: &OutputOptions) -> Result<()> {
+    let wanted: Vec<&String> = matches
+        .get_many::<String>("tag")
+        .into_iter()
+        .flatten()
+        .collect();
+    let mut rows: Vec<Value> = list(ctx)?
+        .iter()
+        .filter(|dashboard| {
+            let tags = tags(dashboard);
+            wanted.iter().all(|tag| tags.contains(tag))
+        })
+        .map(|dashboard| {
+            json!({
+                "id": dashboard_id(dashboard),
+                "title": title(dashboard),
+                "tags": tags(dashboard),
+            })
+        })
+        .collect();
+    rows.sort_by(|a, b| a["title"].as_str().cmp(&b["title"].as_str()));
+    output::print_value(&Value::Array(rows), opts)
+}
+
+fn handle_tag(matches: &clap::ArgMatches, ctx: &ApiContext, opts: &OutputOptions) -> Result<()> {
+    let id = matches
+        .get_one::<String>("id")
+        .ok_or_else(|| anyhow!("missing dashboard id"))?;
+    let id = ids::expand(&ctx.base_url, DASHBOARDS_PATH, id)?;
+    let path = format!("{DASHBOARDS_PATH}/{}", urlencoding::encode(&id));
+    let response = ctx.execute("GET", &path, &[], None, None)?;
+    if response.status >= 400 {
+        return Err(ApiError::from_response(&response))
+            .with_context(|| format!("get dashboard {id} failed"));
+    }
+    let dashboard = response.body.get("data").unwrap_or(&response.body);
+    let mut data = dashboard
+        .get("data")
+        .cloned()
+        .ok_or_else(|| anyhow!("dashboard {id} has no data"))?;
+
+    let mut current = tags(dashboard);
+    let key = |tag: &str| tag.split_once('=').map(|(k, _)| k.to_string());
+    for tag in matches.get_many::<String>("tags").into_iter().flatten() {
+        if matches.get_flag("remove") {
+            current.retain(|t| t != tag && key(t).as_deref() != Some(tag.as_str()));
+        } else {
+            if let Some(k) = key(tag) {
+                current.retain(|t| key(t).as_deref() != Some(k.as_str()));
+            }
+            current.push(tag.clone());
+        }
+    }
+    data["tags"] = json!(current);
+
+    let response = ctx.execute(
+        "PUT",
+        &path,
+        &[],
+        Some(Body::Json(data)),
+        Some("application/json"),
+    )?;
+    if response.status >= 400 {
+        return Err(ApiError::from_response(&response))
+            .with_context(|| format!("update dashboard {id} failed"));
+    }
+    output::print_value(
+        &json!({ "id": id, "title": title(dashboard), "tags": current }),
+        opts,
+    )
+}
+
+fn tags(dashboard: &Value) -> Vec<String> {
+    dashboard
+        .pointer("/data/tags")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|t| t.as_str().map(str::to_string))
+        .collect()
+}
+
+fn dir_arg(matches: &clap::ArgMatches) -> &str {
+    matches
+        .get_one::<String>("dir")
+        .map_or("./dashboards", String::as_str)
+}
+
+fn list(ctx: &ApiContext) -> Result<Vec<Value>> {
+    let response = ctx.execute("GET", DASHBOARDS_PATH, &[], None, None)?;
+    if response.status >= 400 {
+        return Err(ApiError::from_response(&response)).context("list dashboards failed");
+    }
+    Ok(response
+        .body
+        .get("data")
+        .unwrap_or(&response.body)
+        .as_array()
+        .cloned()
+        .unwrap_or_default())
+}
+
+/// `*.json` files in `dir`, sorted by name.
+fn read_dir(dir: &Path) -> Result<Vec<(String, Value)>> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir).with_context(|| format!("read {}", dir.display()))? {
+        let path = entry?.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+        let raw = fs::read_to_string(&path).with_context(|| format!("read {}", path.display()))?;
+        let value =
+            serde_json::from_str(&raw).with_context(|| format!("parse {}", path.display()))?;
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        files.push((name.into_owned(), value));
+    }
+    files.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(files)
+}
+
+/// Newer servers key dashboards by `id` (a UUID); older ones have a numeric
+/// `id` and a separate `uuid`, which is what the API paths take.
+fn dashboard_id(dashboard: &Value) -> Option<String> {
+    ["uuid", "id"]
+        .iter()
+        .find_map(|key| match dashboard.get(key)? {
+            Value::String(s) if !s.is_empty() => Some(s.clone()),
+            _ => None,
+        })
+}
+
+fn title(dashboard: &Value) -> &str {
+    dashboard
+        .pointer("/data/title")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+}
+
+/// `<slugified-title>-<first 8 id chars>.json`: readable, and stable as long
+/// as the title is.
+fn file_name(title: &str, id: &str) -> String {
+    let slug = title
+        .to_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    let short: String = id.chars().filter(|c| *c != '-').take(8).collect();
+    match slug.as_str() {
+        "" => format!("{short}.json"),
+        _ => format!("{slug}-{short}.json"),
+    }
+}
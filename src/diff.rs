@@ -0,0 +1,171 @@
+use anyhow::{anyhow, Context, Result};
+use clap::{Arg, ArgAction, Command};
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+use crate::command_tree::{CommandTree, Operation};
+use crate::config::Config;
+use crate::error::ApiError;
+use crate::normalize::Normalizer;
+use crate::output::{self, OutputOptions};
+use crate::watch;
+use crate::ApiContext;
+use crate::{auth, http, ids};
+
+pub fn command() -> Command {
+    Command::new("diff")
+        .about("Structural diff of one resource between this instance and another profile")
+        .arg(
+            Arg::new("resource")
+                .required(true)
+                .help("Resource name, e.g. dashboards or rules"),
+        )
+        .arg(Arg::new("id").required(true).help("Resource id"))
+        .arg(
+            Arg::new("against")
+                .long("against")
+                .value_name("PROFILE")
+                .required(true)
+                .help("Profile ([profiles.NAME]) to compare with"),
+        )
+        .arg(
+            Arg::new("against-id")
+                .long("against-id")
+                .value_name("ID")
+                .help("Id of the resource on the other instance (default: the same id)"),
+        )
+        .arg(
+            Arg::new("keep-volatile")
+                .long("keep-volatile")
+                .action(ArgAction::SetTrue)
+                .help("Also compare timestamps, authors, and ids ([export] strip / id_fields)"),
+        )
+}
+
+/// `ctx`'s client settings pointed at the `--against` profile, with its
+/// instance and credentials resolved as `--profile` would. `--base-url`,
+/// `--api-key`, and `--token` are for this instance and do not carry over.
+fn against(
+    name: &str,
+    matches: &clap::ArgMatches,
+    tree: &CommandTree,
+    config: &Config,
+    ctx: &ApiContext,
+) -> Result<ApiContext> {
+    let flags = crate::CredentialFlags {
+        profile: Some(name.to_string()),
+        base_url: None,
+        api_key: None,
+        token: None,
+        ..crate::CredentialFlags::from_matches(matches)
+    };
+    let crate::Credentials {
+        base_url,
+        api_key,
+        token,
+    } = crate::resolve_credentials(config, tree, &flags, &ctx.client_opts)?;
+    // Unless `--unix-socket` is given, the socket comes from the base URL:
+    // this instance's may be `unix://` and the other's not, or the reverse.
+    let mut client_opts = ctx.client_opts.clone();
+    if matches.get_one::<String>("unix-socket").is_none() {
+        client_opts.unix_socket = http::unix_socket_path(&base_url);
+        client_opts.shared = Arc::default();
+    }
+    Ok(ApiContext {
+        auth_mode: auth::parse_auth_mode(
+            matches.get_one::<String>("auth"),
+            api_key.as_ref(),
+            token.as_ref(),
+        ),
+        base_url,
+        api_key,
+        token,
+        client_opts,
+        ..ctx.clone()
+    })
+}
+
+/// Fetches the resource with its `get-*` operation from both instances and
+/// prints `{path, from, to}` changes (from = this instance, to = the other).
+pub fn handle(
+    matches: &clap::ArgMatches,
+    tree: &CommandTree,
+    ctx: &ApiContext,
+    config: &Config,
+    opts: &OutputOptions,
+) -> Result<()> {
+    let arg = |name: &str| matches.get_one::<String>(name).map(String::as_str);
+    let resource = arg("resource").unwrap_or_default();
+    let op = get_op(tree, resource)?;
+    let profile = arg("against").unwrap_or_default();
+    let other = against(profile, matches, tree, config, ctx)?;
+
+    let id = ids::expand(&ctx.base_url, &op.path, arg("id").unwrap_or_default())?;
+    let other_id = match arg("against-id") {
+        Some(raw) => ids::expand(&other.base_url, &op.path, raw)?,
+        None => id.clone(),
+    };
+    let normalizer = (!matches.get_flag("keep-volatile"))
+        .then(|| Normalizer::new(&config.export, arg("against-id").is_none()));
+    let left = fetch(ctx, op, &id, normalizer.as_ref())?;
+    let right = fetch(&other, op, &other_id, normalizer.as_ref())?;
+
+    let mut changes = Vec::new();
+    watch::diff(&left, &right, &mut String::new(), &mut changes);
+    output::print_value(
+        &json!({
+            "left": ctx.base_url,
+            "right": other.base_url,
+            "identical": changes.is_empty(),
+            "changes": changes,
+        }),
+        opts,
+    )
+}
+
+/// The resource's single-id GET operation (`get-dashboard`, `get-rule`, ...).
+fn get_op<'a>(tree: &'a CommandTree, resource: &str) -> Result<&'a Operation> {
+    let res = tree
+        .resources
+        .iter()
+        .find(|r| r.name == resource)
+        .ok_or_else(|| anyhow!("unknown resource {resource}"))?;
+    res.ops
+        .iter()
+        .find(|op| {
+            op.method == "GET"
+                && op.name.starts_with("get-")
+                && op.params.iter().filter(|p| p.location == "path").count() == 1
+        })
+        .ok_or_else(|| anyhow!("{resource} has no get-by-id operation to diff"))
+}
+
+fn fetch(
+    ctx: &ApiContext,
+    op: &Operation,
+    id: &str,
+    normalizer: Option<&Normalizer>,
+) -> Result<Value> {
+    let path = op
+        .params
+        .iter()
+        .filter(|p| p.location == "path")
+        .fold(op.path.clone(), |path, p| {
+            path.replace(&format!("{{{}}}", p.param_name), &urlencoding::encode(id))
+        });
+    let response = ctx.execute("GET", &path, &[], None, None)?;
+    if response.status >= 400 {
+        return Err(ApiError::from_response(&response))
+            .with_context(|| format!("{} {id} on {} failed", op.name, ctx.base_url));
+    }
+    let mut body = response.body;
+    if let Some(normalizer) = normalizer {
+        normalizer.apply(&mut body);
+    }
+    Ok(match body {
+        Value::Object(mut map) if map.contains_key("status") && map.contains_key("data") => {
+            map.remove("data").unwrap_or(Value::Null)
+        }
+        other => other,
+    })
+}
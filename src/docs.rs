@@ -0,0 +1,175 @@
+use anyhow::{anyhow, Result};
+use clap::{Arg, Command};
+use std::fmt::Write as _;
+
+use crate::command_tree::{CommandTree, Operation, Resource};
+use crate::output;
+use crate::paginate;
+
+mod generate;
+
+const WIDTH: usize = 78;
+
+pub fn command() -> Command {
+    Command::new("docs")
+        .about("Readable overview of a resource's operations (offline)")
+        .arg(Arg::new("resource").required(true))
+        .subcommand(generate::command())
+        .subcommand_negates_reqs(true)
+        .args_conflicts_with_subcommands(true)
+}
+
+pub fn handle(tree: &CommandTree, matches: &clap::ArgMatches) -> Result<()> {
+    if let Some(("generate", matches)) = matches.subcommand() {
+        return generate::handle(tree, matches);
+    }
+    let name = matches
+        .get_one::<String>("resource")
+        .ok_or_else(|| anyhow!("resource required"))?;
+    let resource = tree
+        .resources
+        .iter()
+        .find(|r| &r.name == name)
+        .ok_or_else(|| {
+            let names: Vec<&str> = tree.resources.iter().map(|r| r.name.as_str()).collect();
+            anyhow!("unknown resource {name}; one of: {}", names.join(", "))
+        })?;
+    let text = render(resource, crate::build_cli(tree).find_subcommand(name));
+    if matches.get_flag("no-pager") {
+        print!("{text}");
+        return Ok(());
+    }
+    output::page(&text)
+}
+
+fn render(resource: &Resource, cmd: Option<&Command>) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "SIGNOZ {}\n", resource.name.to_uppercase());
+    let _ = writeln!(out, "NAME");
+    let _ = writeln!(
+        out,
+        "    signoz {} - {} operation{}\n",
+        resource.name,
+        resource.ops.len(),
+        if resource.ops.len() == 1 { "" } else { "s" }
+    );
+    let _ = writeln!(out, "OPERATIONS");
+    for op in &resource.ops {
+        render_op(&mut out, &resource.name, op);
+    }
+
+    // Curated subcommands layered on top of the generated tree.
+    let extras: Vec<&Command> = cmd
+        .map(|c| {
+            c.get_subcommands()
+                .filter(|sub| !resource.ops.iter().any(|op| op.name == sub.get_name()))
+                .collect()
+        })
+        .unwrap_or_default();
+    if !extras.is_empty() {
+        let _ = writeln!(out, "EXTRA COMMANDS");
+        for sub in extras {
+            let _ = writeln!(out, "    {}", sub.get_name());
+            if let Some(about) = sub.get_about() {
+                wrap(&mut out, &about.to_string(), 8);
+            }
+            for arg in sub.get_arguments().filter(|a| a.get_long().is_some()) {
+                let help = arg.get_help().map(|h| h.to_string()).unwrap_or_default();
+                let _ = writeln!(
+                    out,
+                    "        --{:<20} {help}",
+                    arg.get_long().unwrap_or_default()
+                );
+            }
+            out.push('\n');
+        }
+    }
+    out
+}
+
+fn render_op(out: &mut String, resource: &str, op: &Operation) {
+    let deprecated = match (op.deprecated, &op.replaced_by) {
+        (true, Some(replacement)) => format!("  [deprecated; use {replacement}]"),
+        (true, None) => "  [deprecated]".to_string(),
+        (false, _) => String::new(),
+    };
+    let _ = writeln!(out, "    {}{deprecated}", op.name);
+    let _ = writeln!(out, "        {} {}", op.method, op.path);
+    if let Some(summary) = &op.summary {
+        wrap(out, summary, 8);
+    }
+    if let Some(desc) = op
+        .description
+        .as_deref()
+        .map(str::trim)
+        .filter(|d| Some(*d) != op.summary.as_deref())
+    {
+        wrap(out, desc, 8);
+    }
+    if !op.params.is_empty() {
+        let _ = writeln!(out, "\n        Flags:");
+        for param in &op.params {
+            let mut notes = vec![param.location.clone()];
+            if param.required {
+                notes.push("required".to_string());
+            }
+            if param.is_array {
+                notes.push("repeatable".to_string());
+            }
+            let _ = writeln!(
+                out,
+                "          --{:<24} {:<8} ({})",
+                param.flag,
+                param.schema_type,
+                notes.join(", ")
+            );
+        }
+    }
+    if let Some(body) = &op.request_body {
+        let _ = writeln!(
+            out,
+            "\n        Body: {} ({}{})",
+            body.schema_type,
+            body.content_type,
+            if body.required { ", required" } else { "" }
+        );
+    }
+    let _ = writeln!(
+        out,
+        "\n        Example:\n          {}\n",
+        example(resource, op)
+    );
+}
+
+/// A copy-pasteable invocation with placeholders for required inputs.
+fn example(resource: &str, op: &Operation) -> String {
+    let mut parts = vec!["signoz".to_string(), resource.to_string(), op.name.clone()];
+    for param in op.params.iter().filter(|p| p.required) {
+        parts.push(format!("--{} <{}>", param.flag, param.schema_type));
+    }
+    if op.request_body.as_ref().is_some_and(|b| b.required) {
+        parts.push("--body @body.json".to_string());
+    }
+    if paginate::detect(op).is_some() {
+        parts.push("--all".to_string());
+    }
+    parts.join(" ")
+}
+
+fn wrap(out: &mut String, text: &str, indent: usize) {
+    let pad = " ".repeat(indent);
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        if !line.is_empty() && indent + line.len() + 1 + word.len() > WIDTH {
+            let _ = writeln!(out, "{pad}{line}");
+            line.clear();
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(word);
+    }
+    if !line.is_empty() {
+        let _ = writeln!(out, "{pad}{line}");
+    }
+}
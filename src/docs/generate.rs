@@ -0,0 +1,255 @@
+use anyhow::{anyhow, Context, Result};
+use clap::{Arg, Command};
+use serde_json::Value;
+use std::{fmt::Write as _, fs, path::Path};
+
+use crate::command_tree::{CommandTree, Operation, Resource};
+
+pub fn command() -> Command {
+    Command::new("generate")
+        .about("Write a reference page per resource (man or markdown) from the command tree")
+        .arg(
+            Arg::new("format")
+                .long("format")
+                .value_name("FORMAT")
+                .value_parser(["man", "markdown"])
+                .default_value("markdown"),
+        )
+        .arg(
+            Arg::new("out")
+                .long("out")
+                .value_name("DIR")
+                .required(true)
+                .help("Directory to write the pages to (created if missing)"),
+        )
+}
+
+/// Writes one page per resource plus an index, named so a regenerated
+/// directory only changes where the tree did.
+pub fn handle(tree: &CommandTree, matches: &clap::ArgMatches) -> Result<()> {
+    let dir = Path::new(
+        matches
+            .get_one::<String>("out")
+            .ok_or_else(|| anyhow!("--out required"))?,
+    );
+    let man = matches.get_one::<String>("format").map(String::as_str) == Some("man");
+    fs::create_dir_all(dir).with_context(|| format!("create {}", dir.display()))?;
+
+    let mut pages = vec![if man {
+        ("signoz.1".to_string(), man_index(tree))
+    } else {
+        ("index.md".to_string(), markdown_index(tree))
+    }];
+    for resource in &tree.resources {
+        pages.push(if man {
+            (
+                format!("signoz-{}.1", resource.name),
+                man_page(tree, resource),
+            )
+        } else {
+            (
+                format!("signoz-{}.md", resource.name),
+                markdown_page(resource),
+            )
+        });
+    }
+    for (file, text) in &pages {
+        fs::write(dir.join(file), text).with_context(|| format!("write {file}"))?;
+    }
+    eprintln!("Wrote {} pages to {}", pages.len(), dir.display());
+    Ok(())
+}
+
+fn markdown_index(tree: &CommandTree) -> String {
+    let mut out = String::from("# signoz command reference\n\n");
+    let _ = writeln!(
+        out,
+        "Generated from the command tree{} for `{}`.\n",
+        tree.generated_at
+            .as_deref()
+            .map(|at| format!(" of {at}"))
+            .unwrap_or_default(),
+        tree.base_url
+    );
+    out.push_str("| Resource | Operations |\n|---|---|\n");
+    for resource in &tree.resources {
+        let _ = writeln!(
+            out,
+            "| [{0}](signoz-{0}.md) | {1} |",
+            resource.name,
+            resource.ops.len()
+        );
+    }
+    out
+}
+
+fn markdown_page(resource: &Resource) -> String {
+    let mut out = format!("# signoz {}\n\n", resource.name);
+    for op in &resource.ops {
+        let _ = writeln!(out, "- [`{0}`](#{0})", op.name);
+    }
+    for op in &resource.ops {
+        let _ = writeln!(out, "\n## {}\n", op.name);
+        if op.deprecated {
+            out.push_str("**Deprecated.**\n\n");
+        }
+        let _ = writeln!(out, "`{} {}`\n", op.method, op.path);
+        for text in prose(op) {
+            let _ = writeln!(out, "{text}\n");
+        }
+        let _ = writeln!(out, "```bash\n{}\n```", super::example(&resource.name, op));
+        if !op.params.is_empty() {
+            out.push_str("\n| Flag | Type | In | Required |\n|---|---|---|---|\n");
+            for param in &op.params {
+                let _ = writeln!(
+                    out,
+                    "| `--{}` | {}{} | {} | {} |",
+                    param.flag,
+                    param.schema_type,
+                    if param.is_array { "[]" } else { "" },
+                    param.location,
+                    if param.required { "yes" } else { "no" }
+                );
+            }
+        }
+        if let Some(body) = &op.request_body {
+            let _ = writeln!(
+                out,
+                "\nBody: `{}` ({}{})",
+                body.schema_type,
+                body.content_type,
+                if body.required { ", required" } else { "" }
+            );
+            if let Some(schema) = body.schema.as_deref().and_then(pretty) {
+                let _ = writeln!(out, "\n```json\n{schema}\n```");
+            }
+        }
+    }
+    out
+}
+
+fn man_index(tree: &CommandTree) -> String {
+    let mut out = man_header("signoz", tree);
+    out.push_str(".SH NAME\nsignoz \\- command line client for the SigNoz API\n");
+    out.push_str(".SH RESOURCES\n");
+    for resource in &tree.resources {
+        let _ = writeln!(
+            out,
+            ".TP\n.B {}\n{} operation{}; see\n.BR signoz\\-{} (1)",
+            man_escape(&resource.name),
+            resource.ops.len(),
+            if resource.ops.len() == 1 { "" } else { "s" },
+            man_escape(&resource.name)
+        );
+    }
+    out
+}
+
+fn man_page(tree: &CommandTree, resource: &Resource) -> String {
+    let name = man_escape(&resource.name);
+    let mut out = man_header(&format!("signoz-{}", resource.name), tree);
+    let _ = writeln!(
+        out,
+        ".SH NAME\nsignoz\\-{name} \\- {} operation{}",
+        resource.ops.len(),
+        if resource.ops.len() == 1 { "" } else { "s" }
+    );
+    let _ = writeln!(out, ".SH SYNOPSIS\n.B signoz {name}\n.I OPERATION\n[flags]");
+    out.push_str(".SH OPERATIONS\n");
+    for op in &resource.ops {
+        let _ = writeln!(out, ".SS {}", man_escape(&op.name));
+        if op.deprecated {
+            out.push_str("Deprecated.\n.PP\n");
+        }
+        let _ = writeln!(out, ".B {} {}", op.method, man_escape(&op.path));
+        for text in prose(op) {
+            let _ = writeln!(out, ".PP\n{}", man_escape(text));
+        }
+        if !op.params.is_empty() {
+            out.push_str(".PP\nFlags:\n");
+            for param in &op.params {
+                let mut notes = vec![param.location.clone()];
+                if param.required {
+                    notes.push("required".to_string());
+                }
+                if param.is_array {
+                    notes.push("repeatable".to_string());
+                }
+                let _ = writeln!(
+                    out,
+                    ".TP\n.B \\-\\-{}\n{} ({})",
+                    man_escape(&param.flag),
+                    man_escape(&param.schema_type),
+                    notes.join(", ")
+                );
+            }
+        }
+        if let Some(body) = &op.request_body {
+            let _ = writeln!(
+                out,
+                ".PP\nBody: {} ({}{})",
+                man_escape(&body.schema_type),
+                man_escape(&body.content_type),
+                if body.required { ", required" } else { "" }
+            );
+            if let Some(schema) = body.schema.as_deref().and_then(pretty) {
+                let _ = writeln!(out, ".PP\n.nf\n{}\n.fi", man_escape(&schema));
+            }
+        }
+        let _ = writeln!(
+            out,
+            ".PP\nExample:\n.PP\n.nf\n{}\n.fi",
+            man_escape(&super::example(&resource.name, op))
+        );
+    }
+    let _ = writeln!(out, ".SH SEE ALSO\n.BR signoz (1)");
+    out
+}
+
+fn man_header(title: &str, tree: &CommandTree) -> String {
+    let date = tree
+        .generated_at
+        .as_deref()
+        .and_then(|at| at.get(..10))
+        .unwrap_or_default();
+    format!(
+        ".TH \"{}\" 1 \"{date}\" \"signoz {}\" \"signoz manual\"\n",
+        man_escape(&title.to_uppercase()),
+        env!("CARGO_PKG_VERSION")
+    )
+}
+
+/// The summary, then the description when it says more.
+fn prose(op: &Operation) -> Vec<&str> {
+    let summary = op
+        .summary
+        .as_deref()
+        .map(str::trim)
+        .filter(|s| !s.is_empty());
+    let description = op
+        .description
+        .as_deref()
+        .map(str::trim)
+        .filter(|d| !d.is_empty() && Some(*d) != summary);
+    summary.into_iter().chain(description).collect()
+}
+
+fn pretty(schema: &str) -> Option<String> {
+    serde_json::from_str::<Value>(schema)
+        .ok()
+        .and_then(|value| serde_json::to_string_pretty(&value).ok())
+}
+
+/// Escapes roff: backslashes and hyphens, and control characters at the
+/// start of a line.
+fn man_escape(text: &str) -> String {
+    text.replace('\\', "\\e")
+        .replace('-', "\\-")
+        .lines()
+        .map(|line| match line.starts_with(['.', '\'']) {
+            true => format!("\\&{line}"),
+            false => line.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -0,0 +1,362 @@
+use anyhow::{anyhow, Result};
+use clap::{Arg, ArgAction, Command};
+use serde_json::{json, Value};
+use std::time::Instant;
+use url::Url;
+
+use crate::auth;
+use crate::http::{ClientOptions, HttpClient, HttpResponse};
+use crate::output::{self, OutputOptions};
+use crate::query;
+use crate::timeparse;
+
+/// Unauthenticated endpoints every SigNoz query service serves.
+const HEALTH_PATH: &str = "/api/v1/health";
+const VERSION_PATH: &str = "/api/v1/version";
+/// Cheap authenticated request used to test credentials.
+const WHOAMI_PATH: &str = "/api/v1/user/me";
+/// Skew beyond which relative time ranges (`--since 15m`) drift noticeably.
+const MAX_SKEW_MS: u64 = 30_000;
+
+pub fn command() -> Command {
+    Command::new("doctor")
+        .about("Check the base URL, TLS, API reachability, credentials, server version, and clock skew")
+        .arg(
+            Arg::new("json")
+                .long("json")
+                .action(ArgAction::SetTrue)
+                .help("Emit machine-readable JSON"),
+        )
+}
+
+/// Credentials as resolved from flags, the profile, and the environment,
+/// before falling back to a stored login.
+pub struct Credentials<'a> {
+    pub api_key: Option<&'a String>,
+    pub token: Option<&'a String>,
+}
+
+pub struct Report {
+    checks: Vec<Value>,
+}
+
+impl Report {
+    fn add(&mut self, check: &str, status: &str, detail: impl Into<String>, hint: Option<&str>) {
+        self.checks.push(json!({
+            "check": check,
+            "status": status,
+            "detail": detail.into(),
+            "hint": hint,
+        }));
+    }
+
+    pub fn failures(&self) -> usize {
+        self.checks.iter().filter(|c| c["status"] == "fail").count()
+    }
+
+    pub fn print(&self) {
+        for check in &self.checks {
+            let field = |key: &str| check[key].as_str().unwrap_or_default();
+            println!(
+                "{:<5} {:<12} {}",
+                field("status").to_uppercase(),
+                field("check"),
+                field("detail")
+            );
+            if let Some(hint) = check["hint"].as_str() {
+                println!("{:18}-> {hint}", "");
+            }
+        }
+    }
+}
+
+pub fn run(
+    matches: &clap::ArgMatches,
+    base_url: &str,
+    credentials: Credentials,
+    headers: &[(String, String)],
+    client_opts: &ClientOptions,
+    opts: &OutputOptions,
+) -> Result<()> {
+    let report = checks(base_url, credentials, headers, client_opts);
+    if matches.get_flag("json") {
+        output::print_value(&json!({ "checks": report.checks }), opts)?;
+    } else {
+        report.print();
+    }
+    match report.failures() {
+        0 => Ok(()),
+        n => Err(anyhow!("{n} check(s) failed")),
+    }
+}
+
+/// Runs every check against `base_url`; `signoz init` uses this to verify a
+/// profile before saving it.
+pub fn checks(
+    base_url: &str,
+    credentials: Credentials,
+    headers: &[(String, String)],
+    client_opts: &ClientOptions,
+) -> Report {
+    let mut report = Report { checks: Vec::new() };
+    check(&mut report, base_url, credentials, headers, client_opts);
+    report
+}
+
+fn check(
+    report: &mut Report,
+    base_url: &str,
+    credentials: Credentials,
+    headers: &[(String, String)],
+    client_opts: &ClientOptions,
+) {
+    let url = match Url::parse(base_url) {
+        Ok(url) if matches!(url.scheme(), "http" | "https" | "unix") => url,
+        _ => {
+            report.add(
+                "base url",
+                "fail",
+                base_url,
+                Some("set --base-url, a profile base_url, or SIGNOZ_API_URL to http(s)://host[:port] or unix:///PATH"),
+            );
+            return;
+        }
+    };
+    let local = matches!(
+        url.host_str(),
+        Some("localhost" | "127.0.0.1" | "::1" | "[::1]")
+    );
+    if url.scheme() == "http" && !local {
+        report.add(
+            "base url",
+            "warn",
+            base_url,
+            Some("credentials are sent unencrypted; use https"),
+        );
+    } else {
+        report.add("base url", "pass", base_url, None);
+    }
+
+    let client = |api_key: Option<&String>, token: Option<&String>| {
+        HttpClient::new(
+            base_url.to_string(),
+            api_key.cloned(),
+            token.cloned(),
+            headers.to_vec(),
+            client_opts,
+        )
+    };
+    let anonymous = match client(None, None) {
+        Ok(client) => client,
+        Err(err) => {
+            report.add("client", "fail", format!("{err:#}"), None);
+            return;
+        }
+    };
+
+    let started = Instant::now();
+    let health = match anonymous.execute("GET", HEALTH_PATH, &[], None, None) {
+        Ok(response) => response,
+        Err(err) => {
+            let message = format!("{err:#}");
+            let lower = message.to_lowercase();
+            if ["certificate", "tls", "ssl", "handshake"]
+                .iter()
+                .any(|w| lower.contains(w))
+            {
+                report.add(
+                    "tls",
+                    "fail",
+                    message,
+                    Some("trust an internal CA with --ca-cert ca.pem (or --insecure to test)"),
+                );
+            } else {
+                report.add(
+                    "reachable",
+                    "fail",
+                    message,
+                    Some("check the host and port, VPN, and HTTPS_PROXY/--proxy"),
+                );
+            }
+            return;
+        }
+    };
+    report.add(
+        "reachable",
+        "pass",
+        format!(
+            "GET {HEALTH_PATH} -> {} in {} ms",
+            health.status,
+            started.elapsed().as_millis()
+        ),
+        None,
+    );
+    match (url.scheme(), client_opts.insecure) {
+        ("https", false) => report.add("tls", "pass", "certificate verified", None),
+        ("https", true) => report.add(
+            "tls",
+            "warn",
+            "certificate verification disabled (--insecure)",
+            Some("use --ca-cert instead of --insecure"),
+        ),
+        _ => report.add("tls", "skip", "plain http", None),
+    }
+
+    if crate::is_html_response(&health) {
+        report.add(
+            "api",
+            "fail",
+            "the base URL serves the web UI, not the API",
+            Some("point --base-url at the SigNoz query service (the host serving /api/v1), not the frontend or a marketing site"),
+        );
+        return;
+    }
+    let detail = format!("GET {HEALTH_PATH} -> {}", health.status);
+    match health.status {
+        200..=299 => report.add("api", "pass", "JSON API responding", None),
+        404 => report.add(
+            "api",
+            "fail",
+            detail,
+            Some("no API at this base URL; drop any path after the host, or check the port"),
+        ),
+        500.. => report.add(
+            "api",
+            "fail",
+            detail,
+            Some("the server is up but failing; check the query service logs"),
+        ),
+        _ => report.add(
+            "api",
+            "warn",
+            detail,
+            Some("a proxy in front of SigNoz may be rejecting requests"),
+        ),
+    }
+
+    match anonymous.execute("GET", VERSION_PATH, &[], None, None) {
+        Ok(response) if response.status < 400 => {
+            let data = response.body.get("data").unwrap_or(&response.body);
+            let version = data
+                .get("version")
+                .and_then(Value::as_str)
+                .unwrap_or("unknown");
+            let edition = match data.get("ee").and_then(Value::as_str) {
+                Some("Y") => " (enterprise)",
+                _ => "",
+            };
+            report.add("version", "pass", format!("{version}{edition}"), None);
+        }
+        Ok(response) => report.add(
+            "version",
+            "warn",
+            format!("GET {VERSION_PATH} -> {}", response.status),
+            None,
+        ),
+        Err(err) => report.add("version", "warn", format!("{err:#}"), None),
+    }
+
+    check_clock(report, &health);
+
+    match credentials.api_key {
+        Some(key) => check_auth(report, "api key", client(Some(key), None)),
+        None => report.add(
+            "api key",
+            "skip",
+            "none configured",
+            Some("set --api-key, a profile api_key, or SIGNOZ_API_KEY"),
+        ),
+    }
+    let stored;
+    let token = match credentials.token {
+        Some(token) => Some(token),
+        None => match auth::stored_token(base_url, client_opts) {
+            Ok(token) => {
+                stored = token;
+                stored.as_ref()
+            }
+            Err(err) => {
+                report.add(
+                    "token",
+                    "fail",
+                    format!("{err:#}"),
+                    Some("run `signoz login` again"),
+                );
+                return;
+            }
+        },
+    };
+    match token {
+        Some(token) => check_auth(report, "token", client(None, Some(token))),
+        None => report.add(
+            "token",
+            "skip",
+            "none configured",
+            Some("run `signoz login` or set --token/SIGNOZ_TOKEN"),
+        ),
+    }
+}
+
+fn check_clock(report: &mut Report, response: &HttpResponse) {
+    let server = response
+        .headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("date"))
+        .and_then(|(_, value)| timeparse::parse_http_date(value));
+    let Some(server) = server else {
+        report.add("clock", "skip", "server sent no Date header", None);
+        return;
+    };
+    let local = query::now_millis();
+    let skew = local.abs_diff(server);
+    let detail = format!(
+        "local clock is {:.1} s {} the server",
+        skew as f64 / 1000.0,
+        if local >= server {
+            "ahead of"
+        } else {
+            "behind"
+        }
+    );
+    if skew > MAX_SKEW_MS {
+        report.add(
+            "clock",
+            "warn",
+            detail,
+            Some("relative time ranges (--since) will be off; sync the clock with NTP"),
+        );
+    } else {
+        report.add("clock", "pass", detail, None);
+    }
+}
+
+fn check_auth(report: &mut Report, check: &str, client: Result<HttpClient>) {
+    let response = client.and_then(|c| c.execute("GET", WHOAMI_PATH, &[], None, None));
+    match response {
+        Ok(response) if response.status < 400 => {
+            let data = response.body.get("data").unwrap_or(&response.body);
+            let who = data
+                .get("email")
+                .and_then(Value::as_str)
+                .map_or(String::new(), |email| format!(" as {email}"));
+            report.add(check, "pass", format!("accepted{who}"), None);
+        }
+        Ok(response) if matches!(response.status, 401 | 403) => report.add(
+            check,
+            "fail",
+            format!("rejected: http {}", response.status),
+            Some(if check == "token" {
+                "the token is expired or revoked; run `signoz login`"
+            } else {
+                "the key is wrong, revoked, or for another instance; create one under Settings > API Keys"
+            }),
+        ),
+        Ok(response) => report.add(
+            check,
+            "warn",
+            format!("GET {WHOAMI_PATH} -> {}", response.status),
+            None,
+        ),
+        Err(err) => report.add(check, "fail", format!("{err:#}"), None),
+    }
+}
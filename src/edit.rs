@@ -0,0 +1,197 @@
+use anyhow::{anyhow, Context, Result};
+use clap::{Arg, ArgAction};
+use serde_json::{json, Value};
+use std::{env, fs, process::Command};
+
+use crate::command_tree::{Operation, Resource};
+use crate::error::ApiError;
+use crate::expect;
+use crate::http::Body;
+use crate::output::{self, OutputOptions};
+use crate::schema;
+use crate::ApiContext;
+
+/// Update ops whose body is only part of what their GET returns: (update
+/// op, JSON pointer into the unwrapped GET response).
+const BODY_POINTERS: &[(&str, &str)] = &[("update-dashboard", "/data")];
+
+pub fn arg() -> Arg {
+    Arg::new("edit")
+        .long("edit")
+        .action(ArgAction::SetTrue)
+        .help("Compose the body in $EDITOR (starts from --body/--set, the current resource for updates, or a schema skeleton)")
+}
+
+pub fn requested(matches: &clap::ArgMatches) -> bool {
+    matches!(matches.try_get_one::<bool>("edit"), Ok(Some(true)))
+}
+
+/// Opens the starting document in the editor and sends what comes back.
+/// Nothing is sent when the file is emptied or left unchanged.
+pub fn run(
+    resource: &Resource,
+    op: &Operation,
+    matches: &clap::ArgMatches,
+    ctx: &ApiContext,
+    opts: &OutputOptions,
+) -> Result<()> {
+    let (path, query, header_params) = crate::build_request_parts(ctx, op, matches)?;
+    let content_type = op
+        .request_body
+        .as_ref()
+        .map_or("application/json", |b| b.content_type.as_str());
+    let initial = initial(resource, op, matches, ctx, &path)?;
+
+    let file = env::temp_dir().join(format!("signoz-{}-{}.json", op.name, std::process::id()));
+    fs::write(
+        &file,
+        format!("{}\n", serde_json::to_string_pretty(&initial)?),
+    )
+    .with_context(|| format!("write {}", file.display()))?;
+    open_editor(&file)?;
+    let edited = fs::read_to_string(&file).with_context(|| format!("read {}", file.display()))?;
+    if edited.trim().is_empty() {
+        let _ = fs::remove_file(&file);
+        eprintln!("Empty file; request not sent.");
+        return Ok(());
+    }
+    let body: Value = serde_json::from_str(&edited).map_err(|err| {
+        anyhow!(
+            "invalid JSON in {} ({err}); fix it and rerun with --body @{}",
+            file.display(),
+            file.display()
+        )
+    })?;
+    if body == initial {
+        let _ = fs::remove_file(&file);
+        eprintln!("No changes; request not sent.");
+        return Ok(());
+    }
+    if let Some(body_def) = op
+        .request_body
+        .as_ref()
+        .filter(|_| !schema::skipped(matches))
+    {
+        schema::check(body_def, &body).with_context(|| {
+            format!(
+                "draft kept in {}; fix it and rerun with --body @{}",
+                file.display(),
+                file.display()
+            )
+        })?;
+    }
+    let _ = fs::remove_file(&file);
+
+    let response = crate::execute_op(
+        ctx,
+        op,
+        &header_params,
+        &path,
+        &query,
+        Some(Body::Json(body)),
+        Some(content_type),
+    )?;
+    crate::ensure_api_response(&path, &response)?;
+    output::print_response(&response, opts)?;
+    expect::check(&response, opts)
+}
+
+/// `--body`/`--set` if given; for PUT/PATCH, the resource as its GET (same
+/// path) returns it; otherwise the schema skeleton.
+fn initial(
+    resource: &Resource,
+    op: &Operation,
+    matches: &clap::ArgMatches,
+    ctx: &ApiContext,
+    path: &str,
+) -> Result<Value> {
+    if matches.get_one::<String>("body").is_some() || crate::bodyset::present(matches) {
+        if let (Some(Body::Json(body)), _) = crate::build_body(op, matches)? {
+            return Ok(body);
+        }
+    }
+    if matches!(op.method.as_str(), "PUT" | "PATCH") {
+        if let Some(current) = current(resource, op, ctx, path)? {
+            return Ok(current);
+        }
+    }
+    let skeleton = op.request_body.as_ref().and_then(|b| b.skeleton.as_deref());
+    Ok(match skeleton {
+        Some(raw) => serde_json::from_str(raw).context("invalid body skeleton in command tree")?,
+        None if op
+            .request_body
+            .as_ref()
+            .is_some_and(|b| b.schema_type.starts_with("array")) =>
+        {
+            json!([])
+        }
+        None => json!({}),
+    })
+}
+
+/// The resource an update op at `path` changes, as its GET (same path)
+/// returns it, narrowed to the part the update's body holds and the fields
+/// its schema declares (the GET also returns ids, timestamps, and other
+/// server-side fields). `None` when the resource has no such GET.
+pub fn current(
+    resource: &Resource,
+    op: &Operation,
+    ctx: &ApiContext,
+    path: &str,
+) -> Result<Option<Value>> {
+    let Some(get) = resource
+        .ops
+        .iter()
+        .find(|o| o.method == "GET" && o.path == op.path)
+    else {
+        return Ok(None);
+    };
+    let response = ctx.execute("GET", path, &[], None, None)?;
+    crate::ensure_api_response(path, &response)?;
+    if response.status >= 400 {
+        return Err(ApiError::from_response(&response))
+            .with_context(|| format!("{} failed", get.name));
+    }
+    let current = match response.body {
+        Value::Object(mut map) if map.contains_key("status") && map.contains_key("data") => {
+            map.remove("data").unwrap_or(Value::Null)
+        }
+        other => other,
+    };
+    let mut current = match BODY_POINTERS.iter().find(|(name, _)| *name == op.name) {
+        Some((_, pointer)) => current.pointer(pointer).cloned().unwrap_or(current),
+        None => current,
+    };
+    if let Some(body_def) = &op.request_body {
+        schema::project(body_def, &mut current)?;
+    }
+    Ok(Some(current))
+}
+
+/// `$VISUAL`, then `$EDITOR`, then vi (notepad on Windows). The variable
+/// may carry arguments, e.g. `code --wait`.
+fn open_editor(file: &std::path::Path) -> Result<()> {
+    let editor = env::var("VISUAL")
+        .ok()
+        .or_else(|| env::var("EDITOR").ok())
+        .filter(|e| !e.trim().is_empty())
+        .unwrap_or_else(|| if cfg!(windows) { "notepad" } else { "vi" }.to_string());
+    let status = if cfg!(windows) {
+        Command::new("cmd")
+            .args(["/C", &format!("{editor} \"{}\"", file.display())])
+            .status()
+    } else {
+        Command::new("sh")
+            .args(["-c", &format!("{editor} \"$1\""), "sh"])
+            .arg(file)
+            .status()
+    }
+    .with_context(|| format!("start editor `{editor}`"))?;
+    if !status.success() {
+        return Err(anyhow!(
+            "editor `{editor}` exited with {status}; request not sent (draft kept in {})",
+            file.display()
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,344 @@
+use anyhow::Result;
+use clap::{Arg, ArgAction, Command};
+use serde_json::{json, Value};
+use std::env;
+
+use crate::config::{self, Config, Profile};
+use crate::CommandTree;
+
+/// Every environment variable the CLI reads, with whether its value is a
+/// credential and what it does.
+const VARIABLES: &[(&str, bool, &str)] = &[
+    (
+        "SIGNOZ_API_URL",
+        false,
+        "base URL (after --base-url and the profile's base_url)",
+    ),
+    (
+        "SIGNOZ_ENDPOINT",
+        false,
+        "alias of SIGNOZ_API_URL, used only when that is unset",
+    ),
+    (
+        "SIGNOZ_API_KEY",
+        true,
+        "API key (after --api-key and the profile's api_key)",
+    ),
+    (
+        "SIGNOZ_ACCESS_TOKEN",
+        true,
+        "alias of SIGNOZ_API_KEY (sent as an API key, not a token), used only when that is unset",
+    ),
+    (
+        "SIGNOZ_TOKEN",
+        true,
+        "bearer token (after --token and the profile's token); wins over `signoz login`",
+    ),
+    (
+        "SIGNOZ_PROFILE",
+        false,
+        "profile from config.toml to use when --profile is not given",
+    ),
+    (
+        "SIGNOZ_CONFIG",
+        false,
+        "config file path (default: config.toml in the config directory)",
+    ),
+    (
+        "SIGNOZ_CONFIG_DIR",
+        false,
+        "directory for config.toml, credentials.json, and a synced command_tree.json",
+    ),
+    (
+        "XDG_CONFIG_HOME",
+        false,
+        "config directory parent when SIGNOZ_CONFIG_DIR is unset",
+    ),
+    (
+        "SIGNOZ_COMMAND_TREE",
+        false,
+        "command tree JSON used instead of the synced or built-in one (after --command-tree)",
+    ),
+    (
+        "SIGNOZ_COMMAND_TREE_OVERLAY",
+        false,
+        "extra endpoints merged into the command tree (after --command-tree-overlay)",
+    ),
+    (
+        "SIGNOZ_OPENAPI_URL",
+        false,
+        "OpenAPI document for `signoz spec sync` when --url is not given",
+    ),
+    (
+        "SIGNOZ_CLI_TRACE",
+        false,
+        "set to 1 to export spans of the CLI's own work, like --trace-self",
+    ),
+    (
+        "OTEL_EXPORTER_OTLP_ENDPOINT",
+        false,
+        "OTLP/HTTP collector for `ingest send` and --trace-self (default: http://localhost:4318)",
+    ),
+    (
+        "OTEL_SERVICE_NAME",
+        false,
+        "service.name of --trace-self spans (default: signoz-cli)",
+    ),
+    (
+        "SIGNOZ_INGESTION_KEY",
+        true,
+        "ingestion key sent to the collector by `ingest send` and --trace-self",
+    ),
+    (
+        "SIGNOZ_NO_HISTORY",
+        false,
+        "set to 1 to keep commands out of `signoz history`",
+    ),
+    (
+        "NO_COLOR",
+        false,
+        "disable ANSI colors in output (unless `--color always`)",
+    ),
+    (
+        "PAGER",
+        false,
+        "pager for `signoz docs` and output taller than the terminal",
+    ),
+];
+
+pub fn command() -> Command {
+    Command::new("env")
+        .about("List the environment variables the CLI reads, which are set, and how credentials resolve")
+        .arg(
+            Arg::new("json")
+                .long("json")
+                .action(ArgAction::SetTrue)
+                .help("Emit machine-readable JSON"),
+        )
+        .arg(
+            Arg::new("export")
+                .long("export")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("json")
+                .help("Print the resolved base URL and credentials as `export` lines, for `eval` in shells and CI"),
+        )
+        .arg(
+            Arg::new("mask")
+                .long("mask")
+                .action(ArgAction::SetTrue)
+                .requires("export")
+                .help("Mask the exported API key and token, for logs"),
+        )
+}
+
+pub fn handle(
+    root: &clap::ArgMatches,
+    matches: &clap::ArgMatches,
+    tree: &CommandTree,
+) -> Result<()> {
+    if matches.get_flag("export") {
+        return export(root, matches.get_flag("mask"), tree);
+    }
+    let get = |name: &str| env::var(name).ok().filter(|v| !v.is_empty());
+    let variables: Vec<Value> = VARIABLES
+        .iter()
+        .map(|&(name, secret, about)| {
+            let value = get(name).map(|v| if secret { mask(&v) } else { v });
+            json!({ "name": name, "set": value.is_some(), "value": value, "about": about })
+        })
+        .collect();
+
+    let mut warnings = Vec::new();
+    let mut unknown: Vec<String> = env::vars()
+        .map(|(name, _)| name)
+        .filter(|name| name.starts_with("SIGNOZ_"))
+        .filter(|name| !VARIABLES.iter().any(|(known, _, _)| known == name))
+        .collect();
+    unknown.sort();
+    for name in unknown {
+        warnings.push(format!("{name} is not read by the CLI (typo?)"));
+    }
+
+    let config = match config::load() {
+        Ok(config) => config,
+        Err(err) => {
+            warnings.push(format!("{err:#}"));
+            Config::default()
+        }
+    };
+    let flag = |name: &str| root.get_one::<String>(name).cloned();
+    let profile_name = flag("profile")
+        .map(|name| (name, "--profile"))
+        .or_else(|| get("SIGNOZ_PROFILE").map(|name| (name, "SIGNOZ_PROFILE")));
+    let profile = match &profile_name {
+        Some((name, source)) => match config.profile(name) {
+            Ok(profile) => profile.clone(),
+            Err(err) => {
+                warnings.push(format!("{source}: {err:#}"));
+                Profile::default()
+            }
+        },
+        None => Profile::default(),
+    };
+    if let (Some((name, "--profile")), Some(_)) = (&profile_name, get("SIGNOZ_PROFILE")) {
+        warnings.push(format!(
+            "SIGNOZ_PROFILE is ignored: --profile {name} is given"
+        ));
+    }
+    let from_profile = |field: &Option<String>| {
+        field
+            .as_ref()
+            .and(profile_name.as_ref())
+            .map(|(name, _)| format!("profile {name}"))
+    };
+
+    // Sources in the order `run` consults them; the first one set wins and
+    // every later one that is also set is shadowed.
+    let chains = [
+        (
+            "base url",
+            vec![
+                flag("base-url").map(|_| "--base-url".to_string()),
+                from_profile(&profile.base_url),
+                get("SIGNOZ_API_URL").map(|_| "SIGNOZ_API_URL".to_string()),
+                get("SIGNOZ_ENDPOINT").map(|_| "SIGNOZ_ENDPOINT".to_string()),
+            ],
+        ),
+        (
+            "api key",
+            vec![
+                flag("api-key").map(|_| "--api-key".to_string()),
+                from_profile(&profile.api_key),
+                get("SIGNOZ_API_KEY").map(|_| "SIGNOZ_API_KEY".to_string()),
+                get("SIGNOZ_ACCESS_TOKEN").map(|_| "SIGNOZ_ACCESS_TOKEN".to_string()),
+            ],
+        ),
+        (
+            "token",
+            vec![
+                flag("token").map(|_| "--token".to_string()),
+                from_profile(&profile.token),
+                get("SIGNOZ_TOKEN").map(|_| "SIGNOZ_TOKEN".to_string()),
+            ],
+        ),
+    ];
+    let mut resolved = serde_json::Map::new();
+    for (setting, chain) in chains {
+        let mut set = chain.into_iter().flatten();
+        let winner = set.next();
+        for shadowed in set {
+            let winner = winner.as_deref().unwrap_or_default();
+            warnings.push(format!(
+                "{shadowed} is ignored for the {setting}: {winner} wins"
+            ));
+        }
+        resolved.insert(setting.to_string(), json!(winner));
+    }
+    if resolved["base url"].is_null() {
+        resolved.insert("base url".to_string(), json!("command tree default"));
+    }
+    if resolved["token"].is_null() {
+        resolved.insert("token".to_string(), json!("stored `signoz login`, if any"));
+    }
+    let auth = root
+        .get_one::<String>("auth")
+        .map_or("auto", String::as_str);
+    match (auth, resolved["api key"].is_null()) {
+        ("token", false) => warnings.push(format!(
+            "--auth token: the api key from {} is never sent",
+            resolved["api key"].as_str().unwrap_or_default()
+        )),
+        ("api-key", true) => {
+            warnings.push("--auth api-key but no API key is configured".to_string())
+        }
+        _ => {}
+    }
+    if get("SIGNOZ_CONFIG_DIR").is_some() && get("XDG_CONFIG_HOME").is_some() {
+        warnings.push("XDG_CONFIG_HOME is ignored: SIGNOZ_CONFIG_DIR is set".to_string());
+    }
+
+    let config_path = config::config_path()
+        .map(|p| p.display().to_string())
+        .unwrap_or_default();
+    if matches.get_flag("json") {
+        let out = json!({
+            "variables": variables,
+            "config": config_path,
+            "profile": profile_name.map(|(name, _)| name),
+            "resolved": resolved,
+            "warnings": warnings,
+        });
+        println!("{}", serde_json::to_string_pretty(&out)?);
+        return Ok(());
+    }
+    for var in &variables {
+        let value = var["value"].as_str().unwrap_or("-");
+        println!(
+            "{:<20} {:<28} {}",
+            var["name"].as_str().unwrap_or_default(),
+            truncate(value, 28),
+            var["about"].as_str().unwrap_or_default()
+        );
+    }
+    println!("\nconfig file: {config_path}");
+    for (setting, source) in &resolved {
+        println!("{setting}: {}", source.as_str().unwrap_or("none"));
+    }
+    for warning in &warnings {
+        eprintln!("warning: {warning}");
+    }
+    Ok(())
+}
+
+/// `export` lines for the base URL, API key, and token a command run with
+/// the same flags would use, resolved by the same steps as `run`
+/// (including a stored `signoz login`, refreshed if it is about to expire).
+fn export(root: &clap::ArgMatches, masked: bool, tree: &CommandTree) -> Result<()> {
+    let config = config::load()?;
+    let crate::Credentials {
+        base_url,
+        api_key,
+        token,
+    } = crate::resolve_credentials(
+        &config,
+        tree,
+        &crate::CredentialFlags::from_matches(root),
+        &crate::client_options(root, &config)?,
+    )?;
+
+    let secret = |value: String| if masked { mask(&value) } else { value };
+    let exports = [
+        ("SIGNOZ_API_URL", Some(base_url)),
+        ("SIGNOZ_API_KEY", api_key.map(secret)),
+        ("SIGNOZ_TOKEN", token.map(secret)),
+    ];
+    for (name, value) in exports {
+        if let Some(value) = value {
+            println!("export {name}={}", shell_quote(&value));
+        }
+    }
+    Ok(())
+}
+
+/// Single-quoted for POSIX shells, with embedded quotes spliced in.
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+/// Enough of a credential to tell two apart without revealing it.
+fn mask(value: &str) -> String {
+    let count = value.chars().count();
+    if count <= 8 {
+        return format!("*** ({count} chars)");
+    }
+    let tail: String = value.chars().skip(count - 4).collect();
+    format!("***{tail} ({count} chars)")
+}
+
+fn truncate(value: &str, width: usize) -> String {
+    if value.chars().count() <= width {
+        return value.to_string();
+    }
+    let head: String = value.chars().take(width - 3).collect();
+    format!("{head}...")
+}
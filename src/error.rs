@@ -0,0 +1,136 @@
+use serde_json::Value;
+use std::fmt;
+
+use crate::http::HttpResponse;
+
+/// Exit codes by class of failure, so scripts can branch on them. Clap
+/// exits with [`EXIT_USAGE`] itself for bad flags and arguments; a Ctrl-C
+/// exits with `interrupt::EXIT_INTERRUPTED`.
+pub const EXIT_FAILURE: i32 = 1;
+pub const EXIT_USAGE: i32 = 2;
+pub const EXIT_AUTH: i32 = 3;
+pub const EXIT_NOT_FOUND: i32 = 4;
+pub const EXIT_SERVER: i32 = 5;
+pub const EXIT_NETWORK: i32 = 6;
+pub const EXIT_BUDGET: i32 = 7;
+
+/// An HTTP error from the API, with SigNoz's error envelope parsed: either
+/// `{"status":"error","errorType":...,"error":"..."}` or the newer
+/// `{"status":"error","error":{"code":...,"message":...}}`.
+#[derive(Debug)]
+pub struct ApiError {
+    pub status: u16,
+    /// `errorType` or `error.code`, e.g. `not_found` or `bad_data`.
+    pub error_type: Option<String>,
+    pub message: Option<String>,
+    /// The `X-Request-ID` sent, and the server's id for the request when
+    /// it reports a different one; what support asks for.
+    pub request_id: Option<String>,
+    pub server_request_id: Option<String>,
+}
+
+impl ApiError {
+    pub fn from_response(response: &HttpResponse) -> Self {
+        let body = &response.body;
+        let text = |value: Option<&Value>| {
+            value
+                .and_then(Value::as_str)
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(str::to_string)
+        };
+        let (error_type, message) = match body.get("error") {
+            Some(Value::Object(error)) => (
+                text(error.get("code")).or_else(|| text(body.get("errorType"))),
+                text(error.get("message")),
+            ),
+            Some(error @ Value::String(_)) => (text(body.get("errorType")), text(Some(error))),
+            _ => (text(body.get("errorType")), text(body.get("message"))),
+        };
+        Self {
+            status: response.status,
+            error_type,
+            message,
+            request_id: response.request_id.clone(),
+            server_request_id: response.server_request_id().map(str::to_string),
+        }
+    }
+
+    /// By HTTP status, or by the error type when the status says little.
+    pub fn exit_code(&self) -> i32 {
+        match self.status {
+            401 | 403 => return EXIT_AUTH,
+            404 | 410 => return EXIT_NOT_FOUND,
+            500.. => return EXIT_SERVER,
+            _ => {}
+        }
+        match self.error_type.as_deref() {
+            Some("unauthorized" | "unauthenticated" | "forbidden") => EXIT_AUTH,
+            Some("not_found") => EXIT_NOT_FOUND,
+            Some("internal" | "unavailable" | "timeout" | "execution") => EXIT_SERVER,
+            _ => EXIT_FAILURE,
+        }
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "http {}", self.status)?;
+        match (&self.error_type, &self.message) {
+            (Some(kind), Some(message)) => write!(f, " ({kind}): {message}"),
+            (Some(kind), None) => write!(f, " ({kind})"),
+            (None, Some(message)) => write!(f, ": {message}"),
+            (None, None) => Ok(()),
+        }?;
+        match (&self.request_id, &self.server_request_id) {
+            (Some(ours), Some(theirs)) => write!(f, " [request id {ours}, server id {theirs}]"),
+            (Some(ours), None) => write!(f, " [request id {ours}]"),
+            (None, Some(theirs)) => write!(f, " [server id {theirs}]"),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+/// A failure of a known class with no API error behind it, such as
+/// conflicting flags, a lapsed login, or a response over
+/// `--max-body-bytes` or `--max-duration`.
+#[derive(Debug)]
+pub enum Failure {
+    Usage(String),
+    Auth(String),
+    Budget(String),
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Failure::Usage(message) | Failure::Auth(message) | Failure::Budget(message) => {
+                f.write_str(message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Failure {}
+
+/// The exit code for an error from `run`: the first classified cause in
+/// its chain, else [`EXIT_FAILURE`].
+pub fn exit_code(err: &anyhow::Error) -> i32 {
+    for cause in err.chain() {
+        if let Some(err) = cause.downcast_ref::<ApiError>() {
+            return err.exit_code();
+        }
+        match cause.downcast_ref::<Failure>() {
+            Some(Failure::Usage(_)) => return EXIT_USAGE,
+            Some(Failure::Auth(_)) => return EXIT_AUTH,
+            Some(Failure::Budget(_)) => return EXIT_BUDGET,
+            None => {}
+        }
+        if cause.is::<reqwest::Error>() {
+            return EXIT_NETWORK;
+        }
+    }
+    EXIT_FAILURE
+}
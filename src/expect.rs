@@ -0,0 +1,212 @@
+use anyhow::{anyhow, Result};
+use serde_json::Value;
+
+use crate::error::ApiError;
+use crate::http::HttpResponse;
+use crate::output::OutputOptions;
+
+const OPERATORS: &[&str] = &["==", "!=", ">=", "<=", ">", "<"];
+
+/// `--expect-status` / `--expect-json` checks that turn a command into an
+/// assertion.
+#[derive(Clone, Debug, Default)]
+pub struct Expectations {
+    statuses: Vec<String>,
+    checks: Vec<Check>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Check {
+    source: String,
+    path: Vec<String>,
+    length: bool,
+    compare: Option<(&'static str, Value)>,
+}
+
+impl Expectations {
+    pub fn from_matches(matches: &clap::ArgMatches) -> Result<Option<Self>> {
+        Self::new(
+            matches
+                .get_many::<String>("expect-status")
+                .into_iter()
+                .flatten(),
+            matches
+                .get_many::<String>("expect-json")
+                .into_iter()
+                .flatten(),
+        )
+    }
+
+    /// From `--expect-status` and `--expect-json` values; `None` when both
+    /// are empty.
+    pub fn new<'a>(
+        statuses: impl Iterator<Item = &'a String>,
+        checks: impl Iterator<Item = &'a String>,
+    ) -> Result<Option<Self>> {
+        let statuses: Vec<String> = statuses
+            .flat_map(|raw| raw.split(','))
+            .map(|s| s.trim().to_ascii_lowercase())
+            .filter(|s| !s.is_empty())
+            .collect();
+        for status in &statuses {
+            let valid = status.len() == 3
+                && status
+                    .chars()
+                    .enumerate()
+                    .all(|(i, c)| c.is_ascii_digit() || (i > 0 && c == 'x'));
+            if !valid {
+                return Err(anyhow!(
+                    "invalid --expect-status `{status}`; use 200 or 2xx"
+                ));
+            }
+        }
+        let checks = checks
+            .map(|raw| Check::parse(raw))
+            .collect::<Result<Vec<_>>>()?;
+        if statuses.is_empty() && checks.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(Self { statuses, checks }))
+    }
+}
+
+impl Check {
+    /// Parses `.data.items[0].name == "x"`, `.data | length > 0`, or a bare
+    /// path (must be present and truthy). The right-hand side is JSON, or a
+    /// plain string when it is not valid JSON.
+    pub fn parse(raw: &str) -> Result<Self> {
+        let (lhs, compare) = match OPERATORS
+            .iter()
+            .filter_map(|op| raw.find(op).map(|at| (at, *op)))
+            .min_by_key(|(at, op)| (*at, std::cmp::Reverse(op.len())))
+        {
+            Some((at, op)) => {
+                let rhs = raw[at + op.len()..].trim();
+                let value = serde_json::from_str(rhs).unwrap_or_else(|_| Value::String(rhs.into()));
+                (&raw[..at], Some((op, value)))
+            }
+            None => (raw, None),
+        };
+        let mut lhs = lhs.trim();
+        let mut length = false;
+        if let Some((path, func)) = lhs.split_once('|') {
+            if func.trim() != "length" {
+                return Err(anyhow!(
+                    "unsupported function `{}` in --expect-json",
+                    func.trim()
+                ));
+            }
+            lhs = path.trim();
+            length = true;
+        }
+        let path = lhs
+            .strip_prefix('.')
+            .ok_or_else(|| anyhow!("--expect-json path must start with `.`: {raw}"))?;
+        let path = path
+            .replace('[', ".")
+            .replace(']', "")
+            .split('.')
+            .filter(|s| !s.is_empty())
+            .map(str::to_string)
+            .collect();
+        Ok(Self {
+            source: raw.to_string(),
+            path,
+            length,
+            compare,
+        })
+    }
+
+    pub fn holds(&self, value: &Value) -> bool {
+        self.eval(value).is_ok()
+    }
+
+    /// Errors with the value actually found when the check does not hold.
+    fn eval(&self, body: &Value) -> Result<()> {
+        let mut found = Some(body);
+        for segment in &self.path {
+            found = found.and_then(|v| match v {
+                Value::Array(items) => segment.parse::<usize>().ok().and_then(|i| items.get(i)),
+                _ => v.get(segment),
+            });
+        }
+        let mut found = found.cloned().unwrap_or(Value::Null);
+        if self.length {
+            found = Value::from(match &found {
+                Value::Array(items) => items.len(),
+                Value::Object(map) => map.len(),
+                Value::String(s) => s.chars().count(),
+                _ => 0,
+            });
+        }
+        let ok = match &self.compare {
+            None => !matches!(found, Value::Null | Value::Bool(false)),
+            Some((op, expected)) => compare(&found, op, expected),
+        };
+        if ok {
+            Ok(())
+        } else {
+            Err(anyhow!("expectation failed: {} (got {found})", self.source))
+        }
+    }
+}
+
+fn compare(found: &Value, op: &str, expected: &Value) -> bool {
+    match op {
+        "==" => found == expected,
+        "!=" => found != expected,
+        _ => {
+            let ordering = match (found, expected) {
+                (Value::Number(a), Value::Number(b)) => a
+                    .as_f64()
+                    .zip(b.as_f64())
+                    .and_then(|(a, b)| a.partial_cmp(&b)),
+                (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+                _ => None,
+            };
+            let Some(ordering) = ordering else {
+                return false;
+            };
+            match op {
+                ">" => ordering.is_gt(),
+                "<" => ordering.is_lt(),
+                ">=" => ordering.is_ge(),
+                _ => ordering.is_le(),
+            }
+        }
+    }
+}
+
+fn status_matches(pattern: &str, status: u16) -> bool {
+    let status = status.to_string();
+    pattern
+        .chars()
+        .zip(status.chars())
+        .all(|(p, s)| p == 'x' || p == s)
+}
+
+/// Fails on HTTP errors, or, when expectations are given, on any mismatch
+/// (an expected 4xx status is then not an error).
+pub fn check(response: &HttpResponse, opts: &OutputOptions) -> Result<()> {
+    verify(response, opts.expect.as_ref())
+}
+
+/// [`check`] against `expect` rather than the command line's.
+pub fn verify(response: &HttpResponse, expect: Option<&Expectations>) -> Result<()> {
+    let statuses = expect.map_or(&[][..], |e| &e.statuses);
+    if statuses.is_empty() {
+        if response.status >= 400 {
+            return Err(ApiError::from_response(response).into());
+        }
+    } else if !statuses.iter().any(|p| status_matches(p, response.status)) {
+        return Err(anyhow!(
+            "expectation failed: status {} not in {}",
+            response.status,
+            statuses.join(",")
+        ));
+    }
+    for check in expect.iter().flat_map(|e| &e.checks) {
+        check.eval(&response.body)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,156 @@
+use anyhow::{anyhow, Result};
+use serde_json::{json, Map, Value};
+use std::{sync::Arc, thread};
+
+use crate::auth;
+use crate::command_tree::CommandTree;
+use crate::config::Config;
+use crate::error::{ApiError, Failure};
+use crate::http::{self, ClientOptions};
+use crate::output::{self, OutputOptions};
+use crate::ApiContext;
+
+/// The profiles `--all-profiles` or `--profiles a,b` name, in order; `None`
+/// for a single-instance run.
+pub fn profiles(matches: &clap::ArgMatches, config: &Config) -> Result<Option<Vec<String>>> {
+    if matches.get_flag("all-profiles") {
+        let mut names: Vec<String> = config.profiles.keys().cloned().collect();
+        if names.is_empty() {
+            return Err(Failure::Usage(
+                "--all-profiles: no profiles configured ([profiles.<name>])".into(),
+            )
+            .into());
+        }
+        names.sort();
+        return Ok(Some(names));
+    }
+    let Some(names) = matches.get_many::<String>("profiles") else {
+        return Ok(None);
+    };
+    let mut out: Vec<String> = Vec::new();
+    for name in names.map(|n| n.trim()).filter(|n| !n.is_empty()) {
+        config.profile(name)?;
+        if !out.iter().any(|n| n == name) {
+            out.push(name.to_string());
+        }
+    }
+    Ok(Some(out))
+}
+
+/// Runs the resource operation on the command line against every profile
+/// at once and prints one object keyed by profile: each response as it
+/// would print alone, or `{"error": ...}`. Fails afterwards if any did.
+pub fn run(
+    names: &[String],
+    matches: &clap::ArgMatches,
+    tree: &CommandTree,
+    config: &Config,
+    client_opts: &ClientOptions,
+    opts: &OutputOptions,
+) -> Result<()> {
+    let usage = || -> anyhow::Error {
+        Failure::Usage("--all-profiles/--profiles only run resource operations".into()).into()
+    };
+    let Some((res_name, res_matches)) = matches.subcommand() else {
+        return Err(usage());
+    };
+    let Some((op_name, op_matches)) = res_matches.subcommand() else {
+        return Err(usage());
+    };
+    let Some(op) = crate::find_op(tree, res_name, op_name) else {
+        return Err(usage());
+    };
+    if crate::edit::requested(op_matches) || crate::watch::options(matches)?.is_some() {
+        return Err(Failure::Usage(
+            "--edit and --watch cannot be combined with --all-profiles/--profiles".into(),
+        )
+        .into());
+    }
+
+    let results: Vec<Result<Value>> = thread::scope(|scope| {
+        let handles: Vec<_> = names
+            .iter()
+            .map(|name| {
+                scope.spawn(move || {
+                    let ctx = context(name, matches, tree, config, client_opts)?;
+                    let (response, _) = crate::run_op(&ctx, op, op_matches)?;
+                    if response.status >= 400 {
+                        return Err(ApiError::from_response(&response).into());
+                    }
+                    let value = output::response_value(&response, opts)?;
+                    Ok(output::shape(&value, opts)?.unwrap_or(value))
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|_| Err(anyhow!("profile worker panicked")))
+            })
+            .collect()
+    });
+
+    let mut out = Map::new();
+    let mut failed = 0;
+    for (name, result) in names.iter().zip(results) {
+        let value = result.unwrap_or_else(|err| {
+            failed += 1;
+            json!({ "error": format!("{err:#}") })
+        });
+        out.insert(name.clone(), value);
+    }
+    // Each profile's items were already filtered, sorted, and projected.
+    let opts = OutputOptions {
+        conditions: Vec::new(),
+        sort: Vec::new(),
+        fields: None,
+        ..opts.clone()
+    };
+    output::print_value(&Value::Object(out), &opts)?;
+    match failed {
+        0 => Ok(()),
+        n => Err(anyhow!("{n} of {} profiles failed", names.len())),
+    }
+}
+
+/// The instance and credentials of one profile, resolved as `--profile`
+/// would; `--api-key`, `--token`, and `--auth` still apply to all.
+fn context(
+    name: &str,
+    matches: &clap::ArgMatches,
+    tree: &CommandTree,
+    config: &Config,
+    client_opts: &ClientOptions,
+) -> Result<ApiContext> {
+    let flags = crate::CredentialFlags {
+        profile: Some(name.to_string()),
+        base_url: None,
+        ..crate::CredentialFlags::from_matches(matches)
+    };
+    let crate::Credentials {
+        base_url,
+        api_key,
+        token,
+    } = crate::resolve_credentials(config, tree, &flags, client_opts)?;
+    // A `unix://` profile connects through its own socket, with a pool of
+    // its own; `--unix-socket` still applies to all.
+    let mut client_opts = client_opts.clone();
+    if client_opts.unix_socket.is_none() {
+        if let Some(path) = http::unix_socket_path(&base_url) {
+            client_opts.unix_socket = Some(path);
+            client_opts.shared = Arc::default();
+        }
+    }
+    let auth_flag = matches.get_one::<String>("auth");
+    Ok(ApiContext {
+        auth_mode: auth::parse_auth_mode(auth_flag, api_key.as_ref(), token.as_ref()),
+        base_url,
+        api_key,
+        token,
+        headers: crate::request_headers(matches, config)?,
+        client_opts,
+        defaults: config.defaults.clone(),
+    })
+}
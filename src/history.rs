@@ -0,0 +1,388 @@
+use anyhow::{anyhow, Context, Result};
+use clap::{Arg, ArgAction, Command};
+use serde::{Deserialize, Serialize};
+use std::{
+    env,
+    fs::{self, OpenOptions},
+    io::Write,
+    path::PathBuf,
+};
+
+use crate::error::Failure;
+use crate::{capture, config, query, timeparse};
+
+/// Entries kept; the file is cut back to this many once it holds twice as
+/// many, so appending stays cheap.
+const MAX_ENTRIES: usize = 1000;
+/// Stands in for inline request bodies and `--set`/`--set-json`/`--form`
+/// values, which may hold passwords or keys.
+const REDACTED: &str = "<redacted>";
+/// Flags whose inline values are request content.
+const CONTENT_FLAGS: &[&str] = &["--body", "--patch", "--set", "--set-json", "--form"];
+
+#[derive(Deserialize, Serialize)]
+struct Entry {
+    at: String,
+    args: Vec<String>,
+    exit: i32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    cwd: Option<String>,
+}
+
+pub fn history_command() -> Command {
+    Command::new("history")
+        .about("Show the commands run before, numbered for `signoz rerun`")
+        .long_about(
+            "Every command that gets as far as running is recorded with its time and exit \
+             code in history.jsonl in the config directory, readable only by you. \
+             Credentials (--api-key, --token, --password, auth headers, and user:password in \
+             URLs) are left out, and inline --body, --patch, --set, --set-json, and --form \
+             values are recorded as <redacted>. Set SIGNOZ_NO_HISTORY=1 to record nothing.",
+        )
+        .arg(
+            Arg::new("limit")
+                .long("limit")
+                .short('n')
+                .value_name("N")
+                .value_parser(clap::value_parser!(usize))
+                .default_value("20")
+                .help("Show the last N commands (0 for all)"),
+        )
+        .arg(
+            Arg::new("json")
+                .long("json")
+                .action(ArgAction::SetTrue)
+                .help("Emit machine-readable JSON"),
+        )
+        .arg(
+            Arg::new("clear")
+                .long("clear")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all(["limit", "json"])
+                .help("Delete the history"),
+        )
+}
+
+pub fn rerun_command() -> Command {
+    Command::new("rerun")
+        .about("Run a command from `signoz history` again")
+        .long_about(
+            "Runs history entry N (default: the last) with the current credentials, since \
+             those were not recorded. --set NAME=VALUE replaces the value of --NAME, or adds \
+             --NAME VALUE when the command did not have it.",
+        )
+        .arg(
+            Arg::new("n")
+                .value_name("N")
+                .value_parser(clap::value_parser!(i64))
+                .allow_negative_numbers(true)
+                .help("Entry number; negative counts back from the last (-1)"),
+        )
+        .arg(
+            Arg::new("set")
+                .long("set")
+                .value_name("NAME=VALUE")
+                .action(ArgAction::Append)
+                .help("Replace or add --NAME VALUE (repeatable), e.g. --set since=24h"),
+        )
+        .arg(
+            Arg::new("print")
+                .long("print")
+                .action(ArgAction::SetTrue)
+                .help("Print the command line instead of running it"),
+        )
+}
+
+fn history_path() -> Result<PathBuf> {
+    Ok(config::config_dir()?.join("history.jsonl"))
+}
+
+fn load() -> Result<Vec<Entry>> {
+    let raw = match fs::read_to_string(history_path()?) {
+        Ok(raw) => raw,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err).context("read history"),
+    };
+    // A line cut short by a crash is skipped rather than failing the rest.
+    Ok(raw
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+/// Appends the command that just ended. Best effort: a history that cannot
+/// be written never changes how the command ends.
+pub fn record(exit: i32) {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let skip = matches!(
+        crate::first_subcommand(&crate::build_root(), &args).map(|i| args[i].as_str()),
+        None | Some("history" | "rerun")
+    );
+    if skip || env::var_os("SIGNOZ_NO_HISTORY").is_some_and(|v| !v.is_empty() && v != "0") {
+        return;
+    }
+    let entry = Entry {
+        at: timeparse::format_rfc3339(query::now_millis()),
+        args: scrub(&args),
+        exit,
+        cwd: env::current_dir()
+            .ok()
+            .map(|dir| dir.to_string_lossy().into_owned()),
+    };
+    let _ = append(&entry);
+}
+
+fn append(entry: &Entry) -> Result<()> {
+    let path = history_path()?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut options = OpenOptions::new();
+    options.create(true).append(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        options.mode(0o600);
+        // Files from before this was private too.
+        if let Ok(meta) = fs::metadata(&path) {
+            if meta.permissions().mode() & 0o077 != 0 {
+                fs::set_permissions(&path, fs::Permissions::from_mode(0o600))?;
+            }
+        }
+    }
+    let mut file = options.open(&path)?;
+    writeln!(file, "{}", serde_json::to_string(entry)?)?;
+    drop(file);
+    let entries = load()?;
+    if entries.len() >= 2 * MAX_ENTRIES {
+        let kept: Vec<String> = entries[entries.len() - MAX_ENTRIES..]
+            .iter()
+            .map(serde_json::to_string)
+            .collect::<Result<_, _>>()?;
+        let partial = path.with_extension("jsonl.partial");
+        fs::write(&partial, kept.join("\n") + "\n")?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&partial, fs::Permissions::from_mode(0o600))?;
+        }
+        fs::rename(partial, path)?;
+    }
+    Ok(())
+}
+
+/// The arguments without credentials: secret flags with their values and
+/// auth headers are dropped, not masked, so a rerun falls back to the
+/// configured credentials. `user:password@` is cut from URLs, and inline
+/// bodies and `--set`, `--set-json`, and `--form` values are masked.
+fn scrub(args: &[String]) -> Vec<String> {
+    let mut out = Vec::with_capacity(args.len());
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let flag = arg.split_once('=').map_or(arg.as_str(), |(flag, _)| flag);
+        if capture::SECRET_FLAGS.contains(&flag) {
+            if flag == arg {
+                args.next();
+            }
+            continue;
+        }
+        if CONTENT_FLAGS.contains(&flag) {
+            match arg.split_once('=') {
+                Some((flag, value)) => out.push(format!("{flag}={}", redact_content(flag, value))),
+                None => {
+                    out.push(arg.clone());
+                    if let Some(value) = args.next() {
+                        out.push(redact_content(flag, value));
+                    }
+                }
+            }
+            continue;
+        }
+        if arg == "--header" {
+            match args.next() {
+                Some(value) if capture::is_secret_header(value) => {}
+                Some(value) => out.extend([arg.clone(), value.clone()]),
+                None => out.push(arg.clone()),
+            }
+            continue;
+        }
+        if arg
+            .strip_prefix("--header=")
+            .is_some_and(capture::is_secret_header)
+        {
+            continue;
+        }
+        out.push(strip_userinfo(arg));
+    }
+    out
+}
+
+/// A `--body` or `--patch` that is inline content (not `@file` or `-`), or
+/// the value half of `--set`/`--set-json`/`--form NAME=VALUE`, replaced by
+/// [`REDACTED`]. A `--form` file upload (`NAME=@file`) is kept.
+fn redact_content(flag: &str, value: &str) -> String {
+    match flag {
+        "--set" | "--set-json" | "--form" => match value.split_once('=') {
+            Some((_, file)) if flag == "--form" && file.starts_with('@') => value.to_string(),
+            Some((name, _)) => format!("{name}={REDACTED}"),
+            None => value.to_string(),
+        },
+        _ if value.starts_with('@') || value == "-" => value.to_string(),
+        _ => REDACTED.to_string(),
+    }
+}
+
+/// `URL` or `--flag=URL` without the `user:password@` part.
+fn strip_userinfo(arg: &str) -> String {
+    let (prefix, value) = match arg.split_once('=') {
+        Some((flag, value)) if flag.starts_with("--") => (format!("{flag}="), value),
+        _ => (String::new(), arg),
+    };
+    match url::Url::parse(value) {
+        Ok(mut url) if !url.username().is_empty() || url.password().is_some() => {
+            let _ = url.set_username("");
+            let _ = url.set_password(None);
+            format!("{prefix}{url}")
+        }
+        _ => arg.to_string(),
+    }
+}
+
+pub fn handle_history(matches: &clap::ArgMatches) -> Result<()> {
+    if matches.get_flag("clear") {
+        let path = history_path()?;
+        match fs::remove_file(&path) {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => {
+                return Err(err).with_context(|| format!("remove {}", path.display()))
+            }
+            _ => {}
+        }
+        eprintln!("Cleared the history.");
+        return Ok(());
+    }
+    let entries = load()?;
+    let limit = *matches.get_one::<usize>("limit").expect("default");
+    let start = match limit {
+        0 => 0,
+        limit => entries.len().saturating_sub(limit),
+    };
+    let shown = entries.iter().enumerate().skip(start);
+    if matches.get_flag("json") {
+        let rows: Vec<serde_json::Value> = shown
+            .map(|(i, entry)| {
+                let mut row = serde_json::to_value(entry).unwrap_or_default();
+                row["n"] = (i + 1).into();
+                row
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&rows)?);
+        return Ok(());
+    }
+    if entries.is_empty() {
+        eprintln!("No history yet.");
+    }
+    for (i, entry) in shown {
+        println!(
+            "{:>5}  {}  {:>3}  {}",
+            i + 1,
+            entry.at,
+            entry.exit,
+            command_line(&entry.args)
+        );
+    }
+    Ok(())
+}
+
+pub fn handle_rerun(matches: &clap::ArgMatches) -> Result<()> {
+    let entries = load()?;
+    if entries.is_empty() {
+        return Err(anyhow!("no history yet"));
+    }
+    let n = matches.get_one::<i64>("n").copied().unwrap_or(-1);
+    let index = match n {
+        n if n > 0 => usize::try_from(n - 1).ok(),
+        n if n < 0 => entries.len().checked_sub(n.unsigned_abs() as usize),
+        _ => None,
+    }
+    .filter(|&i| i < entries.len())
+    .ok_or_else(|| {
+        Failure::Usage(format!(
+            "no history entry {n} (there are {}; see `signoz history`)",
+            entries.len()
+        ))
+    })?;
+    let mut args = entries[index].args.clone();
+    for raw in matches.get_many::<String>("set").into_iter().flatten() {
+        let (name, value) = raw
+            .split_once('=')
+            .filter(|(name, _)| !name.is_empty())
+            .ok_or_else(|| {
+                Failure::Usage(format!("invalid --set {raw:?} (expected NAME=VALUE)"))
+            })?;
+        set_flag(&mut args, name.trim_start_matches('-'), value);
+    }
+    if matches.get_flag("print") {
+        println!("signoz {}", command_line(&args));
+        return Ok(());
+    }
+    if args.iter().any(|arg| arg.contains(REDACTED)) {
+        return Err(Failure::Usage(format!(
+            "entry {} has redacted values; replace them with --set NAME=VALUE: signoz {}",
+            index + 1,
+            command_line(&args)
+        ))
+        .into());
+    }
+    eprintln!("signoz {}", command_line(&args));
+    let exe = env::current_exe().context("locate the signoz executable")?;
+    let mut cmd = std::process::Command::new(&exe);
+    cmd.args(&args);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        let err = cmd.exec();
+        Err(anyhow!("run {}: {err}", exe.display()))
+    }
+    #[cfg(not(unix))]
+    {
+        let status = cmd
+            .status()
+            .with_context(|| format!("run {}", exe.display()))?;
+        std::process::exit(status.code().unwrap_or(1));
+    }
+}
+
+/// Replaces the value of `--name` (as `--name VALUE` or `--name=VALUE`),
+/// or appends `--name VALUE` when the command did not have it.
+fn set_flag(args: &mut Vec<String>, name: &str, value: &str) {
+    let flag = format!("--{name}");
+    let inline = format!("{flag}=");
+    for i in 0..args.len() {
+        if args[i].starts_with(&inline) {
+            args[i] = format!("{inline}{value}");
+            return;
+        }
+        if args[i] == flag && i + 1 < args.len() {
+            args[i + 1] = value.to_string();
+            return;
+        }
+    }
+    args.extend([flag, value.to_string()]);
+}
+
+/// The arguments as one line a POSIX shell would split back into them.
+fn command_line(args: &[String]) -> String {
+    let quote = |arg: &String| {
+        let plain = !arg.is_empty()
+            && arg
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "-_./:=,@%+".contains(c));
+        if plain {
+            arg.clone()
+        } else {
+            format!("'{}'", arg.replace('\'', "'\\''"))
+        }
+    };
+    args.iter().map(quote).collect::<Vec<_>>().join(" ")
+}
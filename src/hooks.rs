@@ -0,0 +1,152 @@
+use anyhow::{anyhow, Context, Result};
+use serde_json::{json, Value};
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+    thread,
+};
+
+/// `[hooks]` commands run through the shell around every request. Each gets
+/// a JSON document on stdin; printing a (possibly modified) document on
+/// stdout replaces the request or response, printing nothing keeps it, and
+/// a non-zero exit aborts the command.
+#[derive(Clone, Debug, Default)]
+pub struct Hooks {
+    pub pre_request: Option<String>,
+    pub post_response: Option<String>,
+}
+
+/// What `pre_request` sees and may change.
+pub struct Request {
+    pub method: String,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<String>,
+}
+
+/// What `post_response` sees and may change.
+pub struct Response {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl Hooks {
+    /// `{method, url, headers: [[name, value]], body}`; `body` is the request
+    /// text or null.
+    pub fn pre_request(&self, request: &mut Request) -> Result<()> {
+        let Some(command) = &self.pre_request else {
+            return Ok(());
+        };
+        let input = json!({
+            "method": request.method,
+            "url": request.url,
+            "headers": request.headers,
+            "body": request.body,
+        });
+        let Some(output) = run("pre_request", command, &input)? else {
+            return Ok(());
+        };
+        if let Some(method) = output.get("method").and_then(Value::as_str) {
+            request.method = method.to_string();
+        }
+        if let Some(url) = output.get("url").and_then(Value::as_str) {
+            request.url = url.to_string();
+        }
+        if let Some(headers) = output.get("headers") {
+            request.headers = pairs("pre_request", headers)?;
+        }
+        if let Some(body) = output.get("body") {
+            request.body = body.as_str().map(str::to_string);
+        }
+        Ok(())
+    }
+
+    /// `{request: {method, url}, status, headers: [[name, value]], body}`;
+    /// `body` is the response text.
+    pub fn post_response(&self, method: &str, url: &str, response: &mut Response) -> Result<()> {
+        let Some(command) = &self.post_response else {
+            return Ok(());
+        };
+        let input = json!({
+            "request": { "method": method, "url": url },
+            "status": response.status,
+            "headers": response.headers,
+            "body": response.body,
+        });
+        let Some(output) = run("post_response", command, &input)? else {
+            return Ok(());
+        };
+        if let Some(status) = output.get("status").and_then(Value::as_u64) {
+            response.status =
+                u16::try_from(status).map_err(|_| anyhow!("post_response hook: bad status"))?;
+        }
+        if let Some(headers) = output.get("headers") {
+            response.headers = pairs("post_response", headers)?;
+        }
+        match output.get("body") {
+            Some(Value::String(body)) => response.body = body.clone(),
+            Some(Value::Null) | None => {}
+            Some(other) => response.body = other.to_string(),
+        }
+        Ok(())
+    }
+}
+
+fn run(stage: &str, command: &str, input: &Value) -> Result<Option<Value>> {
+    let (shell, flag) = if cfg!(windows) {
+        ("cmd", "/C")
+    } else {
+        ("sh", "-c")
+    };
+    let mut child = Command::new(shell)
+        .args([flag, command])
+        .env("SIGNOZ_HOOK", stage)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()
+        .with_context(|| format!("start {stage} hook `{command}`"))?;
+    // Written from its own thread: a hook that prints before it has read
+    // everything would otherwise fill its stdout pipe while we are still
+    // blocked on its stdin.
+    let writer = child.stdin.take().map(|mut stdin| {
+        let input = input.to_string();
+        // A hook that only inspects headers may exit without reading.
+        thread::spawn(move || {
+            let _ = stdin.write_all(input.as_bytes());
+        })
+    });
+    let output = child
+        .wait_with_output()
+        .with_context(|| format!("run {stage} hook"))?;
+    if let Some(writer) = writer {
+        let _ = writer.join();
+    }
+    if !output.status.success() {
+        return Err(anyhow!(
+            "{stage} hook `{command}` failed: {}",
+            output.status
+        ));
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if stdout.trim().is_empty() {
+        return Ok(None);
+    }
+    serde_json::from_str(&stdout)
+        .map(Some)
+        .with_context(|| format!("{stage} hook printed invalid JSON"))
+}
+
+fn pairs(stage: &str, headers: &Value) -> Result<Vec<(String, String)>> {
+    let invalid = || anyhow!("{stage} hook: headers must be [[name, value], ...]");
+    headers
+        .as_array()
+        .ok_or_else(invalid)?
+        .iter()
+        .map(|pair| match pair.as_array().map(Vec::as_slice) {
+            Some([Value::String(name), Value::String(value)]) => Ok((name.clone(), value.clone())),
+            _ => Err(invalid()),
+        })
+        .collect()
+}
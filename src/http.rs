@@ -1,29 +1,832 @@
-use anyhow::{Context, Result};
-use reqwest::blocking::Client;
-use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
-use serde_json::Value;
-use std::time::Duration;
+use anyhow::{anyhow, Context, Result};
+use flate2::write::{GzDecoder, GzEncoder};
+use flate2::Compression;
+use reqwest::header::{
+    HeaderMap, HeaderName, HeaderValue, ACCEPT_ENCODING, CONTENT_ENCODING, CONTENT_LENGTH,
+    CONTENT_TYPE, IF_MODIFIED_SINCE, IF_NONE_MATCH,
+};
+use reqwest::Client;
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::{
+    env, fs,
+    io::{self, IsTerminal, Write},
+    net::{IpAddr, SocketAddr, ToSocketAddrs},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, OnceLock},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+use tokio::runtime::Runtime;
 use url::Url;
 
+use crate::cache;
+use crate::capture;
+use crate::error::{ApiError, Failure};
+use crate::hooks::{self, Hooks};
+use crate::progress::Progress;
+use crate::selftrace;
+use crate::stream::{Emitter, Splitter};
+
+/// Upper bound for a single backoff sleep, including server `Retry-After`.
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60);
+/// `--compress` leaves smaller bodies alone; gzip would barely help.
+const COMPRESS_MIN_BYTES: usize = 1024;
+
+#[derive(Clone, Debug)]
+pub struct ClientOptions {
+    pub timeout_secs: Option<u64>,
+    /// Extra attempts for 429 and 5xx responses.
+    pub retries: u32,
+    /// Extra attempts after DNS, connection, and timeout errors, counted
+    /// apart from `retries`.
+    pub connect_retries: u32,
+    /// Base delay for exponential backoff between retries.
+    pub retry_delay: Duration,
+    /// `--record`/`--replay` interaction log shared by every client.
+    pub cassette: Option<Arc<Cassette>>,
+    /// `-v` logs requests and statuses to stderr; `-vv` adds response headers.
+    pub verbosity: u8,
+    /// `--proxy` URL for all schemes; otherwise `HTTP(S)_PROXY` from the env.
+    pub proxy: Option<String>,
+    /// PEM bundle trusted in addition to the built-in roots.
+    pub ca_cert: Option<PathBuf>,
+    /// PEM client certificate and key for mTLS.
+    pub client_cert: Option<(PathBuf, PathBuf)>,
+    /// Skip server certificate verification.
+    pub insecure: bool,
+    /// `--unix-socket`, or the path of a `unix://` base URL: connect there
+    /// instead of to the URL's host.
+    pub unix_socket: Option<PathBuf>,
+    /// `--resolve`/`--connect-to`: hosts connected to at these addresses
+    /// instead of what DNS says; TLS and `Host` keep the name.
+    pub resolve: Vec<(String, Vec<SocketAddr>)>,
+    /// `--request-id`: the idempotency key for writes instead of a fresh
+    /// one per request.
+    pub request_id: Option<String>,
+    /// `X-Request-ID` sent with every request of this invocation, for
+    /// matching it with server logs; none when empty.
+    pub invocation_id: String,
+    /// `--deadline`: wall-clock cutoff for every request, retry and page.
+    pub deadline: Option<Instant>,
+    /// Bodies larger than this need confirmation; `None` with `--force`.
+    pub max_body_bytes: Option<u64>,
+    /// Oversized bodies already let through, as method, path, and size,
+    /// shared by every client so later pages, retries and the auth fallback
+    /// of the same request do not ask again.
+    pub confirmed_bodies: Arc<Mutex<Vec<(String, String, u64)>>>,
+    /// `--max-body-bytes`, `--max-duration` and `--truncate`: how much of
+    /// each response is read.
+    pub budget: ResponseBudget,
+    /// `[hooks]` commands run before each request and after each response.
+    pub hooks: Hooks,
+    /// `-o/--output-file`: where binary bodies are streamed; a file in the
+    /// temp directory otherwise.
+    pub download_to: Option<PathBuf>,
+    /// `--compress`: gzip request bodies of [`COMPRESS_MIN_BYTES`] or more.
+    pub compress: bool,
+    /// GET responses with an `ETag` or `Last-Modified` are cached on disk
+    /// and revalidated; within this `--cache-ttl` they are served without
+    /// asking. `None` with `--no-cache`.
+    pub cache_ttl: Option<Duration>,
+    /// `--rate`: requests per second across every client built from these
+    /// options, so batches, pages and imports share one budget.
+    pub rate_limit: Option<Arc<RateLimiter>>,
+    /// `--timing`: record DNS, connect, time-to-first-byte and total per
+    /// attempt in [`HttpResponse::timing`].
+    pub timing: bool,
+    /// Connection pool shared by every client built from these options;
+    /// created on first use.
+    pub shared: Arc<OnceLock<Client>>,
+    /// `--stream`: items of JSON list responses are written here as they
+    /// are parsed instead of being kept in [`HttpResponse::body`].
+    pub stream: Option<Arc<Emitter>>,
+    /// For live operations: no overall timeout, since the connection stays
+    /// open until Ctrl-C (`--timeout` bounds connecting instead), and
+    /// HTTP/1.1 only, so WebSocket upgrades work.
+    pub live: bool,
+}
+
+impl Default for ClientOptions {
+    fn default() -> Self {
+        Self {
+            timeout_secs: None,
+            retries: 0,
+            connect_retries: 0,
+            retry_delay: Duration::from_millis(500),
+            cassette: None,
+            verbosity: 0,
+            proxy: None,
+            ca_cert: None,
+            client_cert: None,
+            insecure: false,
+            unix_socket: None,
+            resolve: Vec::new(),
+            request_id: None,
+            invocation_id: String::new(),
+            deadline: None,
+            max_body_bytes: None,
+            confirmed_bodies: Arc::default(),
+            budget: ResponseBudget::default(),
+            hooks: Hooks::default(),
+            download_to: None,
+            compress: false,
+            cache_ttl: None,
+            rate_limit: None,
+            timing: false,
+            shared: Arc::default(),
+            stream: None,
+            live: false,
+        }
+    }
+}
+
+/// Limits on each response, so an unbounded query cannot fill the
+/// terminal or the memory of a CI runner.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ResponseBudget {
+    /// Bytes of (decompressed) body read at most.
+    pub bytes: Option<u64>,
+    /// Time from sending the request until the body is read.
+    pub duration: Option<Duration>,
+    /// Keep what arrived within the budget, with a warning, instead of
+    /// failing.
+    pub truncate: bool,
+}
+
+impl ClientOptions {
+    /// The shared `reqwest` client, so fallbacks, retries, pages and
+    /// concurrent queries reuse connections.
+    fn client(&self) -> Result<Client> {
+        if let Some(client) = self.shared.get() {
+            return Ok(client.clone());
+        }
+        let mut builder = Client::builder().user_agent("signoz-cli");
+        match self.timeout_secs {
+            Some(secs) if self.live => builder = builder.connect_timeout(Duration::from_secs(secs)),
+            Some(secs) => builder = builder.timeout(Duration::from_secs(secs)),
+            None => {}
+        }
+        if self.live {
+            builder = builder.http1_only();
+        }
+        if let Some(raw) = &self.proxy {
+            builder = builder.proxy(proxy(raw)?);
+        }
+        if let Some(path) = &self.ca_cert {
+            let pem = fs::read(path).with_context(|| format!("read CA cert {}", path.display()))?;
+            for cert in reqwest::Certificate::from_pem_bundle(&pem).context("invalid --ca-cert")? {
+                builder = builder.add_root_certificate(cert);
+            }
+        }
+        if let Some((cert, key)) = &self.client_cert {
+            let mut pem =
+                fs::read(key).with_context(|| format!("read client key {}", key.display()))?;
+            pem.extend(
+                fs::read(cert).with_context(|| format!("read client cert {}", cert.display()))?,
+            );
+            // PEM identities are only supported by the rustls backend.
+            builder = builder.use_rustls_tls().identity(
+                reqwest::Identity::from_pem(&pem).context("invalid --client-cert/--client-key")?,
+            );
+        }
+        if self.insecure {
+            builder = builder.danger_accept_invalid_certs(true);
+        }
+        if let Some(path) = &self.unix_socket {
+            builder = unix_socket(builder, path)?;
+        }
+        for (host, addrs) in &self.resolve {
+            builder = builder.resolve_to_addrs(host, addrs);
+        }
+        if self.timing {
+            builder = builder
+                .dns_resolver(Arc::new(TimedResolver))
+                .connector_layer(TimedConnect);
+        }
+        let client = builder.build().context("build http client")?;
+        Ok(self.shared.get_or_init(|| client).clone())
+    }
+}
+
+#[cfg(unix)]
+fn unix_socket(builder: reqwest::ClientBuilder, path: &Path) -> Result<reqwest::ClientBuilder> {
+    Ok(builder.unix_socket(path))
+}
+
+#[cfg(not(unix))]
+fn unix_socket(_: reqwest::ClientBuilder, _: &Path) -> Result<reqwest::ClientBuilder> {
+    Err(anyhow!("unix sockets are not supported on this platform"))
+}
+
+/// `Idempotency-Key` for a write (POST, PUT, PATCH, DELETE), so the server
+/// can drop a retried duplicate: `pinned`, else a random UUID. Empty for
+/// reads, and when `headers` already carry a key.
+pub fn idempotency_headers(
+    method: &str,
+    headers: &[(String, String)],
+    pinned: Option<&str>,
+) -> Result<Vec<(String, String)>> {
+    let write = matches!(
+        method.to_ascii_uppercase().as_str(),
+        "POST" | "PUT" | "PATCH" | "DELETE"
+    );
+    if !write
+        || headers
+            .iter()
+            .any(|(name, _)| name.eq_ignore_ascii_case("idempotency-key"))
+    {
+        return Ok(Vec::new());
+    }
+    let key = match pinned {
+        Some(key) => key.to_string(),
+        None => uuid_v4()?,
+    };
+    Ok(vec![("Idempotency-Key".to_string(), key)])
+}
+
+/// `bytes` random bytes as lowercase hex, for tokens and nonces.
+pub fn random_hex(bytes: usize) -> Result<String> {
+    let mut buf = vec![0u8; bytes];
+    SystemRandom::new()
+        .fill(&mut buf)
+        .map_err(|_| anyhow!("no random source"))?;
+    Ok(buf.iter().map(|b| format!("{b:02x}")).collect())
+}
+
+pub fn uuid_v4() -> Result<String> {
+    let mut bytes = [0u8; 16];
+    SystemRandom::new()
+        .fill(&mut bytes)
+        .map_err(|_| anyhow!("no random source"))?;
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex: String = bytes.iter().map(|b| format!("{b:02x}")).collect();
+    Ok(format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    ))
+}
+
+/// curl's `--resolve HOST:PORT:ADDR[,ADDR...]` (IPv6 in brackets). The
+/// override covers HOST on any port: the URL's port is always the one
+/// connected to.
+pub fn parse_resolve(raw: &str) -> Result<(String, Vec<SocketAddr>)> {
+    let mut parts = raw.splitn(3, ':');
+    let (Some(host), Some(port), Some(addrs)) = (parts.next(), parts.next(), parts.next()) else {
+        return Err(anyhow!("expected HOST:PORT:ADDR"));
+    };
+    parse_host_port(host, port)?;
+    let addrs = addrs
+        .split(',')
+        .map(|addr| {
+            let addr = addr.trim().trim_start_matches('[').trim_end_matches(']');
+            addr.parse::<IpAddr>()
+                .map(|ip| SocketAddr::new(ip, 0))
+                .map_err(|_| anyhow!("{addr:?} is not an IP address"))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok((host.to_lowercase(), addrs))
+}
+
+/// curl's `--connect-to HOST:PORT:HOST2:PORT2`, with HOST2 looked up now.
+/// PORT2 may only be empty or PORT: the URL's port is always used.
+pub fn parse_connect_to(raw: &str) -> Result<(String, Vec<SocketAddr>)> {
+    let mut parts = raw.splitn(3, ':');
+    let (Some(host), Some(port), Some(target)) = (parts.next(), parts.next(), parts.next()) else {
+        return Err(anyhow!("expected HOST:PORT:HOST2:PORT2"));
+    };
+    parse_host_port(host, port)?;
+    let (target_host, target_port) = target
+        .rsplit_once(':')
+        .filter(|(_, p)| !p.contains(']'))
+        .unwrap_or((target, ""));
+    if !target_port.is_empty() && target_port != port {
+        return Err(anyhow!(
+            "PORT2 must be empty or {port}; connecting to another port is not supported"
+        ));
+    }
+    let target_host = target_host.trim_start_matches('[').trim_end_matches(']');
+    let addrs: Vec<SocketAddr> = (target_host, 0)
+        .to_socket_addrs()
+        .with_context(|| format!("resolve {target_host}"))?
+        .collect();
+    Ok((host.to_lowercase(), addrs))
+}
+
+fn parse_host_port(host: &str, port: &str) -> Result<()> {
+    if host.is_empty() {
+        return Err(anyhow!("missing HOST"));
+    }
+    if !port.is_empty() && port.parse::<u16>().is_err() {
+        return Err(anyhow!("invalid port {port:?}"));
+    }
+    Ok(())
+}
+
+/// The socket of a `unix:///path/to.sock` base URL. Requests over it are
+/// addressed to `http://localhost`.
+pub fn unix_socket_path(base_url: &str) -> Option<PathBuf> {
+    base_url
+        .strip_prefix("unix://")
+        .map(|path| PathBuf::from(path.trim_end_matches('/')))
+}
+
+/// A token bucket holding a single token: requests are spaced evenly at
+/// the rate, with no bursts. Waits are reserved under the lock, so
+/// concurrent callers queue up in turn.
+#[derive(Debug)]
+pub struct RateLimiter {
+    per_sec: f64,
+    bucket: Mutex<(f64, Instant)>,
+}
+
+impl RateLimiter {
+    const CAPACITY: f64 = 1.0;
+
+    /// `N/s`, `N/m`, `N/h`, or a bare `N` per second; `N` may be fractional.
+    pub fn parse(raw: &str) -> Result<Self> {
+        let (count, unit) = raw.trim().split_once('/').unwrap_or((raw.trim(), "s"));
+        let per = match unit.trim() {
+            "s" | "sec" => 1.0,
+            "m" | "min" => 60.0,
+            "h" | "hour" => 3600.0,
+            other => return Err(anyhow!("unknown rate unit {other:?} (use s, m, or h)")),
+        };
+        let count: f64 = count
+            .trim()
+            .parse()
+            .ok()
+            .filter(|n: &f64| n.is_finite() && *n > 0.0)
+            .ok_or_else(|| anyhow!("expected a positive number of requests, e.g. 5/s"))?;
+        Ok(Self {
+            per_sec: count / per,
+            bucket: Mutex::new((Self::CAPACITY, Instant::now())),
+        })
+    }
+
+    /// Takes a token; returns how long to wait before using it.
+    fn reserve(&self) -> Duration {
+        let mut bucket = self.bucket.lock().unwrap_or_else(|e| e.into_inner());
+        let (tokens, last) = &mut *bucket;
+        let now = Instant::now();
+        *tokens = (*tokens + now.duration_since(*last).as_secs_f64() * self.per_sec)
+            .min(Self::CAPACITY)
+            - 1.0;
+        *last = now;
+        if *tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-*tokens / self.per_sec)
+        }
+    }
+}
+
+tokio::task_local! {
+    /// Connection phases of the request being sent, filled in by the
+    /// resolver and connector `--timing` installs. Unset for connections a
+    /// pool checkout made unnecessary.
+    static PHASES: Arc<Mutex<Phases>>;
+}
+
+#[derive(Default)]
+struct Phases {
+    dns: Option<Duration>,
+    /// Includes `dns`, which happens inside the connector.
+    connect: Option<Duration>,
+}
+
+/// One attempt's durations, for `--timing`. Phases follow each other:
+/// `ttfb_ms` is the wait after the connection is up, and `total_ms` also
+/// covers reading the body. DNS and connect are absent on a reused
+/// connection; connect includes the TLS handshake, which the HTTP client
+/// performs in the same step.
+#[derive(Clone, Debug, Serialize)]
+pub struct Timing {
+    pub method: String,
+    pub url: String,
+    pub status: u16,
+    pub dns_ms: Option<f64>,
+    pub connect_ms: Option<f64>,
+    pub ttfb_ms: f64,
+    pub total_ms: f64,
+}
+
+impl Timing {
+    fn new(method: &str, url: &Url, status: u16, phases: &Phases, started: Instant) -> Self {
+        let dns = phases.dns.unwrap_or_default();
+        Self {
+            method: method.to_string(),
+            url: url.to_string(),
+            status,
+            dns_ms: phases.dns.map(millis),
+            connect_ms: phases.connect.map(|c| millis(c.saturating_sub(dns))),
+            ttfb_ms: millis(
+                started
+                    .elapsed()
+                    .saturating_sub(phases.connect.unwrap_or_default()),
+            ),
+            total_ms: millis(started.elapsed()),
+        }
+    }
+}
+
+/// Extends the last attempt's total over reading its body.
+fn finish_timing(timing: &mut [Timing], started: Instant) {
+    if let Some(last) = timing.last_mut() {
+        last.total_ms = millis(started.elapsed());
+    }
+}
+
+fn millis(duration: Duration) -> f64 {
+    (duration.as_secs_f64() * 1e5).round() / 100.0
+}
+
+/// The system resolver, timed.
+struct TimedResolver;
+
+impl reqwest::dns::Resolve for TimedResolver {
+    fn resolve(&self, name: reqwest::dns::Name) -> reqwest::dns::Resolving {
+        let phases = PHASES.try_with(Arc::clone).ok();
+        let host = name.as_str().to_string();
+        Box::pin(async move {
+            let started = Instant::now();
+            let addrs: Vec<_> = tokio::net::lookup_host((host.as_str(), 0)).await?.collect();
+            if let Some(phases) = phases {
+                phases.lock().unwrap_or_else(|e| e.into_inner()).dns = Some(started.elapsed());
+            }
+            Ok(Box::new(addrs.into_iter()) as reqwest::dns::Addrs)
+        })
+    }
+}
+
+/// Wraps the HTTP client's connector (DNS, TCP, TLS) to time new
+/// connections.
+#[derive(Clone)]
+struct TimedConnect;
+
+impl<S> tower_layer::Layer<S> for TimedConnect {
+    type Service = TimedConnector<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        TimedConnector(inner)
+    }
+}
+
+#[derive(Clone)]
+struct TimedConnector<S>(S);
+
+impl<S, R> tower_service::Service<R> for TimedConnector<S>
+where
+    S: tower_service::Service<R>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future =
+        std::pin::Pin<Box<dyn std::future::Future<Output = Result<S::Response, S::Error>> + Send>>;
+
+    fn poll_ready(
+        &mut self,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), S::Error>> {
+        self.0.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: R) -> Self::Future {
+        let phases = PHASES.try_with(Arc::clone).ok();
+        let started = Instant::now();
+        let connecting = self.0.call(request);
+        Box::pin(async move {
+            let conn = connecting.await;
+            if let Some(phases) = phases {
+                phases.lock().unwrap_or_else(|e| e.into_inner()).connect = Some(started.elapsed());
+            }
+            conn
+        })
+    }
+}
+
+/// Runtime driving every request; sync callers block on it.
+pub fn runtime() -> Result<&'static Runtime> {
+    static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+    if let Some(runtime) = RUNTIME.get() {
+        return Ok(runtime);
+    }
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .context("start async runtime")?;
+    Ok(RUNTIME.get_or_init(|| runtime))
+}
+
 pub struct HttpClient {
     base_url: String,
     api_key: Option<String>,
     token: Option<String>,
     headers: Vec<(String, String)>,
+    retries: u32,
+    connect_retries: u32,
+    request_id: String,
+    retry_delay: Duration,
+    cassette: Option<Arc<Cassette>>,
+    verbosity: u8,
+    timeout: Option<Duration>,
+    deadline: Option<Instant>,
+    max_body_bytes: Option<u64>,
+    confirmed_bodies: Arc<Mutex<Vec<(String, String, u64)>>>,
+    budget: ResponseBudget,
+    hooks: Hooks,
+    download_to: Option<PathBuf>,
+    compress: bool,
+    cache_ttl: Option<Duration>,
+    rate_limit: Option<Arc<RateLimiter>>,
+    timing: bool,
+    stream: Option<Arc<Emitter>>,
     client: Client,
 }
 
+#[derive(Debug)]
 pub struct HttpResponse {
     pub status: u16,
     pub headers: Vec<(String, String)>,
     pub body: Value,
     pub content_type: String,
+    /// Set when a binary body was streamed to this file; `body` then
+    /// describes it as `{"file", "bytes", "content_type"}`.
+    pub saved_to: Option<PathBuf>,
+    /// Every attempt behind this response with `--timing`, retries and
+    /// fallbacks included; empty otherwise.
+    pub timing: Vec<Timing>,
+    /// The `X-Request-ID` sent; `None` for cached and replayed responses.
+    pub request_id: Option<String>,
+    /// With `--stream`, how many items were written out as they arrived;
+    /// `body` then holds the rest of the document with its list emptied.
+    pub streamed: Option<usize>,
+}
+
+impl HttpResponse {
+    /// This response, replacing `earlier` (an auth or API-version
+    /// fallback), with the earlier attempts' timings kept in front.
+    pub fn after(mut self, earlier: HttpResponse) -> Self {
+        let mut timing = earlier.timing;
+        timing.append(&mut self.timing);
+        self.timing = timing;
+        self
+    }
+
+    /// The server's own id for the request, from the first of the usual
+    /// request and trace id headers, unless it only echoes ours.
+    pub fn server_request_id(&self) -> Option<&str> {
+        SERVER_ID_HEADERS.iter().find_map(|name| {
+            self.headers
+                .iter()
+                .find(|(key, _)| key.eq_ignore_ascii_case(name))
+                .map(|(_, value)| value.as_str())
+                .filter(|value| Some(*value) != self.request_id.as_deref())
+        })
+    }
 }
 
+/// Where servers and the proxies in front of them report a request's id.
+const SERVER_ID_HEADERS: [&str; 6] = [
+    "x-request-id",
+    "request-id",
+    "x-trace-id",
+    "traceresponse",
+    "x-amzn-trace-id",
+    "x-cloud-trace-context",
+];
+
 #[derive(Clone)]
 pub enum Body {
     Json(Value),
     Text(String),
+    /// `multipart/form-data`, encoded with a fresh boundary when sent.
+    Form(Vec<FormField>),
+}
+
+#[derive(Clone)]
+pub struct FormField {
+    pub name: String,
+    pub value: FormValue,
+}
+
+#[derive(Clone)]
+pub enum FormValue {
+    Text(String),
+    File {
+        filename: String,
+        content_type: String,
+        data: Vec<u8>,
+    },
+}
+
+impl Body {
+    /// The body as logged, recorded, and shown to hooks; form files appear
+    /// as `name=@file (N bytes)`.
+    fn to_text(&self) -> String {
+        match self {
+            Body::Json(value) => value.to_string(),
+            Body::Text(value) => value.clone(),
+            Body::Form(fields) => fields
+                .iter()
+                .map(|field| match &field.value {
+                    FormValue::Text(value) => format!("{}={value}", field.name),
+                    FormValue::File { filename, data, .. } => {
+                        format!("{}=@{filename} ({} bytes)", field.name, data.len())
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join("&"),
+        }
+    }
+
+    /// The bytes sent, and the content type the body dictates: JSON's
+    /// default, or a form's boundary.
+    fn encode(&self) -> Result<(Vec<u8>, Option<String>)> {
+        Ok(match self {
+            Body::Json(value) => (
+                serde_json::to_vec(value)?,
+                Some("application/json".to_string()),
+            ),
+            Body::Text(value) => (value.clone().into_bytes(), None),
+            Body::Form(fields) => {
+                let (content_type, data) = encode_multipart(fields);
+                (data, Some(content_type))
+            }
+        })
+    }
+
+    fn size(&self) -> u64 {
+        match self {
+            Body::Form(fields) => fields
+                .iter()
+                .map(|field| match &field.value {
+                    FormValue::Text(value) => value.len() as u64,
+                    FormValue::File { data, .. } => data.len() as u64,
+                })
+                .sum(),
+            other => other.to_text().len() as u64,
+        }
+    }
+}
+
+/// Encodes the fields as `multipart/form-data`; returns the content type
+/// (with its boundary) and the body.
+fn encode_multipart(fields: &[FormField]) -> (String, Vec<u8>) {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+    let boundary = format!("signoz-{nanos:x}-{:x}", std::process::id());
+    let mut out = Vec::new();
+    for field in fields {
+        out.extend_from_slice(format!("--{boundary}\r\n").as_bytes());
+        let name = field.name.replace('"', "%22");
+        match &field.value {
+            FormValue::Text(value) => {
+                out.extend_from_slice(
+                    format!("Content-Disposition: form-data; name=\"{name}\"\r\n\r\n").as_bytes(),
+                );
+                out.extend_from_slice(value.as_bytes());
+            }
+            FormValue::File {
+                filename,
+                content_type,
+                data,
+            } => {
+                out.extend_from_slice(
+                    format!(
+                        "Content-Disposition: form-data; name=\"{name}\"; filename=\"{}\"\r\n\
+                         Content-Type: {content_type}\r\n\r\n",
+                        filename.replace('"', "%22")
+                    )
+                    .as_bytes(),
+                );
+                out.extend_from_slice(data);
+            }
+        }
+        out.extend_from_slice(b"\r\n");
+    }
+    out.extend_from_slice(format!("--{boundary}--\r\n").as_bytes());
+    (format!("multipart/form-data; boundary={boundary}"), out)
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct Interaction {
+    method: String,
+    url: String,
+    request_body: Option<String>,
+    status: u16,
+    headers: Vec<(String, String)>,
+    content_type: String,
+    body: String,
+}
+
+#[derive(Debug)]
+enum CassetteMode {
+    Record,
+    Replay,
+    /// Kept in memory for a `--capture` bundle.
+    Capture,
+}
+
+/// Request/response pairs captured with `--record` and served back with
+/// `--replay`, so scripts can run offline against a known transcript.
+#[derive(Debug)]
+pub struct Cassette {
+    path: PathBuf,
+    mode: CassetteMode,
+    interactions: Mutex<Vec<(Interaction, bool)>>,
+}
+
+impl Cassette {
+    pub fn record(path: PathBuf) -> Self {
+        Self {
+            path,
+            mode: CassetteMode::Record,
+            interactions: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn capture() -> Self {
+        Self {
+            path: PathBuf::new(),
+            mode: CassetteMode::Capture,
+            interactions: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn replay(path: PathBuf) -> Result<Self> {
+        let raw = fs::read_to_string(&path)
+            .with_context(|| format!("read replay file {}", path.display()))?;
+        let interactions: Vec<Interaction> =
+            serde_json::from_str(&raw).context("invalid replay file")?;
+        Ok(Self {
+            path,
+            mode: CassetteMode::Replay,
+            interactions: Mutex::new(interactions.into_iter().map(|i| (i, false)).collect()),
+        })
+    }
+
+    /// Rewrites the whole file after each interaction so the transcript
+    /// survives an early exit. Credentials are masked first, as in `-v`
+    /// output, so a transcript can be shared.
+    fn push(&self, interaction: Interaction) -> Result<()> {
+        let mut raw = serde_json::to_value(&interaction)?;
+        capture::redact_interaction(&mut raw);
+        let interaction: Interaction = serde_json::from_value(raw)?;
+        let mut guard = self
+            .interactions
+            .lock()
+            .map_err(|_| anyhow!("record lock poisoned"))?;
+        guard.push((interaction, true));
+        if matches!(self.mode, CassetteMode::Capture) {
+            return Ok(());
+        }
+        let all: Vec<&Interaction> = guard.iter().map(|(i, _)| i).collect();
+        fs::write(&self.path, serde_json::to_string_pretty(&all)?)
+            .with_context(|| format!("write record file {}", self.path.display()))
+    }
+
+    /// Every interaction so far, in the `--record` file format.
+    pub fn transcript(&self) -> Result<Value> {
+        let guard = self
+            .interactions
+            .lock()
+            .map_err(|_| anyhow!("record lock poisoned"))?;
+        let all: Vec<&Interaction> = guard.iter().map(|(i, _)| i).collect();
+        Ok(serde_json::to_value(all)?)
+    }
+
+    /// First unused interaction for method + path/query (the host is ignored
+    /// so a transcript replays under any base URL), preferring an identical
+    /// body.
+    fn take(&self, method: &str, url: &Url, body: Option<&str>) -> Result<Interaction> {
+        let target = request_target(url);
+        let mut guard = self
+            .interactions
+            .lock()
+            .map_err(|_| anyhow!("replay lock poisoned"))?;
+        let candidates = |exact: bool| {
+            guard.iter().position(|(i, used)| {
+                !used
+                    && i.method == method
+                    && Url::parse(&i.url).is_ok_and(|u| request_target(&u) == target)
+                    && (!exact || i.request_body.as_deref() == body)
+            })
+        };
+        let index = candidates(true)
+            .or_else(|| candidates(false))
+            .ok_or_else(|| anyhow!("no recorded response for {method} {target}"))?;
+        guard[index].1 = true;
+        Ok(guard[index].0.clone())
+    }
 }
 
 impl HttpClient {
@@ -32,22 +835,66 @@ impl HttpClient {
         api_key: Option<String>,
         token: Option<String>,
         headers: Vec<(String, String)>,
-        timeout_secs: Option<u64>,
+        opts: &ClientOptions,
     ) -> Result<Self> {
-        let mut builder = Client::builder().user_agent("signoz-cli");
-        if let Some(secs) = timeout_secs {
-            builder = builder.timeout(Duration::from_secs(secs));
-        }
-        let client = builder.build().context("build http client")?;
+        // A `unix://` base URL outside the options' socket (another
+        // profile's) gets a pool of its own.
+        let client = match unix_socket_path(&base_url) {
+            Some(path) if opts.unix_socket.as_ref() != Some(&path) => ClientOptions {
+                unix_socket: Some(path),
+                shared: Arc::default(),
+                ..opts.clone()
+            }
+            .client()?,
+            _ => opts.client()?,
+        };
         Ok(Self {
             base_url,
             api_key,
             token,
             headers,
+            retries: opts.retries,
+            connect_retries: opts.connect_retries,
+            request_id: opts.invocation_id.clone(),
+            retry_delay: opts.retry_delay,
+            cassette: opts.cassette.clone(),
+            verbosity: opts.verbosity,
+            timeout: opts.timeout_secs.map(Duration::from_secs),
+            deadline: opts.deadline,
+            max_body_bytes: opts.max_body_bytes,
+            confirmed_bodies: opts.confirmed_bodies.clone(),
+            budget: opts.budget,
+            hooks: opts.hooks.clone(),
+            download_to: opts.download_to.clone(),
+            compress: opts.compress,
+            cache_ttl: opts.cache_ttl,
+            rate_limit: opts.rate_limit.clone(),
+            timing: opts.timing,
+            stream: opts.stream.clone(),
             client,
         })
     }
 
+    /// Budget left before `--deadline`, or an error once it has passed.
+    fn time_left(&self) -> Result<Option<Duration>> {
+        match self.deadline {
+            Some(deadline) => {
+                let left = deadline.saturating_duration_since(Instant::now());
+                if left.is_zero() {
+                    Err(deadline_exceeded())
+                } else {
+                    Ok(Some(left))
+                }
+            }
+            None => Ok(None),
+        }
+    }
+
+    pub fn with_headers(mut self, extra: &[(String, String)]) -> Self {
+        self.headers.extend(extra.iter().cloned());
+        self
+    }
+
     pub fn execute(
         &self,
         method: &str,
@@ -56,9 +903,101 @@ impl HttpClient {
         body: Option<Body>,
         content_type: Option<&str>,
     ) -> Result<HttpResponse> {
+        if let (Some(limit), Some(body)) = (self.max_body_bytes, &body) {
+            self.confirm_body_size(method, path, body.size(), limit)?;
+        }
+        let _progress = Progress::start(format!("{method} {path}"));
+        runtime()?.block_on(self.execute_async(method, path, query, body, content_type))
+    }
+
+    fn confirm_body_size(&self, method: &str, path: &str, size: u64, limit: u64) -> Result<()> {
+        if size <= limit {
+            return Ok(());
+        }
+        let key = (method.to_string(), path.to_string(), size);
+        let mut confirmed = self
+            .confirmed_bodies
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        if !confirmed.contains(&key) {
+            confirm_body_size(method, path, size, limit)?;
+            confirmed.push(key);
+        }
+        Ok(())
+    }
+
+    /// Opens a live operation: sends a GET with this client's auth and
+    /// headers plus `extra`, and returns the response once its headers
+    /// arrive, for the caller to read or upgrade. Error statuses come back
+    /// as an [`ApiError`]. Retries, the cache, and hooks do not apply, and
+    /// nothing is recorded.
+    pub async fn open_live(
+        &self,
+        path: &str,
+        query: &[(String, String)],
+        extra: &[(&str, String)],
+    ) -> Result<reqwest::Response> {
+        if self
+            .cassette
+            .as_ref()
+            .is_some_and(|c| matches!(c.mode, CassetteMode::Replay))
+        {
+            return Err(anyhow!("live operations cannot be replayed"));
+        }
         let url = build_url(&self.base_url, path, query)?;
-        let mut headers = HeaderMap::new();
+        let mut headers = self.request_headers()?;
+        for (name, value) in extra {
+            headers.insert(
+                HeaderName::from_bytes(name.as_bytes()).context("invalid header name")?,
+                HeaderValue::from_str(value).context("invalid header value")?,
+            );
+        }
+        if self.verbosity > 0 {
+            log_request("GET", &url, &headers, None, None);
+        }
+        let started = Instant::now();
+        let resp = self
+            .client
+            .get(url)
+            .headers(headers)
+            .send()
+            .await
+            .context("send request")?;
+        let status = resp.status().as_u16();
+        if self.verbosity > 0 {
+            eprintln!("< {status} ({} ms)", started.elapsed().as_millis());
+        }
+        if status < 400 {
+            return Ok(resp);
+        }
+        let headers = resp
+            .headers()
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_str().unwrap_or("").to_string()))
+            .collect();
+        let content_type = resp
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("")
+            .to_ascii_lowercase();
+        let text = resp.text().await.unwrap_or_default();
+        let response = HttpResponse {
+            status,
+            headers,
+            body: decode_body(&content_type, text),
+            content_type,
+            saved_to: None,
+            timing: Vec::new(),
+            request_id: (!self.request_id.is_empty()).then(|| self.request_id.clone()),
+            streamed: None,
+        };
+        Err(ApiError::from_response(&response).into())
+    }
 
+    /// Auth, `X-Request-ID`, and `--header` values for a request.
+    fn request_headers(&self) -> Result<HeaderMap> {
+        let mut headers = HeaderMap::new();
         if let Some(key) = &self.api_key {
             headers.insert(
                 HeaderName::from_static("signoz-api-key"),
@@ -75,31 +1014,287 @@ impl HttpClient {
                 HeaderValue::from_str(&value).context("invalid token header")?,
             );
         }
+        if !self.request_id.is_empty() {
+            headers.insert(
+                HeaderName::from_static("x-request-id"),
+                HeaderValue::from_str(&self.request_id).context("invalid request id")?,
+            );
+        }
         for (name, value) in &self.headers {
             let header_name =
                 HeaderName::from_bytes(name.as_bytes()).context("invalid header name")?;
             let header_value = HeaderValue::from_str(value).context("invalid header value")?;
             headers.insert(header_name, header_value);
         }
+        Ok(headers)
+    }
 
-        let mut req = self.client.request(method.parse()?, url).headers(headers);
+    pub async fn execute_async(
+        &self,
+        method: &str,
+        path: &str,
+        query: &[(String, String)],
+        body: Option<Body>,
+        content_type: Option<&str>,
+    ) -> Result<HttpResponse> {
+        let mut url = build_url(&self.base_url, path, query)?;
+        let mut headers = self.request_headers()?;
+        headers
+            .entry(ACCEPT_ENCODING)
+            .or_insert(HeaderValue::from_static("gzip"));
 
-        if let Some(ct) = content_type {
-            req = req.header("content-type", ct);
+        let mut request_body = body.as_ref().map(Body::to_text);
+        let mut method = method.to_string();
+        let mut body = body;
+        if self.hooks.pre_request.is_some() {
+            let mut request = hooks::Request {
+                method,
+                url: url.to_string(),
+                headers: headers
+                    .iter()
+                    .map(|(k, v)| (k.to_string(), v.to_str().unwrap_or("").to_string()))
+                    .collect(),
+                body: request_body.clone(),
+            };
+            self.hooks.pre_request(&mut request)?;
+            method = request.method;
+            url = Url::parse(&request.url).context("pre_request hook returned an invalid url")?;
+            headers = HeaderMap::new();
+            for (name, value) in &request.headers {
+                headers.append(
+                    HeaderName::from_bytes(name.as_bytes()).context("invalid header name")?,
+                    HeaderValue::from_str(value).context("invalid header value")?,
+                );
+            }
+            if request.body != request_body {
+                body = request.body.clone().map(Body::Text);
+                request_body = request.body;
+            }
+        }
+        let method = method.as_str();
+        let cache_key = self.cache_key(method, &url, &headers, body.is_some());
+        let cached = cache_key.as_deref().and_then(cache::load);
+        let fresh = match (&cached, self.cache_ttl) {
+            (Some(entry), Some(ttl)) => entry.is_fresh(ttl),
+            _ => false,
+        };
+        if let Some(entry) = cached.as_ref().filter(|_| !fresh) {
+            if let Some(etag) = &entry.etag {
+                headers.insert(IF_NONE_MATCH, HeaderValue::from_str(etag)?);
+            }
+            if let Some(modified) = &entry.last_modified {
+                headers.insert(IF_MODIFIED_SINCE, HeaderValue::from_str(modified)?);
+            }
+        }
+        if self.verbosity > 0 {
+            log_request(
+                method,
+                &url,
+                &headers,
+                content_type,
+                request_body.as_deref(),
+            );
+        }
+        if let Some(entry) = cached.clone().filter(|_| fresh) {
+            if self.verbosity > 0 {
+                eprintln!("< {} (cached)", entry.status);
+            }
+            return Ok(HttpResponse {
+                status: entry.status,
+                headers: entry.headers,
+                body: decode_body(&entry.content_type, entry.body),
+                content_type: entry.content_type,
+                saved_to: None,
+                timing: Vec::new(),
+                request_id: None,
+                streamed: None,
+            });
+        }
+        if let Some(cassette) = &self.cassette {
+            if matches!(cassette.mode, CassetteMode::Replay) {
+                let hit = cassette.take(method, &url, request_body.as_deref())?;
+                if self.verbosity > 0 {
+                    eprintln!("< {} (replayed)", hit.status);
+                }
+                return Ok(HttpResponse {
+                    status: hit.status,
+                    headers: hit.headers,
+                    body: decode_body(&hit.content_type, hit.body),
+                    content_type: hit.content_type,
+                    saved_to: None,
+                    timing: Vec::new(),
+                    request_id: None,
+                    streamed: None,
+                });
+            }
         }
 
-        if let Some(body) = body {
-            req = match body {
-                Body::Json(value) => req.json(&value),
-                Body::Text(value) => req.body(value),
+        let method: reqwest::Method = method.parse()?;
+        let request_id = headers
+            .get("x-request-id")
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+        let send_failed = || match &request_id {
+            Some(id) => format!("send request (request id {id})"),
+            None => "send request".to_string(),
+        };
+
+        let mut payload = None;
+        if let Some(body) = &body {
+            let (mut data, body_type) = body.encode()?;
+            let content_type = match body {
+                Body::Form(_) => body_type,
+                _ => content_type.map(str::to_string).or(body_type),
             };
+            let gzip = self.compress && data.len() >= COMPRESS_MIN_BYTES;
+            if gzip {
+                let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+                encoder.write_all(&data)?;
+                data = encoder.finish().context("compress request body")?;
+            }
+            payload = Some((data, content_type, gzip));
         }
 
-        let resp = req.send().context("send request")?;
+        let mut attempt = 0;
+        let mut network_attempt = 0;
+        let mut timing = Vec::new();
+        let mut started;
+        let begun = Instant::now();
+        let resp = loop {
+            let mut req = self
+                .client
+                .request(method.clone(), url.clone())
+                .headers(headers.clone());
+            if let Some((data, content_type, gzip)) = &payload {
+                if let Some(ct) = content_type {
+                    req = req.header(CONTENT_TYPE, ct);
+                }
+                if *gzip {
+                    req = req.header(CONTENT_ENCODING, "gzip");
+                }
+                req = req.body(data.clone());
+            } else if let Some(ct) = content_type {
+                req = req.header(CONTENT_TYPE, ct);
+            }
+
+            if let Some(left) = self.time_left()? {
+                req = req.timeout(self.timeout.map_or(left, |t| t.min(left)));
+            }
+
+            if let Some(limiter) = &self.rate_limit {
+                let wait = limiter.reserve();
+                if self.time_left()?.is_some_and(|left| wait >= left) {
+                    return Err(deadline_exceeded());
+                }
+                if !wait.is_zero() {
+                    if self.verbosity > 1 {
+                        eprintln!("* rate limit: waiting {} ms", wait.as_millis());
+                    }
+                    tokio::time::sleep(wait).await;
+                }
+            }
+
+            let span = selftrace::http_span(method.as_str(), url.as_str());
+            if let Some(traceparent) = span.as_ref().and_then(|span| span.traceparent()) {
+                req = req.header("traceparent", traceparent);
+            }
+            started = Instant::now();
+            let phases = Arc::new(Mutex::new(Phases::default()));
+            let send = async {
+                match self.timing {
+                    true => PHASES.scope(phases.clone(), req.send()).await,
+                    false => req.send().await,
+                }
+            };
+            let sent = match self.budget.time_left(begun)? {
+                Some(left) => tokio::time::timeout(left, send)
+                    .await
+                    .map_err(|_| Failure::Budget(self.budget.over_time()))?,
+                None => send.await,
+            };
+            if let Some(span) = span {
+                span.end(
+                    sent.as_ref().ok().map(|resp| resp.status().as_u16()),
+                    attempt + network_attempt,
+                );
+            }
+            let resp = match sent {
+                Ok(resp) => resp,
+                Err(_) if self.time_left().is_err() => return Err(deadline_exceeded()),
+                Err(err)
+                    if (err.is_connect() || err.is_timeout())
+                        && network_attempt < self.connect_retries =>
+                {
+                    let delay = backoff(self.retry_delay, network_attempt).min(MAX_RETRY_DELAY);
+                    if self.time_left()?.is_some_and(|left| delay >= left) {
+                        return Err(err).with_context(send_failed);
+                    }
+                    if self.verbosity > 0 {
+                        eprintln!(
+                            "* {}; connect retry {} in {} ms",
+                            network_error(&err),
+                            network_attempt + 1,
+                            delay.as_millis()
+                        );
+                    }
+                    tokio::time::sleep(delay).await;
+                    network_attempt += 1;
+                    continue;
+                }
+                Err(err) => return Err(err).with_context(send_failed),
+            };
+            let status = resp.status();
+            if self.verbosity > 0 {
+                eprintln!(
+                    "< {} ({} ms)",
+                    status.as_u16(),
+                    started.elapsed().as_millis()
+                );
+                if self.verbosity > 1 {
+                    for (name, value) in resp.headers() {
+                        eprintln!("< {}: {}", name, value.to_str().unwrap_or("<binary>"));
+                    }
+                }
+            }
+            if self.timing {
+                let phases = phases.lock().unwrap_or_else(|e| e.into_inner());
+                timing.push(Timing::new(
+                    method.as_str(),
+                    &url,
+                    status.as_u16(),
+                    &phases,
+                    started,
+                ));
+            }
+            let retryable = status.as_u16() == 429 || status.is_server_error();
+            if !retryable || attempt >= self.retries {
+                break resp;
+            }
+            let delay = retry_after(&resp)
+                .unwrap_or_else(|| backoff(self.retry_delay, attempt))
+                .min(MAX_RETRY_DELAY);
+            if self.time_left()?.is_some_and(|left| delay >= left) {
+                // No budget left to wait out another attempt.
+                break resp;
+            }
+            if self.verbosity > 0 {
+                eprintln!("* retry {} in {} ms", attempt + 1, delay.as_millis());
+            }
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        };
+
         let status = resp.status().as_u16();
+        let gzipped = resp
+            .headers()
+            .get(CONTENT_ENCODING)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.eq_ignore_ascii_case("gzip") || v.eq_ignore_ascii_case("x-gzip"));
+        // Like a decoding client: the body handed on is the decompressed one.
         let headers_out = resp
             .headers()
             .iter()
+            .filter(|(k, _)| !gzipped || (*k != CONTENT_ENCODING && *k != CONTENT_LENGTH))
             .map(|(k, v)| (k.to_string(), v.to_str().unwrap_or("").to_string()))
             .collect::<Vec<_>>();
 
@@ -110,27 +1305,525 @@ impl HttpClient {
             .unwrap_or("")
             .to_ascii_lowercase();
 
-        let text = resp.text().unwrap_or_default();
-        let body = if content_type.contains("json") {
-            serde_json::from_str(&text).unwrap_or(Value::String(text))
-        } else {
-            Value::String(text)
+        if status < 400 && is_binary(&content_type) {
+            let path = self.download_path(&resp);
+            let mut reader = BodyReader::new(resp, gzipped, self.budget, begun);
+            let bytes = save_body(&mut reader, &path).await?;
+            let mut body = json!({ "file": path, "bytes": bytes, "content_type": content_type });
+            if reader.truncated {
+                body["truncated"] = json!(true);
+            }
+            finish_timing(&mut timing, started);
+            if let Some(cassette) = &self.cassette {
+                cassette.push(Interaction {
+                    method: method.to_string(),
+                    url: url.to_string(),
+                    request_body,
+                    status,
+                    headers: headers_out.clone(),
+                    content_type: content_type.clone(),
+                    body: format!("<{bytes} bytes saved to {}>", path.display()),
+                })?;
+            }
+            return Ok(HttpResponse {
+                status,
+                headers: headers_out,
+                body,
+                content_type,
+                saved_to: Some(path),
+                timing,
+                request_id,
+                streamed: None,
+            });
+        }
+
+        if let Some(emitter) = self.stream.as_ref().filter(|_| {
+            status < 400 && content_type.contains("json") && self.hooks.post_response.is_none()
+        }) {
+            let reader = BodyReader::new(resp, gzipped, self.budget, begun);
+            let (body, streamed) = stream_body(reader, emitter).await?;
+            finish_timing(&mut timing, started);
+            if let Some(cassette) = &self.cassette {
+                cassette.push(Interaction {
+                    method: method.to_string(),
+                    url: url.to_string(),
+                    request_body,
+                    status,
+                    headers: headers_out.clone(),
+                    content_type: content_type.clone(),
+                    body: match streamed {
+                        Some(count) => format!("<{count} items streamed>"),
+                        None => body.to_string(),
+                    },
+                })?;
+            }
+            return Ok(HttpResponse {
+                status,
+                headers: headers_out,
+                body,
+                content_type,
+                saved_to: None,
+                timing,
+                request_id,
+                streamed,
+            });
+        }
+
+        let mut reader = BodyReader::new(resp, gzipped, self.budget, begun);
+        let mut data = Vec::new();
+        while let Some(chunk) = reader.next().await? {
+            data.extend(chunk);
+        }
+        let text = match reader.truncated {
+            true if status < 400 && content_type.contains("json") => salvage(&data),
+            _ => String::from_utf8_lossy(&data).into_owned(),
         };
+        finish_timing(&mut timing, started);
+        let (status, headers_out, content_type, text) = match (cache_key, cached) {
+            // Unchanged: the cached response stands in for the empty 304.
+            (Some(key), Some(entry)) if status == 304 => {
+                if self.verbosity > 0 {
+                    eprintln!("< {} (not modified, cached)", entry.status);
+                }
+                let entry = cache::touch(&key, entry.clone()).unwrap_or(entry);
+                (entry.status, entry.headers, entry.content_type, entry.body)
+            }
+            (Some(key), _) if status == 200 && !reader.truncated => {
+                let header = |name: &str| {
+                    headers_out
+                        .iter()
+                        .find(|(k, _)| k.eq_ignore_ascii_case(name))
+                        .map(|(_, v)| v.clone())
+                };
+                let entry = cache::Entry {
+                    etag: header("etag"),
+                    last_modified: header("last-modified"),
+                    stored_at: cache::now_secs(),
+                    status,
+                    headers: headers_out.clone(),
+                    content_type: content_type.clone(),
+                    body: text.clone(),
+                };
+                if entry.etag.is_some() || entry.last_modified.is_some() {
+                    // Best effort, like the id cache.
+                    let _ = cache::store(&key, &entry);
+                }
+                (status, headers_out, content_type, text)
+            }
+            _ => (status, headers_out, content_type, text),
+        };
+        if let Some(cassette) = &self.cassette {
+            cassette.push(Interaction {
+                method: method.to_string(),
+                url: url.to_string(),
+                request_body,
+                status,
+                headers: headers_out.clone(),
+                content_type: content_type.clone(),
+                body: text.clone(),
+            })?;
+        }
+        let mut response = hooks::Response {
+            status,
+            headers: headers_out,
+            body: text,
+        };
+        self.hooks
+            .post_response(method.as_str(), url.as_str(), &mut response)?;
+        let hooks::Response {
+            status,
+            headers: headers_out,
+            body: text,
+        } = response;
+        let body = decode_body(&content_type, text);
 
         Ok(HttpResponse {
             status,
             headers: headers_out,
             body,
             content_type,
+            saved_to: None,
+            timing,
+            request_id,
+            streamed: None,
         })
     }
+
+    /// The cache file for a plain GET, keyed by URL and credential. Off
+    /// with `--no-cache`, while recording or replaying, with `--stream`, and
+    /// when the caller sends its own validators.
+    fn cache_key(
+        &self,
+        method: &str,
+        url: &Url,
+        headers: &HeaderMap,
+        has_body: bool,
+    ) -> Option<String> {
+        if self.cache_ttl.is_none()
+            || self.cassette.is_some()
+            || self.stream.is_some()
+            || method != "GET"
+            || has_body
+            || headers.contains_key(IF_NONE_MATCH)
+            || headers.contains_key(IF_MODIFIED_SINCE)
+        {
+            return None;
+        }
+        let credential = ["signoz-api-key", "authorization"]
+            .iter()
+            .filter_map(|name| headers.get(*name)?.to_str().ok())
+            .collect::<Vec<_>>()
+            .join("\n");
+        Some(cache::key(method, url.as_str(), &credential))
+    }
+
+    /// `--output-file`, else the temp directory under the name from
+    /// `Content-Disposition` or the URL's last segment.
+    fn download_path(&self, resp: &reqwest::Response) -> PathBuf {
+        if let Some(path) = &self.download_to {
+            return path.clone();
+        }
+        let name = resp
+            .headers()
+            .get("content-disposition")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| {
+                v.split(';')
+                    .find_map(|part| part.trim().strip_prefix("filename="))
+            })
+            .map(|name| name.trim_matches('"'))
+            .or_else(|| resp.url().path_segments()?.next_back())
+            .and_then(|name| Path::new(name).file_name()?.to_str())
+            .filter(|name| !name.is_empty())
+            .unwrap_or("download");
+        env::temp_dir().join(format!("signoz-{}-{name}", std::process::id()))
+    }
+}
+
+/// Content types that cannot round-trip through a JSON string: images,
+/// archives, and other downloads.
+fn is_binary(content_type: &str) -> bool {
+    let mime = content_type.split(';').next().unwrap_or_default().trim();
+    !(mime.is_empty()
+        || mime.starts_with("text/")
+        || ["json", "xml", "yaml", "javascript", "x-www-form-urlencoded"]
+            .iter()
+            .any(|kind| mime.contains(kind)))
+}
+
+impl ResponseBudget {
+    /// Time left of `--max-duration` for a request first sent at `begun`,
+    /// or the budget error once it has run out.
+    fn time_left(&self, begun: Instant) -> Result<Option<Duration>> {
+        match self.duration {
+            Some(limit) => {
+                let left = limit.saturating_sub(begun.elapsed());
+                if left.is_zero() {
+                    Err(Failure::Budget(self.over_time()).into())
+                } else {
+                    Ok(Some(left))
+                }
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn over_time(&self) -> String {
+        let secs = self.duration.unwrap_or_default().as_secs_f64();
+        format!("response took longer than --max-duration ({secs}s)")
+    }
+
+    fn over_size(&self) -> String {
+        let limit = self.bytes.unwrap_or_default();
+        format!("response body is larger than --max-body-bytes ({limit} bytes)")
+    }
+}
+
+/// Reads a response body chunk by chunk, decompressing it on the way when
+/// gzipped, and stops at the [`ResponseBudget`]: with `truncate`, after
+/// what fits and a warning, else with the budget error.
+struct BodyReader {
+    resp: reqwest::Response,
+    gzipped: bool,
+    /// Started on the first chunk of a gzipped body, so an empty one stays
+    /// empty: servers mark 204s and HEADs as gzip too.
+    decoder: Option<GzDecoder<Vec<u8>>>,
+    budget: ResponseBudget,
+    begun: Instant,
+    read: u64,
+    done: bool,
+    /// The body was cut short by the budget.
+    truncated: bool,
+}
+
+impl BodyReader {
+    fn new(resp: reqwest::Response, gzipped: bool, budget: ResponseBudget, begun: Instant) -> Self {
+        Self {
+            resp,
+            gzipped,
+            decoder: None,
+            budget,
+            begun,
+            read: 0,
+            done: false,
+            truncated: false,
+        }
+    }
+
+    /// The next piece of the decompressed body; `None` at its end or where
+    /// the budget cut it.
+    async fn next(&mut self) -> Result<Option<Vec<u8>>> {
+        while !self.done {
+            let chunk = match self.budget.time_left(self.begun) {
+                Ok(Some(left)) => match tokio::time::timeout(left, self.resp.chunk()).await {
+                    Ok(chunk) => chunk,
+                    Err(_) => return self.cut(self.budget.over_time()).map(|_| None),
+                },
+                Ok(None) => self.resp.chunk().await,
+                Err(_) => return self.cut(self.budget.over_time()).map(|_| None),
+            };
+            let mut data = match chunk.context("read response body")? {
+                Some(chunk) if self.gzipped => {
+                    let decoder = self
+                        .decoder
+                        .get_or_insert_with(|| GzDecoder::new(Vec::new()));
+                    decoder
+                        .write_all(&chunk)
+                        .context("decompress response body")?;
+                    std::mem::take(decoder.get_mut())
+                }
+                Some(chunk) => chunk.to_vec(),
+                None => {
+                    self.done = true;
+                    match self.decoder.take() {
+                        Some(decoder) => decoder.finish().context("decompress response body")?,
+                        None => Vec::new(),
+                    }
+                }
+            };
+            if let Some(limit) = self.budget.bytes {
+                let left = limit.saturating_sub(self.read);
+                if data.len() as u64 > left {
+                    data.truncate(left as usize);
+                    self.cut(self.budget.over_size())?;
+                }
+            }
+            self.read += data.len() as u64;
+            if !data.is_empty() {
+                return Ok(Some(data));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Stops reading at the budget: fails with `reason` unless truncating.
+    fn cut(&mut self, reason: String) -> Result<()> {
+        if !self.budget.truncate {
+            return Err(
+                Failure::Budget(format!("{reason}; pass --truncate to keep what arrived")).into(),
+            );
+        }
+        if !self.truncated {
+            eprintln!("warning: {reason}; output is truncated");
+        }
+        self.done = true;
+        self.truncated = true;
+        Ok(())
+    }
+}
+
+/// Writes the body to `path` chunk by chunk; returns the size written.
+async fn save_body(reader: &mut BodyReader, path: &Path) -> Result<u64> {
+    let mut file = fs::File::create(path).with_context(|| format!("create {}", path.display()))?;
+    while let Some(data) = reader.next().await? {
+        file.write_all(&data)
+            .with_context(|| format!("write {}", path.display()))?;
+    }
+    Ok(reader.read)
+}
+
+/// Reads the body through a [`Splitter`], handing each chunk's finished
+/// items to `emitter`. Returns the rest of the document and the number of
+/// items, or `None` when it had no item list.
+async fn stream_body(mut reader: BodyReader, emitter: &Emitter) -> Result<(Value, Option<usize>)> {
+    let mut splitter = Splitter::new();
+    let mut count = 0;
+    while let Some(data) = reader.next().await? {
+        let items = splitter.feed(&data)?;
+        count += items.len();
+        if !items.is_empty() {
+            emitter.write(items)?;
+        }
+    }
+    if reader.truncated {
+        let (body, list) = splitter.cut();
+        return Ok((body, list.map(|_| count)));
+    }
+    let (body, found) = splitter.finish()?;
+    Ok((body, found.then_some(count)))
+}
+
+/// A JSON body cut off by the budget, made whole again: the items of its
+/// list that arrived complete, in the document around them. Other bodies
+/// stay as the text that arrived.
+fn salvage(data: &[u8]) -> String {
+    let mut splitter = Splitter::new();
+    let Ok(items) = splitter.feed(data) else {
+        return String::from_utf8_lossy(data).into_owned();
+    };
+    match splitter.cut() {
+        (mut body, Some(list)) => {
+            if let Some(slot) = body.pointer_mut(&list) {
+                *slot = Value::Array(items);
+            }
+            body.to_string()
+        }
+        _ => String::from_utf8_lossy(data).into_owned(),
+    }
+}
+
+/// Guards against accidentally sending a huge file: asks on a terminal,
+/// fails otherwise.
+fn confirm_body_size(method: &str, path: &str, size: u64, limit: u64) -> Result<()> {
+    let mb = |bytes: u64| bytes as f64 / 1e6;
+    let message = format!(
+        "{method} {path} body is {:.1} MB (limit {:.1} MB, [request] max_body_size)",
+        mb(size),
+        mb(limit)
+    );
+    if !io::stdin().is_terminal() {
+        return Err(anyhow!("{message}; pass --force to send it"));
+    }
+    eprint!("{message}. Send anyway? [y/N] ");
+    let mut answer = String::new();
+    io::stdin()
+        .read_line(&mut answer)
+        .context("read confirmation")?;
+    if matches!(answer.trim().to_ascii_lowercase().as_str(), "y" | "yes") {
+        Ok(())
+    } else {
+        Err(anyhow!("aborted: request body too large"))
+    }
+}
+
+fn deadline_exceeded() -> anyhow::Error {
+    anyhow!("--deadline exceeded")
+}
+
+/// Proxy for every scheme; `user:pass@` credentials become basic auth and
+/// `NO_PROXY` still applies.
+fn proxy(raw: &str) -> Result<reqwest::Proxy> {
+    let mut url = Url::parse(raw).with_context(|| format!("invalid --proxy url: {raw}"))?;
+    let user = urlencoding::decode(url.username())?.into_owned();
+    let pass = urlencoding::decode(url.password().unwrap_or(""))?.into_owned();
+    let _ = url.set_username("");
+    let _ = url.set_password(None);
+    let mut proxy = reqwest::Proxy::all(url.as_str())
+        .context("invalid --proxy url")?
+        .no_proxy(reqwest::NoProxy::from_env());
+    if !user.is_empty() {
+        proxy = proxy.basic_auth(&user, &pass);
+    }
+    Ok(proxy)
+}
+
+/// Parts of header names whose values are never written to debug output,
+/// cassettes, or history: `SIGNOZ-API-KEY`, `Authorization`, `Cookie`, and
+/// preset ones like `X-Gateway-Auth`.
+const SECRET_HEADER_PARTS: &[&str] = &["auth", "key", "token", "secret", "cookie"];
+
+pub fn is_secret_header(name: &str) -> bool {
+    let name = name.trim().to_ascii_lowercase();
+    SECRET_HEADER_PARTS.iter().any(|part| name.contains(part))
+}
+
+/// Request bodies longer than this are truncated in debug output.
+const MAX_LOGGED_BODY: usize = 4096;
+
+fn log_request(
+    method: &str,
+    url: &Url,
+    headers: &HeaderMap,
+    content_type: Option<&str>,
+    body: Option<&str>,
+) {
+    eprintln!("> {method} {url}");
+    for (name, value) in headers {
+        let value = if is_secret_header(name.as_str()) {
+            "<redacted>"
+        } else {
+            value.to_str().unwrap_or("<binary>")
+        };
+        eprintln!("> {name}: {value}");
+    }
+    if let Some(ct) = content_type {
+        eprintln!("> content-type: {ct}");
+    }
+    if let Some(body) = body {
+        let body = &capture::redact_body(body);
+        if body.len() > MAX_LOGGED_BODY {
+            let cut = (0..=MAX_LOGGED_BODY)
+                .rev()
+                .find(|i| body.is_char_boundary(*i))
+                .unwrap_or(0);
+            eprintln!("> {}… ({} bytes)", &body[..cut], body.len());
+        } else {
+            eprintln!("> {body}");
+        }
+    }
+}
+
+fn request_target(url: &Url) -> String {
+    match url.query() {
+        Some(query) => format!("{}?{query}", url.path()),
+        None => url.path().to_string(),
+    }
+}
+
+fn decode_body(content_type: &str, text: String) -> Value {
+    if content_type.contains("json") {
+        serde_json::from_str(&text).unwrap_or(Value::String(text))
+    } else {
+        Value::String(text)
+    }
+}
+
+fn retry_after(resp: &reqwest::Response) -> Option<Duration> {
+    resp.headers()
+        .get("retry-after")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .map(Duration::from_secs)
+}
+
+/// What kind of network error made a request fail, for the retry log.
+fn network_error(err: &reqwest::Error) -> &'static str {
+    if err.is_timeout() {
+        "timed out"
+    } else {
+        "connection failed"
+    }
+}
+
+/// `base * 2^attempt`, scaled by a random factor in [0.5, 1.0).
+pub fn backoff(base: Duration, attempt: u32) -> Duration {
+    let exp = base.saturating_mul(1u32 << attempt.min(16));
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or(0);
+    let jitter = 0.5 + f64::from(nanos % 1000) / 2000.0;
+    exp.mul_f64(jitter)
 }
 
 fn build_url(base_url: &str, path: &str, query: &[(String, String)]) -> Result<Url> {
     let base = if path.starts_with("http://") || path.starts_with("https://") {
         path.to_string()
     } else {
-        let mut base = base_url.trim_end_matches('/').to_string();
+        let mut base = match unix_socket_path(base_url) {
+            Some(_) => "http://localhost".to_string(),
+            None => base_url.trim_end_matches('/').to_string(),
+        };
         let path = if path.starts_with('/') {
             path
         } else {
@@ -0,0 +1,185 @@
+use anyhow::{anyhow, Result};
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::TcpStream,
+};
+
+/// Largest request body accepted, after decompression.
+pub const MAX_BODY: usize = 64 << 20;
+
+/// One parsed HTTP/1.1 request.
+pub struct Request {
+    pub method: String,
+    /// The target up to `?`.
+    pub path: String,
+    /// The raw query string after `?`, if any.
+    pub query: String,
+    pub close: bool,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// Status, content type, and body of a reply.
+pub type Reply = (u16, &'static str, Vec<u8>);
+
+/// Answers requests on one keep-alive connection with `handle` (given the
+/// request and the peer address) until the client closes it.
+pub fn serve(stream: TcpStream, handle: impl Fn(&Request, &str) -> Reply) {
+    let peer = stream
+        .peer_addr()
+        .map_or_else(|_| "?".to_string(), |addr| addr.to_string());
+    let Ok(read_half) = stream.try_clone() else {
+        return;
+    };
+    let mut reader = BufReader::new(read_half);
+    let mut writer = stream;
+    loop {
+        let request = match read_request(&mut reader) {
+            Ok(Some(request)) => request,
+            Ok(None) => return,
+            Err(err) => {
+                eprintln!("{peer}: {err:#}");
+                let _ = respond(
+                    &mut writer,
+                    400,
+                    "text/plain",
+                    format!("{err:#}").as_bytes(),
+                );
+                return;
+            }
+        };
+        let (status, content_type, body) = handle(&request, &peer);
+        if respond(&mut writer, status, content_type, &body).is_err() || request.close {
+            return;
+        }
+    }
+}
+
+fn respond(stream: &mut TcpStream, status: u16, content_type: &str, body: &[u8]) -> Result<()> {
+    let reason = match status {
+        200 => "OK",
+        201 => "Created",
+        204 => "No Content",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        429 => "Too Many Requests",
+        502 => "Bad Gateway",
+        _ => "Error",
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {status} {reason}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\n\r\n",
+        body.len()
+    )?;
+    stream.write_all(body)?;
+    Ok(stream.flush()?)
+}
+
+/// `None` when the client closed the connection between requests.
+fn read_request(reader: &mut BufReader<TcpStream>) -> Result<Option<Request>> {
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+    if line.starts_with("PRI * HTTP/2") {
+        return Err(anyhow!(
+            "got an HTTP/2 (gRPC) connection; only HTTP/1.1 is served (for OTLP, set OTEL_EXPORTER_OTLP_PROTOCOL=http/protobuf)"
+        ));
+    }
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(target), version) = (parts.next(), parts.next(), parts.next()) else {
+        return Err(anyhow!("malformed request line {:?}", line.trim_end()));
+    };
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let (method, path, query) = (method.to_string(), path.to_string(), query.to_string());
+    let http10 = version == Some("HTTP/1.0");
+
+    let mut headers = Vec::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            return Err(anyhow!("connection closed inside request headers"));
+        }
+        let trimmed = line.trim_end();
+        if trimmed.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = trimmed.split_once(':') {
+            headers.push((name.trim().to_string(), value.trim().to_string()));
+        }
+    }
+    let mut request = Request {
+        method,
+        path,
+        query,
+        close: false,
+        headers,
+        body: Vec::new(),
+    };
+    request.close = match request.header("connection") {
+        Some(c) if c.eq_ignore_ascii_case("close") => true,
+        Some(c) if c.eq_ignore_ascii_case("keep-alive") => false,
+        _ => http10,
+    };
+    if request
+        .header("expect")
+        .is_some_and(|v| v.eq_ignore_ascii_case("100-continue"))
+    {
+        reader
+            .get_mut()
+            .write_all(b"HTTP/1.1 100 Continue\r\n\r\n")?;
+    }
+
+    if request
+        .header("transfer-encoding")
+        .is_some_and(|v| v.to_ascii_lowercase().contains("chunked"))
+    {
+        loop {
+            line.clear();
+            reader.read_line(&mut line)?;
+            let size = line.trim().split(';').next().unwrap_or_default();
+            let size =
+                usize::from_str_radix(size, 16).map_err(|_| anyhow!("bad chunk size {size:?}"))?;
+            if size == 0 {
+                // Trailers, then the blank line that ends the message.
+                loop {
+                    line.clear();
+                    if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+                        break;
+                    }
+                }
+                break;
+            }
+            if request.body.len() + size > MAX_BODY {
+                return Err(anyhow!("request body over {MAX_BODY} bytes"));
+            }
+            let start = request.body.len();
+            request.body.resize(start + size, 0);
+            reader.read_exact(&mut request.body[start..])?;
+            let mut crlf = [0; 2];
+            reader.read_exact(&mut crlf)?;
+        }
+    } else if let Some(length) = request.header("content-length") {
+        let length: usize = length
+            .parse()
+            .map_err(|_| anyhow!("bad Content-Length {length:?}"))?;
+        if length > MAX_BODY {
+            return Err(anyhow!("request body over {MAX_BODY} bytes"));
+        }
+        request.body.resize(length, 0);
+        reader.read_exact(&mut request.body)?;
+    }
+    Ok(Some(request))
+}
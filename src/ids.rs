@@ -0,0 +1,135 @@
+use anyhow::{anyhow, Context, Result};
+use serde_json::Value;
+use std::{collections::BTreeMap, fs, path::PathBuf};
+
+use crate::config;
+
+/// UUIDs remembered per collection; the oldest are dropped past this.
+const MAX_PER_COLLECTION: usize = 500;
+
+/// Base URL -> collection path (`/api/v1/rules`) -> UUIDs seen there.
+type IdStore = BTreeMap<String, BTreeMap<String, Vec<String>>>;
+
+fn store_path() -> Result<PathBuf> {
+    Ok(config::config_dir()?.join("ids.json"))
+}
+
+fn load_store() -> IdStore {
+    store_path()
+        .and_then(|path| Ok(fs::read_to_string(path)?))
+        .ok()
+        .and_then(|raw| serde_json::from_str(&raw).ok())
+        .unwrap_or_default()
+}
+
+fn save_store(store: &IdStore) -> Result<()> {
+    let path = store_path()?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).context("create config dir")?;
+    }
+    fs::write(&path, serde_json::to_string(store)?).context("write id cache")
+}
+
+/// The static part of an operation path: `/api/v1/rules/{id}` and
+/// `/api/v1/rules` both map to `/api/v1/rules`.
+fn collection(op_path: &str) -> &str {
+    op_path
+        .split('{')
+        .next()
+        .unwrap_or(op_path)
+        .trim_end_matches('/')
+}
+
+pub fn is_uuid(value: &str) -> bool {
+    value.len() == 36
+        && value.char_indices().all(|(i, c)| match i {
+            8 | 13 | 18 | 23 => c == '-',
+            _ => c.is_ascii_hexdigit(),
+        })
+}
+
+/// The start of a UUID, at least 4 characters and short of the whole: hex
+/// with dashes where a UUID has them. All-digit values are taken to be
+/// numeric ids.
+fn is_uuid_prefix(value: &str) -> bool {
+    (4..36).contains(&value.len())
+        && !value.chars().all(|c| c.is_ascii_digit())
+        && value.char_indices().all(|(i, c)| match i {
+            8 | 13 | 18 | 23 => c == '-',
+            _ => c.is_ascii_hexdigit(),
+        })
+}
+
+/// Remembers UUID `id`/`uuid` fields from a successful response so later
+/// path parameters can use a unique prefix. Best effort: cache errors are
+/// ignored.
+pub fn remember(base_url: &str, op_path: &str, body: &Value) {
+    let mut found = Vec::new();
+    collect(body, &mut found);
+    if found.is_empty() {
+        return;
+    }
+    let mut store = load_store();
+    let known = store
+        .entry(base_url.trim_end_matches('/').to_string())
+        .or_default()
+        .entry(collection(op_path).to_string())
+        .or_default();
+    let before = known.clone();
+    for id in found {
+        known.retain(|k| k != &id);
+        known.push(id);
+    }
+    if *known == before {
+        return;
+    }
+    let excess = known.len().saturating_sub(MAX_PER_COLLECTION);
+    known.drain(..excess);
+    let _ = save_store(&store);
+}
+
+fn collect(value: &Value, out: &mut Vec<String>) {
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map {
+                match child {
+                    Value::String(s) if matches!(key.as_str(), "id" | "uuid") && is_uuid(s) => {
+                        out.push(s.to_lowercase())
+                    }
+                    _ => collect(child, out),
+                }
+            }
+        }
+        Value::Array(items) => items.iter().for_each(|item| collect(item, out)),
+        _ => {}
+    }
+}
+
+/// Expands a UUID prefix for a path parameter of `op_path` to the one
+/// remembered UUID it matches in that collection. Anything else, such as
+/// a name or a numeric id, is returned unchanged.
+pub fn expand(base_url: &str, op_path: &str, value: &str) -> Result<String> {
+    if !is_uuid_prefix(value) {
+        return Ok(value.to_string());
+    }
+    let prefix = value.to_lowercase();
+    let store = load_store();
+    let candidates: Vec<&String> = store
+        .get(base_url.trim_end_matches('/'))
+        .and_then(|collections| collections.get(collection(op_path)))
+        .into_iter()
+        .flatten()
+        .filter(|id| id.starts_with(&prefix))
+        .collect();
+    match candidates.as_slice() {
+        [] => Ok(value.to_string()),
+        [id] => Ok(id.to_string()),
+        many => Err(anyhow!(
+            "id prefix {value} is ambiguous; candidates: {}",
+            many.iter()
+                .map(|id| id.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )),
+    }
+}
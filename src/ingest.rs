@@ -0,0 +1,277 @@
+use anyhow::{anyhow, Result};
+use clap::{Arg, ArgAction, Command};
+use serde_json::{json, Value};
+
+use crate::error::ApiError;
+use crate::http::Body;
+use crate::output::{self, OutputOptions};
+use crate::ApiContext;
+
+const KEYS_PATH: &str = "/api/v2/gateway/ingestion_keys";
+
+/// Well-known tag names mapped to OpenTelemetry resource attributes.
+const TAG_ATTRIBUTES: &[(&str, &str)] = &[
+    ("env", "deployment.environment"),
+    ("environment", "deployment.environment"),
+    ("service", "service.name"),
+    ("namespace", "service.namespace"),
+    ("version", "service.version"),
+];
+
+pub fn keys_command() -> Command {
+    Command::new("ingest-keys")
+        .about("Ingestion key helpers (SigNoz Cloud gateway)")
+        .subcommand_required(true)
+        .arg_required_else_help(true)
+        .subcommand(
+            Command::new("tag")
+                .about("Set key tags/limits and print a collector config snippet")
+                .arg(
+                    Arg::new("key")
+                        .required(true)
+                        .help("Ingestion key id or name"),
+                )
+                .arg(
+                    Arg::new("tags")
+                        .value_name("NAME=VALUE")
+                        .num_args(0..)
+                        .help("Tags to set (replaces tags with the same name)"),
+                )
+                .arg(
+                    Arg::new("limit")
+                        .long("limit")
+                        .value_name("SIGNAL=SIZE")
+                        .action(ArgAction::Append)
+                        .help("Daily ingestion size limit per signal, e.g. logs=10GiB"),
+                )
+                .arg(
+                    Arg::new("ingest-endpoint")
+                        .long("ingest-endpoint")
+                        .value_name("HOST:PORT")
+                        .help("OTLP endpoint for the snippet (e.g. ingest.us.signoz.cloud:443)"),
+                )
+                .arg(
+                    Arg::new("snippet")
+                        .long("snippet")
+                        .action(ArgAction::SetTrue)
+                        .help("Print only the collector YAML snippet"),
+                ),
+        )
+}
+
+pub fn handle_keys(
+    matches: &clap::ArgMatches,
+    ctx: &ApiContext,
+    opts: &OutputOptions,
+) -> Result<()> {
+    match matches.subcommand() {
+        Some(("tag", m)) => handle_tag(m, ctx, opts),
+        _ => Err(anyhow!("unknown ingest-keys command")),
+    }
+}
+
+fn handle_tag(matches: &clap::ArgMatches, ctx: &ApiContext, opts: &OutputOptions) -> Result<()> {
+    let wanted = matches
+        .get_one::<String>("key")
+        .ok_or_else(|| anyhow!("missing key"))?;
+    let new_tags: Vec<(String, String)> = matches
+        .get_many::<String>("tags")
+        .into_iter()
+        .flatten()
+        .map(|raw| {
+            raw.split_once('=')
+                .map(|(k, v)| (k.trim().to_string(), v.trim().to_string()))
+                .ok_or_else(|| anyhow!("invalid tag `{raw}`; expected NAME=VALUE"))
+        })
+        .collect::<Result<_>>()?;
+
+    let key = find_key(ctx, wanted)?;
+    let id = key
+        .get("id")
+        .and_then(Value::as_str)
+        .ok_or_else(|| anyhow!("ingestion key has no id"))?
+        .to_string();
+
+    let current: Vec<String> = key
+        .get("tags")
+        .and_then(Value::as_array)
+        .map(|t| {
+            t.iter()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default();
+    let mut tags = current.clone();
+    for (name, value) in &new_tags {
+        tags.retain(|t| t.split_once('=').map(|(k, _)| k) != Some(name.as_str()));
+        tags.push(format!("{name}={value}"));
+    }
+
+    // Only `--limit` or `--snippet`, or tags the key already has, leave the
+    // key as it is.
+    if tags != current {
+        let mut patch = json!({ "name": key.get("name"), "tags": tags });
+        if let Some(expires) = key.get("expires_at") {
+            patch["expires_at"] = expires.clone();
+        }
+        expect_ok(ctx.execute(
+            "PATCH",
+            &format!("{KEYS_PATH}/{id}"),
+            &[],
+            Some(Body::Json(patch)),
+            Some("application/json"),
+        )?)?;
+    }
+
+    let mut limits = Vec::new();
+    for raw in matches.get_many::<String>("limit").into_iter().flatten() {
+        let (signal, size) = raw
+            .split_once('=')
+            .ok_or_else(|| anyhow!("invalid --limit `{raw}`; expected SIGNAL=SIZE"))?;
+        let size = parse_bytes(size)?;
+        limits.push(set_limit(ctx, &key, &id, signal.trim(), size, &tags)?);
+    }
+
+    let snippet = collector_snippet(
+        matches
+            .get_one::<String>("ingest-endpoint")
+            .map(String::as_str),
+        &tags,
+    );
+    if matches.get_flag("snippet") {
+        print!("{snippet}");
+        return Ok(());
+    }
+    output::print_value(
+        &json!({
+            "id": id,
+            "name": key.get("name"),
+            "tags": tags,
+            "limits": limits,
+            "collector_config": snippet,
+        }),
+        opts,
+    )
+}
+
+fn find_key(ctx: &ApiContext, wanted: &str) -> Result<Value> {
+    let response = expect_ok(ctx.execute("GET", KEYS_PATH, &[], None, None)?)?;
+    let keys = response
+        .pointer("/data/keys")
+        .or_else(|| response.get("keys"))
+        .and_then(Value::as_array)
+        .cloned()
+        .unwrap_or_default();
+    keys.into_iter()
+        .find(|k| {
+            ["id", "name"]
+                .iter()
+                .any(|field| k.get(field).and_then(Value::as_str) == Some(wanted))
+        })
+        .ok_or_else(|| anyhow!("ingestion key `{wanted}` not found"))
+}
+
+/// Updates the key's existing limit for `signal`, or creates one.
+fn set_limit(
+    ctx: &ApiContext,
+    key: &Value,
+    id: &str,
+    signal: &str,
+    size: u64,
+    tags: &[String],
+) -> Result<Value> {
+    let config = json!({ "day": { "size": size } });
+    let existing = key
+        .get("limits")
+        .and_then(Value::as_array)
+        .and_then(|limits| {
+            limits
+                .iter()
+                .find(|l| l.get("signal").and_then(Value::as_str) == Some(signal))
+        })
+        .and_then(|l| l.get("id").and_then(Value::as_str));
+    match existing {
+        Some(limit_id) => {
+            expect_ok(ctx.execute(
+                "PATCH",
+                &format!("{KEYS_PATH}/limits/{limit_id}"),
+                &[],
+                Some(Body::Json(json!({ "config": config, "tags": tags }))),
+                Some("application/json"),
+            )?)?;
+            Ok(json!({ "id": limit_id, "signal": signal, "day_size": size, "action": "updated" }))
+        }
+        None => {
+            let body = expect_ok(ctx.execute(
+                "POST",
+                &format!("{KEYS_PATH}/{id}/limits"),
+                &[],
+                Some(Body::Json(
+                    json!({ "signal": signal, "config": config, "tags": tags }),
+                )),
+                Some("application/json"),
+            )?)?;
+            let limit_id = body.pointer("/data/id").cloned().unwrap_or(Value::Null);
+            Ok(json!({ "id": limit_id, "signal": signal, "day_size": size, "action": "created" }))
+        }
+    }
+}
+
+fn expect_ok(response: crate::http::HttpResponse) -> Result<Value> {
+    if response.status >= 400 {
+        return Err(ApiError::from_response(&response).into());
+    }
+    Ok(response.body)
+}
+
+/// Scalars are written double-quoted, as JSON strings (which YAML reads the
+/// same), so tag values with `:` or `#` and quotes survive.
+fn collector_snippet(endpoint: Option<&str>, tags: &[String]) -> String {
+    let quote = |s: &str| Value::from(s).to_string();
+    let endpoint = quote(endpoint.unwrap_or("ingest.<region>.signoz.cloud:443"));
+    let mut out = String::new();
+    out.push_str("processors:\n  resource/signoz-key:\n    attributes:\n");
+    for tag in tags {
+        let Some((name, value)) = tag.split_once('=') else {
+            continue;
+        };
+        let attribute = TAG_ATTRIBUTES
+            .iter()
+            .find(|(tag, _)| *tag == name)
+            .map(|(_, attr)| *attr)
+            .unwrap_or(name);
+        out.push_str(&format!(
+            "      - key: {}\n        value: {}\n        action: upsert\n",
+            quote(attribute),
+            quote(value)
+        ));
+    }
+    out.push_str(&format!(
+        "exporters:\n  otlp/signoz:\n    endpoint: {endpoint}\n    tls:\n      insecure: false\n    headers:\n      signoz-ingestion-key: ${{env:SIGNOZ_INGESTION_KEY}}\n"
+    ));
+    out
+}
+
+/// Parses `1048576`, `500MB`, `10GiB` (decimal and binary units).
+pub fn parse_bytes(raw: &str) -> Result<u64> {
+    let raw = raw.trim();
+    let split = raw
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(raw.len());
+    let (num, unit) = raw.split_at(split);
+    let value: f64 = num.parse().map_err(|_| anyhow!("invalid size: {raw}"))?;
+    let scale: f64 = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 1.0,
+        "kb" => 1e3,
+        "mb" => 1e6,
+        "gb" => 1e9,
+        "tb" => 1e12,
+        "kib" => 1024.0,
+        "mib" => 1024.0 * 1024.0,
+        "gib" => 1024.0 * 1024.0 * 1024.0,
+        "tib" => 1024.0 * 1024.0 * 1024.0 * 1024.0,
+        other => return Err(anyhow!("invalid size unit `{other}` in {raw}")),
+    };
+    Ok((value * scale) as u64)
+}
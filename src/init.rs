@@ -0,0 +1,278 @@
+use anyhow::{anyhow, Context, Result};
+use clap::{Arg, ArgAction, Command};
+use std::io::{self, IsTerminal, Write};
+use url::Url;
+
+use crate::auth;
+use crate::config::{self, Config, Profile};
+use crate::doctor;
+use crate::http::ClientOptions;
+
+/// SigNoz Cloud regions; an instance lives at `https://<name>.<region>.signoz.cloud`.
+const CLOUD_REGIONS: &[&str] = &["us", "eu", "in"];
+
+pub fn command() -> Command {
+    Command::new("init")
+        .about("Set up a first profile: pick the instance, add a credential, check the connection, and save it")
+        .arg(
+            Arg::new("name")
+                .long("name")
+                .value_name("PROFILE")
+                .help("Profile name to write (default: --profile, or asked; `default` when left empty)"),
+        )
+        .arg(
+            Arg::new("deployment")
+                .long("deployment")
+                .value_parser(["cloud", "self-hosted"])
+                .help("Where SigNoz runs (not needed with --base-url)"),
+        )
+        .arg(
+            Arg::new("region")
+                .long("region")
+                .value_parser(CLOUD_REGIONS.to_vec())
+                .help("SigNoz Cloud region"),
+        )
+        .arg(
+            Arg::new("instance")
+                .long("instance")
+                .value_name("NAME")
+                .help("SigNoz Cloud instance name, the first label of <name>.<region>.signoz.cloud"),
+        )
+        .arg(
+            Arg::new("email")
+                .long("email")
+                .help("Log in with email and password instead of an API key (stored like `signoz login`)"),
+        )
+        .arg(
+            Arg::new("no-verify")
+                .long("no-verify")
+                .action(ArgAction::SetTrue)
+                .help("Save the profile without running the doctor checks"),
+        )
+        .arg(
+            Arg::new("overwrite")
+                .long("overwrite")
+                .action(ArgAction::SetTrue)
+                .help("Replace a profile of the same name without asking"),
+        )
+}
+
+/// Asks only for what the flags leave open; without a terminal every answer
+/// must come from flags.
+pub fn run(
+    root: &clap::ArgMatches,
+    matches: &clap::ArgMatches,
+    config: &Config,
+    headers: &[(String, String)],
+    client_opts: &ClientOptions,
+) -> Result<()> {
+    let prompter = Prompter {
+        interactive: io::stdin().is_terminal(),
+    };
+
+    let name = match matches
+        .get_one::<String>("name")
+        .or_else(|| root.get_one::<String>("profile"))
+    {
+        Some(name) => name.clone(),
+        None => prompter.ask("Profile name", Some("default"), "--name")?,
+    };
+    if config.profiles.contains_key(&name)
+        && !matches.get_flag("overwrite")
+        && !prompter.yes_no(&format!("Profile {name} already exists; replace it?"))?
+    {
+        return Err(anyhow!(
+            "profile {name} already exists; pass --overwrite or choose another --name"
+        ));
+    }
+
+    let base_url = match root.get_one::<String>("base-url") {
+        Some(url) => url.clone(),
+        None => base_url(matches, &prompter)?,
+    };
+    let base_url = base_url.trim_end_matches('/').to_string();
+    Url::parse(&base_url)
+        .ok()
+        .filter(|url| matches!(url.scheme(), "http" | "https" | "unix"))
+        .ok_or_else(|| {
+            anyhow!("invalid base URL {base_url}; expected http(s)://host[:port] or unix:///PATH")
+        })?;
+
+    let mut profile = Profile {
+        base_url: Some(base_url.clone()),
+        ..Profile::default()
+    };
+    let email = match (
+        root.get_one::<String>("api-key"),
+        matches.get_one::<String>("email"),
+    ) {
+        (Some(key), _) => {
+            profile.api_key = Some(key.clone());
+            None
+        }
+        (None, Some(email)) => Some(email.clone()),
+        (None, None) => {
+            let method = prompter.choose(
+                "Authenticate with",
+                &["api-key", "login"],
+                "--api-key or --email",
+            )?;
+            if method == "api-key" {
+                eprintln!("Create a key under Settings > API Keys in the SigNoz UI.");
+                let key = rpassword::prompt_password("API key: ").context("read API key")?;
+                let key = key.trim();
+                if key.is_empty() {
+                    return Err(anyhow!("no API key entered"));
+                }
+                profile.api_key = Some(key.to_string());
+                None
+            } else {
+                Some(prompter.ask("Email", None, "--email")?)
+            }
+        }
+    };
+    if let Some(email) = email {
+        if !prompter.interactive {
+            return Err(anyhow!(
+                "--email needs a terminal for the password; use --api-key, or `signoz login` first"
+            ));
+        }
+        let password = rpassword::prompt_password("Password: ").context("read password")?;
+        let token = auth::login(&base_url, &email, &password, None, client_opts)?;
+        eprintln!(
+            "Logged in as {}; the session is stored for {base_url}.",
+            token.email.as_deref().unwrap_or(&email)
+        );
+    }
+
+    if !matches.get_flag("no-verify") {
+        eprintln!("Checking {base_url} ...");
+        let report = doctor::checks(
+            &base_url,
+            doctor::Credentials {
+                api_key: profile.api_key.as_ref(),
+                token: None,
+            },
+            headers,
+            client_opts,
+        );
+        report.print();
+        if report.failures() > 0
+            && !prompter.yes_no("Some checks failed. Save the profile anyway?")?
+        {
+            return Err(anyhow!(
+                "profile not saved; fix the failing checks or pass --no-verify"
+            ));
+        }
+    }
+
+    let path = config::save_profile(&name, &profile)?;
+    eprintln!("Saved profile {name} to {}.", path.display());
+    eprintln!("Use it with `signoz --profile {name} ...` or `export SIGNOZ_PROFILE={name}`.");
+    Ok(())
+}
+
+/// Cloud instances are built from region and instance name; self-hosted
+/// ones are asked for directly.
+fn base_url(matches: &clap::ArgMatches, prompter: &Prompter) -> Result<String> {
+    let deployment = match matches.get_one::<String>("deployment") {
+        Some(deployment) => deployment.clone(),
+        None if matches.contains_id("region") || matches.contains_id("instance") => {
+            "cloud".to_string()
+        }
+        None => prompter.choose(
+            "Deployment",
+            &["cloud", "self-hosted"],
+            "--deployment or --base-url",
+        )?,
+    };
+    if deployment == "self-hosted" {
+        return prompter.ask(
+            "Base URL of the query service (e.g. http://localhost:8080)",
+            None,
+            "--base-url",
+        );
+    }
+    let instance = match matches.get_one::<String>("instance") {
+        Some(instance) => instance.clone(),
+        None => prompter.ask(
+            "Instance name (the <name> in <name>.<region>.signoz.cloud, or the full URL)",
+            None,
+            "--instance",
+        )?,
+    };
+    if instance.contains("://") {
+        return Ok(instance);
+    }
+    let region = match matches.get_one::<String>("region") {
+        Some(region) => region.clone(),
+        None => prompter.choose("Region", CLOUD_REGIONS, "--region")?,
+    };
+    Ok(format!("https://{instance}.{region}.signoz.cloud"))
+}
+
+struct Prompter {
+    interactive: bool,
+}
+
+impl Prompter {
+    /// `flag` names what supplies the answer when there is no terminal.
+    fn ask(&self, question: &str, default: Option<&str>, flag: &str) -> Result<String> {
+        if !self.interactive {
+            return Err(anyhow!("no terminal to ask on; pass {flag}"));
+        }
+        loop {
+            match default {
+                Some(default) => eprint!("{question} [{default}]: "),
+                None => eprint!("{question}: "),
+            }
+            io::stderr().flush().ok();
+            let mut answer = String::new();
+            if io::stdin().read_line(&mut answer).context("read answer")? == 0 {
+                return Err(anyhow!("setup cancelled"));
+            }
+            match (answer.trim(), default) {
+                ("", Some(default)) => return Ok(default.to_string()),
+                ("", None) => continue,
+                (answer, _) => return Ok(answer.to_string()),
+            }
+        }
+    }
+
+    /// Accepts an option by name or by its number; the first is the default.
+    fn choose(&self, question: &str, options: &[&str], flag: &str) -> Result<String> {
+        let listed = options
+            .iter()
+            .enumerate()
+            .map(|(i, option)| format!("{}) {option}", i + 1))
+            .collect::<Vec<_>>()
+            .join(", ");
+        loop {
+            let answer = self.ask(&format!("{question}: {listed}"), Some(options[0]), flag)?;
+            let picked = answer
+                .parse::<usize>()
+                .ok()
+                .and_then(|n| options.get(n.wrapping_sub(1)))
+                .or_else(|| options.iter().find(|o| o.eq_ignore_ascii_case(&answer)));
+            match picked {
+                Some(option) => return Ok(option.to_string()),
+                None => eprintln!("Pick one of: {}", options.join(", ")),
+            }
+        }
+    }
+
+    /// Defaults to no, and is no without a terminal.
+    fn yes_no(&self, question: &str) -> Result<bool> {
+        if !self.interactive {
+            return Ok(false);
+        }
+        eprint!("{question} [y/N] ");
+        io::stderr().flush().ok();
+        let mut answer = String::new();
+        io::stdin().read_line(&mut answer).context("read answer")?;
+        Ok(matches!(
+            answer.trim().to_ascii_lowercase().as_str(),
+            "y" | "yes"
+        ))
+    }
+}
@@ -0,0 +1,69 @@
+use serde_json::Value;
+use std::{
+    fmt,
+    io::Write,
+    sync::atomic::{AtomicBool, Ordering},
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Exit code after a Ctrl-C, matching the shell convention (128 + SIGINT).
+pub const EXIT_INTERRUPTED: i32 = 130;
+
+static GRACEFUL: AtomicBool = AtomicBool::new(false);
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Installs the SIGINT handler. Outside a [`graceful`] section (or on a
+/// second Ctrl-C) the process exits right away; inside one the flag is set
+/// and long-running loops stop after the request in flight.
+pub fn install() {
+    let _ = ctrlc::set_handler(|| {
+        if GRACEFUL.load(Ordering::SeqCst) && !INTERRUPTED.swap(true, Ordering::SeqCst) {
+            eprintln!("\ninterrupted; finishing current request (Ctrl-C again to abort)");
+            return;
+        }
+        let _ = std::io::stdout().flush();
+        std::process::exit(EXIT_INTERRUPTED);
+    });
+}
+
+/// Marks the start of a loop that can stop cleanly on Ctrl-C.
+pub fn graceful() {
+    GRACEFUL.store(true, Ordering::SeqCst);
+}
+
+pub fn requested() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
+
+/// Sleeps for `duration`, waking early on Ctrl-C. Returns false if
+/// interrupted.
+pub fn sleep(duration: Duration) -> bool {
+    let until = Instant::now() + duration;
+    while !requested() {
+        let left = until.saturating_duration_since(Instant::now());
+        if left.is_zero() {
+            return true;
+        }
+        thread::sleep(left.min(Duration::from_millis(100)));
+    }
+    false
+}
+
+/// Error returned after partial output has been flushed; `main` prints the
+/// checkpoint and exits with [`EXIT_INTERRUPTED`].
+#[derive(Debug)]
+pub struct Interrupted {
+    pub checkpoint: Option<Value>,
+}
+
+impl fmt::Display for Interrupted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.checkpoint {
+            Some(checkpoint) => write!(f, "interrupted; resume from {checkpoint}"),
+            None => write!(f, "interrupted"),
+        }
+    }
+}
+
+impl std::error::Error for Interrupted {}
@@ -0,0 +1,477 @@
+use anyhow::{anyhow, Context, Result};
+use ring::{
+    digest,
+    rand::{SecureRandom, SystemRandom},
+};
+use serde_json::Value;
+use std::{sync::Arc, time::Duration};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use crate::command_tree::Operation;
+use crate::error::ApiError;
+use crate::http::{self, HttpClient};
+use crate::interrupt;
+use crate::output::OutputOptions;
+use crate::stream::Emitter;
+use crate::{ApiContext, AuthMode};
+
+/// First wait before reconnecting; doubled per failed attempt.
+const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(30);
+/// How often a read with nothing to show wakes up to check for Ctrl-C.
+const POLL: Duration = Duration::from_millis(200);
+/// Appended to the handshake key to derive `Sec-WebSocket-Accept` (RFC 6455).
+const WEBSOCKET_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+/// How a connection ended without an error.
+enum Ended {
+    /// It was closed or dropped, for this reason; reconnect.
+    Closed(String),
+    Interrupted,
+}
+
+/// One live operation: its request, the clients to open it with (API key,
+/// then token in auto mode), and what carries over between connections.
+struct Session {
+    path: String,
+    query: Vec<(String, String)>,
+    websocket: bool,
+    clients: Vec<HttpClient>,
+    /// Index of the client that last got past auth.
+    auth: usize,
+    emitter: Emitter,
+    /// The last SSE `id`, sent back as `Last-Event-ID` on reconnect.
+    last_event_id: Option<String>,
+    /// The server's SSE `retry` delay, used instead of the backoff.
+    retry: Option<Duration>,
+    /// Events received on the current connection.
+    received: usize,
+}
+
+/// Runs a live (`sse` or `websocket`) tree operation: prints each event as
+/// one JSON line until Ctrl-C, reopening the connection with backoff when
+/// it drops or fails. Error statuses other than 408, 429, and 5xx end it.
+pub fn run(
+    ctx: &ApiContext,
+    op: &Operation,
+    matches: &clap::ArgMatches,
+    opts: &OutputOptions,
+) -> Result<()> {
+    let (path, query, header_params) = crate::build_request_parts(ctx, op, matches)?;
+    let emitter = Emitter::new(opts, &format!("`{} {}`", op.tags.join(" "), op.name))?;
+    let mut live = ctx.clone();
+    live.client_opts.live = true;
+    live.client_opts.shared = Arc::default();
+    let with_headers = |client: HttpClient| client.with_headers(&header_params);
+    let clients = match (live.auth_mode, &live.api_key, &live.token) {
+        (AuthMode::ApiKey, _, _) => vec![live.client(live.api_key.as_ref(), None)?],
+        (AuthMode::Token, _, _) | (AuthMode::Auto, None, _) => {
+            vec![live.client(None, live.token.as_ref())?]
+        }
+        (AuthMode::Auto, Some(key), token) => {
+            let mut clients = vec![live.client(Some(key), None)?];
+            if token.is_some() {
+                clients.push(live.client(None, token.as_ref())?);
+            }
+            clients
+        }
+    };
+    let mut session = Session {
+        path,
+        query,
+        websocket: op.live.as_deref() == Some("websocket"),
+        clients: clients.into_iter().map(with_headers).collect(),
+        auth: 0,
+        emitter,
+        last_event_id: None,
+        retry: None,
+        received: 0,
+    };
+
+    interrupt::graceful();
+    let runtime = http::runtime()?;
+    let mut attempt = 0;
+    loop {
+        session.received = 0;
+        let reason = match runtime.block_on(session.connect()) {
+            Ok(Ended::Interrupted) => break,
+            Ok(Ended::Closed(reason)) => reason,
+            Err(err) if !recoverable(&err) => return Err(err),
+            Err(err) => format!("{err:#}"),
+        };
+        if session.received > 0 {
+            attempt = 0;
+        }
+        let delay = session
+            .retry
+            .unwrap_or_else(|| http::backoff(RECONNECT_DELAY, attempt).min(MAX_RECONNECT_DELAY));
+        eprintln!(
+            "warning: {reason}; reconnecting in {:.1}s",
+            delay.as_secs_f64()
+        );
+        attempt += 1;
+        if !interrupt::sleep(delay) {
+            break;
+        }
+    }
+    Err(interrupt::Interrupted { checkpoint: None }.into())
+}
+
+/// Failing to connect, and 408, 429, and 5xx statuses, are worth another
+/// attempt; other statuses, handshake and output errors are not.
+fn recoverable(err: &anyhow::Error) -> bool {
+    err.chain()
+        .any(|cause| match cause.downcast_ref::<ApiError>() {
+            Some(api) => matches!(api.status, 408 | 429) || api.status >= 500,
+            None => cause.is::<reqwest::Error>(),
+        })
+}
+
+impl Session {
+    /// Opens the connection (moving on to the next credentials on 401 or
+    /// 403) and reads events until it ends.
+    async fn connect(&mut self) -> Result<Ended> {
+        let key = self.websocket.then(websocket_key).transpose()?;
+        let mut headers = Vec::new();
+        match &key {
+            Some(key) => headers.extend([
+                ("connection", "Upgrade".to_string()),
+                ("upgrade", "websocket".to_string()),
+                ("sec-websocket-version", "13".to_string()),
+                ("sec-websocket-key", key.clone()),
+            ]),
+            None => {
+                headers.push(("accept", "text/event-stream".to_string()));
+                if let Some(id) = &self.last_event_id {
+                    headers.push(("last-event-id", id.clone()));
+                }
+            }
+        }
+        let resp = loop {
+            let client = &self.clients[self.auth];
+            match client.open_live(&self.path, &self.query, &headers).await {
+                Err(err)
+                    if self.auth + 1 < self.clients.len()
+                        && err
+                            .downcast_ref::<ApiError>()
+                            .is_some_and(|api| matches!(api.status, 401 | 403)) =>
+                {
+                    self.auth += 1;
+                }
+                resp => break resp?,
+            }
+        };
+        match key {
+            Some(key) => self.read_websocket(resp, &key).await,
+            None => self.read_sse(resp).await,
+        }
+    }
+
+    async fn read_sse(&mut self, mut resp: reqwest::Response) -> Result<Ended> {
+        let mut parser = SseParser::default();
+        loop {
+            let chunk = match tokio::time::timeout(POLL, resp.chunk()).await {
+                Ok(Ok(chunk)) => chunk,
+                Ok(Err(err)) => return Ok(Ended::Closed(format!("read event stream: {err}"))),
+                Err(_) if interrupt::requested() => return Ok(Ended::Interrupted),
+                Err(_) => continue,
+            };
+            let Some(chunk) = chunk else {
+                return Ok(Ended::Closed("connection closed".to_string()));
+            };
+            for event in parser.feed(&chunk) {
+                if let Some(id) = event.id {
+                    self.last_event_id = Some(id);
+                }
+                if let Some(retry) = event.retry {
+                    self.retry = Some(retry);
+                }
+                if event.name == "error" {
+                    eprintln!(
+                        "warning: server sent an error event: {}",
+                        event.data.unwrap_or_default()
+                    );
+                } else if let Some(data) = event.data.as_deref().filter(|d| !d.is_empty()) {
+                    self.emit(data.as_bytes())?;
+                }
+            }
+            if interrupt::requested() {
+                return Ok(Ended::Interrupted);
+            }
+        }
+    }
+
+    async fn read_websocket(&mut self, resp: reqwest::Response, key: &str) -> Result<Ended> {
+        let status = resp.status().as_u16();
+        if status != 101 {
+            return Err(anyhow!(
+                "{} answered http {status} instead of switching to WebSocket",
+                self.path
+            ));
+        }
+        let accept = resp
+            .headers()
+            .get("sec-websocket-accept")
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default()
+            .to_string();
+        let expected = digest::digest(
+            &digest::SHA1_FOR_LEGACY_USE_ONLY,
+            format!("{key}{WEBSOCKET_GUID}").as_bytes(),
+        );
+        if accept != base64(expected.as_ref()) {
+            return Err(anyhow!(
+                "invalid WebSocket handshake: unexpected Sec-WebSocket-Accept"
+            ));
+        }
+        let mut socket = resp.upgrade().await.context("upgrade to WebSocket")?;
+
+        let mut frames = FrameParser::default();
+        let mut message: Option<Vec<u8>> = None;
+        let mut buf = vec![0; 16 * 1024];
+        loop {
+            let read = match tokio::time::timeout(POLL, socket.read(&mut buf)).await {
+                Ok(Ok(read)) => read,
+                Ok(Err(err)) => return Ok(Ended::Closed(format!("read WebSocket: {err}"))),
+                Err(_) if interrupt::requested() => {
+                    // Best effort: the server may already be gone.
+                    let _ = socket
+                        .write_all(&frame(OP_CLOSE, &1000u16.to_be_bytes())?)
+                        .await;
+                    return Ok(Ended::Interrupted);
+                }
+                Err(_) => continue,
+            };
+            if read == 0 {
+                return Ok(Ended::Closed("connection closed".to_string()));
+            }
+            for (fin, opcode, payload) in frames.feed(&buf[..read])? {
+                match opcode {
+                    OP_TEXT | OP_BINARY => message = Some(payload),
+                    OP_CONTINUATION => match &mut message {
+                        Some(message) => message.extend(payload),
+                        None => return Err(anyhow!("WebSocket continuation without a message")),
+                    },
+                    OP_PING => {
+                        if let Err(err) = socket.write_all(&frame(OP_PONG, &payload)?).await {
+                            return Ok(Ended::Closed(format!("write WebSocket: {err}")));
+                        }
+                        continue;
+                    }
+                    OP_CLOSE => {
+                        let _ = socket.write_all(&frame(OP_CLOSE, &payload)?).await;
+                        return Ok(Ended::Closed("server closed the WebSocket".to_string()));
+                    }
+                    _ => continue,
+                }
+                if fin {
+                    if let Some(message) = message.take() {
+                        self.emit(&message)?;
+                    }
+                }
+            }
+            if interrupt::requested() {
+                let _ = socket
+                    .write_all(&frame(OP_CLOSE, &1000u16.to_be_bytes())?)
+                    .await;
+                return Ok(Ended::Interrupted);
+            }
+        }
+    }
+
+    /// Writes one event: its JSON, or the text when it is not JSON.
+    fn emit(&mut self, data: &[u8]) -> Result<()> {
+        let value = serde_json::from_slice(data)
+            .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(data).into_owned()));
+        self.received += 1;
+        self.emitter.write(vec![value])
+    }
+}
+
+/// A dispatched server-sent event.
+struct SseEvent {
+    /// `event`, `message` when unnamed.
+    name: String,
+    data: Option<String>,
+    id: Option<String>,
+    retry: Option<Duration>,
+}
+
+/// Splits an event stream into events: `field: value` lines, ended by a
+/// blank line, with `\n`, `\r\n`, or `\r` line breaks.
+#[derive(Default)]
+struct SseParser {
+    line: Vec<u8>,
+    /// The last byte was `\r`, so a `\n` right after it is part of the same
+    /// break.
+    after_cr: bool,
+    name: Option<String>,
+    data: Option<String>,
+    id: Option<String>,
+    retry: Option<Duration>,
+}
+
+impl SseParser {
+    fn feed(&mut self, chunk: &[u8]) -> Vec<SseEvent> {
+        let mut events = Vec::new();
+        for &byte in chunk {
+            let after_cr = std::mem::replace(&mut self.after_cr, byte == b'\r');
+            match byte {
+                b'\n' if after_cr => {}
+                b'\n' | b'\r' => {
+                    let line = std::mem::take(&mut self.line);
+                    events.extend(self.line_done(&String::from_utf8_lossy(&line)));
+                }
+                _ => self.line.push(byte),
+            }
+        }
+        events
+    }
+
+    fn line_done(&mut self, line: &str) -> Option<SseEvent> {
+        if line.is_empty() {
+            let data = self.data.take();
+            let name = self.name.take();
+            if data.is_none() && self.id.is_none() && self.retry.is_none() {
+                return None;
+            }
+            return Some(SseEvent {
+                name: name.unwrap_or_else(|| "message".to_string()),
+                data,
+                id: self.id.take(),
+                retry: self.retry.take(),
+            });
+        }
+        let (field, value) = match line.split_once(':') {
+            Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+            None => (line, ""),
+        };
+        match field {
+            // A comment, often sent as a keep-alive.
+            "" => {}
+            "event" => self.name = Some(value.to_string()),
+            "data" => match &mut self.data {
+                Some(data) => {
+                    data.push('\n');
+                    data.push_str(value);
+                }
+                None => self.data = Some(value.to_string()),
+            },
+            "id" if !value.contains('\0') => self.id = Some(value.to_string()),
+            "retry" => {
+                if let Ok(ms) = value.parse() {
+                    self.retry = Some(Duration::from_millis(ms));
+                }
+            }
+            _ => {}
+        }
+        None
+    }
+}
+
+const OP_CONTINUATION: u8 = 0x0;
+const OP_TEXT: u8 = 0x1;
+const OP_BINARY: u8 = 0x2;
+const OP_CLOSE: u8 = 0x8;
+const OP_PING: u8 = 0x9;
+const OP_PONG: u8 = 0xA;
+
+/// Reassembles WebSocket frames from the bytes read so far.
+#[derive(Default)]
+struct FrameParser {
+    buf: Vec<u8>,
+}
+
+impl FrameParser {
+    /// Complete frames as `(fin, opcode, payload)`, unmasked.
+    fn feed(&mut self, bytes: &[u8]) -> Result<Vec<(bool, u8, Vec<u8>)>> {
+        self.buf.extend_from_slice(bytes);
+        let mut frames = Vec::new();
+        loop {
+            let buf = &self.buf;
+            if buf.len() < 2 {
+                break;
+            }
+            let (fin, opcode) = (buf[0] & 0x80 != 0, buf[0] & 0x0F);
+            let masked = buf[1] & 0x80 != 0;
+            let (len, mut at) = match buf[1] & 0x7F {
+                126 if buf.len() >= 4 => (u64::from(u16::from_be_bytes([buf[2], buf[3]])), 4),
+                127 if buf.len() >= 10 => {
+                    let mut len = [0; 8];
+                    len.copy_from_slice(&buf[2..10]);
+                    (u64::from_be_bytes(len), 10)
+                }
+                126 | 127 => break,
+                len => (u64::from(len), 2),
+            };
+            let len = usize::try_from(len).map_err(|_| anyhow!("WebSocket frame too large"))?;
+            let mask_len = if masked { 4 } else { 0 };
+            if buf.len() < at + mask_len + len {
+                break;
+            }
+            let mask: Option<[u8; 4]> =
+                masked.then(|| [buf[at], buf[at + 1], buf[at + 2], buf[at + 3]]);
+            at += mask_len;
+            let mut payload = buf[at..at + len].to_vec();
+            if let Some(mask) = mask {
+                for (i, byte) in payload.iter_mut().enumerate() {
+                    *byte ^= mask[i % 4];
+                }
+            }
+            self.buf.drain(..at + len);
+            frames.push((fin, opcode, payload));
+        }
+        Ok(frames)
+    }
+}
+
+/// A single-frame client message; clients mask every frame.
+fn frame(opcode: u8, payload: &[u8]) -> Result<Vec<u8>> {
+    let mut mask = [0; 4];
+    SystemRandom::new()
+        .fill(&mut mask)
+        .map_err(|_| anyhow!("generate WebSocket mask"))?;
+    let mut out = vec![0x80 | opcode];
+    match payload.len() {
+        len if len < 126 => out.push(0x80 | len as u8),
+        len if len <= usize::from(u16::MAX) => {
+            out.push(0x80 | 126);
+            out.extend((len as u16).to_be_bytes());
+        }
+        len => {
+            out.push(0x80 | 127);
+            out.extend((len as u64).to_be_bytes());
+        }
+    }
+    out.extend(mask);
+    out.extend(payload.iter().enumerate().map(|(i, b)| b ^ mask[i % 4]));
+    Ok(out)
+}
+
+/// A fresh `Sec-WebSocket-Key`: 16 random bytes, base64-encoded.
+fn websocket_key() -> Result<String> {
+    let mut key = [0; 16];
+    SystemRandom::new()
+        .fill(&mut key)
+        .map_err(|_| anyhow!("generate WebSocket key"))?;
+    Ok(base64(&key))
+}
+
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, &b)| n | u32::from(b) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 0x3F) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
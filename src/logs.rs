@@ -0,0 +1,477 @@
+use anyhow::{anyhow, Context, Result};
+use clap::{Arg, ArgAction, Command};
+use serde_json::{json, Value};
+use std::{collections::HashMap, time::Duration};
+
+use crate::error::ApiError;
+use crate::interrupt;
+use crate::output::{self, OutputOptions};
+use crate::query;
+use crate::timeparse;
+use crate::watch::PollFailures;
+use crate::ApiContext;
+
+/// Rows fetched per poll while following.
+const FOLLOW_PAGE: usize = 1000;
+/// Re-query this far behind the cursor so late-arriving rows are not missed
+/// (duplicates are dropped by id).
+const OVERLAP_MS: u64 = 5_000;
+/// `--services` looks at least this far back for matching service names, so
+/// services quiet during a short `--since` window are still tailed.
+const RESOLVE_WINDOW_MS: u64 = 3_600_000;
+
+pub fn extend(cmd: Command) -> Command {
+    cmd.subcommand(
+        Command::new("query")
+            .about("Query logs with simple flags instead of a query_range body")
+            .arg(
+                Arg::new("service")
+                    .long("service")
+                    .value_name("NAME")
+                    .help("Only logs from this service.name"),
+            )
+            .arg(
+                Arg::new("severity")
+                    .long("severity")
+                    .value_name("LEVEL")
+                    .help("Severity text, comma-separated (e.g. error or warn,error)"),
+            )
+            .arg(
+                Arg::new("filter")
+                    .long("filter")
+                    .value_name("EXPR")
+                    .help("Filter expression, e.g. \"service.name = 'checkout'\""),
+            )
+            .arg(
+                Arg::new("since")
+                    .long("since")
+                    .value_name("DURATION|TIME")
+                    .help(
+                        "How far back (15m, 1h; default 1h) or a start time (RFC 3339, epoch ms)",
+                    ),
+            )
+            .arg(timeparse::until_arg())
+            .arg(
+                Arg::new("limit")
+                    .long("limit")
+                    .value_name("N")
+                    .default_value("100")
+                    .help("Maximum rows to return"),
+            )
+            .arg(
+                Arg::new("order")
+                    .long("order")
+                    .value_name("DIR")
+                    .value_parser(["asc", "desc"])
+                    .default_value("desc")
+                    .help("Sort by timestamp"),
+            )
+            .arg(
+                Arg::new("print-query")
+                    .long("print-query")
+                    .action(ArgAction::SetTrue)
+                    .help("Print the generated query_range body instead of running it"),
+            ),
+    )
+    .subcommand(
+        Command::new("tail")
+            .about("Print recent logs and optionally follow new ones")
+            .arg(
+                Arg::new("service")
+                    .long("service")
+                    .value_name("NAME")
+                    .help("Only logs from this service.name"),
+            )
+            .arg(
+                Arg::new("services")
+                    .long("services")
+                    .value_name("GLOB")
+                    .conflicts_with("service")
+                    .help("Tail every service whose name matches, e.g. 'payments-*' (comma-separated globs)"),
+            )
+            .arg(
+                Arg::new("filter")
+                    .long("filter")
+                    .value_name("EXPR")
+                    .help("Extra filter expression, e.g. \"severity_text = 'ERROR'\""),
+            )
+            .arg(
+                Arg::new("since")
+                    .long("since")
+                    .value_name("DURATION|TIME")
+                    .help("How far back to start (30s, 5m; default 5m) or a start time"),
+            )
+            .arg(
+                Arg::new("lines")
+                    .long("lines")
+                    .short('n')
+                    .value_name("N")
+                    .default_value("100")
+                    .help("Most recent lines to print before following (per service with --services)"),
+            )
+            .arg(
+                Arg::new("follow")
+                    .long("follow")
+                    .short('f')
+                    .action(ArgAction::SetTrue)
+                    .help("Keep polling and stream new lines until Ctrl-C"),
+            )
+            .arg(
+                Arg::new("interval")
+                    .long("interval")
+                    .value_name("DURATION")
+                    .default_value("2s")
+                    .help("Polling interval with --follow"),
+            ),
+    )
+}
+
+pub fn handle(
+    op: &str,
+    matches: &clap::ArgMatches,
+    ctx: &ApiContext,
+    opts: &OutputOptions,
+) -> Option<Result<()>> {
+    match op {
+        "query" => Some(handle_query(matches, ctx, opts)),
+        "tail" => Some(handle_tail(matches, ctx, opts)),
+        _ => None,
+    }
+}
+
+fn handle_query(matches: &clap::ArgMatches, ctx: &ApiContext, opts: &OutputOptions) -> Result<()> {
+    let arg = |name: &str| matches.get_one::<String>(name).map(String::as_str);
+    let (start, end) =
+        timeparse::range(ctx.since("logs", matches, "1h"), timeparse::until(matches))?;
+    let limit: usize = arg("limit")
+        .unwrap_or("100")
+        .parse()
+        .map_err(|_| anyhow!("invalid --limit"))?;
+    let body = raw_query_body(
+        &filter_expression(arg("service"), arg("severity"), arg("filter")),
+        start,
+        end,
+        arg("order").unwrap_or("desc"),
+        limit,
+    );
+    if let Some(result) = query::dry_run(matches, &body, opts) {
+        return result;
+    }
+    let rows = fetch_rows(ctx, body)?;
+    output::print_value(&Value::Array(rows), opts)
+}
+
+/// Joins the convenience flags into one v5 filter expression.
+fn filter_expression(
+    service: Option<&str>,
+    severity: Option<&str>,
+    filter: Option<&str>,
+) -> String {
+    let quote = |s: &str| format!("'{}'", s.trim().replace('\'', "\\'"));
+    let mut clauses = Vec::new();
+    if let Some(service) = service {
+        clauses.push(format!("service.name = {}", quote(service)));
+    }
+    if let Some(severity) = severity {
+        let levels: Vec<String> = severity
+            .split(',')
+            .filter(|s| !s.trim().is_empty())
+            .map(|s| quote(&s.to_uppercase()))
+            .collect();
+        clauses.push(format!("severity_text IN ({})", levels.join(", ")));
+    }
+    if let Some(filter) = filter {
+        clauses.push(format!("({filter})"));
+    }
+    clauses.join(" AND ")
+}
+
+fn raw_query_body(filter: &str, start: u64, end: u64, direction: &str, limit: usize) -> Value {
+    let spec = json!({
+        "filter": { "expression": filter },
+        "order": [
+            { "key": { "name": "timestamp" }, "direction": direction },
+            { "key": { "name": "id" }, "direction": direction },
+        ],
+        "limit": limit,
+    });
+    query::query_range_body(
+        start,
+        end,
+        "raw",
+        vec![query::builder_query("A", "logs", spec)],
+    )
+}
+
+fn fetch_rows(ctx: &ApiContext, body: Value) -> Result<Vec<Value>> {
+    let response = query::run_query_range(ctx, body)?;
+    if response.status >= 400 {
+        return Err(ApiError::from_response(&response)).context("logs query failed");
+    }
+    Ok(response
+        .body
+        .pointer("/data/data/results/0/rows")
+        .and_then(Value::as_array)
+        .cloned()
+        .unwrap_or_default())
+}
+
+struct Tail {
+    filter: String,
+    /// Row id -> timestamp (ms) for rows already printed.
+    seen: HashMap<String, u64>,
+    cursor_ms: u64,
+    /// Rows older than this were cut by `--lines` and stay hidden.
+    floor_ms: u64,
+}
+
+/// With `--services`, each matching service gets its own cursor and is
+/// polled concurrently; every round's rows are merged by timestamp.
+fn handle_tail(matches: &clap::ArgMatches, ctx: &ApiContext, opts: &OutputOptions) -> Result<()> {
+    let arg = |name: &str| matches.get_one::<String>(name).map(String::as_str);
+    let (start, end) = timeparse::range(ctx.since("logs", matches, "5m"), None)?;
+    let interval = timeparse::parse_duration_ms(arg("interval").unwrap_or("2s"))?;
+    let lines: usize = arg("lines")
+        .unwrap_or("100")
+        .parse()
+        .map_err(|_| anyhow!("invalid --lines"))?;
+
+    let filters = match arg("services") {
+        Some(globs) => {
+            let services = resolve_services(ctx, globs, start, end)?;
+            eprintln!(
+                "tailing {} services: {}",
+                services.len(),
+                services.join(", ")
+            );
+            services
+                .iter()
+                .map(|service| filter_expression(Some(service), None, arg("filter")))
+                .collect()
+        }
+        None => vec![filter_expression(arg("service"), None, arg("filter"))],
+    };
+    let mut tails: Vec<Tail> = filters
+        .into_iter()
+        .map(|filter| Tail {
+            filter,
+            seen: HashMap::new(),
+            cursor_ms: start,
+            floor_ms: 0,
+        })
+        .collect();
+
+    // Backlog: newest N rows in the window, printed oldest first.
+    let batches = fetch_all(ctx, &tails, |tail| (tail.cursor_ms, end, "desc", lines))?;
+    let mut rows = Vec::new();
+    for (tail, mut batch) in tails.iter_mut().zip(batches) {
+        batch.reverse();
+        tail.floor_ms = match batch.first().and_then(row_timestamp_ns) {
+            Some(oldest) if batch.len() >= lines => oldest / 1_000_000,
+            _ => tail.cursor_ms,
+        };
+        rows.extend(tail.fresh(batch, end));
+    }
+    print_merged(rows, opts)?;
+    if !matches.get_flag("follow") {
+        return Ok(());
+    }
+
+    interrupt::graceful();
+    let interval = Duration::from_millis(interval.max(100));
+    let mut failures = PollFailures::new(interval);
+    loop {
+        let mut full = true;
+        let mut wait = interval;
+        while full {
+            let end = query::now_millis();
+            let fetched = fetch_all(ctx, &tails, |tail| {
+                (
+                    tail.cursor_ms.saturating_sub(OVERLAP_MS),
+                    end,
+                    "asc",
+                    FOLLOW_PAGE,
+                )
+            });
+            // Cursors only move on success, so the next poll picks up
+            // where the failed one would have.
+            let batches = match fetched {
+                Ok(batches) => batches,
+                Err(err) => {
+                    wait = failures.failed(err)?;
+                    break;
+                }
+            };
+            failures.succeeded();
+            full = batches.iter().any(|batch| batch.len() >= FOLLOW_PAGE);
+            let mut rows = Vec::new();
+            for (tail, batch) in tails.iter_mut().zip(batches) {
+                rows.extend(tail.fresh(batch, end));
+            }
+            print_merged(rows, opts)?;
+        }
+        if !interrupt::sleep(wait) {
+            let cursor_ms = tails.iter().map(|tail| tail.cursor_ms).min();
+            return Err(interrupt::Interrupted {
+                checkpoint: Some(json!({ "cursor_ms": cursor_ms })),
+            }
+            .into());
+        }
+    }
+}
+
+/// Service names with logs in the last hour (or the `--since` window, if
+/// longer) matching any of the comma-separated globs.
+fn resolve_services(ctx: &ApiContext, globs: &str, start: u64, end: u64) -> Result<Vec<String>> {
+    let patterns: Vec<&str> = globs
+        .split(',')
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .collect();
+    let count = query::builder_query(
+        "A",
+        "logs",
+        json!({
+            "aggregations": [{ "expression": "count()" }],
+            "groupBy": [{ "name": "service.name" }],
+            "limit": 1000,
+        }),
+    );
+    let start = start.min(end.saturating_sub(RESOLVE_WINDOW_MS));
+    let body = query::query_range_body(start, end, "scalar", vec![count]);
+    let response = query::run_query_range(ctx, body)?;
+    if response.status >= 400 {
+        return Err(ApiError::from_response(&response)).context("list services failed");
+    }
+    let mut services: Vec<String> = query::scalar_rows(&response.body)
+        .into_iter()
+        .filter_map(|(groups, _)| groups.into_iter().next())
+        .filter(|service| patterns.iter().any(|p| glob_match(p, service)))
+        .collect();
+    services.sort();
+    services.dedup();
+    if services.is_empty() {
+        return Err(anyhow!("no service with recent logs matches {globs}"));
+    }
+    Ok(services)
+}
+
+/// `*` matches any run of characters, `?` any single one.
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let (p, t): (Vec<char>, Vec<char>) = (pattern.chars().collect(), text.chars().collect());
+    let (mut pi, mut ti) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while ti < t.len() {
+        match p.get(pi) {
+            Some('*') => {
+                star = Some((pi, ti));
+                pi += 1;
+            }
+            Some(&c) if c == '?' || c == t[ti] => {
+                pi += 1;
+                ti += 1;
+            }
+            _ => match star {
+                Some((sp, st)) => {
+                    pi = sp + 1;
+                    ti = st + 1;
+                    star = Some((sp, st + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    p[pi..].iter().all(|&c| c == '*')
+}
+
+/// Runs one query per tail concurrently; `window` gives each tail's
+/// `(start, end, direction, limit)`.
+fn fetch_all(
+    ctx: &ApiContext,
+    tails: &[Tail],
+    window: impl Fn(&Tail) -> (u64, u64, &'static str, usize),
+) -> Result<Vec<Vec<Value>>> {
+    if let [tail] = tails {
+        let (start, end, direction, limit) = window(tail);
+        return Ok(vec![tail.fetch(ctx, start, end, direction, limit)?]);
+    }
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = tails
+            .iter()
+            .map(|tail| {
+                let (start, end, direction, limit) = window(tail);
+                scope.spawn(move || tail.fetch(ctx, start, end, direction, limit))
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|_| Err(anyhow!("tail thread panicked")))
+            })
+            .collect()
+    })
+}
+
+/// Prints rows from every tail in timestamp order.
+fn print_merged(mut rows: Vec<Value>, opts: &OutputOptions) -> Result<()> {
+    rows.sort_by_key(|row| row_timestamp_ns(row).unwrap_or(u64::MAX));
+    for row in &rows {
+        output::print_value(row, opts)?;
+    }
+    Ok(())
+}
+
+impl Tail {
+    fn fetch(
+        &self,
+        ctx: &ApiContext,
+        start: u64,
+        end: u64,
+        direction: &str,
+        limit: usize,
+    ) -> Result<Vec<Value>> {
+        fetch_rows(
+            ctx,
+            raw_query_body(&self.filter, start, end, direction, limit),
+        )
+    }
+
+    /// Rows not seen before; advances the cursor past them.
+    fn fresh(&mut self, rows: Vec<Value>, end: u64) -> Vec<Value> {
+        let mut fresh = Vec::new();
+        let mut newest = None;
+        for row in rows {
+            let ts_ms = row_timestamp_ns(&row).map(|ns| ns / 1_000_000);
+            let key = row
+                .pointer("/data/id")
+                .and_then(Value::as_str)
+                .map(str::to_string)
+                .unwrap_or_else(|| row.to_string());
+            if self.seen.contains_key(&key) || ts_ms.is_some_and(|ts| ts < self.floor_ms) {
+                continue;
+            }
+            self.seen.insert(key, ts_ms.unwrap_or(end));
+            newest = newest.max(ts_ms);
+            fresh.push(row);
+        }
+        // With no new timestamped rows, advance to the window end.
+        self.cursor_ms = self.cursor_ms.max(newest.unwrap_or(end));
+        let horizon = self.cursor_ms.saturating_sub(2 * OVERLAP_MS);
+        self.seen.retain(|_, ts| *ts >= horizon);
+        fresh
+    }
+}
+
+/// Row timestamp in nanoseconds (`data.timestamp`, else the row's own).
+fn row_timestamp_ns(row: &Value) -> Option<u64> {
+    [row.pointer("/data/timestamp"), row.get("timestamp")]
+        .into_iter()
+        .flatten()
+        .find_map(|v| match v {
+            Value::Number(n) => n.as_u64(),
+            Value::String(s) => s.parse().ok(),
+            _ => None,
+        })
+}
@@ -0,0 +1,185 @@
+use anyhow::{anyhow, Context, Result};
+use clap::{Arg, ArgAction};
+use serde_json::Value;
+use std::io::{self, IsTerminal};
+
+use crate::command_tree::{Operation, Resource};
+use crate::error::Failure;
+use crate::http::Body;
+use crate::output::{self, OutputOptions};
+use crate::{bodyset, edit, expect, schema, watch, ApiContext};
+
+pub fn args() -> [Arg; 2] {
+    [
+        Arg::new("merge")
+            .long("merge")
+            .action(ArgAction::SetTrue)
+            .conflicts_with("edit")
+            .help("Merge --body/--set over the current resource, show the diff, and confirm before sending"),
+        Arg::new("yes")
+            .long("yes")
+            .short('y')
+            .action(ArgAction::SetTrue)
+            .requires("merge")
+            .help("Send the --merge result without asking"),
+    ]
+}
+
+pub fn requested(matches: &clap::ArgMatches) -> bool {
+    matches!(matches.try_get_one::<bool>("merge"), Ok(Some(true)))
+}
+
+/// Fetches the resource, merges the partial body over it, and sends the
+/// whole document once the diff is confirmed, so fields the partial body
+/// leaves out keep their values instead of being wiped by the replace.
+pub fn run(
+    resource: &Resource,
+    op: &Operation,
+    matches: &clap::ArgMatches,
+    ctx: &ApiContext,
+    opts: &OutputOptions,
+) -> Result<()> {
+    // Not checked against the schema: a partial body lacks required fields
+    // by design; the merged document is checked instead.
+    let body = match matches.get_one::<String>("body") {
+        Some(raw) => {
+            Some(serde_json::from_str(&crate::read_body_input(raw)?).context("invalid JSON body")?)
+        }
+        None if bodyset::present(matches) => None,
+        None => return Err(Failure::Usage("--merge needs --body or --set".into()).into()),
+    };
+    let patch = bodyset::apply(body, matches)?;
+    if !patch.is_object() {
+        return Err(Failure::Usage("--merge needs a JSON object body".into()).into());
+    }
+    let missing = format!(
+        "--merge: {} has no GET on {} to fetch the current resource from",
+        resource.name, op.path
+    );
+    update(resource, op, matches, ctx, opts, &missing, |doc| {
+        merge(doc, patch);
+        Ok(())
+    })
+}
+
+/// Fetches the resource `op` updates (see [`edit::current`]), changes it
+/// with `change`, and sends the whole document once it passes the schema.
+/// Nothing is sent when the change leaves it as it was. With `--merge`
+/// the changes are shown and confirmed first; `missing` is the error for
+/// a resource without a GET to fetch it from.
+pub fn update(
+    resource: &Resource,
+    op: &Operation,
+    matches: &clap::ArgMatches,
+    ctx: &ApiContext,
+    opts: &OutputOptions,
+    missing: &str,
+    change: impl FnOnce(&mut Value) -> Result<()>,
+) -> Result<()> {
+    let (path, query, header_params) = crate::build_request_parts(ctx, op, matches)?;
+    let current = edit::current(resource, op, ctx, &path)?
+        .ok_or_else(|| Failure::Usage(missing.to_string()))?;
+    let mut updated = current.clone();
+    change(&mut updated)?;
+
+    let mut changes = Vec::new();
+    watch::diff(&current, &updated, &mut String::new(), &mut changes);
+    if changes.is_empty() {
+        eprintln!("No changes; request not sent.");
+        return Ok(());
+    }
+    if requested(matches) {
+        print_changes(&changes, opts.color);
+    }
+    if let Some(body_def) = op
+        .request_body
+        .as_ref()
+        .filter(|_| !schema::skipped(matches))
+    {
+        schema::check(body_def, &updated)?;
+    }
+    if requested(matches) && !matches.get_flag("yes") {
+        confirm("Send this update?")?;
+    }
+
+    let content_type = op
+        .request_body
+        .as_ref()
+        .map_or("application/json", |b| b.content_type.as_str());
+    let response = crate::execute_op(
+        ctx,
+        op,
+        &header_params,
+        &path,
+        &query,
+        Some(Body::Json(updated)),
+        Some(content_type),
+    )?;
+    crate::ensure_api_response(&path, &response)?;
+    output::print_response(&response, opts)?;
+    expect::check(&response, opts)
+}
+
+/// Deep merge in the manner of JSON Merge Patch (RFC 7396): objects merge
+/// key by key, `null` removes a key, and anything else (arrays included)
+/// replaces what was there.
+pub fn merge(target: &mut Value, patch: Value) {
+    let Value::Object(patch) = patch else {
+        *target = patch;
+        return;
+    };
+    if !target.is_object() {
+        *target = Value::Object(Default::default());
+    }
+    if let Value::Object(map) = target {
+        for (key, value) in patch {
+            if value.is_null() {
+                map.remove(&key);
+            } else {
+                merge(map.entry(key).or_insert(Value::Null), value);
+            }
+        }
+    }
+}
+
+/// `{path, from, to}` changes as `~`/`+`/`-` lines on stderr, colored like
+/// a diff.
+fn print_changes(changes: &[Value], color: bool) {
+    let paint = |style: &str, text: String| {
+        if color {
+            format!("\x1b[{style}m{text}\x1b[0m")
+        } else {
+            text
+        }
+    };
+    for change in changes {
+        let path = change["path"].as_str().unwrap_or_default();
+        let line = match (change.get("from"), change.get("to")) {
+            (Some(from), Some(to)) => paint("33", format!("~ {path}: {from} -> {to}")),
+            (None, Some(to)) => paint("32", format!("+ {path}: {to}")),
+            (Some(from), None) => paint("31", format!("- {path}: {from}")),
+            (None, None) => continue,
+        };
+        eprintln!("{line}");
+    }
+}
+
+/// Asks `question` on the terminal; anything but yes cancels. Without a
+/// terminal to ask on, `--yes` is required.
+pub fn confirm(question: &str) -> Result<()> {
+    if !io::stdin().is_terminal() {
+        return Err(
+            Failure::Usage("refusing to go ahead without a terminal; pass --yes".into()).into(),
+        );
+    }
+    eprint!("{question} [y/N] ");
+    let mut answer = String::new();
+    io::stdin()
+        .read_line(&mut answer)
+        .context("read confirmation")?;
+    if matches!(answer.trim().to_ascii_lowercase().as_str(), "y" | "yes") {
+        Ok(())
+    } else {
+        Err(anyhow!("cancelled; nothing sent"))
+    }
+}
@@ -0,0 +1,162 @@
+use anyhow::{anyhow, Context, Result};
+use clap::{Arg, ArgAction, Command};
+use serde_json::{json, Map, Value};
+
+use crate::error::ApiError;
+use crate::output::{self, OutputOptions};
+use crate::query;
+use crate::timeparse;
+use crate::ApiContext;
+
+pub fn extend(cmd: Command) -> Command {
+    cmd.subcommand(
+        Command::new("query")
+            .about("Query a metric as a timeseries with simple flags")
+            .arg(
+                Arg::new("metric")
+                    .long("metric")
+                    .value_name("NAME")
+                    .required(true)
+                    .help("Metric name, e.g. http_server_duration"),
+            )
+            .arg(
+                Arg::new("agg")
+                    .long("agg")
+                    .value_name("AGG")
+                    .value_parser([
+                        "sum", "avg", "min", "max", "count", "rate", "increase", "p50", "p75",
+                        "p90", "p95", "p99",
+                    ])
+                    .default_value("avg")
+                    .help("Aggregation (percentiles expect a histogram metric)"),
+            )
+            .arg(
+                Arg::new("group-by")
+                    .long("group-by")
+                    .value_name("KEY")
+                    .action(ArgAction::Append)
+                    .help("Attribute to group series by (repeatable)"),
+            )
+            .arg(
+                Arg::new("filter")
+                    .long("filter")
+                    .value_name("EXPR")
+                    .help("Filter expression, e.g. \"service.name = 'checkout'\""),
+            )
+            .arg(
+                Arg::new("since")
+                    .long("since")
+                    .value_name("DURATION|TIME")
+                    .help(
+                        "How far back (15m, 1h; default 1h) or a start time (RFC 3339, epoch ms)",
+                    ),
+            )
+            .arg(timeparse::until_arg())
+            .arg(
+                Arg::new("step")
+                    .long("step")
+                    .value_name("DURATION")
+                    .default_value("60s")
+                    .help("Interval between points"),
+            )
+            .arg(
+                Arg::new("print-query")
+                    .long("print-query")
+                    .action(ArgAction::SetTrue)
+                    .help("Print the generated query_range body instead of running it"),
+            ),
+    )
+}
+
+pub fn handle(
+    op: &str,
+    matches: &clap::ArgMatches,
+    ctx: &ApiContext,
+    opts: &OutputOptions,
+) -> Option<Result<()>> {
+    match op {
+        "query" => Some(handle_query(matches, ctx, opts)),
+        _ => None,
+    }
+}
+
+fn handle_query(matches: &clap::ArgMatches, ctx: &ApiContext, opts: &OutputOptions) -> Result<()> {
+    let arg = |name: &str| matches.get_one::<String>(name).map(String::as_str);
+    let metric = arg("metric").ok_or_else(|| anyhow!("--metric is required"))?;
+    let (start, end) = timeparse::range(
+        ctx.since("metrics", matches, "1h"),
+        timeparse::until(matches),
+    )?;
+    let step = timeparse::parse_duration_ms(arg("step").unwrap_or("60s"))? / 1000;
+    let group_by: Vec<Value> = matches
+        .get_many::<String>("group-by")
+        .into_iter()
+        .flatten()
+        .map(|key| json!({ "name": key }))
+        .collect();
+
+    let spec = json!({
+        "aggregations": [aggregation(metric, arg("agg").unwrap_or("avg"))],
+        "groupBy": group_by,
+        "filter": { "expression": arg("filter").unwrap_or_default() },
+        "stepInterval": step.max(1),
+    });
+    let body = query::query_range_body(
+        start,
+        end,
+        "time_series",
+        vec![query::builder_query("A", "metrics", spec)],
+    );
+    if let Some(result) = query::dry_run(matches, &body, opts) {
+        return result;
+    }
+    let response = query::run_query_range(ctx, body)?;
+    if response.status >= 400 {
+        return Err(ApiError::from_response(&response)).context("metrics query failed");
+    }
+    output::print_value(&Value::Array(series(&response.body)), opts)
+}
+
+/// Maps `--agg` onto v5 time/space aggregations: percentiles aggregate
+/// histogram buckets across series, rate/increase sum per-series deltas, and
+/// the rest apply the same function over time and across series.
+pub fn aggregation(metric: &str, agg: &str) -> Value {
+    match agg {
+        p if p.starts_with('p') => json!({ "metricName": metric, "spaceAggregation": p }),
+        "rate" | "increase" => json!({
+            "metricName": metric,
+            "timeAggregation": agg,
+            "spaceAggregation": "sum",
+        }),
+        "count" => json!({
+            "metricName": metric,
+            "timeAggregation": "count",
+            "spaceAggregation": "sum",
+        }),
+        _ => json!({ "metricName": metric, "timeAggregation": agg, "spaceAggregation": agg }),
+    }
+}
+
+/// Flattens a time_series result into `{labels: {key: value}, values:
+/// [[timestamp, value], ...]}` entries.
+pub fn series(body: &Value) -> Vec<Value> {
+    let list = |v: Option<&Value>| v.and_then(Value::as_array).cloned().unwrap_or_default();
+    list(body.pointer("/data/data/results/0/aggregations"))
+        .iter()
+        .flat_map(|agg| list(agg.get("series")))
+        .map(|series| {
+            let labels: Map<String, Value> = list(series.get("labels"))
+                .iter()
+                .filter_map(|label| {
+                    let key = label.pointer("/key/name")?.as_str()?;
+                    Some((key.to_string(), label.get("value")?.clone()))
+                })
+                .collect();
+            let values: Vec<Value> = list(series.get("values"))
+                .iter()
+                .map(|point| json!([point.get("timestamp"), point.get("value")]))
+                .collect();
+            json!({ "labels": labels, "values": values })
+        })
+        .collect()
+}
@@ -0,0 +1,72 @@
+use serde_json::Value;
+
+use crate::config::ExportConfig;
+
+/// Removes fields that change on every save (timestamps, authors, server ids)
+/// so exported resources can be committed to git without churn.
+#[derive(Clone, Debug, Default)]
+pub struct Normalizer {
+    strip: Vec<String>,
+    id_fields: Vec<String>,
+}
+
+impl Normalizer {
+    pub fn new(cfg: &ExportConfig, keep_ids: bool) -> Self {
+        Self {
+            strip: cfg.strip.clone(),
+            id_fields: if keep_ids {
+                Vec::new()
+            } else {
+                cfg.id_fields.clone()
+            },
+        }
+    }
+
+    /// Normalizes an API response: unwraps the `data` envelope and treats each
+    /// array element (or the lone object) as a resource.
+    pub fn apply(&self, value: &mut Value) {
+        match value {
+            Value::Object(map) if map.contains_key("data") && map.contains_key("status") => {
+                if let Some(data) = map.get_mut("data") {
+                    self.apply(data);
+                }
+            }
+            Value::Array(items) => {
+                for item in items {
+                    self.apply_resource(item);
+                }
+            }
+            _ => self.apply_resource(value),
+        }
+    }
+
+    /// Volatile fields are stripped at any depth; id fields only at the root,
+    /// since nested ids (widgets, layouts) are references that must survive.
+    pub fn apply_resource(&self, value: &mut Value) {
+        if let Value::Object(map) = value {
+            for field in &self.id_fields {
+                map.remove(field);
+            }
+        }
+        self.strip_volatile(value);
+    }
+
+    fn strip_volatile(&self, value: &mut Value) {
+        match value {
+            Value::Object(map) => {
+                for field in &self.strip {
+                    map.remove(field);
+                }
+                for child in map.values_mut() {
+                    self.strip_volatile(child);
+                }
+            }
+            Value::Array(items) => {
+                for item in items {
+                    self.strip_volatile(item);
+                }
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,179 @@
+use anyhow::{anyhow, Context, Result};
+use clap::{Arg, Command};
+use serde_json::{json, Value};
+
+use crate::error::ApiError;
+use crate::http::Body;
+use crate::output::{self, OutputOptions};
+use crate::ApiContext;
+
+const ORG_PATH: &str = "/api/v2/orgs/me";
+const PREFERENCES_PATH: &str = "/api/v1/org/preferences";
+/// Pseudo-setting for the organization's display name, which lives on the
+/// org itself rather than in its preferences.
+const DISPLAY_NAME: &str = "display_name";
+
+pub fn extend(cmd: Command) -> Command {
+    cmd.visible_alias("org").subcommand(
+        Command::new("settings")
+            .about("Read and change organization settings (display name and org preferences)")
+            .subcommand_required(true)
+            .subcommand(
+                Command::new("get")
+                    .about("Show all settings, or one by name")
+                    .arg(Arg::new("name").help("Setting name")),
+            )
+            .subcommand(
+                Command::new("set")
+                    .about("Change a setting; the value is checked against its type and allowed values")
+                    .arg(Arg::new("name").required(true).help("Setting name"))
+                    .arg(Arg::new("value").required(true).help("New value")),
+            ),
+    )
+}
+
+pub fn handle(
+    op: &str,
+    matches: &clap::ArgMatches,
+    ctx: &ApiContext,
+    opts: &OutputOptions,
+) -> Option<Result<()>> {
+    match (op, matches.subcommand()) {
+        ("settings", Some(("get", sub))) => Some(handle_get(sub, ctx, opts)),
+        ("settings", Some(("set", sub))) => Some(handle_set(sub, ctx, opts)),
+        _ => None,
+    }
+}
+
+fn handle_get(matches: &clap::ArgMatches, ctx: &ApiContext, opts: &OutputOptions) -> Result<()> {
+    let mut settings = vec![json!({
+        "name": DISPLAY_NAME,
+        "value": org(ctx)?.get("displayName"),
+        "valueType": "string",
+    })];
+    settings.extend(preferences(ctx)?.into_iter().map(|p| {
+        json!({
+            "name": p.get("name"),
+            "value": p.get("value"),
+            "defaultValue": p.get("defaultValue"),
+            "valueType": p.get("valueType"),
+            "allowedValues": p.get("allowedValues"),
+            "description": p.get("description"),
+        })
+    }));
+    match matches.get_one::<String>("name") {
+        Some(name) => {
+            let setting = settings
+                .into_iter()
+                .find(|s| s["name"] == json!(name))
+                .ok_or_else(|| anyhow!("unknown setting {name} (see `signoz org settings get`)"))?;
+            output::print_value(&setting, opts)
+        }
+        None => output::print_value(&Value::Array(settings), opts),
+    }
+}
+
+fn handle_set(matches: &clap::ArgMatches, ctx: &ApiContext, opts: &OutputOptions) -> Result<()> {
+    let arg = |name: &str| {
+        matches
+            .get_one::<String>(name)
+            .map(String::as_str)
+            .unwrap_or_default()
+    };
+    let (name, raw) = (arg("name"), arg("value"));
+
+    if name == DISPLAY_NAME {
+        let mut org = org(ctx)?;
+        let before = org.get("displayName").cloned().unwrap_or(Value::Null);
+        org["displayName"] = json!(raw);
+        let response = ctx.execute(
+            "PUT",
+            ORG_PATH,
+            &[],
+            Some(Body::Json(org)),
+            Some("application/json"),
+        )?;
+        check(&response, "update organization")?;
+        return output::print_value(&json!({ "name": name, "from": before, "to": raw }), opts);
+    }
+
+    let preference = preferences(ctx)?
+        .into_iter()
+        .find(|p| p.get("name").and_then(Value::as_str) == Some(name))
+        .ok_or_else(|| anyhow!("unknown setting {name} (see `signoz org settings get`)"))?;
+    let value = typed_value(&preference, raw)?;
+    let path = format!("{PREFERENCES_PATH}/{}", urlencoding::encode(name));
+    let response = ctx.execute(
+        "PUT",
+        &path,
+        &[],
+        Some(Body::Json(json!({ "value": value }))),
+        Some("application/json"),
+    )?;
+    check(&response, &format!("update {name}"))?;
+    output::print_value(
+        &json!({ "name": name, "from": preference.get("value"), "to": value }),
+        opts,
+    )
+}
+
+/// `raw` converted to the preference's `valueType` and checked against its
+/// `allowedValues`.
+fn typed_value(preference: &Value, raw: &str) -> Result<Value> {
+    let name = preference["name"].as_str().unwrap_or_default();
+    let value = match preference.get("valueType").and_then(Value::as_str) {
+        Some("boolean") => match raw.to_lowercase().as_str() {
+            "true" | "on" | "yes" | "1" => json!(true),
+            "false" | "off" | "no" | "0" => json!(false),
+            _ => return Err(anyhow!("{name} takes true or false, not {raw}")),
+        },
+        Some("integer") => json!(raw
+            .parse::<i64>()
+            .map_err(|_| anyhow!("{name} takes an integer, not {raw}"))?),
+        Some("float") => json!(raw
+            .parse::<f64>()
+            .map_err(|_| anyhow!("{name} takes a number, not {raw}"))?),
+        _ => json!(raw),
+    };
+    let allowed: Vec<&str> = preference
+        .get("allowedValues")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+        .collect();
+    let text = value
+        .as_str()
+        .map_or_else(|| value.to_string(), str::to_string);
+    if !allowed.is_empty() && !allowed.contains(&text.as_str()) {
+        return Err(anyhow!(
+            "{name} must be one of {}, not {raw}",
+            allowed.join(", ")
+        ));
+    }
+    Ok(value)
+}
+
+fn org(ctx: &ApiContext) -> Result<Value> {
+    let response = ctx.execute("GET", ORG_PATH, &[], None, None)?;
+    check(&response, "get organization")?;
+    Ok(response.body.get("data").unwrap_or(&response.body).clone())
+}
+
+fn preferences(ctx: &ApiContext) -> Result<Vec<Value>> {
+    let response = ctx.execute("GET", PREFERENCES_PATH, &[], None, None)?;
+    check(&response, "list org preferences")?;
+    Ok(response
+        .body
+        .get("data")
+        .and_then(Value::as_array)
+        .cloned()
+        .unwrap_or_default())
+}
+
+fn check(response: &crate::http::HttpResponse, action: &str) -> Result<()> {
+    if response.status >= 400 {
+        return Err(ApiError::from_response(response)).with_context(|| format!("{action} failed"));
+    }
+    Ok(())
+}
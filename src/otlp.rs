@@ -0,0 +1,1079 @@
+use anyhow::{anyhow, Context, Result};
+use clap::{Arg, ArgAction, Command};
+use flate2::read::GzDecoder;
+use ring::rand::{SecureRandom, SystemRandom};
+use serde_json::{json, Map, Value};
+use std::{
+    env,
+    fmt::Write as _,
+    io::Read,
+    net::TcpListener,
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::error::ApiError;
+use crate::http::{Body, HttpClient};
+use crate::httpd::{self, Request, MAX_BODY};
+use crate::output::{self, OutputOptions};
+use crate::{metrics, query, render, timeparse, ApiContext};
+
+pub fn command() -> Command {
+    Command::new("ingest")
+        .visible_alias("otlp")
+        .about("Telemetry tools: a local OTLP receiver and an ingestion smoke test")
+        .subcommand_required(true)
+        .arg_required_else_help(true)
+        .subcommand(
+            Command::new("listen")
+                .about(
+                    "Run a local OTLP/HTTP receiver that prints incoming spans, logs, and metrics",
+                )
+                .arg(
+                    Arg::new("port")
+                        .long("port")
+                        .value_name("PORT")
+                        .default_value("4318")
+                        .value_parser(clap::value_parser!(u16)),
+                )
+                .arg(
+                    Arg::new("host")
+                        .long("host")
+                        .value_name("ADDR")
+                        .default_value("127.0.0.1")
+                        .help("Address to bind (0.0.0.0 to accept from containers)"),
+                )
+                .arg(
+                    Arg::new("json")
+                        .long("json")
+                        .action(ArgAction::SetTrue)
+                        .help("Print each export request as one line of OTLP/JSON"),
+                ),
+        )
+        .subcommand(
+            Command::new("send")
+                .about("Send a synthetic span, log, or metric to a collector and wait for it to be queryable")
+                .arg(
+                    Arg::new("kind")
+                        .long("kind")
+                        .value_name("KIND")
+                        .value_parser(["span", "log", "metric"])
+                        .default_value("span"),
+                )
+                .arg(
+                    Arg::new("service")
+                        .long("service")
+                        .value_name("NAME")
+                        .default_value("signoz-cli-probe")
+                        .help("service.name of the synthetic resource"),
+                )
+                .arg(
+                    Arg::new("endpoint")
+                        .long("endpoint")
+                        .value_name("URL")
+                        .help("OTLP/HTTP collector (default: OTEL_EXPORTER_OTLP_ENDPOINT, else http://localhost:4318)"),
+                )
+                .arg(
+                    Arg::new("ingestion-key")
+                        .long("ingestion-key")
+                        .value_name("KEY")
+                        .help("Sent as signoz-ingestion-key, for SigNoz Cloud (SIGNOZ_INGESTION_KEY)"),
+                )
+                .arg(
+                    Arg::new("wait")
+                        .long("wait")
+                        .value_name("DURATION")
+                        .default_value("2m")
+                        .help("How long to poll the query API before giving up"),
+                )
+                .arg(
+                    Arg::new("interval")
+                        .long("interval")
+                        .value_name("DURATION")
+                        .default_value("2s")
+                        .help("Time between polls"),
+                )
+                .arg(
+                    Arg::new("no-verify")
+                        .long("no-verify")
+                        .action(ArgAction::SetTrue)
+                        .help("Only send; do not poll for the data"),
+                ),
+        )
+}
+
+#[derive(Clone, Copy)]
+struct Listen {
+    json: bool,
+    color: bool,
+}
+
+/// Subcommands that need no SigNoz credentials, handled by [`handle`];
+/// the rest go to [`handle_send`].
+pub fn is_local(matches: &clap::ArgMatches) -> bool {
+    matches.subcommand_name() == Some("listen")
+}
+
+pub fn handle(matches: &clap::ArgMatches, opts: &OutputOptions) -> Result<()> {
+    let Some(("listen", matches)) = matches.subcommand() else {
+        return Err(anyhow!("unknown ingest command"));
+    };
+    let host = matches
+        .get_one::<String>("host")
+        .map_or("127.0.0.1", String::as_str);
+    let port = matches.get_one::<u16>("port").copied().unwrap_or(4318);
+    let listener = TcpListener::bind((host, port))
+        .with_context(|| format!("bind {host}:{port} (is a collector already listening?)"))?;
+    let listen = Listen {
+        json: matches.get_flag("json"),
+        color: opts.color,
+    };
+    eprintln!(
+        "Listening for OTLP/HTTP on http://{} (/v1/traces, /v1/logs, /v1/metrics); Ctrl-C to stop.",
+        listener.local_addr()?
+    );
+    eprintln!("  export OTEL_EXPORTER_OTLP_ENDPOINT=http://localhost:{port} OTEL_EXPORTER_OTLP_PROTOCOL=http/protobuf");
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                thread::spawn(move || {
+                    httpd::serve(stream, |request, peer| {
+                        handle_request(request, peer, listen)
+                    })
+                });
+            }
+            Err(err) => eprintln!("accept: {err}"),
+        }
+    }
+    Ok(())
+}
+
+fn handle_request(request: &Request, peer: &str, listen: Listen) -> httpd::Reply {
+    let Some(signal) = SIGNALS.iter().find(|s| s.path == request.path) else {
+        return (404, "text/plain", b"unknown OTLP path".to_vec());
+    };
+    if request.method != "POST" {
+        return (405, "text/plain", b"use POST".to_vec());
+    }
+    let json_body = request
+        .header("content-type")
+        .is_some_and(|v| v.contains("json"));
+    let decoded = decompress(request).and_then(|raw| {
+        if json_body {
+            serde_json::from_slice::<Value>(&raw).context("invalid OTLP/JSON")
+        } else {
+            decode(&raw, signal.schema).context("invalid OTLP protobuf")
+        }
+    });
+    let payload = match decoded {
+        Ok(payload) => payload,
+        Err(err) => {
+            eprintln!("{peer}: POST {}: {err:#}", request.path);
+            return (400, "text/plain", format!("{err:#}").into_bytes());
+        }
+    };
+
+    let encoding = request
+        .header("content-encoding")
+        .map(|e| format!(", {e}"))
+        .unwrap_or_default();
+    let format = if json_body { "json" } else { "protobuf" };
+    if listen.json {
+        println!(
+            "{}",
+            json!({ "signal": signal.name, "from": peer, "format": format, "request": payload })
+        );
+    } else {
+        let lines = signal.lines(&payload, listen.color);
+        eprintln!(
+            "{peer}: POST {} ({format}{encoding}): {} {}",
+            request.path,
+            signal.count(&payload),
+            signal.unit
+        );
+        if !lines.is_empty() {
+            println!("{}", lines.join("\n"));
+        }
+    }
+    if json_body {
+        (200, "application/json", b"{}".to_vec())
+    } else {
+        // An empty Export*ServiceResponse: full success.
+        (200, "application/x-protobuf", Vec::new())
+    }
+}
+
+fn decompress(request: &Request) -> Result<Vec<u8>> {
+    match request.header("content-encoding") {
+        None | Some("identity") => Ok(request.body.clone()),
+        Some("gzip") => {
+            let mut out = Vec::new();
+            GzDecoder::new(request.body.as_slice())
+                .take(MAX_BODY as u64 + 1)
+                .read_to_end(&mut out)
+                .context("gunzip body")?;
+            if out.len() > MAX_BODY {
+                return Err(anyhow!("request body over {MAX_BODY} bytes"));
+            }
+            Ok(out)
+        }
+        Some(other) => Err(anyhow!("unsupported Content-Encoding {other}")),
+    }
+}
+
+// Protobuf decoding. Each OTLP message is described by a field table and
+// decoded into the shape of its OTLP/JSON encoding (camelCase names, hex
+// ids), so both wire formats share one printer.
+
+#[derive(Clone, Copy)]
+enum Kind {
+    Str,
+    Hex,
+    Uint,
+    Int,
+    Sint,
+    Bool,
+    Fixed64,
+    Sfixed64,
+    Double,
+    Packed64,
+    PackedDouble,
+    Msg(&'static [Field]),
+}
+
+struct Field {
+    number: u64,
+    name: &'static str,
+    kind: Kind,
+    repeated: bool,
+}
+
+const fn one(number: u64, name: &'static str, kind: Kind) -> Field {
+    Field {
+        number,
+        name,
+        kind,
+        repeated: false,
+    }
+}
+
+const fn many(number: u64, name: &'static str, kind: Kind) -> Field {
+    Field {
+        number,
+        name,
+        kind,
+        repeated: true,
+    }
+}
+
+static ANY_VALUE: [Field; 7] = [
+    one(1, "stringValue", Kind::Str),
+    one(2, "boolValue", Kind::Bool),
+    one(3, "intValue", Kind::Int),
+    one(4, "doubleValue", Kind::Double),
+    one(5, "arrayValue", Kind::Msg(&ARRAY_VALUE)),
+    one(6, "kvlistValue", Kind::Msg(&KEY_VALUE_LIST)),
+    one(7, "bytesValue", Kind::Hex),
+];
+static ARRAY_VALUE: [Field; 1] = [many(1, "values", Kind::Msg(&ANY_VALUE))];
+static KEY_VALUE_LIST: [Field; 1] = [many(1, "values", Kind::Msg(&KEY_VALUE))];
+static KEY_VALUE: [Field; 2] = [
+    one(1, "key", Kind::Str),
+    one(2, "value", Kind::Msg(&ANY_VALUE)),
+];
+const ATTRIBUTES: Kind = Kind::Msg(&KEY_VALUE);
+static RESOURCE: [Field; 1] = [many(1, "attributes", ATTRIBUTES)];
+static SCOPE: [Field; 3] = [
+    one(1, "name", Kind::Str),
+    one(2, "version", Kind::Str),
+    many(3, "attributes", ATTRIBUTES),
+];
+
+static TRACES_REQUEST: [Field; 1] = [many(1, "resourceSpans", Kind::Msg(&RESOURCE_SPANS))];
+static RESOURCE_SPANS: [Field; 2] = [
+    one(1, "resource", Kind::Msg(&RESOURCE)),
+    many(2, "scopeSpans", Kind::Msg(&SCOPE_SPANS)),
+];
+static SCOPE_SPANS: [Field; 2] = [
+    one(1, "scope", Kind::Msg(&SCOPE)),
+    many(2, "spans", Kind::Msg(&SPAN)),
+];
+static SPAN: [Field; 12] = [
+    one(1, "traceId", Kind::Hex),
+    one(2, "spanId", Kind::Hex),
+    one(3, "traceState", Kind::Str),
+    one(4, "parentSpanId", Kind::Hex),
+    one(5, "name", Kind::Str),
+    one(6, "kind", Kind::Uint),
+    one(7, "startTimeUnixNano", Kind::Fixed64),
+    one(8, "endTimeUnixNano", Kind::Fixed64),
+    many(9, "attributes", ATTRIBUTES),
+    many(11, "events", Kind::Msg(&EVENT)),
+    many(13, "links", Kind::Msg(&LINK)),
+    one(15, "status", Kind::Msg(&STATUS)),
+];
+static EVENT: [Field; 3] = [
+    one(1, "timeUnixNano", Kind::Fixed64),
+    one(2, "name", Kind::Str),
+    many(3, "attributes", ATTRIBUTES),
+];
+static LINK: [Field; 3] = [
+    one(1, "traceId", Kind::Hex),
+    one(2, "spanId", Kind::Hex),
+    many(4, "attributes", ATTRIBUTES),
+];
+static STATUS: [Field; 2] = [one(2, "message", Kind::Str), one(3, "code", Kind::Uint)];
+
+static LOGS_REQUEST: [Field; 1] = [many(1, "resourceLogs", Kind::Msg(&RESOURCE_LOGS))];
+static RESOURCE_LOGS: [Field; 2] = [
+    one(1, "resource", Kind::Msg(&RESOURCE)),
+    many(2, "scopeLogs", Kind::Msg(&SCOPE_LOGS)),
+];
+static SCOPE_LOGS: [Field; 2] = [
+    one(1, "scope", Kind::Msg(&SCOPE)),
+    many(2, "logRecords", Kind::Msg(&LOG_RECORD)),
+];
+static LOG_RECORD: [Field; 9] = [
+    one(1, "timeUnixNano", Kind::Fixed64),
+    one(11, "observedTimeUnixNano", Kind::Fixed64),
+    one(2, "severityNumber", Kind::Uint),
+    one(3, "severityText", Kind::Str),
+    one(5, "body", Kind::Msg(&ANY_VALUE)),
+    many(6, "attributes", ATTRIBUTES),
+    one(9, "traceId", Kind::Hex),
+    one(10, "spanId", Kind::Hex),
+    one(12, "eventName", Kind::Str),
+];
+
+static METRICS_REQUEST: [Field; 1] = [many(1, "resourceMetrics", Kind::Msg(&RESOURCE_METRICS))];
+static RESOURCE_METRICS: [Field; 2] = [
+    one(1, "resource", Kind::Msg(&RESOURCE)),
+    many(2, "scopeMetrics", Kind::Msg(&SCOPE_METRICS)),
+];
+static SCOPE_METRICS: [Field; 2] = [
+    one(1, "scope", Kind::Msg(&SCOPE)),
+    many(2, "metrics", Kind::Msg(&METRIC)),
+];
+static METRIC: [Field; 8] = [
+    one(1, "name", Kind::Str),
+    one(2, "description", Kind::Str),
+    one(3, "unit", Kind::Str),
+    one(5, "gauge", Kind::Msg(&GAUGE)),
+    one(7, "sum", Kind::Msg(&SUM)),
+    one(9, "histogram", Kind::Msg(&HISTOGRAM)),
+    one(10, "exponentialHistogram", Kind::Msg(&EXP_HISTOGRAM)),
+    one(11, "summary", Kind::Msg(&SUMMARY)),
+];
+static GAUGE: [Field; 1] = [many(1, "dataPoints", Kind::Msg(&NUMBER_POINT))];
+static SUM: [Field; 3] = [
+    many(1, "dataPoints", Kind::Msg(&NUMBER_POINT)),
+    one(2, "aggregationTemporality", Kind::Uint),
+    one(3, "isMonotonic", Kind::Bool),
+];
+static HISTOGRAM: [Field; 2] = [
+    many(1, "dataPoints", Kind::Msg(&HISTOGRAM_POINT)),
+    one(2, "aggregationTemporality", Kind::Uint),
+];
+static EXP_HISTOGRAM: [Field; 2] = [
+    many(1, "dataPoints", Kind::Msg(&EXP_HISTOGRAM_POINT)),
+    one(2, "aggregationTemporality", Kind::Uint),
+];
+static SUMMARY: [Field; 1] = [many(1, "dataPoints", Kind::Msg(&SUMMARY_POINT))];
+static NUMBER_POINT: [Field; 5] = [
+    many(7, "attributes", ATTRIBUTES),
+    one(2, "startTimeUnixNano", Kind::Fixed64),
+    one(3, "timeUnixNano", Kind::Fixed64),
+    one(4, "asDouble", Kind::Double),
+    one(6, "asInt", Kind::Sfixed64),
+];
+static HISTOGRAM_POINT: [Field; 9] = [
+    many(9, "attributes", ATTRIBUTES),
+    one(2, "startTimeUnixNano", Kind::Fixed64),
+    one(3, "timeUnixNano", Kind::Fixed64),
+    one(4, "count", Kind::Fixed64),
+    one(5, "sum", Kind::Double),
+    many(6, "bucketCounts", Kind::Packed64),
+    many(7, "explicitBounds", Kind::PackedDouble),
+    one(11, "min", Kind::Double),
+    one(12, "max", Kind::Double),
+];
+static EXP_HISTOGRAM_POINT: [Field; 9] = [
+    many(1, "attributes", ATTRIBUTES),
+    one(2, "startTimeUnixNano", Kind::Fixed64),
+    one(3, "timeUnixNano", Kind::Fixed64),
+    one(4, "count", Kind::Fixed64),
+    one(5, "sum", Kind::Double),
+    one(6, "scale", Kind::Sint),
+    one(7, "zeroCount", Kind::Fixed64),
+    one(12, "min", Kind::Double),
+    one(13, "max", Kind::Double),
+];
+static SUMMARY_POINT: [Field; 6] = [
+    many(7, "attributes", ATTRIBUTES),
+    one(2, "startTimeUnixNano", Kind::Fixed64),
+    one(3, "timeUnixNano", Kind::Fixed64),
+    one(4, "count", Kind::Fixed64),
+    one(5, "sum", Kind::Double),
+    many(6, "quantileValues", Kind::Msg(&QUANTILE)),
+];
+static QUANTILE: [Field; 2] = [
+    one(1, "quantile", Kind::Double),
+    one(2, "value", Kind::Double),
+];
+
+/// Unknown fields are skipped, as protobuf requires.
+fn decode(mut buf: &[u8], fields: &[Field]) -> Result<Value> {
+    let mut out = Map::new();
+    while !buf.is_empty() {
+        let key = varint(&mut buf)?;
+        let field = fields.iter().find(|f| f.number == key >> 3);
+        let value = match key & 7 {
+            0 => {
+                let raw = varint(&mut buf)?;
+                field.and_then(|f| match f.kind {
+                    Kind::Uint => Some(json!(raw)),
+                    Kind::Int => Some(json!(raw as i64)),
+                    Kind::Sint => Some(json!((raw >> 1) as i64 ^ -((raw & 1) as i64))),
+                    Kind::Bool => Some(json!(raw != 0)),
+                    _ => None,
+                })
+            }
+            1 => {
+                let raw = u64::from_le_bytes(take(&mut buf, 8)?.try_into()?);
+                field.and_then(|f| match f.kind {
+                    Kind::Fixed64 | Kind::Packed64 => Some(json!(raw)),
+                    Kind::Sfixed64 => Some(json!(raw as i64)),
+                    Kind::Double | Kind::PackedDouble => Some(json!(f64::from_bits(raw))),
+                    _ => None,
+                })
+            }
+            2 => {
+                let len = usize::try_from(varint(&mut buf)?)?;
+                let bytes = take(&mut buf, len)?;
+                match field.map(|f| f.kind) {
+                    Some(Kind::Str) => Some(json!(String::from_utf8_lossy(bytes))),
+                    Some(Kind::Hex) => Some(json!(hex(bytes))),
+                    Some(Kind::Msg(schema)) => Some(decode(bytes, schema)?),
+                    Some(Kind::Packed64) => Some(
+                        bytes
+                            .chunks_exact(8)
+                            .map(|c| json!(u64::from_le_bytes(c.try_into().unwrap_or_default())))
+                            .collect(),
+                    ),
+                    Some(Kind::PackedDouble) => Some(
+                        bytes
+                            .chunks_exact(8)
+                            .map(|c| json!(f64::from_le_bytes(c.try_into().unwrap_or_default())))
+                            .collect(),
+                    ),
+                    _ => None,
+                }
+            }
+            5 => {
+                take(&mut buf, 4)?;
+                None
+            }
+            wire => return Err(anyhow!("unsupported wire type {wire}")),
+        };
+        let (Some(field), Some(value)) = (field, value) else {
+            continue;
+        };
+        if !field.repeated {
+            out.insert(field.name.to_string(), value);
+            continue;
+        }
+        let items = out
+            .entry(field.name)
+            .or_insert_with(|| Value::Array(Vec::new()));
+        if let Value::Array(items) = items {
+            match value {
+                Value::Array(packed) => items.extend(packed),
+                value => items.push(value),
+            }
+        }
+    }
+    Ok(Value::Object(out))
+}
+
+fn varint(buf: &mut &[u8]) -> Result<u64> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let (&byte, rest) = buf
+            .split_first()
+            .ok_or_else(|| anyhow!("truncated varint"))?;
+        *buf = rest;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(anyhow!("varint longer than 10 bytes"))
+}
+
+fn take<'a>(buf: &mut &'a [u8], len: usize) -> Result<&'a [u8]> {
+    if buf.len() < len {
+        return Err(anyhow!(
+            "truncated field ({len} bytes wanted, {} left)",
+            buf.len()
+        ));
+    }
+    let (head, rest) = buf.split_at(len);
+    *buf = rest;
+    Ok(head)
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().fold(String::new(), |mut out, b| {
+        let _ = write!(out, "{b:02x}");
+        out
+    })
+}
+
+// Printing.
+
+struct Signal {
+    name: &'static str,
+    path: &'static str,
+    schema: &'static [Field],
+    resources: &'static str,
+    scopes: &'static str,
+    items: &'static str,
+    unit: &'static str,
+}
+
+const SIGNALS: [Signal; 3] = [
+    Signal {
+        name: "traces",
+        path: "/v1/traces",
+        schema: &TRACES_REQUEST,
+        resources: "resourceSpans",
+        scopes: "scopeSpans",
+        items: "spans",
+        unit: "spans",
+    },
+    Signal {
+        name: "logs",
+        path: "/v1/logs",
+        schema: &LOGS_REQUEST,
+        resources: "resourceLogs",
+        scopes: "scopeLogs",
+        items: "logRecords",
+        unit: "log records",
+    },
+    Signal {
+        name: "metrics",
+        path: "/v1/metrics",
+        schema: &METRICS_REQUEST,
+        resources: "resourceMetrics",
+        scopes: "scopeMetrics",
+        items: "metrics",
+        unit: "metrics",
+    },
+];
+
+impl Signal {
+    /// Every item with its resource's `service.name`.
+    fn items<'a>(&self, payload: &'a Value) -> Vec<(String, &'a Value)> {
+        let list = |value: &'a Value, key: &str| {
+            value
+                .get(key)
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+        };
+        let mut out = Vec::new();
+        for resource in list(payload, self.resources) {
+            let service = attributes(resource.pointer("/resource/attributes"))
+                .get("service.name")
+                .and_then(Value::as_str)
+                .unwrap_or("-")
+                .to_string();
+            for scope in list(resource, self.scopes) {
+                out.extend(list(scope, self.items).map(|item| (service.clone(), item)));
+            }
+        }
+        out
+    }
+
+    fn count(&self, payload: &Value) -> usize {
+        self.items(payload).len()
+    }
+
+    fn lines(&self, payload: &Value, color: bool) -> Vec<String> {
+        let paint = |style: &str, text: &str| {
+            if color {
+                format!("\x1b[{style}m{text}\x1b[0m")
+            } else {
+                text.to_string()
+            }
+        };
+        let mut out = Vec::new();
+        for (service, item) in self.items(payload) {
+            let service = paint(render::service_color(&service), &service);
+            match self.name {
+                "traces" => out.extend(span_lines(&service, item, &paint)),
+                "logs" => out.push(log_line(&service, item, &paint)),
+                _ => out.extend(metric_lines(&service, item)),
+            }
+        }
+        out
+    }
+}
+
+fn span_lines(service: &str, span: &Value, paint: &dyn Fn(&str, &str) -> String) -> Vec<String> {
+    let start = nanos(span.get("startTimeUnixNano"));
+    let duration = nanos(span.get("endTimeUnixNano")).saturating_sub(start);
+    let kind = match span.get("kind") {
+        Some(Value::String(kind)) => kind.trim_start_matches("SPAN_KIND_").to_lowercase(),
+        other => match nanos(other) {
+            1 => "internal",
+            2 => "server",
+            3 => "client",
+            4 => "producer",
+            5 => "consumer",
+            _ => "unspecified",
+        }
+        .to_string(),
+    };
+    let status = match span.pointer("/status/code") {
+        Some(Value::String(code)) => code.trim_start_matches("STATUS_CODE_").to_string(),
+        code => match nanos(code) {
+            1 => "OK".to_string(),
+            2 => "ERROR".to_string(),
+            _ => String::new(),
+        },
+    };
+    let status = match (status.as_str(), text(span.pointer("/status/message"))) {
+        ("ERROR", "") => paint("31", " ERROR"),
+        ("ERROR", message) => paint("31", &format!(" ERROR: {message}")),
+        ("", _) => String::new(),
+        (other, _) => format!(" {other}"),
+    };
+    let parent = match text(span.get("parentSpanId")) {
+        "" => String::new(),
+        parent => format!(" parent={parent}"),
+    };
+    let mut out = vec![format!(
+        "{} span   {service} {} ({kind}) {}{status} trace={} span={}{parent}{}",
+        time(start),
+        text(span.get("name")),
+        render::human_duration(duration as f64),
+        text(span.get("traceId")),
+        text(span.get("spanId")),
+        pairs(&attributes(span.get("attributes"))),
+    )];
+    for event in span
+        .get("events")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        out.push(format!(
+            "    event {}{}",
+            text(event.get("name")),
+            pairs(&attributes(event.get("attributes")))
+        ));
+    }
+    out
+}
+
+fn log_line(service: &str, record: &Value, paint: &dyn Fn(&str, &str) -> String) -> String {
+    let timestamp = match nanos(record.get("timeUnixNano")) {
+        0 => nanos(record.get("observedTimeUnixNano")),
+        ns => ns,
+    };
+    let level = match text(record.get("severityText")) {
+        "" => match nanos(record.get("severityNumber")) {
+            1..=4 => "TRACE",
+            5..=8 => "DEBUG",
+            9..=12 => "INFO",
+            13..=16 => "WARN",
+            17..=20 => "ERROR",
+            21.. => "FATAL",
+            0 => "-",
+        }
+        .to_string(),
+        level => level.to_uppercase(),
+    };
+    let body = match any_value(record.get("body")) {
+        Value::String(body) => body,
+        Value::Null => String::new(),
+        other => other.to_string(),
+    };
+    let trace = match text(record.get("traceId")) {
+        "" => String::new(),
+        trace => format!(" trace={trace}"),
+    };
+    format!(
+        "{} log    {service} {} {}{}{trace}",
+        time(timestamp),
+        paint(render::level_style(&level), &format!("{level:<5}")),
+        body.replace('\n', "\\n"),
+        pairs(&attributes(record.get("attributes"))),
+    )
+}
+
+/// One line per data point.
+fn metric_lines(service: &str, metric: &Value) -> Vec<String> {
+    let name = text(metric.get("name"));
+    let unit = match text(metric.get("unit")) {
+        "" | "1" => String::new(),
+        unit => format!(" {unit}"),
+    };
+    let Some((kind, data)) = [
+        "gauge",
+        "sum",
+        "histogram",
+        "exponentialHistogram",
+        "summary",
+    ]
+    .iter()
+    .find_map(|kind| Some((*kind, metric.get(*kind)?))) else {
+        return vec![format!("- metric {service} {name} (no data)")];
+    };
+    let points = data.get("dataPoints").and_then(Value::as_array);
+    points
+        .into_iter()
+        .flatten()
+        .map(|point| {
+            let value = match kind {
+                "gauge" | "sum" => number(point.get("asDouble"))
+                    .or_else(|| number(point.get("asInt")))
+                    .map_or_else(|| "-".to_string(), |n| format!("{n}{unit}")),
+                _ => {
+                    let mut value = format!("count={}", nanos(point.get("count")));
+                    for key in ["sum", "min", "max"] {
+                        if let Some(n) = number(point.get(key)) {
+                            let _ = write!(value, " {key}={n}{unit}");
+                        }
+                    }
+                    for q in point
+                        .get("quantileValues")
+                        .and_then(Value::as_array)
+                        .into_iter()
+                        .flatten()
+                    {
+                        let quantile = number(q.get("quantile")).unwrap_or_default();
+                        let _ = write!(
+                            value,
+                            " p{}={}{unit}",
+                            quantile * 100.0,
+                            number(q.get("value")).unwrap_or_default()
+                        );
+                    }
+                    value
+                }
+            };
+            format!(
+                "{} metric {service} {name} {value} ({kind}){}",
+                time(nanos(point.get("timeUnixNano"))),
+                pairs(&attributes(point.get("attributes"))),
+            )
+        })
+        .collect()
+}
+
+/// OTLP `[{key, value: AnyValue}]` as a plain object.
+fn attributes(list: Option<&Value>) -> Map<String, Value> {
+    list.and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|kv| {
+            Some((
+                kv.get("key")?.as_str()?.to_string(),
+                any_value(kv.get("value")),
+            ))
+        })
+        .collect()
+}
+
+fn any_value(value: Option<&Value>) -> Value {
+    let Some(Value::Object(value)) = value else {
+        return Value::Null;
+    };
+    let Some((kind, inner)) = value.iter().next() else {
+        return Value::Null;
+    };
+    match kind.as_str() {
+        "intValue" => number(Some(inner)).map_or(Value::Null, |n| json!(n as i64)),
+        "doubleValue" => number(Some(inner)).map_or(Value::Null, |n| json!(n)),
+        "arrayValue" => inner
+            .get("values")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .map(|v| any_value(Some(v)))
+            .collect(),
+        "kvlistValue" => Value::Object(attributes(inner.get("values"))),
+        _ => inner.clone(),
+    }
+}
+
+/// ` key=value ...`, strings unquoted unless they contain spaces.
+fn pairs(attributes: &Map<String, Value>) -> String {
+    attributes
+        .iter()
+        .fold(String::new(), |mut out, (key, value)| {
+            let value = match value {
+                Value::String(s) if !s.is_empty() && !s.contains(char::is_whitespace) => s.clone(),
+                other => other.to_string(),
+            };
+            let _ = write!(out, " {key}={value}");
+            out
+        })
+}
+
+fn time(ns: u64) -> String {
+    match ns {
+        0 => "-".to_string(),
+        ns => timeparse::format_rfc3339(ns / 1_000_000),
+    }
+}
+
+/// OTLP/JSON writes 64-bit integers as strings; protobuf gives numbers.
+fn number(value: Option<&Value>) -> Option<f64> {
+    match value? {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.parse().ok(),
+        _ => None,
+    }
+}
+
+fn nanos(value: Option<&Value>) -> u64 {
+    match value {
+        Some(Value::Number(n)) => n.as_u64().unwrap_or_default(),
+        Some(Value::String(s)) => s.parse().unwrap_or_default(),
+        _ => 0,
+    }
+}
+
+fn text(value: Option<&Value>) -> &str {
+    value.and_then(Value::as_str).unwrap_or_default()
+}
+
+// Sending.
+
+/// Marks the synthetic data so the poll finds exactly this probe.
+const PROBE_ATTRIBUTE: &str = "signoz.probe.id";
+const PROBE_METRIC: &str = "signoz_cli_probe";
+
+/// Exports one synthetic item over OTLP/HTTP (JSON encoding), then polls
+/// query_range until it shows up and reports the round trip.
+pub fn handle_send(
+    matches: &clap::ArgMatches,
+    ctx: &ApiContext,
+    opts: &OutputOptions,
+) -> Result<()> {
+    let Some(("send", matches)) = matches.subcommand() else {
+        return Err(anyhow!("unknown ingest command"));
+    };
+    let arg = |name: &str| matches.get_one::<String>(name).map(String::as_str);
+    let kind = arg("kind").unwrap_or("span");
+    let service = arg("service").unwrap_or("signoz-cli-probe");
+    let endpoint = arg("endpoint")
+        .map(str::to_string)
+        .or_else(|| env::var("OTEL_EXPORTER_OTLP_ENDPOINT").ok())
+        .unwrap_or_else(|| "http://localhost:4318".to_string());
+    let mut headers = Vec::new();
+    if let Some(key) = arg("ingestion-key")
+        .map(str::to_string)
+        .or_else(|| env::var("SIGNOZ_INGESTION_KEY").ok())
+    {
+        headers.push(("signoz-ingestion-key".to_string(), key));
+    }
+    let wait = Duration::from_millis(timeparse::parse_duration_ms(arg("wait").unwrap_or("2m"))?);
+    let interval = Duration::from_millis(timeparse::parse_duration_ms(
+        arg("interval").unwrap_or("2s"),
+    )?);
+
+    let probe = Probe::new(kind, service)?;
+    let signal = &SIGNALS[match kind {
+        "span" => 0,
+        "log" => 1,
+        _ => 2,
+    }];
+    let collector = HttpClient::new(
+        endpoint.trim_end_matches('/').to_string(),
+        None,
+        None,
+        headers,
+        &ctx.client_opts,
+    )?;
+    let sent = Instant::now();
+    let response = collector
+        .execute(
+            "POST",
+            signal.path,
+            &[],
+            Some(Body::Json(probe.payload())),
+            Some("application/json"),
+        )
+        .with_context(|| format!("export to {endpoint}"))?;
+    if response.status >= 400 {
+        return Err(ApiError::from_response(&response))
+            .with_context(|| format!("collector rejected the {kind}"));
+    }
+    let export_ms = sent.elapsed().as_millis() as u64;
+    let mut report = json!({
+        "kind": kind,
+        "service": service,
+        "probe_id": probe.id,
+        "endpoint": endpoint,
+        "export_status": response.status,
+        "export_ms": export_ms,
+    });
+    if matches.get_flag("no-verify") {
+        return output::print_value(&report, opts);
+    }
+
+    eprintln!(
+        "Sent {kind} {} to {endpoint}{}; waiting for it in {}...",
+        probe.id, signal.path, ctx.base_url
+    );
+    let deadline = sent + wait;
+    loop {
+        let response = query::run_query_range(ctx, probe.query())?;
+        if response.status >= 400 {
+            return Err(ApiError::from_response(&response)).context("query failed");
+        }
+        if probe.found(&response.body) {
+            report["latency_ms"] = json!(sent.elapsed().as_millis() as u64);
+            return output::print_value(&report, opts);
+        }
+        if Instant::now() + interval > deadline {
+            return Err(anyhow!(
+                "{kind} {} not queryable after {}s; check the collector's exporter and the ingestion key",
+                probe.id,
+                wait.as_secs()
+            ));
+        }
+        thread::sleep(interval);
+    }
+}
+
+struct Probe {
+    kind: &'static str,
+    service: String,
+    id: String,
+    trace_id: String,
+    span_id: String,
+    sent_ns: u64,
+}
+
+impl Probe {
+    fn new(kind: &str, service: &str) -> Result<Self> {
+        let random = |len: usize| -> Result<String> {
+            let mut bytes = vec![0u8; len];
+            SystemRandom::new()
+                .fill(&mut bytes)
+                .map_err(|_| anyhow!("no random source"))?;
+            Ok(hex(&bytes))
+        };
+        Ok(Self {
+            kind: match kind {
+                "span" => "span",
+                "log" => "log",
+                _ => "metric",
+            },
+            service: service.to_string(),
+            id: random(8)?,
+            trace_id: random(16)?,
+            span_id: random(8)?,
+            sent_ns: query::now_millis() * 1_000_000,
+        })
+    }
+
+    fn payload(&self) -> Value {
+        let resource = json!({
+            "attributes": [{ "key": "service.name", "value": { "stringValue": self.service } }],
+        });
+        let scope = json!({ "name": "signoz-cli", "version": env!("CARGO_PKG_VERSION") });
+        let attributes = json!([{ "key": PROBE_ATTRIBUTE, "value": { "stringValue": self.id } }]);
+        let now = self.sent_ns.to_string();
+        match self.kind {
+            "span" => json!({ "resourceSpans": [{
+                "resource": resource,
+                "scopeSpans": [{ "scope": scope, "spans": [{
+                    "traceId": self.trace_id,
+                    "spanId": self.span_id,
+                    "name": "signoz-cli probe",
+                    "kind": 1,
+                    "startTimeUnixNano": (self.sent_ns - 1_000_000).to_string(),
+                    "endTimeUnixNano": now,
+                    "attributes": attributes,
+                    "status": {},
+                }]}],
+            }]}),
+            "log" => json!({ "resourceLogs": [{
+                "resource": resource,
+                "scopeLogs": [{ "scope": scope, "logRecords": [{
+                    "timeUnixNano": now,
+                    "observedTimeUnixNano": now,
+                    "severityNumber": 9,
+                    "severityText": "INFO",
+                    "body": { "stringValue": format!("signoz-cli probe {}", self.id) },
+                    "attributes": attributes,
+                }]}],
+            }]}),
+            _ => json!({ "resourceMetrics": [{
+                "resource": resource,
+                "scopeMetrics": [{ "scope": scope, "metrics": [{
+                    "name": PROBE_METRIC,
+                    "description": "Synthetic data point from signoz ingest send",
+                    "gauge": { "dataPoints": [{
+                        "timeUnixNano": now,
+                        "asDouble": 1.0,
+                        "attributes": attributes,
+                    }]},
+                }]}],
+            }]}),
+        }
+    }
+
+    /// A query matching only this probe, over a window wide enough for
+    /// clock skew between this host and the collector.
+    fn query(&self) -> Value {
+        let sent_ms = self.sent_ns / 1_000_000;
+        let (start, end) = (sent_ms - 10 * 60_000, query::now_millis() + 10 * 60_000);
+        let marker = format!("{PROBE_ATTRIBUTE} = '{}'", self.id);
+        let (signal, request_type, spec) = match self.kind {
+            "span" => (
+                "traces",
+                "raw",
+                json!({ "filter": { "expression": format!("trace_id = '{}'", self.trace_id) }, "limit": 1 }),
+            ),
+            "log" => (
+                "logs",
+                "raw",
+                json!({ "filter": { "expression": format!("body CONTAINS '{}'", self.id) }, "limit": 1 }),
+            ),
+            _ => (
+                "metrics",
+                "time_series",
+                json!({
+                    "aggregations": [metrics::aggregation(PROBE_METRIC, "max")],
+                    "filter": { "expression": marker },
+                    "stepInterval": 60,
+                }),
+            ),
+        };
+        query::query_range_body(
+            start,
+            end,
+            request_type,
+            vec![query::builder_query("A", signal, spec)],
+        )
+    }
+
+    fn found(&self, body: &Value) -> bool {
+        let non_empty = |pointer: &str| {
+            body.pointer(pointer)
+                .and_then(Value::as_array)
+                .is_some_and(|items| !items.is_empty())
+        };
+        match self.kind {
+            "metric" => non_empty("/data/data/results/0/aggregations/0/series"),
+            _ => non_empty("/data/data/results/0/rows"),
+        }
+    }
+}
@@ -0,0 +1,576 @@
+use anyhow::{anyhow, Context, Result};
+use serde_json::{json, Map, Number, Value};
+use std::{
+    env, fs,
+    io::{self, IsTerminal, Write},
+    path::PathBuf,
+    process::{Command, Stdio},
+    sync::Arc,
+};
+
+use crate::command_tree::Operation;
+use crate::config::Config;
+use crate::error::{ApiError, Failure};
+use crate::expect::Expectations;
+use crate::http::{HttpResponse, Timing};
+use crate::normalize::Normalizer;
+use crate::paginate::items_pointer;
+use crate::render::{self, Render};
+use crate::stream::Emitter;
+use crate::transform::{self, JoinSpec};
+
+mod csv;
+mod highlight;
+mod parquet;
+mod sqlite;
+mod table;
+
+pub use table::{ColumnType, Table};
+
+/// `--output FORMAT:PATH`: write the response's items to a file instead of
+/// printing them.
+#[derive(Clone, Debug)]
+pub enum Sink {
+    /// `sqlite:results.db` or `sqlite:results.db#table` (default `results`).
+    Sqlite { path: String, table: String },
+    /// `parquet:results.parquet`; the file is replaced on each run.
+    Parquet { path: String },
+    /// `csv` (stdout, or `--output-file`) or `csv:results.csv`.
+    Csv { path: Option<PathBuf> },
+}
+
+impl Sink {
+    fn parse(raw: &str) -> Result<Self> {
+        match raw.split_once(':') {
+            Some(("sqlite", target)) if !target.is_empty() => {
+                let (path, table) = target.split_once('#').unwrap_or((target, "results"));
+                Ok(Self::Sqlite {
+                    path: path.to_string(),
+                    table: table.to_string(),
+                })
+            }
+            Some(("parquet", path)) if !path.is_empty() => Ok(Self::Parquet {
+                path: path.to_string(),
+            }),
+            Some(("csv", path)) if !path.is_empty() => Ok(Self::Csv {
+                path: Some(path.into()),
+            }),
+            None if raw == "csv" => Ok(Self::Csv { path: None }),
+            _ => Err(anyhow!(
+                "invalid --output {raw} (expected sqlite:FILE[#TABLE], parquet:FILE, or csv[:FILE])"
+            )),
+        }
+    }
+
+    fn write(&self, value: &Value) -> Result<()> {
+        match self {
+            Self::Sqlite { path, table } => {
+                let rows = sqlite::write(path, table, &table::Table::from_value(value)?)?;
+                eprintln!("wrote {rows} rows to {path} (table {table})");
+            }
+            Self::Parquet { path } => {
+                let rows = parquet::write(path, &table::Table::from_value(value)?)?;
+                eprintln!("wrote {rows} rows to {path}");
+            }
+            Self::Csv { path } => {
+                let data = table::Table::from_value(&csv::tidy(value))?;
+                match path {
+                    Some(path) => {
+                        let mut file = fs::File::create(path)
+                            .with_context(|| format!("create {}", path.display()))?;
+                        let rows = csv::write(&mut file, &data)?;
+                        eprintln!("wrote {rows} rows to {}", path.display());
+                    }
+                    None => {
+                        csv::write(&mut io::stdout().lock(), &data)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct OutputOptions {
+    pub pretty: bool,
+    pub raw: bool,
+    /// `--unwrap` or `[output] unwrap`: print the `data` of success
+    /// envelopes, and fail on error envelopes.
+    pub unwrap: bool,
+    pub stable: bool,
+    pub normalize: Option<Normalizer>,
+    pub join: Option<JoinSpec>,
+    pub expect: Option<Expectations>,
+    pub render: Option<Render>,
+    /// Table columns from the operation's hints, for the default table.
+    pub columns: Option<Vec<String>>,
+    /// `--where` conditions every printed item must meet.
+    pub conditions: Vec<transform::Condition>,
+    /// `--sort-by` keys, applied in order.
+    pub sort: Vec<transform::SortKey>,
+    /// `--fields a,b.c`: project items down to these dotted paths.
+    pub fields: Option<Vec<String>>,
+    /// `--stats FIELD`: summarize a numeric field instead of printing items.
+    pub stats: Option<String>,
+    pub sink: Option<Sink>,
+    /// ANSI colors for JSON and rendered output: `--color always|never`, or
+    /// with `auto` on a terminal unless `--no-color` or `NO_COLOR` is set.
+    pub color: bool,
+    /// `--raw-values`: keep nanosecond/byte numbers as is in rendered tables.
+    pub raw_values: bool,
+    /// `--quiet`: print nothing; the exit code tells the outcome.
+    pub quiet: bool,
+    /// `--status-only`: print just the HTTP status of responses.
+    pub status_only: bool,
+    /// `--fail-on-empty`: exit non-zero when there are no items.
+    pub fail_on_empty: bool,
+    /// `--stream`: where the operation's items went as they arrived.
+    pub stream: Option<Arc<Emitter>>,
+    /// Show output taller than the terminal through [`page`]: stdout is a
+    /// terminal and neither `--no-pager` nor `--watch` was given.
+    pub pager: bool,
+    /// `-o/--output-file`: write what would be printed to this file; text
+    /// bodies (CSV, YAML, ...) are written as is rather than as JSON strings.
+    pub output_file: Option<PathBuf>,
+}
+
+impl OutputOptions {
+    pub fn from_matches(matches: &clap::ArgMatches, config: &Config) -> Result<Self> {
+        let normalize = matches
+            .get_flag("normalize")
+            .then(|| Normalizer::new(&config.export, matches.get_flag("keep-ids")));
+        let join = match matches.get_many::<String>("join") {
+            Some(values) => Some(JoinSpec::from_args(&values.cloned().collect::<Vec<_>>())?),
+            None => None,
+        };
+        let output_file: Option<PathBuf> = matches.get_one::<String>("output-file").map(Into::into);
+        let mut sink = matches
+            .get_one::<String>("output")
+            .map(|raw| Sink::parse(raw))
+            .transpose()?;
+        match (&mut sink, &output_file) {
+            (Some(Sink::Csv { path: path @ None }), Some(file)) => *path = Some(file.clone()),
+            (Some(_), Some(_)) => {
+                return Err(
+                    Failure::Usage("--output-file only combines with --output csv".into()).into(),
+                )
+            }
+            _ => {}
+        }
+        let usage = |err: anyhow::Error| Failure::Usage(err.to_string());
+        let conditions = matches
+            .get_many::<String>("where")
+            .into_iter()
+            .flatten()
+            .map(|raw| transform::Condition::parse(raw).map_err(usage))
+            .collect::<Result<_, _>>()?;
+        let sort = matches
+            .get_many::<String>("sort-by")
+            .into_iter()
+            .flatten()
+            .map(|raw| transform::SortKey::parse(raw).map_err(usage))
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            pretty: matches.get_flag("pretty"),
+            raw: matches.get_flag("raw"),
+            unwrap: (matches.get_flag("unwrap") || config.output.unwrap)
+                && !matches.get_flag("no-unwrap"),
+            stable: matches.get_flag("stable-output"),
+            normalize,
+            join,
+            expect: Expectations::from_matches(matches)?,
+            render: Render::from_matches(matches),
+            columns: None,
+            conditions,
+            sort,
+            fields: matches.get_many::<String>("fields").map(|fields| {
+                fields
+                    .map(|f| f.trim().to_string())
+                    .filter(|f| !f.is_empty())
+                    .collect()
+            }),
+            stats: matches.get_one::<String>("stats").cloned(),
+            sink,
+            color: match matches.get_one::<String>("color").map(String::as_str) {
+                Some("always") => true,
+                Some("never") => false,
+                _ => {
+                    !matches.get_flag("no-color")
+                        && env::var_os("NO_COLOR").is_none_or(|v| v.is_empty())
+                        && io::stdout().is_terminal()
+                        && !matches.contains_id("output-file")
+                }
+            },
+            raw_values: matches.get_flag("raw-values"),
+            quiet: matches.get_flag("quiet"),
+            status_only: matches.get_flag("status-only"),
+            fail_on_empty: matches.get_flag("fail-on-empty"),
+            stream: None,
+            pager: !matches.get_flag("no-pager")
+                && !matches.contains_id("watch")
+                && output_file.is_none()
+                && io::stdout().is_terminal(),
+            output_file,
+        })
+    }
+
+    /// A table of the operation's hinted columns when nothing else was
+    /// asked of the output and it goes to a terminal; JSON otherwise.
+    pub fn for_op(&self, op: &Operation) -> Self {
+        let plain = self.render.is_none()
+            && !self.pretty
+            && !self.raw
+            && !self.stable
+            && self.normalize.is_none()
+            && self.join.is_none()
+            && self.fields.is_none()
+            && self.stats.is_none()
+            && self.sink.is_none()
+            && !self.quiet
+            && !self.status_only
+            && self.output_file.is_none();
+        match &op.columns {
+            Some(columns) if plain && io::stdout().is_terminal() => Self {
+                render: Some(Render::Table),
+                columns: Some(columns.clone()),
+                ..self.clone()
+            },
+            _ => self.clone(),
+        }
+    }
+}
+
+pub fn print_response(response: &HttpResponse, opts: &OutputOptions) -> Result<()> {
+    if opts.status_only && !opts.quiet {
+        println!("{}", response.status);
+    }
+    if let Some(path) = response
+        .saved_to
+        .as_ref()
+        .filter(|_| opts.output_file.is_some())
+    {
+        if !opts.quiet {
+            eprintln!(
+                "wrote {} bytes to {}",
+                response.body["bytes"],
+                path.display()
+            );
+        }
+        return Ok(());
+    }
+    if !opts.raw {
+        for timing in &response.timing {
+            eprintln!("{}", timing_line(timing));
+        }
+    }
+    if response.streamed.is_some() {
+        // The items went out as they arrived; those `--where` dropped
+        // were never printed.
+        let written = opts.stream.as_ref().map_or(0, |emitter| emitter.written());
+        if opts.fail_on_empty && written == 0 {
+            return Err(anyhow!("no results (--fail-on-empty)"));
+        }
+        return Ok(());
+    }
+    print_value(&response_value(response, opts)?, opts)
+}
+
+fn timing_line(timing: &Timing) -> String {
+    let phase = |name: &str, ms: Option<f64>| match ms {
+        Some(ms) => format!("{name} {ms:.1} ms"),
+        None => format!("{name} -"),
+    };
+    format!(
+        "* timing {} {} -> {}: {}, {}, ttfb {:.1} ms, total {:.1} ms",
+        timing.method,
+        timing.url,
+        timing.status,
+        phase("dns", timing.dns_ms),
+        phase("connect", timing.connect_ms),
+        timing.ttfb_ms,
+        timing.total_ms
+    )
+}
+
+/// The value `print_response` would print: joined, normalized, unwrapped,
+/// and wrapped with status/headers under `--raw`.
+pub fn response_value(response: &HttpResponse, opts: &OutputOptions) -> Result<Value> {
+    let mut body = response.body.clone();
+    if let Some(join) = &opts.join {
+        join.apply(&mut body)?;
+    }
+    if let Some(normalizer) = &opts.normalize {
+        normalizer.apply(&mut body);
+    }
+    if opts.unwrap {
+        body = unwrap(body, response)?;
+    }
+    Ok(if opts.raw {
+        let mut envelope = json!({
+            "status": response.status,
+            "headers": response.headers,
+            "body": body,
+        });
+        if !response.timing.is_empty() {
+            envelope["timing"] = json!(response.timing);
+        }
+        if let Some(id) = &response.request_id {
+            envelope["request_id"] = json!(id);
+        }
+        if let Some(id) = response.server_request_id() {
+            envelope["server_request_id"] = json!(id);
+        }
+        envelope
+    } else {
+        body
+    })
+}
+
+/// The `data` of a `{"status": "success", "data": ...}` envelope; an
+/// envelope with `"status": "error"` is the API error it describes, and
+/// other bodies pass through.
+fn unwrap(body: Value, response: &HttpResponse) -> Result<Value> {
+    let Value::Object(mut map) = body else {
+        return Ok(body);
+    };
+    match map.get("status").and_then(Value::as_str) {
+        Some("success") if map.contains_key("data") => Ok(map.remove("data").unwrap_or_default()),
+        Some("error") => Err(ApiError::from_response(response).into()),
+        _ => Ok(Value::Object(map)),
+    }
+}
+
+pub fn print_value(value: &Value, opts: &OutputOptions) -> Result<()> {
+    let shaped = shape(value, opts)?;
+    let value = shaped.as_ref().unwrap_or(value);
+    if !(opts.quiet || opts.status_only) {
+        write_value(value, opts)?;
+    }
+    if opts.fail_on_empty {
+        let body = if opts.raw {
+            value.get("body").unwrap_or(value)
+        } else {
+            value
+        };
+        if is_empty(body) {
+            return Err(anyhow!("no results (--fail-on-empty)"));
+        }
+    }
+    Ok(())
+}
+
+/// An empty item list, or no data at all: a null body or `data`, or a
+/// query with no results or rows.
+fn is_empty(value: &Value) -> bool {
+    if let Some(pointer) = items_pointer(value) {
+        return value
+            .pointer(pointer)
+            .and_then(Value::as_array)
+            .is_some_and(Vec::is_empty);
+    }
+    [
+        "",
+        "/data",
+        "/data/data/results/0/rows",
+        "/data/result/0/list",
+    ]
+    .iter()
+    .any(|p| value.pointer(p).is_some_and(Value::is_null))
+        || ["/data/data/results", "/data/result"].iter().any(|p| {
+            value
+                .pointer(p)
+                .and_then(Value::as_array)
+                .is_some_and(Vec::is_empty)
+        })
+}
+
+/// `--where`, `--sort-by`, then `--fields`, applied to the body of a
+/// `--raw` envelope; `None` when none was given.
+pub fn shape(value: &Value, opts: &OutputOptions) -> Result<Option<Value>> {
+    if opts.conditions.is_empty() && opts.sort.is_empty() && opts.fields.is_none() {
+        return Ok(None);
+    }
+    let mut value = value.clone();
+    let body = match value.get_mut("body").filter(|_| opts.raw) {
+        Some(body) => body,
+        None => &mut value,
+    };
+    if !(opts.conditions.is_empty() && opts.sort.is_empty()) {
+        transform::select(body, &opts.conditions, &opts.sort)?;
+    }
+    if let Some(fields) = &opts.fields {
+        *body = transform::project(body, fields);
+    }
+    Ok(Some(value))
+}
+
+fn write_value(value: &Value, opts: &OutputOptions) -> Result<()> {
+    if let Some(field) = &opts.stats {
+        return emit(&json_text(&transform::stats(value, field)?, opts)?, opts);
+    }
+    if let Some(sink) = &opts.sink {
+        return sink.write(value);
+    }
+    if let Some(text) = opts.render.and_then(|mode| {
+        render::render(
+            value,
+            mode,
+            opts.color,
+            !opts.raw_values,
+            opts.columns.as_deref(),
+        )
+    }) {
+        return emit(&text, opts);
+    }
+    match (&opts.output_file, value) {
+        (Some(path), Value::String(text)) => {
+            fs::write(path, text).with_context(|| format!("write {}", path.display()))
+        }
+        _ => emit(&json_text(value, opts)?, opts),
+    }
+}
+
+/// [`render_value`], highlighted when colors are on.
+fn json_text(value: &Value, opts: &OutputOptions) -> Result<String> {
+    let text = render_value(value, opts)?;
+    Ok(if opts.color {
+        highlight::json(&text)
+    } else {
+        text
+    })
+}
+
+/// Prints a line of output, or writes it to `--output-file`.
+fn emit(text: &str, opts: &OutputOptions) -> Result<()> {
+    match &opts.output_file {
+        Some(path) => fs::write(path, format!("{text}\n"))
+            .with_context(|| format!("write {}", path.display())),
+        None if opts.pager
+            && terminal_size().is_none_or(|(rows, cols)| screen_rows(text, cols) >= rows) =>
+        {
+            page(&format!("{text}\n"))
+        }
+        None => {
+            println!("{text}");
+            Ok(())
+        }
+    }
+}
+
+/// The terminal's rows and columns: `LINES` and `COLUMNS`, else `stty size`
+/// on the controlling terminal.
+fn terminal_size() -> Option<(usize, usize)> {
+    let var = |name: &str| {
+        env::var(name)
+            .ok()
+            .and_then(|v| v.trim().parse().ok())
+            .filter(|n: &usize| *n > 0)
+    };
+    if let (Some(rows), Some(cols)) = (var("LINES"), var("COLUMNS")) {
+        return Some((rows, cols));
+    }
+    let tty = fs::File::open("/dev/tty").ok()?;
+    let out = Command::new("stty")
+        .arg("size")
+        .stdin(tty)
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    let out = String::from_utf8_lossy(&out.stdout);
+    let mut size = out
+        .split_whitespace()
+        .map(|n| n.parse().ok().filter(|n: &usize| *n > 0));
+    let (rows, cols) = (size.next()??, size.next()??);
+    Some((var("LINES").unwrap_or(rows), var("COLUMNS").unwrap_or(cols)))
+}
+
+/// How many terminal rows `text` takes once lines wider than `cols` wrap;
+/// color escapes take no room.
+fn screen_rows(text: &str, cols: usize) -> usize {
+    text.lines()
+        .map(|line| {
+            let mut width: usize = 0;
+            let mut chars = line.chars();
+            while let Some(c) = chars.next() {
+                if c == '\x1b' {
+                    chars.by_ref().find(|c| c.is_ascii_alphabetic());
+                } else {
+                    width += 1;
+                }
+            }
+            width.div_ceil(cols).max(1)
+        })
+        .sum()
+}
+
+/// Shows `text` through `$PAGER` (default `less -FRX`; `LESS=FRX` unless set,
+/// as git does) when stdout is a terminal; prints it directly otherwise or
+/// if the pager cannot start.
+pub fn page(text: &str) -> Result<()> {
+    if !io::stdout().is_terminal() {
+        print!("{text}");
+        return Ok(());
+    }
+    let pager = env::var("PAGER")
+        .ok()
+        .filter(|p| !p.trim().is_empty())
+        .unwrap_or_else(|| "less -FRX".to_string());
+    let mut parts = pager.split_whitespace();
+    let program = parts.next().unwrap_or("less");
+    let mut command = Command::new(program);
+    command.args(parts).stdin(Stdio::piped());
+    if env::var_os("LESS").is_none() {
+        command.env("LESS", "FRX");
+    }
+    match command.spawn() {
+        Ok(mut child) => {
+            if let Some(mut stdin) = child.stdin.take() {
+                // The pager may exit early (e.g. `q`); a broken pipe is fine.
+                let _ = stdin.write_all(text.as_bytes());
+            }
+            child.wait()?;
+        }
+        Err(_) => print!("{text}"),
+    }
+    Ok(())
+}
+
+pub fn render_value(value: &Value, opts: &OutputOptions) -> Result<String> {
+    if opts.stable {
+        return Ok(serde_json::to_string_pretty(&stabilize(value))?);
+    }
+    if opts.pretty {
+        return Ok(serde_json::to_string_pretty(value)?);
+    }
+    Ok(serde_json::to_string(value)?)
+}
+
+/// Sorts object keys and collapses integral floats (`1.0`, `-0.0`) to integers
+/// so repeated exports of the same data produce byte-identical files.
+pub fn stabilize(value: &Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+            let mut out = Map::new();
+            for key in keys {
+                out.insert(key.clone(), stabilize(&map[key]));
+            }
+            Value::Object(out)
+        }
+        Value::Array(items) => Value::Array(items.iter().map(stabilize).collect()),
+        Value::Number(n) => Value::Number(normalize_number(n)),
+        other => other.clone(),
+    }
+}
+
+fn normalize_number(n: &Number) -> Number {
+    if n.is_i64() || n.is_u64() {
+        return n.clone();
+    }
+    match n.as_f64() {
+        Some(f) if f.fract() == 0.0 && f.abs() < 9_007_199_254_740_992.0 => Number::from(f as i64),
+        _ => n.clone(),
+    }
+}
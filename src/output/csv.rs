@@ -0,0 +1,60 @@
+use anyhow::Result;
+use serde_json::{Map, Value};
+use std::io::Write;
+
+use super::table::Table;
+
+/// Writes the table as RFC 4180 CSV with a header row. Returns the number
+/// of rows written.
+pub fn write(out: &mut dyn Write, data: &Table) -> Result<usize> {
+    if data.columns.is_empty() {
+        return Ok(0);
+    }
+    let header: Vec<String> = data.columns.iter().map(|(name, _)| quote(name)).collect();
+    write!(out, "{}\r\n", header.join(","))?;
+    for row in &data.rows {
+        let cells: Vec<String> = data
+            .columns
+            .iter()
+            .map(|(name, _)| match row.get(name) {
+                None | Some(Value::Null) => String::new(),
+                Some(Value::String(s)) => quote(s),
+                Some(other) => quote(&other.to_string()),
+            })
+            .collect();
+        write!(out, "{}\r\n", cells.join(","))?;
+    }
+    out.flush()?;
+    Ok(data.rows.len())
+}
+
+/// Raw logs/traces rows (`{timestamp, data: {...}}`) with the `data` fields
+/// lifted beside the timestamp, so columns read `body`, `trace_id`,
+/// `attributes_string.http.method` rather than `data.body`, ...
+pub fn tidy(value: &Value) -> Value {
+    let mut value = value.clone();
+    if let Some(Value::Array(rows)) = value.pointer_mut("/data/data/results/0/rows") {
+        for row in rows.iter_mut() {
+            let Some(Value::Object(data)) = row.get("data") else {
+                continue;
+            };
+            let mut lifted = Map::new();
+            if let Some(timestamp) = row.get("timestamp") {
+                lifted.insert("timestamp".to_string(), timestamp.clone());
+            }
+            for (key, field) in data {
+                lifted.entry(key.clone()).or_insert_with(|| field.clone());
+            }
+            *row = Value::Object(lifted);
+        }
+    }
+    value
+}
+
+fn quote(cell: &str) -> String {
+    if cell.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", cell.replace('"', "\"\""))
+    } else {
+        cell.to_string()
+    }
+}
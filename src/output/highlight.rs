@@ -0,0 +1,65 @@
+/// ANSI styles, close to jq's defaults.
+const KEY: &str = "34;1";
+const STRING: &str = "32";
+const NUMBER: &str = "33";
+const BOOLEAN: &str = "35";
+const NULL: &str = "90";
+
+/// Colors serialized JSON (compact or pretty) without changing a byte of
+/// it otherwise: keys, strings, numbers, booleans, and null each get a
+/// style; punctuation and whitespace are left plain.
+pub fn json(text: &str) -> String {
+    let mut out = String::with_capacity(text.len() * 2);
+    let mut chars = text.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        let style = match c {
+            '"' => {
+                let mut escaped = false;
+                let mut end = text.len();
+                for (i, c) in chars.by_ref() {
+                    match c {
+                        _ if escaped => escaped = false,
+                        '\\' => escaped = true,
+                        '"' => {
+                            end = i + 1;
+                            break;
+                        }
+                        _ => {}
+                    }
+                }
+                let is_key = text[end..].trim_start().starts_with(':');
+                paint(
+                    &mut out,
+                    if is_key { KEY } else { STRING },
+                    &text[start..end],
+                );
+                continue;
+            }
+            '-' | '0'..='9' => NUMBER,
+            't' | 'f' => BOOLEAN,
+            'n' => NULL,
+            _ => {
+                out.push(c);
+                continue;
+            }
+        };
+        let mut end = start + c.len_utf8();
+        while let Some(&(i, c)) = chars.peek() {
+            if !(c.is_ascii_alphanumeric() || matches!(c, '.' | '+' | '-')) {
+                break;
+            }
+            end = i + c.len_utf8();
+            chars.next();
+        }
+        paint(&mut out, style, &text[start..end]);
+    }
+    out
+}
+
+fn paint(out: &mut String, style: &str, token: &str) {
+    out.push_str("\x1b[");
+    out.push_str(style);
+    out.push('m');
+    out.push_str(token);
+    out.push_str("\x1b[0m");
+}
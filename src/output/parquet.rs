@@ -0,0 +1,70 @@
+use ::parquet::{arrow::ArrowWriter, basic::Compression, file::properties::WriterProperties};
+use anyhow::{Context, Result};
+use arrow_array::{ArrayRef, BooleanArray, Float64Array, Int64Array, RecordBatch, StringArray};
+use arrow_schema::{DataType, Field, Schema};
+use serde_json::Value;
+use std::{fs::File, sync::Arc};
+
+use super::table::{ColumnType, Table};
+
+/// Writes the table to a new Parquet file at `path` (replacing any existing
+/// one) with one typed, nullable column per table column. Returns the number
+/// of rows written.
+pub fn write(path: &str, data: &Table) -> Result<usize> {
+    let fields: Vec<Field> = data
+        .columns
+        .iter()
+        .map(|(name, kind)| Field::new(name, data_type(*kind), true))
+        .collect();
+    let arrays: Vec<ArrayRef> = data
+        .columns
+        .iter()
+        .map(|(name, kind)| {
+            let cells = data
+                .rows
+                .iter()
+                .map(|row| row.get(name).filter(|v| !v.is_null()));
+            column(*kind, cells)
+        })
+        .collect();
+    let schema = Arc::new(Schema::new(fields));
+    let batch = RecordBatch::try_new(schema.clone(), arrays).context("build record batch")?;
+
+    let file = File::create(path).with_context(|| format!("create {path}"))?;
+    let props = WriterProperties::builder()
+        .set_compression(Compression::SNAPPY)
+        .build();
+    let mut writer = ArrowWriter::try_new(file, schema, Some(props))?;
+    writer.write(&batch)?;
+    writer.close()?;
+    Ok(data.rows.len())
+}
+
+fn data_type(kind: ColumnType) -> DataType {
+    match kind {
+        ColumnType::Integer => DataType::Int64,
+        ColumnType::Real => DataType::Float64,
+        ColumnType::Boolean => DataType::Boolean,
+        ColumnType::Text => DataType::Utf8,
+    }
+}
+
+/// Text columns hold every cell as text, so a column that mixed numbers and
+/// strings keeps both.
+fn column<'a>(kind: ColumnType, cells: impl Iterator<Item = Option<&'a Value>>) -> ArrayRef {
+    match kind {
+        ColumnType::Integer => Arc::new(cells.map(|v| v?.as_i64()).collect::<Int64Array>()),
+        ColumnType::Real => Arc::new(cells.map(|v| v?.as_f64()).collect::<Float64Array>()),
+        ColumnType::Boolean => Arc::new(cells.map(|v| v?.as_bool()).collect::<BooleanArray>()),
+        ColumnType::Text => Arc::new(
+            cells
+                .map(|v| {
+                    v.map(|v| match v {
+                        Value::String(s) => s.clone(),
+                        other => other.to_string(),
+                    })
+                })
+                .collect::<StringArray>(),
+        ),
+    }
+}
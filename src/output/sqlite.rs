@@ -0,0 +1,117 @@
+use anyhow::{Context, Result};
+use rusqlite::{types::Value as SqlValue, Connection};
+use serde_json::Value;
+
+use super::table::{ColumnType, Table};
+
+/// Appends the table's rows to `table` in the SQLite file at `path`,
+/// creating the table (or adding missing columns) as needed. Returns the
+/// number of rows written.
+pub fn write(path: &str, table: &str, data: &Table) -> Result<usize> {
+    if data.columns.is_empty() {
+        return Ok(0);
+    }
+    let mut conn = Connection::open(path).with_context(|| format!("open {path}"))?;
+    let tx = conn.transaction()?;
+    let quoted = quote(table);
+    let names = column_names(&data.columns);
+
+    let existing: Vec<String> = {
+        let mut stmt = tx.prepare(&format!("PRAGMA table_info({quoted})"))?;
+        let names = stmt.query_map([], |row| row.get::<_, String>(1))?;
+        names.collect::<rusqlite::Result<_>>()?
+    };
+    if existing.is_empty() {
+        let columns: Vec<String> = names
+            .iter()
+            .zip(&data.columns)
+            .map(|(name, (_, kind))| format!("{} {}", quote(name), sql_type(*kind)))
+            .collect();
+        tx.execute(
+            &format!("CREATE TABLE {quoted} ({})", columns.join(", ")),
+            [],
+        )
+        .with_context(|| format!("create table {table}"))?;
+    } else {
+        let missing = names.iter().zip(&data.columns).filter(|(name, _)| {
+            !existing
+                .iter()
+                .any(|column| column.eq_ignore_ascii_case(name))
+        });
+        for (name, (_, kind)) in missing {
+            tx.execute(
+                &format!(
+                    "ALTER TABLE {quoted} ADD COLUMN {} {}",
+                    quote(name),
+                    sql_type(*kind)
+                ),
+                [],
+            )?;
+        }
+    }
+
+    {
+        let quoted_names: Vec<String> = names.iter().map(|n| quote(n)).collect();
+        let slots = vec!["?"; names.len()].join(", ");
+        let mut stmt = tx.prepare(&format!(
+            "INSERT INTO {quoted} ({}) VALUES ({slots})",
+            quoted_names.join(", ")
+        ))?;
+        for row in &data.rows {
+            let cells = data
+                .columns
+                .iter()
+                .map(|(name, _)| sql_value(row.get(name).unwrap_or(&Value::Null)));
+            stmt.execute(rusqlite::params_from_iter(cells))?;
+        }
+    }
+    tx.commit()?;
+    Ok(data.rows.len())
+}
+
+/// SQLite compares column names without regard to case, so a name that
+/// differs from an earlier one only in case (`Status`, `status`) gets a
+/// `_2`, `_3`, ... suffix instead of colliding.
+fn column_names(columns: &[(String, ColumnType)]) -> Vec<String> {
+    let mut names: Vec<String> = Vec::with_capacity(columns.len());
+    for (name, _) in columns {
+        let mut unique = name.clone();
+        let mut n = 2;
+        while names
+            .iter()
+            .any(|taken| taken.eq_ignore_ascii_case(&unique))
+        {
+            unique = format!("{name}_{n}");
+            n += 1;
+        }
+        names.push(unique);
+    }
+    names
+}
+
+fn sql_type(kind: ColumnType) -> &'static str {
+    match kind {
+        ColumnType::Integer | ColumnType::Boolean => "INTEGER",
+        ColumnType::Real => "REAL",
+        ColumnType::Text => "TEXT",
+    }
+}
+
+fn sql_value(cell: &Value) -> SqlValue {
+    match cell {
+        Value::Null => SqlValue::Null,
+        Value::Bool(b) => SqlValue::Integer(i64::from(*b)),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => SqlValue::Integer(i),
+            // Above i64::MAX: kept exact as text, as the column's type is.
+            None if n.is_u64() => SqlValue::Text(n.to_string()),
+            None => SqlValue::Real(n.as_f64().unwrap_or(f64::NAN)),
+        },
+        Value::String(s) => SqlValue::Text(s.clone()),
+        other => SqlValue::Text(other.to_string()),
+    }
+}
+
+fn quote(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
@@ -0,0 +1,185 @@
+use anyhow::{anyhow, Result};
+use serde_json::{json, Map, Value};
+
+use crate::paginate::items_pointer;
+
+/// Column type inferred from every non-null value in the column.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColumnType {
+    Integer,
+    Real,
+    Boolean,
+    Text,
+}
+
+/// The response's items as flat rows: nested objects become dotted column
+/// names and arrays are kept as JSON text. Time series become one row per
+/// point (`labels.*`, `timestamp`, `value`) and scalar results one row per
+/// group. Columns are in first-seen order.
+pub struct Table {
+    pub columns: Vec<(String, ColumnType)>,
+    pub rows: Vec<Map<String, Value>>,
+}
+
+impl Table {
+    pub fn from_value(value: &Value) -> Result<Self> {
+        let items = match value.pointer(items_pointer(value).unwrap_or("")) {
+            Some(Value::Array(items)) => items.clone(),
+            _ if value.pointer("/data/data/results").is_some() => results(value),
+            Some(item @ Value::Object(_)) => vec![item.clone()],
+            _ => return Err(anyhow!("response has no item list to write as rows")),
+        };
+        let rows: Vec<Map<String, Value>> = items
+            .iter()
+            .flat_map(points)
+            .map(|item| {
+                let mut row = Map::new();
+                flatten(&item, "", &mut row);
+                row
+            })
+            .collect();
+
+        let mut columns: Vec<(String, Option<ColumnType>)> = Vec::new();
+        for row in &rows {
+            for (key, cell) in row {
+                let index = match columns.iter().position(|(name, _)| name == key) {
+                    Some(index) => index,
+                    None => {
+                        columns.push((key.clone(), None));
+                        columns.len() - 1
+                    }
+                };
+                let seen = &mut columns[index].1;
+                *seen = merge_type(*seen, cell);
+            }
+        }
+        Ok(Self {
+            columns: columns
+                .into_iter()
+                .map(|(name, kind)| (name, kind.unwrap_or(ColumnType::Text)))
+                .collect(),
+            rows,
+        })
+    }
+}
+
+/// `metrics query` series (`{labels, values: [[ts, v], ...]}`) as one item
+/// per point; anything else is kept as is.
+fn points(item: &Value) -> Vec<Value> {
+    let (Some(labels), Some(Value::Array(values))) = (item.get("labels"), item.get("values"))
+    else {
+        return vec![item.clone()];
+    };
+    if !values
+        .iter()
+        .all(|p| p.as_array().is_some_and(|p| p.len() == 2))
+    {
+        return vec![item.clone()];
+    }
+    values
+        .iter()
+        .map(|point| json!({ "labels": labels, "timestamp": point[0], "value": point[1] }))
+        .collect()
+}
+
+/// A raw time_series query_range response as `{query, labels, timestamp,
+/// value}` items; a scalar one as `{query, <column>: value, ...}` items.
+fn results(value: &Value) -> Vec<Value> {
+    let list = |v: Option<&Value>| v.and_then(Value::as_array).cloned().unwrap_or_default();
+    let mut items = Vec::new();
+    for result in list(value.pointer("/data/data/results")) {
+        let query = result.get("queryName").cloned().unwrap_or(Value::Null);
+        if let (Some(Value::Array(columns)), Some(Value::Array(rows))) =
+            (result.get("columns"), result.get("data"))
+        {
+            let names: Vec<String> = columns
+                .iter()
+                .enumerate()
+                .map(|(i, column)| {
+                    column
+                        .get("name")
+                        .and_then(Value::as_str)
+                        .map_or_else(|| format!("column_{i}"), str::to_string)
+                })
+                .collect();
+            for row in rows.iter().filter_map(Value::as_array) {
+                let mut item = Map::new();
+                item.insert("query".to_string(), query.clone());
+                for (name, cell) in names.iter().zip(row) {
+                    item.insert(name.clone(), cell.clone());
+                }
+                items.push(Value::Object(item));
+            }
+            continue;
+        }
+        for series in list(result.get("aggregations"))
+            .iter()
+            .flat_map(|agg| list(agg.get("series")))
+        {
+            let labels: Map<String, Value> = list(series.get("labels"))
+                .iter()
+                .filter_map(|label| {
+                    let key = label.pointer("/key/name")?.as_str()?;
+                    Some((key.to_string(), label.get("value")?.clone()))
+                })
+                .collect();
+            for point in list(series.get("values")) {
+                items.push(json!({
+                    "query": query,
+                    "labels": labels,
+                    "timestamp": point.get("timestamp"),
+                    "value": point.get("value"),
+                }));
+            }
+        }
+    }
+    items
+}
+
+fn flatten(value: &Value, prefix: &str, out: &mut Map<String, Value>) {
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            for (key, child) in map {
+                let name = match prefix {
+                    "" => key.clone(),
+                    _ => format!("{prefix}.{key}"),
+                };
+                flatten(child, &name, out);
+            }
+        }
+        Value::Array(_) | Value::Object(_) => {
+            out.insert(field(prefix), Value::String(value.to_string()));
+        }
+        other => {
+            out.insert(field(prefix), other.clone());
+        }
+    }
+}
+
+/// A scalar item (not an object) goes in a `value` column.
+fn field(prefix: &str) -> String {
+    match prefix {
+        "" => "value".to_string(),
+        _ => prefix.to_string(),
+    }
+}
+
+/// Integers above `i64::MAX` (unsigned 64-bit counters) make the column text,
+/// so they are written exactly rather than dropped or rounded.
+fn merge_type(seen: Option<ColumnType>, cell: &Value) -> Option<ColumnType> {
+    let kind = match cell {
+        Value::Null => return seen,
+        Value::Bool(_) => ColumnType::Boolean,
+        Value::Number(n) if n.is_i64() => ColumnType::Integer,
+        Value::Number(n) if n.is_u64() => ColumnType::Text,
+        Value::Number(_) => ColumnType::Real,
+        _ => ColumnType::Text,
+    };
+    Some(match (seen, kind) {
+        (None, kind) => kind,
+        (Some(a), b) if a == b => a,
+        (Some(ColumnType::Integer), ColumnType::Real)
+        | (Some(ColumnType::Real), ColumnType::Integer) => ColumnType::Real,
+        _ => ColumnType::Text,
+    })
+}
@@ -0,0 +1,270 @@
+use anyhow::{anyhow, Result};
+use clap::{Arg, ArgAction};
+use serde_json::{json, Value};
+
+use crate::command_tree::Operation;
+use crate::http::{Body, HttpResponse};
+use crate::interrupt;
+use crate::progress::Progress;
+
+const DEFAULT_PAGE_SIZE: usize = 100;
+
+const OFFSET_PARAMS: &[&str] = &["offset"];
+const LIMIT_PARAMS: &[&str] = &["limit", "pageSize", "page_size", "per_page"];
+const CURSOR_PARAMS: &[&str] = &["cursor", "pageToken", "page_token", "after"];
+
+/// Where list items live in a page, tried in order.
+pub const ITEM_POINTERS: &[&str] = &[
+    "/data/data/results/0/rows",
+    "/data/result/0/list",
+    "/data/items",
+    "/data",
+    "/items",
+    "",
+];
+const CURSOR_POINTERS: &[&str] = &[
+    "/data/data/results/0/nextCursor",
+    "/data/nextCursor",
+    "/nextCursor",
+    "/data/next_cursor",
+    "/next_cursor",
+];
+
+#[derive(Clone, Debug)]
+pub enum Pager {
+    /// `offset` + `limit` style query params.
+    Offset { offset: String, limit: String },
+    /// Opaque cursor query param, next value read from the response.
+    Cursor {
+        cursor: String,
+        limit: Option<String>,
+    },
+    /// query_range bodies: `limit`/`offset` on each builder query spec.
+    QueryRange,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct PageOptions {
+    pub limit: Option<usize>,
+    pub page_size: usize,
+}
+
+pub fn detect(op: &Operation) -> Option<Pager> {
+    let query_param = |names: &[&str]| {
+        op.params
+            .iter()
+            .find(|p| p.location == "query" && names.contains(&p.param_name.as_str()))
+            .map(|p| p.param_name.clone())
+    };
+    let limit = query_param(LIMIT_PARAMS);
+    if let (Some(offset), Some(limit)) = (query_param(OFFSET_PARAMS), limit.clone()) {
+        return Some(Pager::Offset { offset, limit });
+    }
+    if let Some(cursor) = query_param(CURSOR_PARAMS) {
+        return Some(Pager::Cursor { cursor, limit });
+    }
+    match &op.request_body {
+        Some(body) if body.schema_type == "QueryRangeRequest" => Some(Pager::QueryRange),
+        _ => None,
+    }
+}
+
+/// Paging flags; `--limit`/`--page-size` are skipped when the operation
+/// already owns a flag with that name.
+pub fn args(op: &Operation) -> Vec<Arg> {
+    let taken = |flag: &str| op.params.iter().any(|p| p.flag == flag);
+    let mut out = vec![Arg::new("page-all")
+        .long("all")
+        .action(ArgAction::SetTrue)
+        .help("Fetch every page and concatenate the results")];
+    if !taken("limit") {
+        out.push(
+            Arg::new("page-limit")
+                .long("limit")
+                .value_name("N")
+                .help("Stop after N items (implies --all)"),
+        );
+    }
+    if !taken("page-size") {
+        out.push(
+            Arg::new("page-size")
+                .long("page-size")
+                .value_name("N")
+                .help("Items requested per page"),
+        );
+    }
+    out
+}
+
+pub fn options(matches: &clap::ArgMatches) -> Result<Option<PageOptions>> {
+    let number = |id: &str| -> Result<Option<usize>> {
+        let Ok(Some(raw)) = matches.try_get_one::<String>(id) else {
+            return Ok(None);
+        };
+        raw.parse::<usize>()
+            .map(Some)
+            .map_err(|_| anyhow!("invalid number: {raw}"))
+    };
+    let limit = number("page-limit")?;
+    let all = matches.try_get_one::<bool>("page-all").ok().flatten() == Some(&true);
+    if !all && limit.is_none() {
+        return Ok(None);
+    }
+    let page_size = number("page-size")?
+        .unwrap_or(DEFAULT_PAGE_SIZE)
+        .min(limit.unwrap_or(usize::MAX))
+        .max(1);
+    Ok(Some(PageOptions { limit, page_size }))
+}
+
+/// Repeatedly calls `fetch` advancing the page, and returns the first page's
+/// response with its item list replaced by the concatenation of all pages.
+/// On Ctrl-C the pages fetched so far are returned along with a checkpoint
+/// (the paging param to resume from).
+pub fn fetch_all<F>(
+    pager: &Pager,
+    opts: PageOptions,
+    query: &[(String, String)],
+    body: Option<Body>,
+    mut fetch: F,
+) -> Result<(HttpResponse, Option<Value>)>
+where
+    F: FnMut(Vec<(String, String)>, Option<Body>) -> Result<HttpResponse>,
+{
+    let mut items: Vec<Value> = Vec::new();
+    // Items so far, counting those already written out by `--stream`.
+    let mut fetched = 0;
+    let mut first: Option<(HttpResponse, &str)> = None;
+    let mut cursor: Option<String> = None;
+    let mut checkpoint: Option<Value> = None;
+    let progress = Progress::start("fetching page 1");
+    interrupt::graceful();
+
+    for page in 1.. {
+        if first.is_some() && interrupt::requested() {
+            checkpoint = Some(match pager {
+                Pager::Offset { offset, .. } => json!({ offset.as_str(): fetched }),
+                Pager::Cursor { cursor: c, .. } => json!({ c.as_str(): cursor }),
+                Pager::QueryRange => json!({ "offset": fetched }),
+            });
+            break;
+        }
+        let remaining = opts.limit.map(|l| l.saturating_sub(fetched));
+        let size = remaining.unwrap_or(usize::MAX).min(opts.page_size);
+        let offset = fetched;
+
+        let (page_query, page_body) = match pager {
+            Pager::Offset { offset: o, limit } => (
+                with_params(query, &[(o, offset.to_string()), (limit, size.to_string())]),
+                body.clone(),
+            ),
+            Pager::Cursor { cursor: c, limit } => {
+                let mut params = Vec::new();
+                if let Some(value) = &cursor {
+                    params.push((c, value.clone()));
+                }
+                if let Some(limit) = limit {
+                    params.push((limit, size.to_string()));
+                }
+                (with_params(query, &params), body.clone())
+            }
+            Pager::QueryRange => (query.to_vec(), Some(page_query_range(&body, offset, size)?)),
+        };
+
+        if page > 1 {
+            progress.set(format!("fetching page {page} ({fetched} items so far)"));
+        }
+        let response = fetch(page_query, page_body)?;
+        if response.status >= 400 {
+            return Ok((response, None));
+        }
+        let Some(pointer) = items_pointer(&response.body) else {
+            // Not a list response; nothing to concatenate.
+            return Ok((response, None));
+        };
+        let page: Vec<Value> = response
+            .body
+            .pointer(pointer)
+            .and_then(Value::as_array)
+            .cloned()
+            .unwrap_or_default();
+        let got = response.streamed.unwrap_or(page.len());
+        fetched += got;
+        items.extend(page);
+        cursor = CURSOR_POINTERS
+            .iter()
+            .find_map(|p| response.body.pointer(p))
+            .and_then(|v| match v {
+                Value::String(s) if !s.is_empty() => Some(s.clone()),
+                Value::Number(n) => Some(n.to_string()),
+                _ => None,
+            });
+        match &mut first {
+            Some((first, _)) => first.timing.extend(response.timing),
+            None => first = Some((response, pointer)),
+        }
+
+        if let Some(limit) = opts.limit {
+            if fetched >= limit {
+                items.truncate(limit);
+                break;
+            }
+        }
+        let exhausted = match pager {
+            Pager::Cursor { .. } => cursor.is_none() || got == 0,
+            _ => got < size,
+        };
+        if exhausted {
+            break;
+        }
+    }
+
+    let (mut response, pointer) = first.ok_or_else(|| anyhow!("no pages fetched"))?;
+    if response.streamed.is_some() {
+        response.streamed = Some(fetched);
+    }
+    if pointer.is_empty() {
+        response.body = Value::Array(items);
+    } else if let Some(slot) = response.body.pointer_mut(pointer) {
+        *slot = Value::Array(items);
+    }
+    Ok((response, checkpoint))
+}
+
+/// JSON pointer of the item list in a list-shaped response, if any.
+pub fn items_pointer(value: &Value) -> Option<&'static str> {
+    ITEM_POINTERS
+        .iter()
+        .copied()
+        .find(|p| value.pointer(p).is_some_and(Value::is_array))
+}
+
+fn with_params(query: &[(String, String)], params: &[(&String, String)]) -> Vec<(String, String)> {
+    let mut out: Vec<(String, String)> = query
+        .iter()
+        .filter(|(k, _)| !params.iter().any(|(name, _)| *name == k))
+        .cloned()
+        .collect();
+    for (name, value) in params {
+        out.push(((*name).clone(), value.clone()));
+    }
+    out
+}
+
+fn page_query_range(body: &Option<Body>, offset: usize, size: usize) -> Result<Body> {
+    let Some(Body::Json(value)) = body else {
+        return Err(anyhow!("--all on query-range needs a JSON --body"));
+    };
+    let mut value = value.clone();
+    let queries = value
+        .pointer_mut("/compositeQuery/queries")
+        .and_then(Value::as_array_mut)
+        .ok_or_else(|| anyhow!("query body has no compositeQuery.queries"))?;
+    for query in queries {
+        if let Some(spec) = query.get_mut("spec").and_then(Value::as_object_mut) {
+            spec.insert("offset".to_string(), json!(offset));
+            spec.insert("limit".to_string(), json!(size));
+        }
+    }
+    Ok(Body::Json(value))
+}
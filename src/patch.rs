@@ -0,0 +1,342 @@
+use anyhow::{anyhow, Context, Result};
+use clap::Arg;
+use serde_json::Value;
+use std::{fs, io::Read};
+
+use crate::command_tree::{Operation, Resource};
+use crate::error::Failure;
+use crate::http::Body;
+use crate::output::{self, OutputOptions};
+use crate::{bodyset, expect, merge, ApiContext};
+
+pub const MERGE_PATCH: &str = "application/merge-patch+json";
+pub const JSON_PATCH: &str = "application/json-patch+json";
+
+pub fn args() -> [Arg; 2] {
+    [
+        Arg::new("patch")
+            .long("patch")
+            .value_name("JSON|@file|@-")
+            .conflicts_with_all(["body", "edit", "merge", "json-patch"])
+            .help("Send a JSON Merge Patch (RFC 7396); emulated with GET, merge, and a whole-document update when the endpoint does not take one"),
+        Arg::new("json-patch")
+            .long("json-patch")
+            .value_name("FILE|-")
+            .conflicts_with_all(["body", "edit", "merge"])
+            .help("Send JSON Patch operations (RFC 6902) from FILE; applied to the fetched resource and sent whole when the endpoint does not take them"),
+    ]
+}
+
+pub fn requested(matches: &clap::ArgMatches) -> bool {
+    ["patch", "json-patch"]
+        .iter()
+        .any(|name| matches!(matches.try_get_one::<String>(name), Ok(Some(_))))
+}
+
+/// Whether the endpoint takes `media_type` as its body.
+fn accepts(op: &Operation, media_type: &str) -> bool {
+    op.request_body.as_ref().is_some_and(|body| {
+        body.content_type == media_type
+            || body.patch_types.iter().flatten().any(|t| t == media_type)
+    })
+}
+
+/// Sends `--patch`/`--json-patch` as is to an endpoint that takes the
+/// format; otherwise fetches the resource, applies the patch locally, and
+/// sends the result with the endpoint's own content type.
+pub fn run(
+    resource: &Resource,
+    op: &Operation,
+    matches: &clap::ArgMatches,
+    ctx: &ApiContext,
+    opts: &OutputOptions,
+) -> Result<()> {
+    if bodyset::present(matches) {
+        return Err(
+            Failure::Usage("--set cannot be combined with --patch or --json-patch".into()).into(),
+        );
+    }
+    let (media_type, patch) = match matches.get_one::<String>("patch") {
+        Some(raw) => {
+            let patch: Value = serde_json::from_str(&crate::read_body_input(raw)?)
+                .context("invalid JSON in --patch")?;
+            (MERGE_PATCH, patch)
+        }
+        None => {
+            let file = matches.get_one::<String>("json-patch").expect("requested");
+            let raw = match file.as_str() {
+                "-" | "@-" => {
+                    let mut buf = String::new();
+                    std::io::stdin().read_to_string(&mut buf)?;
+                    buf
+                }
+                path => {
+                    let path = path.strip_prefix('@').unwrap_or(path);
+                    fs::read_to_string(path).with_context(|| format!("read {path}"))?
+                }
+            };
+            let patch: Value =
+                serde_json::from_str(&raw).with_context(|| format!("invalid JSON in {file}"))?;
+            if !patch.is_array() {
+                return Err(Failure::Usage(format!(
+                    "{file}: a JSON Patch is an array of operations"
+                ))
+                .into());
+            }
+            (JSON_PATCH, patch)
+        }
+    };
+    if !accepts(op, media_type) {
+        if ctx.client_opts.verbosity > 0 {
+            eprintln!(
+                "* {} does not take {media_type}; sending the patched resource",
+                op.name
+            );
+        }
+        let missing = format!(
+            "{} does not take {media_type} and {} has no GET on {} to apply it to",
+            op.name, resource.name, op.path
+        );
+        return merge::update(resource, op, matches, ctx, opts, &missing, |doc| {
+            emulate(media_type, doc, patch)
+        });
+    }
+
+    let (path, query, header_params) = crate::build_request_parts(ctx, op, matches)?;
+    let response = crate::execute_op(
+        ctx,
+        op,
+        &header_params,
+        &path,
+        &query,
+        Some(Body::Json(patch)),
+        Some(media_type),
+    )?;
+    crate::ensure_api_response(&path, &response)?;
+    output::print_response(&response, opts)?;
+    expect::check(&response, opts)
+}
+
+/// Applies a patch of `media_type` to `doc` locally, for an endpoint that
+/// only takes whole documents.
+fn emulate(media_type: &str, doc: &mut Value, patch: Value) -> Result<()> {
+    match media_type {
+        MERGE_PATCH => merge::merge(doc, patch),
+        _ => apply(doc, &patch)?,
+    }
+    Ok(())
+}
+
+/// Applies JSON Patch operations (RFC 6902) in order; `doc` is left
+/// half-patched on error, so callers patch a copy.
+pub fn apply(doc: &mut Value, ops: &Value) -> Result<()> {
+    for (i, op) in ops.as_array().into_iter().flatten().enumerate() {
+        let field = |name: &str| op.get(name).and_then(Value::as_str);
+        let name = field("op").unwrap_or_default();
+        let path = field("path").ok_or_else(|| anyhow!("json patch op {i}: missing path"))?;
+        let value = || {
+            op.get("value")
+                .cloned()
+                .ok_or_else(|| anyhow!("missing value"))
+        };
+        let from = || field("from").ok_or_else(|| anyhow!("missing from"));
+        let result = match name {
+            "add" => value().and_then(|value| add(doc, path, value)),
+            "remove" => remove(doc, path).map(drop),
+            "replace" => value().and_then(|value| {
+                let target = doc
+                    .pointer_mut(path)
+                    .ok_or_else(|| anyhow!("nothing at {path}"))?;
+                *target = value;
+                Ok(())
+            }),
+            "move" => from().and_then(|from| {
+                if path.starts_with(&format!("{from}/")) {
+                    return Err(anyhow!("cannot move {from} into itself"));
+                }
+                let value = remove(doc, from)?;
+                add(doc, path, value)
+            }),
+            "copy" => from().and_then(|from| {
+                let value = doc
+                    .pointer(from)
+                    .cloned()
+                    .ok_or_else(|| anyhow!("nothing at {from}"))?;
+                add(doc, path, value)
+            }),
+            "test" => value().and_then(|value| match doc.pointer(path) {
+                Some(found) if *found == value => Ok(()),
+                Some(found) => Err(anyhow!("test failed: {path} is {found}")),
+                None => Err(anyhow!("test failed: nothing at {path}")),
+            }),
+            other => Err(anyhow!("unknown op {other:?}")),
+        };
+        result.with_context(|| format!("json patch op {i} ({name} {path})"))?;
+    }
+    Ok(())
+}
+
+/// The container holding the last token of `path`, and that token
+/// unescaped.
+fn parent<'a>(doc: &'a mut Value, path: &str) -> Result<(&'a mut Value, String)> {
+    let split = path
+        .rfind('/')
+        .ok_or_else(|| anyhow!("{path:?} is not a JSON pointer"))?;
+    let key = path[split + 1..].replace("~1", "/").replace("~0", "~");
+    let parent = doc
+        .pointer_mut(&path[..split])
+        .ok_or_else(|| anyhow!("nothing at {}", &path[..split]))?;
+    Ok((parent, key))
+}
+
+fn add(doc: &mut Value, path: &str, value: Value) -> Result<()> {
+    if path.is_empty() {
+        *doc = value;
+        return Ok(());
+    }
+    match parent(doc, path)? {
+        (Value::Object(map), key) => {
+            map.insert(key, value);
+        }
+        (Value::Array(items), key) if key == "-" => items.push(value),
+        (Value::Array(items), key) => match key.parse::<usize>() {
+            Ok(index) if index <= items.len() => items.insert(index, value),
+            _ => return Err(anyhow!("bad array index {key:?}")),
+        },
+        _ => return Err(anyhow!("parent of {path} is not an object or array")),
+    }
+    Ok(())
+}
+
+fn remove(doc: &mut Value, path: &str) -> Result<Value> {
+    match parent(doc, path)? {
+        (Value::Object(map), key) => map.remove(&key),
+        (Value::Array(items), key) => key
+            .parse::<usize>()
+            .ok()
+            .filter(|&index| index < items.len())
+            .map(|index| items.remove(index)),
+        _ => None,
+    }
+    .ok_or_else(|| anyhow!("nothing at {path}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command_tree::RequestBodyDef;
+    use crate::schema;
+    use serde_json::json;
+
+    fn patched(doc: Value, ops: Value) -> Result<Value> {
+        let mut doc = doc;
+        apply(&mut doc, &ops)?;
+        Ok(doc)
+    }
+
+    #[test]
+    fn apply_runs_every_operation() {
+        let doc = json!({"a": {"b": 1}, "list": [1, 2], "x/y": 0});
+        assert_eq!(
+            patched(
+                doc.clone(),
+                json!([{"op": "add", "path": "/a/c", "value": 2}])
+            )
+            .unwrap(),
+            json!({"a": {"b": 1, "c": 2}, "list": [1, 2], "x/y": 0})
+        );
+        assert_eq!(
+            patched(
+                doc.clone(),
+                json!([{"op": "add", "path": "/list/-", "value": 3}])
+            )
+            .unwrap()["list"],
+            json!([1, 2, 3])
+        );
+        assert_eq!(
+            patched(
+                doc.clone(),
+                json!([{"op": "add", "path": "/list/0", "value": 0}])
+            )
+            .unwrap()["list"],
+            json!([0, 1, 2])
+        );
+        assert_eq!(
+            patched(doc.clone(), json!([{"op": "remove", "path": "/x~1y"}])).unwrap(),
+            json!({"a": {"b": 1}, "list": [1, 2]})
+        );
+        assert_eq!(
+            patched(
+                doc.clone(),
+                json!([{"op": "replace", "path": "/a/b", "value": 5}])
+            )
+            .unwrap()["a"],
+            json!({"b": 5})
+        );
+        assert_eq!(
+            patched(
+                doc.clone(),
+                json!([{"op": "move", "from": "/a/b", "path": "/b"}])
+            )
+            .unwrap(),
+            json!({"a": {}, "b": 1, "list": [1, 2], "x/y": 0})
+        );
+        assert_eq!(
+            patched(
+                doc.clone(),
+                json!([{"op": "copy", "from": "/list", "path": "/a/list"}])
+            )
+            .unwrap()["a"],
+            json!({"b": 1, "list": [1, 2]})
+        );
+        assert_eq!(
+            patched(
+                doc.clone(),
+                json!([{"op": "test", "path": "/a/b", "value": 1}])
+            )
+            .unwrap(),
+            doc
+        );
+    }
+
+    #[test]
+    fn apply_rejects_what_rfc_6902_forbids() {
+        let doc = json!({"a": {"b": 1}, "list": [1]});
+        for ops in [
+            json!([{"op": "test", "path": "/a/b", "value": 2}]),
+            json!([{"op": "replace", "path": "/missing", "value": 1}]),
+            json!([{"op": "remove", "path": "/list/1"}]),
+            json!([{"op": "add", "path": "/list/5", "value": 1}]),
+            json!([{"op": "move", "from": "/a", "path": "/a/b"}]),
+            json!([{"op": "add", "path": "/a/c"}]),
+            json!([{"op": "frobnicate", "path": "/a"}]),
+        ] {
+            assert!(patched(doc.clone(), ops.clone()).is_err(), "{ops}");
+        }
+    }
+
+    #[test]
+    fn emulated_merge_patch_sends_back_only_what_the_update_takes() {
+        // `preferences update-org-preference` takes only `value`, while its
+        // GET also returns the preference's description and allowed values.
+        let body_def = RequestBodyDef {
+            required: true,
+            content_type: "application/json".into(),
+            schema_type: "object".into(),
+            skeleton: None,
+            schema: Some(json!({"type": "object", "properties": {"value": {}}}).to_string()),
+            patch_types: None,
+        };
+        let mut doc = json!({
+            "name": "X",
+            "description": "d",
+            "allowedValues": null,
+            "valueType": "object",
+            "value": {"a": 1},
+        });
+        schema::project(&body_def, &mut doc).unwrap();
+        emulate(MERGE_PATCH, &mut doc, json!({"value": {"b": 2}})).unwrap();
+        assert_eq!(doc, json!({"value": {"a": 1, "b": 2}}));
+        schema::check(&body_def, &doc).unwrap();
+    }
+}
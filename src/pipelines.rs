@@ -0,0 +1,320 @@
+use anyhow::{anyhow, Context, Result};
+use clap::{Arg, ArgAction, Command};
+use serde_json::{json, Map, Value};
+use std::fs;
+
+use crate::apply;
+use crate::error::ApiError;
+use crate::http::Body;
+use crate::output::{self, OutputOptions};
+use crate::query;
+use crate::watch;
+use crate::ApiContext;
+
+const PIPELINES_PATH: &str = "/api/v1/logs/pipelines";
+const LATEST_PATH: &str = "/api/v1/logs/pipelines/latest";
+const PREVIEW_PATH: &str = "/api/v1/logs/pipelines/preview";
+
+pub fn extend(cmd: Command) -> Command {
+    let file = || {
+        Arg::new("file")
+            .short('f')
+            .long("file")
+            .value_name("FILE")
+            .help("Pipelines (YAML or JSON): a list, or an object with a `pipelines` list")
+    };
+    cmd.subcommand(
+        Command::new("apply")
+            .about("Replace the deployed log pipelines with the ones in a file")
+            .arg(file().required(true))
+            .arg(
+                Arg::new("dry-run")
+                    .long("dry-run")
+                    .action(ArgAction::SetTrue)
+                    .help("Show the changes without deploying them"),
+            )
+            .arg(
+                Arg::new("auto-approve")
+                    .long("auto-approve")
+                    .action(ArgAction::SetTrue)
+                    .help("Deploy without asking for confirmation"),
+            ),
+    )
+    .subcommand(
+        Command::new("simulate")
+            .about("Show how sample logs would be transformed, without deploying anything")
+            .arg(
+                Arg::new("sample")
+                    .long("sample")
+                    .value_name("NDJSON")
+                    .required(true)
+                    .help("One log per line: a JSON log record or a plain message"),
+            )
+            .arg(file().help("Pipelines to simulate (default: the deployed ones)")),
+    )
+}
+
+pub fn handle(
+    op: &str,
+    matches: &clap::ArgMatches,
+    ctx: &ApiContext,
+    opts: &OutputOptions,
+) -> Option<Result<()>> {
+    match op {
+        "apply" => Some(handle_apply(matches, ctx, opts)),
+        "simulate" => Some(handle_simulate(matches, ctx, opts)),
+        _ => None,
+    }
+}
+
+/// Saving pipelines replaces the whole set with a new version, so the plan
+/// lists deployed pipelines missing from the file as removals. Pipelines are
+/// matched by name, keep their ids, and are ordered as in the file.
+fn handle_apply(matches: &clap::ArgMatches, ctx: &ApiContext, opts: &OutputOptions) -> Result<()> {
+    let path = matches
+        .get_one::<String>("file")
+        .ok_or_else(|| anyhow!("missing --file"))?;
+    let mut desired = read_pipelines(path)?;
+    let live = deployed(ctx)?;
+
+    let mut plan = Vec::new();
+    let (mut added, mut changed, mut removed) = (0, 0, 0);
+    for pipeline in &mut desired {
+        let name = name(pipeline).to_string();
+        match live.iter().find(|p| self::name(p) == name) {
+            None => {
+                added += 1;
+                plan.push(format!("  + pipeline \"{name}\""));
+            }
+            Some(current) => {
+                if let (Some(id), Value::Object(map)) = (current.get("id"), &mut *pipeline) {
+                    map.entry("id").or_insert_with(|| id.clone());
+                }
+                let mut changes = Vec::new();
+                watch::diff(
+                    &apply::project(current, pipeline),
+                    pipeline,
+                    &mut String::new(),
+                    &mut changes,
+                );
+                if !changes.is_empty() {
+                    changed += 1;
+                    plan.push(format!("  ~ pipeline \"{name}\""));
+                    for change in &changes {
+                        let show = |key: &str| {
+                            change
+                                .get(key)
+                                .map_or("(none)".to_string(), Value::to_string)
+                        };
+                        plan.push(format!(
+                            "      {}: {} -> {}",
+                            change["path"].as_str().unwrap_or_default(),
+                            show("from"),
+                            show("to")
+                        ));
+                    }
+                }
+            }
+        }
+    }
+    for current in &live {
+        let name = name(current);
+        if !desired.iter().any(|p| self::name(p) == name) {
+            removed += 1;
+            plan.push(format!("  - pipeline \"{name}\" (not in {path})"));
+        }
+    }
+
+    if plan.is_empty() {
+        println!("No changes. The deployed pipelines match {path}.");
+        return Ok(());
+    }
+    println!("{}", plan.join("\n"));
+    println!("\nPlan: {added} to add, {changed} to change, {removed} to remove.");
+    if matches.get_flag("dry-run") {
+        return Ok(());
+    }
+    if !matches.get_flag("auto-approve") {
+        apply::confirm()?;
+    }
+    let response = ctx.execute(
+        "POST",
+        PIPELINES_PATH,
+        &[],
+        Some(Body::Json(json!({ "pipelines": desired }))),
+        Some("application/json"),
+    )?;
+    if response.status >= 400 {
+        return Err(ApiError::from_response(&response)).context("save pipelines failed");
+    }
+    let version = response
+        .body
+        .pointer("/data/version")
+        .cloned()
+        .unwrap_or(Value::Null);
+    output::print_value(
+        &json!({ "version": version, "pipelines": desired.len() }),
+        opts,
+    )
+}
+
+/// Sends the pipelines and samples to the preview endpoint and reports each
+/// log before and after, with the fields that changed.
+fn handle_simulate(
+    matches: &clap::ArgMatches,
+    ctx: &ApiContext,
+    opts: &OutputOptions,
+) -> Result<()> {
+    let pipelines = match matches.get_one::<String>("file") {
+        Some(path) => read_pipelines(path)?,
+        None => deployed(ctx)?,
+    };
+    let sample = matches
+        .get_one::<String>("sample")
+        .ok_or_else(|| anyhow!("missing --sample"))?;
+    let raw = fs::read_to_string(sample).with_context(|| format!("read {sample}"))?;
+    let logs: Vec<Value> = raw
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(sample_log)
+        .collect();
+    if logs.is_empty() {
+        return Err(anyhow!("{sample} has no log lines"));
+    }
+
+    let response = ctx.execute(
+        "POST",
+        PREVIEW_PATH,
+        &[],
+        Some(Body::Json(json!({ "pipelines": pipelines, "logs": logs }))),
+        Some("application/json"),
+    )?;
+    if response.status >= 400 {
+        return Err(ApiError::from_response(&response)).context("pipeline preview failed");
+    }
+    let data = response.body.get("data").unwrap_or(&response.body);
+    for line in data
+        .get("collectorLogs")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        eprintln!(
+            "collector: {}",
+            line.as_str().map_or(line.to_string(), str::to_string)
+        );
+    }
+    let outputs = data
+        .get("logs")
+        .and_then(Value::as_array)
+        .cloned()
+        .unwrap_or_default();
+    let results: Vec<Value> = logs
+        .iter()
+        .enumerate()
+        .map(|(index, input)| {
+            let output = outputs.get(index).cloned().unwrap_or(Value::Null);
+            let mut changes = Vec::new();
+            if !output.is_null() {
+                watch::diff(input, &output, &mut String::new(), &mut changes);
+            }
+            json!({ "index": index, "input": input, "output": output, "changes": changes })
+        })
+        .collect();
+    output::print_value(&Value::Array(results), opts)
+}
+
+fn read_pipelines(path: &str) -> Result<Vec<Value>> {
+    let raw = fs::read_to_string(path).with_context(|| format!("read {path}"))?;
+    let parsed: Value = serde_yaml::from_str(&raw).with_context(|| format!("parse {path}"))?;
+    let list = match parsed {
+        Value::Array(items) => items,
+        Value::Object(mut map) => match map.remove("pipelines") {
+            Some(Value::Array(items)) => items,
+            _ => return Err(anyhow!("{path}: expected a `pipelines` list")),
+        },
+        _ => return Err(anyhow!("{path}: expected a list of pipelines")),
+    };
+    list.into_iter()
+        .enumerate()
+        .map(|(i, mut pipeline)| {
+            let map = pipeline
+                .as_object_mut()
+                .ok_or_else(|| anyhow!("{path}: pipeline {} is not an object", i + 1))?;
+            if !map.get("name").is_some_and(Value::is_string) {
+                return Err(anyhow!("{path}: pipeline {} has no name", i + 1));
+            }
+            map.insert("orderId".to_string(), json!(i + 1));
+            map.entry("enabled").or_insert(json!(true));
+            Ok(pipeline)
+        })
+        .collect()
+}
+
+/// The deployed pipelines; none when nothing was ever saved.
+fn deployed(ctx: &ApiContext) -> Result<Vec<Value>> {
+    let response = ctx.execute("GET", LATEST_PATH, &[], None, None)?;
+    if response.status == 404 {
+        return Ok(Vec::new());
+    }
+    if response.status >= 400 {
+        return Err(ApiError::from_response(&response)).context("get pipelines failed");
+    }
+    Ok(response
+        .body
+        .pointer("/data/pipelines")
+        .and_then(Value::as_array)
+        .cloned()
+        .unwrap_or_default())
+}
+
+fn name(pipeline: &Value) -> &str {
+    pipeline
+        .get("name")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+}
+
+/// A sample line as the preview endpoint's log record: plain text becomes
+/// the body, and `attributes`/`resources` maps are split into the typed
+/// `attributes_string`/`attributes_int`/... fields the API expects.
+fn sample_log(line: &str) -> Value {
+    let mut log = match serde_json::from_str::<Value>(line) {
+        Ok(Value::Object(map)) => map,
+        _ => Map::from_iter([("body".to_string(), json!(line))]),
+    };
+    if let Some(Value::Object(attributes)) = log.remove("attributes") {
+        for (key, value) in attributes {
+            let field = match &value {
+                Value::Bool(_) => "attributes_bool",
+                Value::Number(n) if n.is_i64() || n.is_u64() => "attributes_int",
+                Value::Number(_) => "attributes_float",
+                _ => "attributes_string",
+            };
+            let value = match value {
+                Value::String(_) | Value::Number(_) | Value::Bool(_) => value,
+                other => json!(other.to_string()),
+            };
+            log.entry(field)
+                .or_insert_with(|| json!({}))
+                .as_object_mut()
+                .map(|map| map.insert(key, value));
+        }
+    }
+    if let Some(Value::Object(resources)) = log.remove("resources") {
+        let strings: Map<String, Value> = resources
+            .into_iter()
+            .map(|(k, v)| match v {
+                Value::String(_) => (k, v),
+                other => (k, json!(other.to_string())),
+            })
+            .collect();
+        log.insert("resources_string".to_string(), Value::Object(strings));
+    }
+    log.entry("timestamp")
+        .or_insert_with(|| json!(query::now_millis() * 1_000_000));
+    for field in ["attributes_string", "resources_string"] {
+        log.entry(field).or_insert_with(|| json!({}));
+    }
+    Value::Object(log)
+}
@@ -0,0 +1,141 @@
+use anyhow::{anyhow, Result};
+use clap::Command;
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::{env, fs};
+
+use crate::auth::AuthMode;
+use crate::command_tree::CommandTree;
+use crate::ApiContext;
+
+const PREFIX: &str = "signoz-";
+
+pub fn command() -> Command {
+    Command::new("plugins")
+        .about("List `signoz-<name>` executables on PATH, run as `signoz <name> ...`")
+}
+
+/// The plugin for the first subcommand on the command line, when that names
+/// neither a resource nor a built-in command.
+pub fn find(root: &Command, tree: &CommandTree, args: &[String]) -> Option<PathBuf> {
+    let name = &args[crate::first_subcommand(root, args)?];
+    if crate::is_command(root, tree, name)
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return None;
+    }
+    installed().remove(name.as_str())
+}
+
+/// Runs the plugin with the arguments after its name. The resolved
+/// instance and credentials are passed as SIGNOZ_API_URL, SIGNOZ_API_KEY,
+/// and SIGNOZ_TOKEN (only the one `--auth` selects), and this binary as
+/// SIGNOZ_CLI. On Unix the plugin replaces this process.
+pub fn run(path: &Path, matches: &clap::ArgMatches, ctx: &ApiContext) -> Result<()> {
+    let args: Vec<OsString> = matches
+        .subcommand()
+        .and_then(|(_, sub)| sub.get_many::<OsString>(""))
+        .into_iter()
+        .flatten()
+        .cloned()
+        .collect();
+    let mut cmd = std::process::Command::new(path);
+    cmd.args(&args).env("SIGNOZ_API_URL", &ctx.base_url);
+    match (&ctx.api_key, matches!(ctx.auth_mode, AuthMode::Token)) {
+        (Some(key), false) => cmd.env("SIGNOZ_API_KEY", key),
+        _ => cmd.env_remove("SIGNOZ_API_KEY"),
+    };
+    match (&ctx.token, matches!(ctx.auth_mode, AuthMode::ApiKey)) {
+        (Some(token), false) => cmd.env("SIGNOZ_TOKEN", token),
+        _ => cmd.env_remove("SIGNOZ_TOKEN"),
+    };
+    if let Ok(exe) = env::current_exe() {
+        cmd.env("SIGNOZ_CLI", exe);
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        let err = cmd.exec();
+        Err(anyhow!("run plugin {}: {err}", path.display()))
+    }
+    #[cfg(not(unix))]
+    {
+        use anyhow::Context;
+        let status = cmd
+            .status()
+            .with_context(|| format!("run plugin {}", path.display()))?;
+        std::process::exit(status.code().unwrap_or(1));
+    }
+}
+
+pub fn handle_list(tree: &CommandTree, root: &Command) -> Result<()> {
+    let plugins = installed();
+    if plugins.is_empty() {
+        eprintln!("No {PREFIX}<name> executables on PATH.");
+        return Ok(());
+    }
+    for (name, path) in plugins {
+        if crate::is_command(root, tree, &name) {
+            println!(
+                "{name}\t{}\t(shadowed by the built-in command)",
+                path.display()
+            );
+        } else {
+            println!("{name}\t{}", path.display());
+        }
+    }
+    Ok(())
+}
+
+/// Plugin name to executable; the first match on PATH wins, as for the
+/// shell.
+fn installed() -> BTreeMap<String, PathBuf> {
+    let mut found = BTreeMap::new();
+    let Some(path) = env::var_os("PATH") else {
+        return found;
+    };
+    for dir in env::split_paths(&path) {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let file = entry.path();
+            let Some(name) = plugin_name(&file) else {
+                continue;
+            };
+            if is_executable(&file) {
+                found.entry(name).or_insert(file);
+            }
+        }
+    }
+    found
+}
+
+fn plugin_name(file: &Path) -> Option<String> {
+    let name = if cfg!(windows) {
+        file.file_stem()?
+    } else {
+        file.file_name()?
+    };
+    let name = name.to_str()?.strip_prefix(PREFIX)?;
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+#[cfg(unix)]
+fn is_executable(file: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    fs::metadata(file).is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(file: &Path) -> bool {
+    file.extension().is_some_and(|ext| {
+        ext.eq_ignore_ascii_case("exe")
+            || ext.eq_ignore_ascii_case("cmd")
+            || ext.eq_ignore_ascii_case("bat")
+    }) && file.is_file()
+}
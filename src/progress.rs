@@ -0,0 +1,119 @@
+use std::{
+    io::{self, IsTerminal, Write},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+/// Quick operations finish before anything is drawn.
+const DELAY: Duration = Duration::from_millis(800);
+const TICK: Duration = Duration::from_millis(100);
+const FRAMES: &[char] = &['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+/// One status line at a time: nested or concurrent indicators (a request
+/// inside a paginated fetch, bench workers) leave it to the outer one.
+static SHOWING: AtomicBool = AtomicBool::new(false);
+
+/// Turns indicators on when stderr is a terminal, unless `--no-progress`,
+/// `-q`, or `-v` (whose request log would interleave with the line).
+pub fn init(matches: &clap::ArgMatches) {
+    let enabled = io::stderr().is_terminal()
+        && !matches.get_flag("no-progress")
+        && !matches.get_flag("quiet")
+        && matches.get_count("verbose") == 0;
+    ENABLED.store(enabled, Ordering::SeqCst);
+}
+
+/// Turns indicators off for long-running modes whose stderr is a request
+/// log, like `serve`.
+pub fn disable() {
+    ENABLED.store(false, Ordering::SeqCst);
+}
+
+struct State {
+    message: Mutex<String>,
+    done: AtomicBool,
+}
+
+/// A spinner with a message and the elapsed time on stderr, drawn once the
+/// operation has taken longer than [`DELAY`] and cleared when dropped.
+pub struct Progress {
+    state: Option<Arc<State>>,
+    drawer: Option<JoinHandle<bool>>,
+}
+
+impl Progress {
+    pub fn start(message: impl Into<String>) -> Self {
+        if !ENABLED.load(Ordering::SeqCst) || SHOWING.swap(true, Ordering::SeqCst) {
+            return Self {
+                state: None,
+                drawer: None,
+            };
+        }
+        let state = Arc::new(State {
+            message: Mutex::new(message.into()),
+            done: AtomicBool::new(false),
+        });
+        let shared = state.clone();
+        let drawer = thread::spawn(move || draw(&shared));
+        Self {
+            state: Some(state),
+            drawer: Some(drawer),
+        }
+    }
+
+    /// Replaces the message, e.g. with a running count.
+    pub fn set(&self, message: impl Into<String>) {
+        if let Some(state) = &self.state {
+            *state.message.lock().unwrap_or_else(|e| e.into_inner()) = message.into();
+        }
+    }
+}
+
+impl Drop for Progress {
+    fn drop(&mut self) {
+        let Some(state) = self.state.take() else {
+            return;
+        };
+        state.done.store(true, Ordering::SeqCst);
+        let drawn = self.drawer.take().is_some_and(|drawer| {
+            drawer.thread().unpark();
+            drawer.join().unwrap_or(false)
+        });
+        if drawn {
+            eprint!("\r\x1b[K");
+            let _ = io::stderr().flush();
+        }
+        SHOWING.store(false, Ordering::SeqCst);
+    }
+}
+
+/// Redraws the line every [`TICK`] until done; returns whether anything was
+/// drawn.
+fn draw(state: &State) -> bool {
+    let started = Instant::now();
+    let mut frame = 0;
+    while !state.done.load(Ordering::SeqCst) {
+        thread::park_timeout(TICK);
+        if started.elapsed() < DELAY || state.done.load(Ordering::SeqCst) {
+            continue;
+        }
+        let message = state
+            .message
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone();
+        eprint!(
+            "\r\x1b[K{} {message} ({:.1}s)",
+            FRAMES[frame % FRAMES.len()],
+            started.elapsed().as_secs_f64()
+        );
+        let _ = io::stderr().flush();
+        frame += 1;
+    }
+    frame > 0
+}
@@ -0,0 +1,78 @@
+use anyhow::{anyhow, Context, Result};
+use clap::{Arg, ArgAction, Command};
+use serde_json::{json, Value};
+
+use crate::error::ApiError;
+use crate::metrics;
+use crate::output::{self, OutputOptions};
+use crate::query;
+use crate::timeparse;
+use crate::ApiContext;
+
+pub fn command() -> Command {
+    Command::new("promql")
+        .about("Run a PromQL query over a time range (draw it with --render chart or table)")
+        .arg(
+            Arg::new("query")
+                .value_name("PROMQL")
+                .required(true)
+                .help("PromQL expression, e.g. 'sum(rate(http_requests_total[5m]))'"),
+        )
+        .arg(
+            Arg::new("since")
+                .long("since")
+                .value_name("DURATION|TIME")
+                .help("How far back (15m, 1h; default 1h) or a start time (RFC 3339, epoch ms)"),
+        )
+        .arg(timeparse::until_arg())
+        .arg(
+            Arg::new("step")
+                .long("step")
+                .value_name("DURATION")
+                .default_value("60s")
+                .help("Interval between points"),
+        )
+        .arg(
+            Arg::new("print-query")
+                .long("print-query")
+                .action(ArgAction::SetTrue)
+                .help("Print the generated query_range body instead of running it"),
+        )
+}
+
+/// Prints the result as `metrics query` does: one `{labels, values}` entry
+/// per series, which `--render chart` and `--render table` understand.
+pub fn handle(matches: &clap::ArgMatches, ctx: &ApiContext, opts: &OutputOptions) -> Result<()> {
+    let expr = matches
+        .get_one::<String>("query")
+        .map(|q| q.trim())
+        .filter(|q| !q.is_empty())
+        .ok_or_else(|| anyhow!("PromQL query is empty"))?;
+    let (start, end) = timeparse::range(
+        ctx.since("promql", matches, "1h"),
+        timeparse::until(matches),
+    )?;
+    let step = timeparse::parse_duration_ms(
+        matches
+            .get_one::<String>("step")
+            .map_or("60s", String::as_str),
+    )? / 1000;
+
+    let body = query::query_range_body(
+        start,
+        end,
+        "time_series",
+        vec![json!({
+            "type": "promql",
+            "spec": { "name": "A", "query": expr, "step": step.max(1), "disabled": false },
+        })],
+    );
+    if let Some(result) = query::dry_run(matches, &body, opts) {
+        return result;
+    }
+    let response = query::run_query_range(ctx, body)?;
+    if response.status >= 400 {
+        return Err(ApiError::from_response(&response)).context("promql query failed");
+    }
+    output::print_value(&Value::Array(metrics::series(&response.body)), opts)
+}
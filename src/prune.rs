@@ -0,0 +1,211 @@
+use anyhow::{anyhow, Context, Result};
+use clap::{Arg, ArgAction, Command};
+use serde_json::{json, Value};
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+};
+
+use crate::command_tree::{CommandTree, Operation, Resource};
+use crate::error::{ApiError, Failure};
+use crate::output::{self, OutputOptions};
+use crate::{merge, rename, transform, ApiContext};
+
+/// Resources that get a `prune` verb: (resource, list op, delete op, dotted
+/// id field and dotted name field of a listed item). Rows whose ops are
+/// missing from the tree are skipped.
+const PRUNABLE: &[(&str, &str, &str, &str, &str)] = &[
+    (
+        "dashboards",
+        "list-dashboards",
+        "delete-dashboard",
+        "uuid",
+        "data.title",
+    ),
+    ("rules", "list-rules", "delete-rule", "id", "alert"),
+];
+
+struct Spec<'a> {
+    list: &'a Operation,
+    delete: &'a Operation,
+    id_field: &'static str,
+    name_field: &'static str,
+}
+
+fn spec(resource: &Resource) -> Option<Spec<'_>> {
+    let (_, list, delete, id_field, name_field) =
+        PRUNABLE.iter().find(|(name, ..)| *name == resource.name)?;
+    let op = |name: &str| resource.ops.iter().find(|op| op.name == name);
+    Some(Spec {
+        list: op(list)?,
+        delete: op(delete)?,
+        id_field,
+        name_field,
+    })
+}
+
+pub fn extend(resource: &Resource, cmd: Command) -> Command {
+    if spec(resource).is_none() {
+        return cmd;
+    }
+    cmd.subcommand(
+        Command::new("prune")
+            .about(format!(
+                "Delete every {} matching --where, after showing them",
+                resource.name
+            ))
+            .long_about(format!(
+                "Lists {0}, keeps those matching every --where (e.g. --where 'title startsWith \
+                 \"tmp-\"'), shows them, and deletes them once confirmed. At least one --where \
+                 is required.",
+                resource.name
+            ))
+            .arg(
+                Arg::new("dry-run")
+                    .long("dry-run")
+                    .action(ArgAction::SetTrue)
+                    .help("Print the matches without deleting anything"),
+            )
+            .arg(
+                Arg::new("yes")
+                    .long("yes")
+                    .short('y')
+                    .action(ArgAction::SetTrue)
+                    .conflicts_with("dry-run")
+                    .help("Delete without asking"),
+            )
+            .arg(
+                Arg::new("concurrency")
+                    .long("concurrency")
+                    .value_name("N")
+                    .value_parser(clap::value_parser!(usize))
+                    .default_value("4")
+                    .help("Deletions in flight at once"),
+            ),
+    )
+}
+
+pub fn handle(
+    tree: &CommandTree,
+    resource: &str,
+    op: &str,
+    matches: &clap::ArgMatches,
+    ctx: &ApiContext,
+    opts: &OutputOptions,
+) -> Option<Result<()>> {
+    if op != "prune" {
+        return None;
+    }
+    let resource = tree.resources.iter().find(|r| r.name == resource)?;
+    let spec = spec(resource)?;
+    Some(handle_prune(&resource.name, &spec, matches, ctx, opts))
+}
+
+fn handle_prune(
+    resource: &str,
+    spec: &Spec,
+    matches: &clap::ArgMatches,
+    ctx: &ApiContext,
+    opts: &OutputOptions,
+) -> Result<()> {
+    if opts.conditions.is_empty() {
+        return Err(Failure::Usage(format!(
+            "prune needs at least one --where to pick the {resource} to delete"
+        ))
+        .into());
+    }
+    let response = ctx.execute(&spec.list.method, &spec.list.path, &[], None, None)?;
+    if response.status >= 400 {
+        return Err(ApiError::from_response(&response))
+            .with_context(|| format!("{} failed", spec.list.name));
+    }
+    // Dashboards list under `data`, rules under `data.rules`.
+    let data = response.body.get("data").unwrap_or(&response.body);
+    let mut items = data.get(resource).unwrap_or(data).clone();
+    if !items.is_array() {
+        return Err(anyhow!("{} did not return a list", spec.list.name));
+    }
+    transform::select(&mut items, &opts.conditions, &[])?;
+    let text = |item: &Value, path: &str| match transform::lookup(item, path) {
+        Some(Value::String(s)) => s.clone(),
+        Some(Value::Null) | None => String::new(),
+        Some(other) => other.to_string(),
+    };
+    let targets: Vec<(String, String)> = items
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|item| (text(item, spec.id_field), text(item, spec.name_field)))
+        .filter(|(id, _)| !id.is_empty())
+        .collect();
+
+    // The conditions picked the targets; the summary is printed as is.
+    let opts = OutputOptions {
+        conditions: Vec::new(),
+        ..opts.clone()
+    };
+    if targets.is_empty() {
+        eprintln!("No {resource} match.");
+        return output::print_value(&json!([]), &opts);
+    }
+    for (id, name) in &targets {
+        eprintln!("  - {name} ({id})");
+    }
+    if matches.get_flag("dry-run") {
+        eprintln!("{} {resource} would be deleted.", targets.len());
+        let rows = targets
+            .iter()
+            .map(|(id, name)| json!({ "id": id, "name": name }))
+            .collect();
+        return output::print_value(&Value::Array(rows), &opts);
+    }
+    if !matches.get_flag("yes") {
+        merge::confirm(&format!("Delete these {} {resource}?", targets.len()))?;
+    }
+
+    let concurrency = (*matches.get_one::<usize>("concurrency").expect("default")).max(1);
+    let next = AtomicUsize::new(0);
+    let results = Mutex::new(vec![Value::Null; targets.len()]);
+    thread::scope(|scope| {
+        for _ in 0..concurrency.min(targets.len()) {
+            scope.spawn(|| loop {
+                let i = next.fetch_add(1, Ordering::SeqCst);
+                let Some((id, name)) = targets.get(i) else {
+                    break;
+                };
+                let mut row = json!({ "id": id, "name": name });
+                match delete(spec.delete, id, ctx) {
+                    Ok(()) => row["deleted"] = json!(true),
+                    Err(err) => {
+                        row["deleted"] = json!(false);
+                        row["error"] = json!(format!("{err:#}"));
+                    }
+                }
+                if let Ok(mut results) = results.lock() {
+                    results[i] = row;
+                }
+            });
+        }
+    });
+    let results = results.into_inner().unwrap_or_default();
+    let failed = results
+        .iter()
+        .filter(|r| r["deleted"] != json!(true))
+        .count();
+    output::print_value(&Value::Array(results), &opts)?;
+    match failed {
+        0 => Ok(()),
+        n => Err(anyhow!("{n} of {} deletions failed", targets.len())),
+    }
+}
+
+fn delete(op: &Operation, id: &str, ctx: &ApiContext) -> Result<()> {
+    let response = ctx.execute(&op.method, &rename::with_id(op, id), &[], None, None)?;
+    if response.status >= 400 {
+        return Err(ApiError::from_response(&response).into());
+    }
+    Ok(())
+}
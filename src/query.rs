@@ -0,0 +1,394 @@
+use anyhow::{anyhow, Context, Result};
+use clap::{Arg, Command};
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::http::{Body, HttpResponse};
+use crate::output::{self, OutputOptions};
+use crate::timeparse;
+use crate::ApiContext;
+
+mod build;
+
+pub const QUERY_RANGE_PATH: &str = "/api/v5/query_range";
+
+const DEFAULT_WINDOW_MS: u64 = 60 * 60 * 1000;
+/// Assumed distinct values per group-by key when estimating series counts.
+const ASSUMED_GROUP_CARDINALITY: f64 = 50.0;
+
+#[derive(Debug, Deserialize)]
+struct MultiQueryFile {
+    start: Option<u64>,
+    end: Option<u64>,
+    queries: BTreeMap<String, QuerySpec>,
+}
+
+#[derive(Debug, Deserialize)]
+struct QuerySpec {
+    /// Inline query_range request body.
+    body: Option<Value>,
+    /// Path to a JSON/YAML query_range body, relative to the queries file.
+    file: Option<String>,
+}
+
+pub fn command() -> Command {
+    Command::new("query")
+        .about("Query helpers built on query_range")
+        .subcommand_required(true)
+        .arg_required_else_help(true)
+        .subcommand(
+            Command::new("multi")
+                .about("Run named queries concurrently over a shared time range")
+                .arg(
+                    Arg::new("file")
+                        .short('f')
+                        .long("file")
+                        .value_name("PATH")
+                        .required(true)
+                        .help("YAML/JSON file with a `queries` map"),
+                )
+                .arg(Arg::new("start").long("start").value_name("TIME").help(
+                    "Range start: now-1h, RFC 3339, or epoch ms (default: file value or end - 1h)",
+                ))
+                .arg(
+                    Arg::new("end")
+                        .long("end")
+                        .value_name("TIME")
+                        .help("Range end: now, RFC 3339, or epoch ms (default: file value or now)"),
+                ),
+        )
+        .subcommand(
+            Command::new("build")
+                .about("Build a logs/traces/metrics query in a line REPL, then run or save it"),
+        )
+}
+
+pub fn handle(matches: &clap::ArgMatches, ctx: &ApiContext, opts: &OutputOptions) -> Result<()> {
+    match matches.subcommand() {
+        Some(("multi", m)) => handle_multi(m, ctx, opts),
+        Some(("build", _)) => build::run(ctx, opts),
+        _ => Err(anyhow!("unknown query command")),
+    }
+}
+
+fn handle_multi(matches: &clap::ArgMatches, ctx: &ApiContext, opts: &OutputOptions) -> Result<()> {
+    let path = matches
+        .get_one::<String>("file")
+        .ok_or_else(|| anyhow!("missing --file"))?;
+    let raw = fs::read_to_string(path).with_context(|| format!("read {path}"))?;
+    let spec: MultiQueryFile = serde_yaml::from_str(&raw).context("invalid queries file")?;
+    if spec.queries.is_empty() {
+        return Err(anyhow!("queries file defines no queries"));
+    }
+
+    let end = time_arg(matches, "end")?
+        .or(spec.end)
+        .unwrap_or_else(now_millis);
+    let start = time_arg(matches, "start")?
+        .or(spec.start)
+        .unwrap_or_else(|| end.saturating_sub(DEFAULT_WINDOW_MS));
+
+    let base_dir = Path::new(path).parent().unwrap_or(Path::new("."));
+    let mut bodies = Vec::new();
+    for (name, query) in &spec.queries {
+        let mut body = load_query_body(name, query, base_dir)?;
+        set_time_range(&mut body, start, end)?;
+        bodies.push((name.clone(), body));
+    }
+    if matches.get_flag("estimate") {
+        let estimates: Map<String, Value> = bodies
+            .iter()
+            .map(|(name, body)| (name.clone(), estimate(body)))
+            .collect();
+        return output::print_value(&Value::Object(estimates), opts);
+    }
+
+    let results: Vec<(String, Result<HttpResponse>)> = std::thread::scope(|scope| {
+        let handles: Vec<_> = bodies
+            .into_iter()
+            .map(|(name, body)| {
+                let handle = scope.spawn(move || run_query_range(ctx, body));
+                (name, handle)
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|(name, handle)| {
+                let result = handle
+                    .join()
+                    .unwrap_or_else(|_| Err(anyhow!("query thread panicked")));
+                (name, result)
+            })
+            .collect()
+    });
+
+    let mut out = Map::new();
+    let mut errors = Map::new();
+    for (name, result) in results {
+        match result {
+            Ok(response) if response.status < 400 => {
+                out.insert(name, response.body);
+            }
+            Ok(response) => {
+                errors.insert(
+                    name,
+                    json!({ "status": response.status, "body": response.body }),
+                );
+            }
+            Err(err) => {
+                errors.insert(name, json!({ "error": err.to_string() }));
+            }
+        }
+    }
+
+    let failed = errors.len();
+    let mut doc = json!({ "start": start, "end": end, "results": out });
+    if failed > 0 {
+        doc["errors"] = Value::Object(errors);
+    }
+    output::print_value(&doc, opts)?;
+    if failed > 0 {
+        return Err(anyhow!("{failed} queries failed"));
+    }
+    Ok(())
+}
+
+pub fn run_query_range(ctx: &ApiContext, body: Value) -> Result<HttpResponse> {
+    warn_if_heavy(&body);
+    ctx.execute(
+        "POST",
+        QUERY_RANGE_PATH,
+        &[],
+        Some(Body::Json(body)),
+        Some("application/json"),
+    )
+}
+
+/// Handles `--print-query` and `--estimate` for commands that build a
+/// query_range body; `None` means the query should run.
+pub fn dry_run(
+    matches: &clap::ArgMatches,
+    body: &Value,
+    opts: &OutputOptions,
+) -> Option<Result<()>> {
+    if flag(matches, "estimate") {
+        return Some(output::print_value(&estimate(body), opts));
+    }
+    if flag(matches, "print-query") {
+        return Some(output::print_value(body, opts));
+    }
+    None
+}
+
+/// Whether [`dry_run`] would answer instead of the query running.
+pub fn dry_run_requested(matches: &clap::ArgMatches) -> bool {
+    flag(matches, "estimate") || flag(matches, "print-query")
+}
+
+fn flag(matches: &clap::ArgMatches, name: &str) -> bool {
+    matches!(matches.try_get_one::<bool>(name), Ok(Some(true)))
+}
+
+/// Heuristic cost of a query_range body from its time range, step, and
+/// group-by keys (each assumed to have ~50 values); no server round trip.
+pub fn estimate(body: &Value) -> Value {
+    let start = body.get("start").and_then(Value::as_u64).unwrap_or(0);
+    let end = body.get("end").and_then(Value::as_u64).unwrap_or(start);
+    let window_secs = end.saturating_sub(start) as f64 / 1000.0;
+    let hours = window_secs / 3600.0;
+    let request_type = body
+        .get("requestType")
+        .and_then(Value::as_str)
+        .unwrap_or("time_series");
+    let queries: Vec<Value> = body
+        .pointer("/compositeQuery/queries")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|query| {
+            let spec = query.get("spec")?;
+            let signal = spec.get("signal").and_then(Value::as_str).unwrap_or("");
+            let group_by = spec
+                .get("groupBy")
+                .and_then(Value::as_array)
+                .map_or(0, Vec::len);
+            let step = spec
+                .get("stepInterval")
+                .or_else(|| spec.get("step"))
+                .and_then(Value::as_f64)
+                .filter(|s| *s > 0.0)
+                .unwrap_or((window_secs / 300.0).max(60.0));
+            let filtered = spec
+                .pointer("/filter/expression")
+                .and_then(Value::as_str)
+                .is_some_and(|f| !f.trim().is_empty());
+            let series = ASSUMED_GROUP_CARDINALITY.powi(group_by as i32).min(1e9);
+            let points = match request_type {
+                "time_series" => series * (window_secs / step).ceil().max(1.0),
+                _ => series,
+            };
+            let mut notes = Vec::new();
+            if hours > 24.0 {
+                notes.push(format!("scans {:.1} days of {signal}", hours / 24.0));
+            }
+            if group_by >= 2 {
+                notes.push(format!("{group_by} group-by keys multiply series"));
+            }
+            if !filtered && !matches!(signal, "metrics" | "") {
+                notes.push("no filter: full scan of the window".to_string());
+            }
+            let cost = if (hours > 24.0 && (group_by >= 2 || !filtered)) || points > 1e6 {
+                "high"
+            } else if hours > 6.0 || points > 1e5 {
+                "medium"
+            } else {
+                "low"
+            };
+            Some(json!({
+                "name": spec.get("name"),
+                "signal": signal,
+                "group_by": group_by,
+                "step_seconds": step,
+                "estimated_series": series,
+                "estimated_points": points,
+                "cost": cost,
+                "notes": notes,
+            }))
+        })
+        .collect();
+    let cost = ["high", "medium", "low"]
+        .into_iter()
+        .find(|level| queries.iter().any(|q| q["cost"] == *level))
+        .unwrap_or("low");
+    json!({
+        "window_hours": (hours * 100.0).round() / 100.0,
+        "request_type": request_type,
+        "cost": cost,
+        "queries": queries,
+    })
+}
+
+/// Prints a stderr warning before running a query estimated as heavy.
+pub fn warn_if_heavy(body: &Value) {
+    let estimate = estimate(body);
+    if estimate["cost"] != "high" {
+        return;
+    }
+    let notes: Vec<&str> = estimate["queries"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .flat_map(|q| q["notes"].as_array().into_iter().flatten())
+        .filter_map(Value::as_str)
+        .collect();
+    eprintln!(
+        "warning: expensive query ({}); narrow the range or use --estimate to check first",
+        notes.join("; ")
+    );
+}
+
+fn load_query_body(name: &str, query: &QuerySpec, base_dir: &Path) -> Result<Value> {
+    match (&query.body, &query.file) {
+        (Some(body), None) => Ok(body.clone()),
+        (None, Some(file)) => {
+            let path = base_dir.join(file);
+            let raw = fs::read_to_string(&path)
+                .with_context(|| format!("read {} for query {name}", path.display()))?;
+            serde_yaml::from_str(&raw).with_context(|| format!("invalid body for query {name}"))
+        }
+        _ => Err(anyhow!(
+            "query {name} needs exactly one of `body` or `file`"
+        )),
+    }
+}
+
+fn time_arg(matches: &clap::ArgMatches, name: &str) -> Result<Option<u64>> {
+    matches
+        .get_one::<String>(name)
+        .map(|v| timeparse::parse_time_ms(v).map_err(|err| anyhow!("invalid --{name}: {err}")))
+        .transpose()
+}
+
+pub fn set_time_range(body: &mut Value, start: u64, end: u64) -> Result<()> {
+    let obj = body
+        .as_object_mut()
+        .ok_or_else(|| anyhow!("query body must be a JSON object"))?;
+    obj.insert("start".to_string(), json!(start));
+    obj.insert("end".to_string(), json!(end));
+    Ok(())
+}
+
+/// A v5 builder query for `signal` with the given spec fields merged in.
+pub fn builder_query(name: &str, signal: &str, spec: Value) -> Value {
+    let mut base = json!({ "name": name, "signal": signal, "disabled": false });
+    if let (Some(base), Value::Object(extra)) = (base.as_object_mut(), spec) {
+        base.extend(extra);
+    }
+    json!({ "type": "builder_query", "spec": base })
+}
+
+pub fn query_range_body(start: u64, end: u64, request_type: &str, queries: Vec<Value>) -> Value {
+    json!({
+        "start": start,
+        "end": end,
+        "requestType": request_type,
+        "variables": {},
+        "compositeQuery": { "queries": queries },
+    })
+}
+
+/// Flattens a scalar query_range result into `(group values, first
+/// aggregation value)` rows.
+pub fn scalar_rows(body: &Value) -> Vec<(Vec<String>, Option<f64>)> {
+    let Some(result) = body.pointer("/data/data/results/0") else {
+        return Vec::new();
+    };
+    let columns = result
+        .get("columns")
+        .and_then(Value::as_array)
+        .cloned()
+        .unwrap_or_default();
+    let is_group = |i: usize| {
+        columns
+            .get(i)
+            .and_then(|c| c.get("columnType"))
+            .and_then(Value::as_str)
+            == Some("group")
+    };
+    let rows = result
+        .get("data")
+        .and_then(Value::as_array)
+        .cloned()
+        .unwrap_or_default();
+    rows.iter()
+        .filter_map(Value::as_array)
+        .map(|row| {
+            let mut groups = Vec::new();
+            let mut value = None;
+            for (i, cell) in row.iter().enumerate() {
+                if is_group(i) {
+                    groups.push(match cell {
+                        Value::String(s) => s.clone(),
+                        other => other.to_string(),
+                    });
+                } else if value.is_none() {
+                    value = cell.as_f64();
+                }
+            }
+            (groups, value)
+        })
+        .collect()
+}
+
+pub fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
@@ -0,0 +1,262 @@
+use anyhow::{Context, Result};
+use serde_json::{json, Value};
+use std::{
+    fs,
+    io::{self, BufRead, Write},
+};
+
+use crate::metrics;
+use crate::output::{self, OutputOptions};
+use crate::timeparse;
+use crate::ApiContext;
+
+use super::{builder_query, query_range_body, run_query_range};
+use crate::error::ApiError;
+
+const FIELD_KEYS_PATH: &str = "/api/v1/fields/keys";
+const MAX_SUGGESTIONS: usize = 20;
+
+const HELP: &str = "\
+commands:
+  f KEY OP VALUE   add a filter clause, e.g. f service.name = 'api'
+  f EXPR           add a raw filter expression
+  a EXPR           aggregation: count(), avg(duration_nano), p99(duration_nano);
+                   for metrics: a METRIC [sum|avg|min|max|count|rate|increase|p50..p99]
+  g KEY            group by KEY
+  t SINCE          time range, e.g. t 6h or t 2024-05-01T10:00:00Z (default 1h)
+  l N              row limit for raw queries (default 100)
+  u                undo the last filter, aggregation, or group-by
+  ? TEXT           suggest field keys containing TEXT
+  x                execute and print the result
+  s FILE           save the request body as JSON (use with --body @FILE or query multi)
+  q                quit";
+
+/// Query under construction; turned into a query_range body on each change.
+struct Draft {
+    signal: String,
+    filters: Vec<String>,
+    aggregation: Option<Value>,
+    group_by: Vec<String>,
+    since: String,
+    limit: u64,
+    /// Which list each edit went to, for `u`.
+    history: Vec<char>,
+}
+
+impl Draft {
+    fn body(&self) -> Result<Value> {
+        let (start, end) = timeparse::range(&self.since, None)?;
+        let mut spec = json!({
+            "filter": { "expression": self.filters.join(" AND ") },
+            "groupBy": self.group_by.iter().map(|key| json!({ "name": key })).collect::<Vec<_>>(),
+        });
+        let request_type = match &self.aggregation {
+            Some(aggregation) => {
+                spec["aggregations"] = json!([aggregation]);
+                spec["stepInterval"] = json!(60);
+                "time_series"
+            }
+            None => {
+                spec["limit"] = json!(self.limit);
+                spec["order"] = json!([{ "key": { "name": "timestamp" }, "direction": "desc" }]);
+                "raw"
+            }
+        };
+        Ok(query_range_body(
+            start,
+            end,
+            request_type,
+            vec![builder_query("A", &self.signal, spec)],
+        ))
+    }
+}
+
+/// A line REPL: each command edits the draft and the generated body is
+/// shown again; a failed command is reported and the prompt comes back.
+/// Prompts and previews go to stderr, results to stdout, so commands can
+/// also be piped in.
+pub fn run(ctx: &ApiContext, opts: &OutputOptions) -> Result<()> {
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    let mut prompt = |text: &str| -> Result<Option<String>> {
+        eprint!("{text}");
+        io::stderr().flush()?;
+        lines
+            .next()
+            .transpose()
+            .context("read input")
+            .map(|line| line.map(|l| l.trim().to_string()))
+    };
+
+    let signal = loop {
+        let Some(answer) = prompt("signal [logs/traces/metrics] (logs): ")? else {
+            return Ok(());
+        };
+        match answer.as_str() {
+            "" => break "logs".to_string(),
+            "logs" | "traces" | "metrics" => break answer,
+            other => eprintln!("unknown signal {other}"),
+        }
+    };
+    let mut draft = Draft {
+        signal,
+        filters: Vec::new(),
+        aggregation: None,
+        group_by: Vec::new(),
+        since: "1h".to_string(),
+        limit: 100,
+        history: Vec::new(),
+    };
+    eprintln!("{HELP}");
+
+    loop {
+        let Some(line) = prompt(&format!("{}> ", draft.signal))? else {
+            return Ok(());
+        };
+        let (command, rest) = line.split_once(' ').unwrap_or((line.as_str(), ""));
+        let rest = rest.trim();
+        let edited = match command {
+            "" => false,
+            "q" | "quit" => return Ok(()),
+            "h" | "help" => {
+                eprintln!("{HELP}");
+                false
+            }
+            "?" => {
+                match field_keys(ctx, &draft.signal, rest) {
+                    Ok(keys) if keys.is_empty() => eprintln!("no matching fields"),
+                    Ok(keys) => eprintln!("{}", keys.join("\n")),
+                    Err(err) => eprintln!("warning: {err:#}"),
+                }
+                false
+            }
+            "f" if !rest.is_empty() => {
+                draft.filters.push(rest.to_string());
+                draft.history.push('f');
+                true
+            }
+            "a" if !rest.is_empty() => {
+                draft.aggregation = Some(match draft.signal.as_str() {
+                    "metrics" => {
+                        let (metric, agg) = rest.split_once(' ').unwrap_or((rest, "avg"));
+                        metrics::aggregation(metric, agg.trim())
+                    }
+                    _ => json!({ "expression": rest }),
+                });
+                draft.history.push('a');
+                true
+            }
+            "g" if !rest.is_empty() => {
+                draft.group_by.push(rest.to_string());
+                draft.history.push('g');
+                true
+            }
+            "t" if !rest.is_empty() => match timeparse::range(rest, None) {
+                Ok(_) => {
+                    draft.since = rest.to_string();
+                    true
+                }
+                Err(err) => {
+                    eprintln!("error: {err:#}");
+                    false
+                }
+            },
+            "l" => match rest.parse() {
+                Ok(limit) => {
+                    draft.limit = limit;
+                    true
+                }
+                Err(_) => {
+                    eprintln!("usage: l N");
+                    false
+                }
+            },
+            "u" => {
+                match draft.history.pop() {
+                    Some('f') => drop(draft.filters.pop()),
+                    Some('a') => draft.aggregation = None,
+                    Some('g') => drop(draft.group_by.pop()),
+                    _ => eprintln!("nothing to undo"),
+                }
+                true
+            }
+            "x" => {
+                if draft.signal == "metrics" && draft.aggregation.is_none() {
+                    eprintln!("metrics queries need an aggregation: a METRIC [AGG]");
+                    continue;
+                }
+                let result = draft
+                    .body()
+                    .and_then(|body| run_query_range(ctx, body))
+                    .and_then(|response| match response.status {
+                        400.. => Err(ApiError::from_response(&response)).context("query failed"),
+                        _ => output::print_value(&response.body, opts),
+                    });
+                if let Err(err) = result {
+                    eprintln!("error: {err:#}");
+                }
+                false
+            }
+            "s" if !rest.is_empty() => {
+                match save(&draft, rest) {
+                    Ok(()) => eprintln!(
+                        "saved {rest}; run it with: signoz {} query-range --body @{rest}",
+                        draft.signal
+                    ),
+                    Err(err) => eprintln!("error: {err:#}"),
+                }
+                false
+            }
+            _ => {
+                eprintln!("unknown or incomplete command `{line}` (h for help)");
+                false
+            }
+        };
+        if edited {
+            eprintln!("{}", serde_json::to_string_pretty(&draft.body()?)?);
+        }
+    }
+}
+
+fn save(draft: &Draft, path: &str) -> Result<()> {
+    let text = serde_json::to_string_pretty(&draft.body()?)? + "\n";
+    fs::write(path, text).with_context(|| format!("write {path}"))
+}
+
+/// Field keys for `signal` whose name contains `search`, from the telemetry
+/// keys API.
+fn field_keys(ctx: &ApiContext, signal: &str, search: &str) -> Result<Vec<String>> {
+    let query = [
+        ("signal".to_string(), signal.to_string()),
+        ("searchText".to_string(), search.to_string()),
+        ("limit".to_string(), MAX_SUGGESTIONS.to_string()),
+    ];
+    let response = ctx.execute("GET", FIELD_KEYS_PATH, &query, None, None)?;
+    if response.status >= 400 {
+        return Err(ApiError::from_response(&response)).context("field lookup failed");
+    }
+    let mut names = Vec::new();
+    collect_names(
+        response
+            .body
+            .pointer("/data/keys")
+            .unwrap_or(&response.body),
+        &mut names,
+    );
+    names.retain(|name| name.contains(search));
+    names.sort();
+    names.dedup();
+    names.truncate(MAX_SUGGESTIONS);
+    Ok(names)
+}
+
+fn collect_names(value: &Value, out: &mut Vec<String>) {
+    match value {
+        Value::Object(map) => match map.get("name") {
+            Some(Value::String(name)) => out.push(name.clone()),
+            _ => map.values().for_each(|v| collect_names(v, out)),
+        },
+        Value::Array(items) => items.iter().for_each(|item| collect_names(item, out)),
+        _ => {}
+    }
+}
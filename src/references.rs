@@ -0,0 +1,218 @@
+use anyhow::{anyhow, Result};
+use clap::{Arg, ArgAction};
+use serde_json::Value;
+use std::collections::{BTreeSet, HashMap};
+
+use crate::ApiContext;
+
+const METADATA_PATH: &str = "/api/v2/metrics/metadata";
+
+/// Variables SigNoz fills in itself, usable in any dashboard query.
+const BUILTIN_VARIABLES: &[&str] = &[
+    "SIGNOZ_START_TIME",
+    "SIGNOZ_END_TIME",
+    "start_timestamp",
+    "end_timestamp",
+    "start_timestamp_ms",
+    "end_timestamp_ms",
+    "start_timestamp_nano",
+    "end_timestamp_nano",
+    "start_datetime",
+    "end_datetime",
+];
+
+pub fn skip_arg() -> Arg {
+    Arg::new("no-ref-check")
+        .long("no-ref-check")
+        .action(ArgAction::SetTrue)
+        .help("Skip checking that referenced channels, metrics, and dashboard variables exist")
+}
+
+/// Collects references that will not resolve on the target instance, so
+/// `finish` can fail with one report before anything is sent.
+pub struct Checker<'a> {
+    ctx: &'a ApiContext,
+    /// `None` when the server could not say either way.
+    metrics: HashMap<String, Option<bool>>,
+    unresolved: Vec<String>,
+}
+
+impl<'a> Checker<'a> {
+    pub fn new(ctx: &'a ApiContext) -> Self {
+        Self {
+            ctx,
+            metrics: HashMap::new(),
+            unresolved: Vec::new(),
+        }
+    }
+
+    /// Metric names in metrics queries (`aggregateAttribute.key` of v3/v4
+    /// builder queries, `metricName` of v5 aggregations) must be known to
+    /// the metrics explorer.
+    pub fn metrics(&mut self, owner: &str, definition: &Value) {
+        let mut names = BTreeSet::new();
+        metric_names(definition, &mut names);
+        for name in names {
+            match self.metric_exists(&name) {
+                Some(true) => {}
+                Some(false) => self.unresolved.push(format!(
+                    "{owner}: metric \"{name}\" has no data on this instance"
+                )),
+                None => eprintln!("warning: {owner}: could not verify metric \"{name}\""),
+            }
+        }
+    }
+
+    /// Variables used in widget queries (`$name`, `{{.name}}`, `[[name]]`)
+    /// must be defined in the dashboard's `variables`.
+    pub fn dashboard_variables(&mut self, owner: &str, data: &Value) {
+        let defined: BTreeSet<&str> = data
+            .get("variables")
+            .and_then(Value::as_object)
+            .into_iter()
+            .flatten()
+            .flat_map(|(key, var)| [Some(key.as_str()), var.get("name").and_then(Value::as_str)])
+            .flatten()
+            .collect();
+        let mut used = BTreeSet::new();
+        for widget in data
+            .get("widgets")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+        {
+            strings(widget.get("query"), &mut |s| used_variables(s, &mut used));
+        }
+        for var in data
+            .get("variables")
+            .and_then(Value::as_object)
+            .into_iter()
+            .flat_map(|vars| vars.values())
+        {
+            strings(var.get("queryValue"), &mut |s| used_variables(s, &mut used));
+        }
+        for name in used {
+            if !defined.contains(name.as_str()) && !BUILTIN_VARIABLES.contains(&name.as_str()) {
+                let known = defined.iter().copied().collect::<Vec<_>>().join(", ");
+                self.unresolved.push(format!(
+                    "{owner}: variable \"{name}\" is used but not defined (defined: {})",
+                    if known.is_empty() { "none" } else { &known }
+                ));
+            }
+        }
+    }
+
+    /// Every entry of a rule's `preferredChannels` must name a channel in
+    /// `known`.
+    pub fn channels(&mut self, owner: &str, rule: &Value, known: &BTreeSet<String>) {
+        for name in rule
+            .get("preferredChannels")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+        {
+            if !known.contains(name) {
+                self.unresolved.push(format!(
+                    "{owner}: notification channel \"{name}\" does not exist"
+                ));
+            }
+        }
+    }
+
+    pub fn finish(self) -> Result<()> {
+        if self.unresolved.is_empty() {
+            return Ok(());
+        }
+        Err(anyhow!(
+            "unresolved references; nothing was changed:\n  {}\n(pass --no-ref-check to skip this check, e.g. for metrics not reported yet)",
+            self.unresolved.join("\n  ")
+        ))
+    }
+
+    fn metric_exists(&mut self, name: &str) -> Option<bool> {
+        if let Some(known) = self.metrics.get(name) {
+            return *known;
+        }
+        let query = [("metricName".to_string(), name.to_string())];
+        let exists = match self.ctx.execute("GET", METADATA_PATH, &query, None, None) {
+            Ok(response) if response.status == 404 => Some(false),
+            Ok(response) if response.status < 400 => {
+                Some(response.body.get("data").is_some_and(|d| !d.is_null()))
+            }
+            _ => None,
+        };
+        self.metrics.insert(name.to_string(), exists);
+        exists
+    }
+}
+
+fn metric_names(value: &Value, out: &mut BTreeSet<String>) {
+    match value {
+        Value::Object(map) => {
+            let source = map.get("dataSource").or_else(|| map.get("signal"));
+            if source.and_then(Value::as_str) == Some("metrics") {
+                if let Some(key) = map
+                    .get("aggregateAttribute")
+                    .and_then(|a| a.get("key"))
+                    .and_then(Value::as_str)
+                {
+                    insert_metric(key, out);
+                }
+            }
+            if let Some(Value::String(name)) = map.get("metricName") {
+                insert_metric(name, out);
+            }
+            map.values().for_each(|v| metric_names(v, out));
+        }
+        Value::Array(items) => items.iter().for_each(|v| metric_names(v, out)),
+        _ => {}
+    }
+}
+
+/// Skips empty names and ones filled in from a dashboard variable.
+fn insert_metric(name: &str, out: &mut BTreeSet<String>) {
+    if !name.is_empty() && !name.contains('$') && !name.contains("{{") {
+        out.insert(name.to_string());
+    }
+}
+
+fn strings(value: Option<&Value>, visit: &mut dyn FnMut(&str)) {
+    match value {
+        Some(Value::String(s)) => visit(s),
+        Some(Value::Array(items)) => items.iter().for_each(|v| strings(Some(v), visit)),
+        Some(Value::Object(map)) => map.values().for_each(|v| strings(Some(v), visit)),
+        _ => {}
+    }
+}
+
+fn used_variables(text: &str, out: &mut BTreeSet<String>) {
+    let ident = |s: &str| -> String {
+        s.chars()
+            .take_while(|c| c.is_ascii_alphanumeric() || *c == '_')
+            .collect()
+    };
+    let mut rest = text;
+    while let Some(at) = rest.find(['$', '{', '[']) {
+        let tail = &rest[at..];
+        let name = if let Some(after) = tail.strip_prefix('$') {
+            ident(after)
+        } else if let Some(after) = tail.strip_prefix("{{") {
+            after
+                .trim_start()
+                .strip_prefix('.')
+                .map(ident)
+                .unwrap_or_default()
+        } else if let Some(after) = tail.strip_prefix("[[") {
+            ident(after.trim_start())
+        } else {
+            String::new()
+        };
+        if name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+            && !name.starts_with("__")
+        {
+            out.insert(name);
+        }
+        rest = &tail[1..];
+    }
+}
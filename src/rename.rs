@@ -0,0 +1,117 @@
+use anyhow::{anyhow, Context, Result};
+use clap::{Arg, Command};
+use serde_json::Value;
+
+use crate::command_tree::{CommandTree, Operation, Resource};
+use crate::error::ApiError;
+use crate::http::Body;
+use crate::ids;
+use crate::output::{self, OutputOptions};
+use crate::ApiContext;
+
+/// Resources that get a `rename` verb: (resource, get op, update op, dotted
+/// name field in the fetched object). Rows whose ops are missing from the
+/// tree are skipped.
+const RENAMEABLE: &[(&str, &str, &str, &str)] = &[
+    (
+        "dashboards",
+        "get-dashboard",
+        "update-dashboard",
+        "data.title",
+    ),
+    ("rules", "get-rule", "update-rule", "alert"),
+    ("users", "get-user", "update-user", "displayName"),
+];
+
+fn spec(resource: &Resource) -> Option<(&Operation, &Operation, &'static str)> {
+    let (_, get, update, field) = RENAMEABLE
+        .iter()
+        .find(|(name, ..)| *name == resource.name)?;
+    let op = |name: &str| resource.ops.iter().find(|op| op.name == name);
+    Some((op(get)?, op(update)?, field))
+}
+
+pub fn extend(resource: &Resource, cmd: Command) -> Command {
+    let Some((_, _, field)) = spec(resource) else {
+        return cmd;
+    };
+    cmd.subcommand(
+        Command::new("rename")
+            .about(format!("Change `{field}` (GET, modify, PUT)"))
+            .arg(Arg::new("id").required(true).help("Resource id"))
+            .arg(Arg::new("name").required(true).help("New name")),
+    )
+}
+
+pub fn handle(
+    tree: &CommandTree,
+    resource: &str,
+    op: &str,
+    matches: &clap::ArgMatches,
+    ctx: &ApiContext,
+    opts: &OutputOptions,
+) -> Option<Result<()>> {
+    if op != "rename" {
+        return None;
+    }
+    let resource = tree.resources.iter().find(|r| r.name == resource)?;
+    let (get, update, field) = spec(resource)?;
+    Some(handle_rename(get, update, field, matches, ctx, opts))
+}
+
+fn handle_rename(
+    get: &Operation,
+    update: &Operation,
+    field: &str,
+    matches: &clap::ArgMatches,
+    ctx: &ApiContext,
+    opts: &OutputOptions,
+) -> Result<()> {
+    let id = matches
+        .get_one::<String>("id")
+        .ok_or_else(|| anyhow!("missing id"))?;
+    let name = matches
+        .get_one::<String>("name")
+        .ok_or_else(|| anyhow!("missing name"))?;
+    let id = &ids::expand(&ctx.base_url, &get.path, id)?;
+
+    let response = ctx.execute(&get.method, &with_id(get, id), &[], None, None)?;
+    if response.status >= 400 {
+        return Err(ApiError::from_response(&response))
+            .with_context(|| format!("{} failed", get.name));
+    }
+    let mut object = match response.body {
+        Value::Object(mut map) if map.contains_key("status") && map.contains_key("data") => {
+            map.remove("data").unwrap_or(Value::Null)
+        }
+        other => other,
+    };
+    let slot = field
+        .split('.')
+        .try_fold(&mut object, |value, key| value.get_mut(key))
+        .ok_or_else(|| anyhow!("{} response has no `{field}`", get.name))?;
+    *slot = Value::String(name.clone());
+
+    let response = ctx.execute(
+        &update.method,
+        &with_id(update, id),
+        &[],
+        Some(Body::Json(object)),
+        Some("application/json"),
+    )?;
+    output::print_response(&response, opts)?;
+    if response.status >= 400 {
+        return Err(ApiError::from_response(&response).into());
+    }
+    Ok(())
+}
+
+/// The op's path with its single path parameter set to `id`.
+pub fn with_id(op: &Operation, id: &str) -> String {
+    op.params
+        .iter()
+        .filter(|p| p.location == "path")
+        .fold(op.path.clone(), |path, p| {
+            path.replace(&format!("{{{}}}", p.param_name), &urlencoding::encode(id))
+        })
+}
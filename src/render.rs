@@ -0,0 +1,558 @@
+use serde_json::Value;
+use std::{collections::HashMap, fmt::Write as _};
+
+use crate::output::{ColumnType, Table};
+use crate::timeparse;
+
+/// `--render` modes: terminal views for recognized response shapes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Render {
+    Waterfall,
+    Logs,
+    Chart,
+    Table,
+}
+
+impl Render {
+    pub fn from_matches(matches: &clap::ArgMatches) -> Option<Self> {
+        match matches.get_one::<String>("render")?.as_str() {
+            "waterfall" => Some(Self::Waterfall),
+            "logs" => Some(Self::Logs),
+            "chart" => Some(Self::Chart),
+            "table" => Some(Self::Table),
+            _ => None,
+        }
+    }
+}
+
+/// Renders `value` in `mode`, or `None` when its shape is not recognized
+/// (the caller then falls back to JSON). `color` enables ANSI styling;
+/// `humanize` shows duration and byte-size columns in readable units;
+/// `columns` picks and orders table columns.
+pub fn render(
+    value: &Value,
+    mode: Render,
+    color: bool,
+    humanize: bool,
+    columns: Option<&[String]>,
+) -> Option<String> {
+    match mode {
+        Render::Waterfall => waterfall(value),
+        Render::Logs => logs(value, color),
+        Render::Chart => chart(value),
+        Render::Table => table(value, humanize, columns),
+    }
+}
+
+const SPARK: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+const CHART_WIDTH: usize = 60;
+
+/// A sparkline per series with min/max/last. Accepts `metrics query` output
+/// (`[{labels, values: [[ts, v]]}]`) or a raw time_series query_range
+/// response.
+fn chart(value: &Value) -> Option<String> {
+    let series: Vec<(String, Vec<f64>)> = match value {
+        Value::Array(items) => items
+            .iter()
+            .map(|s| {
+                let points = s.get("values")?.as_array()?;
+                let values = points
+                    .iter()
+                    .filter_map(|p| p.get(1).and_then(number))
+                    .collect();
+                Some((series_label(s.get("labels")?), values))
+            })
+            .collect::<Option<_>>()?,
+        Value::Object(_) => value
+            .pointer("/data/data/results")?
+            .as_array()?
+            .iter()
+            .flat_map(|result| {
+                let name = result
+                    .get("queryName")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default()
+                    .to_string();
+                result
+                    .get("aggregations")
+                    .and_then(Value::as_array)
+                    .into_iter()
+                    .flatten()
+                    .flat_map(|agg| {
+                        agg.get("series")
+                            .and_then(Value::as_array)
+                            .into_iter()
+                            .flatten()
+                    })
+                    .map(move |s| {
+                        let labels: serde_json::Map<String, Value> = s
+                            .get("labels")
+                            .and_then(Value::as_array)
+                            .into_iter()
+                            .flatten()
+                            .filter_map(|l| {
+                                Some((
+                                    l.pointer("/key/name")?.as_str()?.to_string(),
+                                    l.get("value")?.clone(),
+                                ))
+                            })
+                            .collect();
+                        let values = s
+                            .get("values")
+                            .and_then(Value::as_array)
+                            .into_iter()
+                            .flatten()
+                            .filter_map(|p| p.get("value").and_then(number))
+                            .collect();
+                        let label = series_label(&Value::Object(labels));
+                        (format!("{name} {label}").trim().to_string(), values)
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect(),
+        _ => return None,
+    };
+    if series.is_empty() {
+        return None;
+    }
+
+    let label_width = series
+        .iter()
+        .map(|(label, _)| label.chars().count())
+        .max()
+        .unwrap_or(0)
+        .min(40);
+    let mut out = String::new();
+    for (label, values) in &series {
+        let label = truncate(label, 40);
+        if values.is_empty() {
+            let _ = writeln!(out, "{label:<label_width$}  (no data)");
+            continue;
+        }
+        let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let last = values[values.len() - 1];
+        let _ = writeln!(
+            out,
+            "{label:<label_width$}  {}  min {} max {} last {}",
+            sparkline(values, min, max),
+            compact(min),
+            compact(max),
+            compact(last)
+        );
+    }
+    Some(out.trim_end().to_string())
+}
+
+/// Buckets `values` into at most `CHART_WIDTH` cells (averaging each bucket).
+fn sparkline(values: &[f64], min: f64, max: f64) -> String {
+    let buckets = values.len().min(CHART_WIDTH);
+    (0..buckets)
+        .map(|b| {
+            let from = b * values.len() / buckets;
+            let to = ((b + 1) * values.len() / buckets).max(from + 1);
+            let avg = values[from..to].iter().sum::<f64>() / (to - from) as f64;
+            let level = if max > min {
+                ((avg - min) / (max - min) * (SPARK.len() - 1) as f64).round() as usize
+            } else {
+                SPARK.len() / 2
+            };
+            SPARK[level.min(SPARK.len() - 1)]
+        })
+        .collect()
+}
+
+fn series_label(labels: &Value) -> String {
+    match labels {
+        Value::Object(map) if !map.is_empty() => map
+            .iter()
+            .map(|(k, v)| {
+                format!(
+                    "{k}={}",
+                    v.as_str().map_or_else(|| v.to_string(), str::to_string)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(","),
+        _ => "(all)".to_string(),
+    }
+}
+
+fn number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.parse().ok(),
+        _ => None,
+    }
+}
+
+/// Short human number: `1.2k`, `3.4M`, `0.012`.
+fn compact(n: f64) -> String {
+    let abs = n.abs();
+    if abs >= 1e9 {
+        format!("{:.1}G", n / 1e9)
+    } else if abs >= 1e6 {
+        format!("{:.1}M", n / 1e6)
+    } else if abs >= 1e4 {
+        format!("{:.1}k", n / 1e3)
+    } else if abs >= 100.0 || n.fract() == 0.0 {
+        format!("{n:.0}")
+    } else {
+        format!("{n:.3}")
+    }
+}
+
+const TABLE_CELL_WIDTH: usize = 40;
+
+/// The response's items as aligned columns (flattened like `--output`),
+/// numbers right-aligned.
+fn table(value: &Value, humanize: bool, only: Option<&[String]>) -> Option<String> {
+    let mut data = Table::from_value(value).ok()?;
+    if let Some(only) = only {
+        data.columns = only
+            .iter()
+            .filter_map(|name| data.columns.iter().find(|(n, _)| n == name).cloned())
+            .collect();
+    }
+    if data.columns.is_empty() {
+        return None;
+    }
+    let columns: Vec<(&str, Option<Unit>, bool)> = data
+        .columns
+        .iter()
+        .map(|(name, kind)| {
+            let unit = unit_hint(name).filter(|_| humanize && *kind != ColumnType::Text);
+            (name.as_str(), unit, *kind != ColumnType::Text)
+        })
+        .collect();
+    let rows: Vec<Vec<String>> = data
+        .rows
+        .iter()
+        .map(|row| {
+            columns
+                .iter()
+                .map(|(name, unit, _)| {
+                    let cell = match (row.get(*name), unit) {
+                        (None | Some(Value::Null), _) => String::new(),
+                        (Some(v), Some(unit)) => {
+                            number(v).map_or_else(|| v.to_string(), |n| unit.format(n))
+                        }
+                        (Some(Value::String(s)), None) => s.clone(),
+                        (Some(v), None) => v.to_string(),
+                    };
+                    truncate(&cell, TABLE_CELL_WIDTH)
+                })
+                .collect()
+        })
+        .collect();
+    let widths: Vec<usize> = columns
+        .iter()
+        .enumerate()
+        .map(|(i, (name, _, _))| {
+            rows.iter()
+                .map(|row| row[i].chars().count())
+                .chain([name.chars().count().min(TABLE_CELL_WIDTH)])
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    let line = |cells: &[String], header: bool| {
+        cells
+            .iter()
+            .zip(&columns)
+            .zip(&widths)
+            .map(|((cell, (_, _, numeric)), width)| {
+                if *numeric && !header {
+                    format!("{cell:>width$}")
+                } else {
+                    format!("{cell:<width$}")
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+    let header: Vec<String> = columns
+        .iter()
+        .map(|(name, _, _)| truncate(name, TABLE_CELL_WIDTH))
+        .collect();
+    let mut out = line(&header, true);
+    for row in &rows {
+        out.push('\n');
+        out.push_str(&line(row, false));
+    }
+    Some(out)
+}
+
+/// Units recognized from field names, since the API schema does not
+/// describe them: SigNoz reports span durations in nanoseconds
+/// (`durationNano`, `duration_nano`), a few timings in milliseconds
+/// (`durationMs`), and sizes in bytes (`bytesScanned`, `*_bytes`).
+#[derive(Clone, Copy)]
+enum Unit {
+    Nanos,
+    Millis,
+    Bytes,
+}
+
+fn unit_hint(column: &str) -> Option<Unit> {
+    let raw = column.rsplit('.').next().unwrap_or(column).to_lowercase();
+    let field = raw.replace(['_', '-'], "");
+    if field.contains("bytes") {
+        Some(Unit::Bytes)
+    } else if field.ends_with("nano") || field.ends_with("nanos") || raw.ends_with("_ns") {
+        Some(Unit::Nanos)
+    } else if field.ends_with("durationms") || raw.ends_with("_ms") {
+        Some(Unit::Millis)
+    } else {
+        None
+    }
+}
+
+impl Unit {
+    fn format(self, n: f64) -> String {
+        match self {
+            Self::Nanos => human_duration(n),
+            Self::Millis => human_duration(n * 1e6),
+            Self::Bytes => human_bytes(n),
+        }
+    }
+}
+
+/// `850 ns`, `12.3 µs`, `1.24 s`, `3.5 min`, `2.1 h`.
+pub fn human_duration(ns: f64) -> String {
+    const STEPS: [(f64, &str); 6] = [
+        (3600e9, "h"),
+        (60e9, "min"),
+        (1e9, "s"),
+        (1e6, "ms"),
+        (1e3, "µs"),
+        (1.0, "ns"),
+    ];
+    match STEPS.iter().find(|(scale, _)| ns.abs() >= *scale) {
+        Some((scale, unit)) => format!("{} {unit}", significant(ns / scale)),
+        None => format!("{ns:.0} ns"),
+    }
+}
+
+/// IEC sizes: `512 B`, `1.5 KiB`, `3.1 GiB`.
+fn human_bytes(bytes: f64) -> String {
+    const UNITS: [&str; 6] = ["B", "KiB", "MiB", "GiB", "TiB", "PiB"];
+    let mut value = bytes;
+    let mut unit = 0;
+    while value.abs() >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    match unit {
+        0 => format!("{value:.0} B"),
+        _ => format!("{value:.1} {}", UNITS[unit]),
+    }
+}
+
+/// Three significant digits: `1.24`, `12.3`, `123`.
+fn significant(n: f64) -> String {
+    match n.abs() {
+        a if a >= 100.0 => format!("{n:.0}"),
+        a if a >= 10.0 => format!("{n:.1}"),
+        _ => format!("{n:.2}"),
+    }
+}
+
+/// One `timestamp level service message` line per log row. Accepts a single
+/// row, an array of rows, or a raw query_range response.
+fn logs(value: &Value, color: bool) -> Option<String> {
+    let rows: Vec<&Value> = match value {
+        Value::Array(items) => items.iter().collect(),
+        Value::Object(_) => match value.pointer("/data/data/results/0/rows") {
+            Some(Value::Array(rows)) => rows.iter().collect(),
+            _ => vec![value],
+        },
+        _ => return None,
+    };
+    let lines: Vec<String> = rows
+        .into_iter()
+        .map(|row| log_line(row, color))
+        .collect::<Option<_>>()?;
+    Some(lines.join("\n"))
+}
+
+fn log_line(row: &Value, color: bool) -> Option<String> {
+    let data = row.get("data").unwrap_or(row);
+    let body = match data.get("body")? {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    };
+    let level = data
+        .get("severity_text")
+        .and_then(Value::as_str)
+        .filter(|s| !s.is_empty())
+        .unwrap_or("-")
+        .to_uppercase();
+    let service = ["/resources_string/service.name", "/resource/service.name"]
+        .iter()
+        .find_map(|p| data.pointer(p))
+        .or_else(|| data.get("service.name"))
+        .and_then(Value::as_str)
+        .unwrap_or("-");
+    let timestamp = match row.get("timestamp").or_else(|| data.get("timestamp")) {
+        Some(Value::String(s)) if s.contains('T') => s.clone(),
+        Some(Value::Number(n)) => n
+            .as_u64()
+            .map(|ns| timeparse::format_rfc3339(ns / 1_000_000))
+            .unwrap_or_default(),
+        Some(Value::String(s)) => s
+            .parse::<u64>()
+            .map(|ns| timeparse::format_rfc3339(ns / 1_000_000))
+            .unwrap_or_else(|_| s.clone()),
+        _ => "-".to_string(),
+    };
+    let level_text = format!("{level:<5}");
+    let (level_text, service) = if color {
+        (
+            format!("\x1b[{}m{level_text}\x1b[0m", level_style(&level)),
+            format!("\x1b[{}m{service}\x1b[0m", service_color(service)),
+        )
+    } else {
+        (level_text, service.to_string())
+    };
+    Some(format!(
+        "{timestamp} {level_text} {service} {}",
+        body.replace('\n', "\\n")
+    ))
+}
+
+/// ANSI style for an upper-cased log level.
+pub fn level_style(level: &str) -> &'static str {
+    match level {
+        "FATAL" | "CRITICAL" | "EMERGENCY" => "1;35",
+        "ERROR" => "31",
+        "WARN" | "WARNING" => "33",
+        "INFO" => "32",
+        "DEBUG" | "TRACE" => "2",
+        _ => "0",
+    }
+}
+
+/// A stable color per service name, so interleaved services (e.g. from
+/// `logs tail --services`) are easy to tell apart.
+pub fn service_color(service: &str) -> &'static str {
+    const PALETTE: [&str; 8] = ["36", "34", "35", "32", "96", "94", "95", "92"];
+    // FNV-1a: cheap and the same on every run.
+    let hash = service.bytes().fold(0x811c_9dc5_u32, |hash, byte| {
+        (hash ^ u32::from(byte)).wrapping_mul(0x0100_0193)
+    });
+    PALETTE[hash as usize % PALETTE.len()]
+}
+
+const NAME_WIDTH: usize = 44;
+const BAR_WIDTH: usize = 40;
+
+/// Span tree (from `traces show`) with each span's bar placed and scaled on
+/// the trace's timeline.
+fn waterfall(value: &Value) -> Option<String> {
+    struct Span<'a> {
+        id: &'a str,
+        parent: &'a str,
+        service: &'a str,
+        operation: &'a str,
+        start: u64,
+        duration_ns: u64,
+    }
+    let spans: Vec<Span> = value
+        .as_array()?
+        .iter()
+        .map(|span| {
+            let text = |key: &str| span.get(key).and_then(Value::as_str).unwrap_or_default();
+            Some(Span {
+                id: span.get("spanID")?.as_str()?,
+                parent: text("parentSpanID"),
+                service: text("service"),
+                operation: text("operation"),
+                start: span.get("start_ns").and_then(Value::as_u64).unwrap_or(0),
+                duration_ns: (span.get("duration_ms")?.as_f64()? * 1e6) as u64,
+            })
+        })
+        .collect::<Option<_>>()?;
+    if spans.is_empty() {
+        return None;
+    }
+
+    let begin = spans.iter().map(|s| s.start).min()?;
+    let finish = spans.iter().map(|s| s.start + s.duration_ns).max()?;
+    let total = (finish - begin).max(1);
+
+    // Spans whose parent is missing from the result are shown as roots.
+    let ids: HashMap<&str, usize> = spans.iter().enumerate().map(|(i, s)| (s.id, i)).collect();
+    let mut children: HashMap<&str, Vec<usize>> = HashMap::new();
+    let mut roots = Vec::new();
+    for (i, span) in spans.iter().enumerate() {
+        if ids.contains_key(span.parent) && span.parent != span.id {
+            children.entry(span.parent).or_default().push(i);
+        } else {
+            roots.push(i);
+        }
+    }
+    let by_start = |list: &mut Vec<usize>| list.sort_by_key(|&i| spans[i].start);
+    by_start(&mut roots);
+    children.values_mut().for_each(by_start);
+
+    let trace_id = value
+        .pointer("/0/traceID")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "trace {trace_id}  {} spans  {}",
+        spans.len(),
+        format_ms(total)
+    );
+
+    let mut stack: Vec<(usize, usize)> = roots.iter().rev().map(|&i| (i, 0)).collect();
+    let mut visited = vec![false; spans.len()];
+    while let Some((i, depth)) = stack.pop() {
+        if std::mem::replace(&mut visited[i], true) {
+            continue;
+        }
+        let span = &spans[i];
+        let label = format!("{}{} {}", "  ".repeat(depth), span.service, span.operation);
+        let offset = ((span.start - begin) as u128 * BAR_WIDTH as u128 / total as u128) as usize;
+        let offset = offset.min(BAR_WIDTH - 1);
+        let width = ((span.duration_ns as u128 * BAR_WIDTH as u128).div_ceil(total as u128)
+            as usize)
+            .clamp(1, BAR_WIDTH - offset);
+        let _ = writeln!(
+            out,
+            "{:<NAME_WIDTH$} {:>10} |{}{}{}|",
+            truncate(&label, NAME_WIDTH),
+            format_ms(span.duration_ns),
+            " ".repeat(offset),
+            "█".repeat(width),
+            " ".repeat(BAR_WIDTH - offset - width),
+        );
+        if let Some(kids) = children.get(span.id) {
+            stack.extend(kids.iter().rev().map(|&k| (k, depth + 1)));
+        }
+        // Spans caught in a parent cycle are unreachable from any root.
+        if stack.is_empty() {
+            stack.extend(visited.iter().position(|v| !v).map(|k| (k, 0)));
+        }
+    }
+    Some(out.trim_end().to_string())
+}
+
+fn format_ms(ns: u64) -> String {
+    format!("{:.2} ms", ns as f64 / 1e6)
+}
+
+fn truncate(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
+    }
+    let mut cut: String = text.chars().take(width - 1).collect();
+    cut.push('…');
+    cut
+}
@@ -0,0 +1,343 @@
+use anyhow::{anyhow, Context, Result};
+use clap::{Arg, ArgAction, Command};
+use serde_json::{json, Map, Value};
+use std::{cell::RefCell, collections::BTreeMap, fs, thread, time::Duration};
+
+use crate::command_tree::CommandTree;
+use crate::expect::{self, Check, Expectations};
+use crate::http::HttpResponse;
+use crate::interrupt;
+use crate::output::{self, OutputOptions};
+use crate::template;
+use crate::timeparse;
+use crate::ApiContext;
+
+const STEP_FIELDS: &[&str] = &[
+    "name",
+    "resource",
+    "op",
+    "params",
+    "body",
+    "if",
+    "expect",
+    "expect_status",
+    "retries",
+    "retry_delay",
+];
+const DEFAULT_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+pub fn command() -> Command {
+    Command::new("run")
+        .about("Run a runbook: ordered steps that can use earlier responses")
+        .long_about(
+            "A runbook is a YAML or JSON file with `vars` defaults, as in a template, and a \
+             list of `steps`, each naming an operation like a `signoz batch` line. Steps run \
+             in order; each may use {{name}} for a variable and {{steps.NAME.status}} or \
+             {{steps.NAME.body.data.id}} for an earlier step's response. `if` skips a step \
+             unless a check such as `.steps.create.status == 200` holds; `expect` and \
+             `expect_status` take --expect-json and --expect-status checks; `retries` and \
+             `retry_delay` (default 1s) retry a failed step. The first step that still fails \
+             stops the run. One NDJSON line is printed per step.",
+        )
+        .arg(
+            Arg::new("file")
+                .required(true)
+                .value_name("FILE")
+                .help("Runbook (YAML or JSON) with vars and steps"),
+        )
+        .arg(
+            Arg::new("var")
+                .long("var")
+                .value_name("NAME=VALUE")
+                .action(ArgAction::Append)
+                .help("Set a runbook variable (repeatable); overrides the runbook's default"),
+        )
+}
+
+struct Step {
+    name: String,
+    /// `resource`, `op`, `params`, and `body`, still with placeholders.
+    request: Value,
+    condition: Option<String>,
+    expect_status: Vec<String>,
+    expect: Vec<String>,
+    retries: u32,
+    retry_delay: Duration,
+}
+
+pub fn handle(
+    matches: &clap::ArgMatches,
+    tree: &CommandTree,
+    ctx: &ApiContext,
+    opts: &OutputOptions,
+) -> Result<()> {
+    let file = matches.get_one::<String>("file").expect("required");
+    let raw = fs::read_to_string(file).with_context(|| format!("read {file}"))?;
+    let runbook: Value =
+        serde_yaml::from_str(&raw).with_context(|| format!("invalid runbook {file}"))?;
+    let Value::Object(fields) = &runbook else {
+        return Err(anyhow!(
+            "runbook {file}: expected a mapping with vars and steps"
+        ));
+    };
+    let vars = template::vars(fields, matches.get_many::<String>("var"))
+        .with_context(|| format!("runbook {file}"))?;
+    let steps = parse_steps(fields).with_context(|| format!("runbook {file}"))?;
+    check_vars(&steps, &vars).with_context(|| format!("runbook {file}"))?;
+
+    let cli = crate::build_cli(tree);
+    // What placeholders and `if` checks see: `vars` and the steps so far.
+    let mut context = json!({ "vars": vars, "steps": {} });
+    for step in &steps {
+        // A reference that resolves to nothing would otherwise be left in
+        // place like any unknown dotted name, so it is collected here.
+        let unresolved = RefCell::new(Vec::new());
+        let lookup = |name: &str| {
+            let value = resolve(&context, &vars, name);
+            if value.is_none() && is_reference(name) {
+                unresolved.borrow_mut().push(name.to_string());
+            }
+            value
+        };
+        if let Some(condition) = &step.condition {
+            let mut missing = Vec::new();
+            let condition = template::fill_str(condition, &lookup, &mut missing)
+                .with_context(|| format!("step {}", step.name))?;
+            missing.append(&mut unresolved.borrow_mut());
+            require(&step.name, missing)?;
+            let check = Check::parse(&condition)
+                .with_context(|| format!("step {}: invalid `if`", step.name))?;
+            if !check.holds(&context) {
+                let record = json!({ "skipped": true });
+                emit(&step.name, &record, opts);
+                context["steps"][&step.name] = record;
+                continue;
+            }
+        }
+        let mut missing = Vec::new();
+        let entry = template::fill(&step.request, &lookup, &mut missing)
+            .with_context(|| format!("step {}", step.name))?;
+        missing.append(&mut unresolved.borrow_mut());
+        require(&step.name, missing)?;
+        let (op, op_matches) = crate::batch::op_matches(&cli, tree, &entry)
+            .with_context(|| format!("step {}", step.name))?;
+        let expect = Expectations::new(step.expect_status.iter(), step.expect.iter())
+            .with_context(|| format!("step {}", step.name))?;
+
+        let mut attempt = 0;
+        let response = loop {
+            let result = crate::run_op(ctx, op, &op_matches).and_then(|(response, checkpoint)| {
+                if checkpoint.is_some() {
+                    return Err(interrupt::Interrupted { checkpoint }.into());
+                }
+                expect::verify(&response, expect.as_ref())?;
+                Ok(response)
+            });
+            match result {
+                Ok(response) => break response,
+                Err(err) if attempt < step.retries && !err.is::<interrupt::Interrupted>() => {
+                    attempt += 1;
+                    eprintln!(
+                        "warning: step {} failed: {err:#}; retry {attempt}/{} in {:.1}s",
+                        step.name,
+                        step.retries,
+                        step.retry_delay.as_secs_f64()
+                    );
+                    thread::sleep(step.retry_delay);
+                }
+                Err(err) => return Err(err.context(format!("step {}", step.name))),
+            }
+        };
+        let record = record(&response);
+        emit(&step.name, &record, opts);
+        context["steps"][&step.name] = record;
+    }
+    Ok(())
+}
+
+/// Checks every step up front, so a typo in the last one does not surface
+/// after the first ones have already changed things.
+fn parse_steps(fields: &Map<String, Value>) -> Result<Vec<Step>> {
+    let Some(Value::Array(raw_steps)) = fields.get("steps") else {
+        return Err(anyhow!("\"steps\" must be a list"));
+    };
+    if raw_steps.is_empty() {
+        return Err(anyhow!("\"steps\" is empty"));
+    }
+    let mut steps: Vec<Step> = Vec::new();
+    for (index, raw) in raw_steps.iter().enumerate() {
+        let step = parse_step(index, raw).with_context(|| format!("step {}", index + 1))?;
+        if steps.iter().any(|s| s.name == step.name) {
+            return Err(anyhow!("two steps are named {}", step.name));
+        }
+        steps.push(step);
+    }
+    Ok(steps)
+}
+
+fn parse_step(index: usize, raw: &Value) -> Result<Step> {
+    let Value::Object(fields) = raw else {
+        return Err(anyhow!(
+            "expected a mapping with resource, op, params, and body"
+        ));
+    };
+    if let Some(unknown) = fields.keys().find(|k| !STEP_FIELDS.contains(&k.as_str())) {
+        return Err(anyhow!(
+            "unknown field \"{unknown}\" (use {})",
+            STEP_FIELDS.join(", ")
+        ));
+    }
+    let text = |name: &str| -> Result<Option<String>> {
+        match fields.get(name) {
+            None | Some(Value::Null) => Ok(None),
+            Some(Value::String(s)) => Ok(Some(s.clone())),
+            Some(Value::Number(n)) => Ok(Some(n.to_string())),
+            Some(_) => Err(anyhow!("\"{name}\" must be a string")),
+        }
+    };
+    // A single check or status may be given without a list.
+    let list = |name: &str| -> Result<Vec<String>> {
+        match fields.get(name) {
+            None | Some(Value::Null) => Ok(Vec::new()),
+            Some(Value::Array(items)) => items
+                .iter()
+                .map(|item| match item {
+                    Value::String(s) => Ok(s.clone()),
+                    Value::Number(n) => Ok(n.to_string()),
+                    _ => Err(anyhow!("\"{name}\" must be a string or a list of strings")),
+                })
+                .collect(),
+            Some(_) => Ok(text(name)?.into_iter().collect()),
+        }
+    };
+
+    let name = text("name")?.unwrap_or_else(|| (index + 1).to_string());
+    if name.is_empty() || name.contains(['.', '[', ']', ' ']) {
+        return Err(anyhow!(
+            "step name {name:?} must be non-empty without dots, brackets, or spaces"
+        ));
+    }
+    for required in ["resource", "op"] {
+        if text(required)?.is_none() {
+            return Err(anyhow!("missing \"{required}\""));
+        }
+    }
+    let request: Map<String, Value> = fields
+        .iter()
+        .filter(|(k, _)| matches!(k.as_str(), "resource" | "op" | "params" | "body"))
+        .map(|(k, v)| (k.clone(), v.clone()))
+        .collect();
+    let retries = match fields.get("retries") {
+        None | Some(Value::Null) => 0,
+        Some(value) => value
+            .as_u64()
+            .and_then(|n| u32::try_from(n).ok())
+            .ok_or_else(|| anyhow!("\"retries\" must be a whole number"))?,
+    };
+    let retry_delay = match text("retry_delay")? {
+        Some(raw) => Duration::from_millis(
+            timeparse::parse_duration_ms(&raw)
+                .with_context(|| format!("invalid \"retry_delay\": {raw}"))?,
+        ),
+        None => DEFAULT_RETRY_DELAY,
+    };
+    Ok(Step {
+        name,
+        request: Value::Object(request),
+        condition: text("if")?,
+        expect_status: list("expect_status")?,
+        expect: list("expect")?,
+        retries,
+        retry_delay,
+    })
+}
+
+/// Fails on variables used anywhere without a value before the first step
+/// runs; references to responses can only be checked as they are reached.
+fn check_vars(steps: &[Step], vars: &BTreeMap<String, String>) -> Result<()> {
+    let lookup = |name: &str| match is_reference(name) {
+        true => Some(String::new()),
+        false => vars.get(name).cloned(),
+    };
+    let mut missing = Vec::new();
+    for step in steps {
+        // Errors such as a unit on a reference show up when the step runs.
+        let _ = template::fill(&step.request, &lookup, &mut missing);
+        if let Some(condition) = &step.condition {
+            let _ = template::fill_str(condition, &lookup, &mut missing);
+        }
+    }
+    require_vars(missing)
+}
+
+fn is_reference(name: &str) -> bool {
+    name.starts_with("steps.") || name.starts_with("vars.")
+}
+
+fn require_vars(mut missing: Vec<String>) -> Result<()> {
+    if missing.is_empty() {
+        return Ok(());
+    }
+    missing.sort();
+    missing.dedup();
+    let flags: Vec<String> = missing.iter().map(|m| format!("--var {m}=...")).collect();
+    Err(anyhow!("missing variable(s); pass {}", flags.join(" ")))
+}
+
+/// Fails on placeholders that were left unfilled: a variable without a
+/// value, or a response that is not there.
+fn require(step: &str, missing: Vec<String>) -> Result<()> {
+    let (mut refs, vars): (Vec<String>, Vec<String>) =
+        missing.into_iter().partition(|name| is_reference(name));
+    require_vars(vars).with_context(|| format!("step {step}"))?;
+    if refs.is_empty() {
+        return Ok(());
+    }
+    refs.sort();
+    refs.dedup();
+    Err(anyhow!(
+        "step {step}: nothing at {} (a later or skipped step, or a path its response lacks)",
+        refs.join(", ")
+    ))
+}
+
+/// `steps.NAME.PATH` and `vars.NAME` read the run so far, with array
+/// elements as `.0` or `[0]`; other names are variables.
+fn resolve(context: &Value, vars: &BTreeMap<String, String>, name: &str) -> Option<String> {
+    if !is_reference(name) {
+        return vars.get(name).cloned();
+    }
+    let path = name.replace('[', ".").replace(']', "");
+    let found = path.split('.').filter(|part| !part.is_empty()).try_fold(
+        context,
+        |cur, part| match cur {
+            Value::Array(items) => items.get(part.parse::<usize>().ok()?),
+            _ => cur.get(part),
+        },
+    )?;
+    match found {
+        Value::Null => None,
+        Value::String(s) => Some(s.clone()),
+        other => Some(other.to_string()),
+    }
+}
+
+fn record(response: &HttpResponse) -> Value {
+    json!({ "status": response.status, "body": response.body })
+}
+
+/// Prints a step's result as one NDJSON line, as `batch` does.
+fn emit(name: &str, record: &Value, opts: &OutputOptions) {
+    let mut result = record.clone();
+    if let Some(normalizer) = &opts.normalize {
+        if let Some(body) = result.get_mut("body") {
+            normalizer.apply(body);
+        }
+    }
+    result["step"] = json!(name);
+    if opts.stable {
+        println!("{}", output::stabilize(&result));
+    } else {
+        println!("{result}");
+    }
+}
@@ -0,0 +1,559 @@
+use anyhow::{anyhow, Context, Result};
+use clap::{Arg, ArgAction};
+use serde_json::{Map, Value};
+
+use crate::command_tree::RequestBodyDef;
+
+/// Deeper than any real body; stops runaway recursion on odd schemas.
+const MAX_DEPTH: usize = 64;
+
+pub fn arg() -> Arg {
+    Arg::new("no-validate")
+        .long("no-validate")
+        .action(ArgAction::SetTrue)
+        .help("Send the body without checking it against the operation's schema")
+}
+
+pub fn skipped(matches: &clap::ArgMatches) -> bool {
+    matches!(matches.try_get_one::<bool>("no-validate"), Ok(Some(true)))
+}
+
+/// Fails with one line per violation, each led by the JSON pointer of the
+/// offending value. Bodies without a schema pass.
+pub fn check(body_def: &RequestBodyDef, body: &Value) -> Result<()> {
+    let Some(raw) = &body_def.schema else {
+        return Ok(());
+    };
+    let schema: Value = serde_json::from_str(raw).context("invalid body schema in command tree")?;
+    let mut violations = Vec::new();
+    validate(&schema, body, "", 0, Some(&[]), &mut violations);
+    if violations.is_empty() {
+        return Ok(());
+    }
+    Err(anyhow!(
+        "request body does not match the operation's schema:\n  {}\n(pass --no-validate to send it anyway)",
+        violations.join("\n  ")
+    ))
+}
+
+/// Drops the fields of `value` that `body_def`'s schema does not declare,
+/// so a fetched resource can go back to an update that takes less than
+/// its GET returns. Free-form objects (no `properties`, or
+/// `additionalProperties`) and values behind `oneOf`/`anyOf`/`$ref` are
+/// kept whole.
+pub fn project(body_def: &RequestBodyDef, value: &mut Value) -> Result<()> {
+    let Some(raw) = &body_def.schema else {
+        return Ok(());
+    };
+    let schema: Value = serde_json::from_str(raw).context("invalid body schema in command tree")?;
+    prune(&schema, value, 0);
+    Ok(())
+}
+
+fn prune(schema: &Value, value: &mut Value, depth: usize) {
+    let Some(schema) = schema.as_object() else {
+        return;
+    };
+    let all_of = schema.get("allOf").and_then(Value::as_array);
+    let parts: Vec<&Map<String, Value>> = std::iter::once(schema)
+        .chain(all_of.into_iter().flatten().filter_map(Value::as_object))
+        .collect();
+    let opaque = |part: &&Map<String, Value>| {
+        ["$ref", "oneOf", "anyOf"]
+            .iter()
+            .any(|key| part.contains_key(*key))
+    };
+    // Nested `allOf`s are not unpicked either.
+    if depth > MAX_DEPTH
+        || parts.iter().any(opaque)
+        || parts[1..].iter().any(|p| p.contains_key("allOf"))
+    {
+        return;
+    }
+    match value {
+        Value::Object(map) => {
+            let properties: Vec<&Map<String, Value>> = parts
+                .iter()
+                .filter_map(|part| part.get("properties").and_then(Value::as_object))
+                .collect();
+            let open = properties.is_empty()
+                || parts
+                    .iter()
+                    .any(|part| part.contains_key("additionalProperties"));
+            map.retain(|key, value| {
+                let declared: Vec<&Value> = properties.iter().filter_map(|p| p.get(key)).collect();
+                for property in &declared {
+                    prune(property, value, depth + 1);
+                }
+                open || !declared.is_empty()
+            });
+        }
+        Value::Array(items) => {
+            for item in parts.iter().filter_map(|part| part.get("items")) {
+                for value in items.iter_mut() {
+                    prune(item, value, depth + 1);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// The subset of JSON Schema the OpenAPI spec uses. A leftover `$ref` (a
+/// recursive type) accepts anything. `known` is `None` for an `allOf` part,
+/// which leaves unknown properties to the schema holding the `allOf`;
+/// otherwise it lists property names declared beside this schema by an
+/// enclosing `allOf`, which are not unknown here.
+fn validate(
+    schema: &Value,
+    value: &Value,
+    pointer: &str,
+    depth: usize,
+    known: Option<&[String]>,
+    out: &mut Vec<String>,
+) {
+    let Some(schema) = schema.as_object() else {
+        return;
+    };
+    if depth > MAX_DEPTH || schema.contains_key("$ref") {
+        return;
+    }
+    if value.is_null() && schema.get("nullable") == Some(&Value::Bool(true)) {
+        return;
+    }
+    let at = if pointer.is_empty() { "/" } else { pointer };
+
+    // An object split over `allOf` parts is checked for unknown properties
+    // once, against the properties of all parts together.
+    let parts = schema.get("allOf").and_then(Value::as_array);
+    let known = known.map(|known| {
+        let mut known = known.to_vec();
+        for part in parts.into_iter().flatten() {
+            known.extend(declared(part.get("properties")));
+        }
+        known
+    });
+    for part in parts.into_iter().flatten() {
+        validate(part, value, pointer, depth + 1, None, out);
+    }
+    for key in ["oneOf", "anyOf"] {
+        let Some(parts) = schema.get(key).and_then(Value::as_array) else {
+            continue;
+        };
+        let matched = parts.iter().any(|part| {
+            let mut errors = Vec::new();
+            validate(
+                part,
+                value,
+                pointer,
+                depth + 1,
+                known.as_deref(),
+                &mut errors,
+            );
+            errors.is_empty()
+        });
+        if !matched && !parts.is_empty() {
+            out.push(format!(
+                "{at}: matches none of the {} allowed shapes",
+                parts.len()
+            ));
+        }
+    }
+
+    if let Some(expected) = schema.get("type").and_then(Value::as_str) {
+        if !type_matches(expected, value) {
+            out.push(format!(
+                "{at}: expected {expected}, got {}",
+                describe(value)
+            ));
+            return;
+        }
+    }
+    if let Some(allowed) = schema.get("enum").and_then(Value::as_array) {
+        if !allowed.contains(value) {
+            let allowed: Vec<String> = allowed.iter().map(Value::to_string).collect();
+            out.push(format!(
+                "{at}: {value} is not one of {}",
+                allowed.join(", ")
+            ));
+        }
+    }
+
+    let bound = |key: &str| schema.get(key).and_then(Value::as_f64);
+    match value {
+        Value::Object(map) => object(schema, map, pointer, depth, known.as_deref(), out),
+        Value::Array(items) => {
+            let len = items.len() as f64;
+            if let Some(min) = bound("minItems").filter(|min| len < *min) {
+                out.push(format!("{at}: needs at least {min} item(s), got {len}"));
+            }
+            if let Some(max) = bound("maxItems").filter(|max| len > *max) {
+                out.push(format!("{at}: allows at most {max} item(s), got {len}"));
+            }
+            if let Some(item) = schema.get("items") {
+                for (i, value) in items.iter().enumerate() {
+                    validate(
+                        item,
+                        value,
+                        &format!("{pointer}/{i}"),
+                        depth + 1,
+                        Some(&[]),
+                        out,
+                    );
+                }
+            }
+        }
+        Value::Number(n) => {
+            let n = n.as_f64().unwrap_or_default();
+            if let Some(min) = bound("minimum").filter(|min| n < *min) {
+                out.push(format!("{at}: {n} is below the minimum {min}"));
+            }
+            if let Some(max) = bound("maximum").filter(|max| n > *max) {
+                out.push(format!("{at}: {n} is above the maximum {max}"));
+            }
+        }
+        Value::String(s) => {
+            let len = s.chars().count() as f64;
+            if let Some(min) = bound("minLength").filter(|min| len < *min) {
+                out.push(format!("{at}: needs at least {min} character(s)"));
+            }
+            if let Some(max) = bound("maxLength").filter(|max| len > *max) {
+                out.push(format!("{at}: allows at most {max} character(s)"));
+            }
+        }
+        _ => {}
+    }
+}
+
+/// The names in a schema's `properties`.
+fn declared(properties: Option<&Value>) -> Vec<String> {
+    properties
+        .and_then(Value::as_object)
+        .into_iter()
+        .flat_map(|p| p.keys().cloned())
+        .collect()
+}
+
+fn object(
+    schema: &Map<String, Value>,
+    map: &Map<String, Value>,
+    pointer: &str,
+    depth: usize,
+    known: Option<&[String]>,
+    out: &mut Vec<String>,
+) {
+    for name in schema
+        .get("required")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+    {
+        if !map.contains_key(name) {
+            out.push(format!(
+                "{pointer}/{}: required property is missing",
+                escape(name)
+            ));
+        }
+    }
+    let properties = schema.get("properties").and_then(Value::as_object);
+    let mut names = declared(schema.get("properties"));
+    names.extend(known.into_iter().flatten().cloned());
+    for (key, value) in map {
+        let child = format!("{pointer}/{}", escape(key));
+        match (
+            properties.and_then(|p| p.get(key)),
+            schema.get("additionalProperties"),
+        ) {
+            (Some(property), _) => validate(property, value, &child, depth + 1, Some(&[]), out),
+            // Checked by the `allOf` part that declares it.
+            (None, _) if names.contains(key) => {}
+            (None, Some(extra @ Value::Object(_))) => {
+                validate(extra, value, &child, depth + 1, Some(&[]), out)
+            }
+            (None, Some(Value::Bool(false))) if known.is_some() => {
+                out.push(unknown(&child, key, &names))
+            }
+            (None, Some(_)) => {}
+            // The server decodes bodies into Go structs, which drop unknown
+            // fields silently; flag them so a misspelled key is not lost.
+            (None, None) if known.is_some() && !names.is_empty() => {
+                out.push(unknown(&child, key, &names))
+            }
+            (None, None) => {}
+        }
+    }
+}
+
+fn unknown(pointer: &str, key: &str, names: &[String]) -> String {
+    let closest = names
+        .iter()
+        .map(|name| {
+            (
+                edit_distance(&key.to_lowercase(), &name.to_lowercase()),
+                name,
+            )
+        })
+        .filter(|(distance, _)| *distance <= 2 && *distance < key.len())
+        .min();
+    match closest {
+        Some((_, name)) => format!("{pointer}: unknown property (did you mean \"{name}\"?)"),
+        None => format!("{pointer}: unknown property"),
+    }
+}
+
+fn type_matches(expected: &str, value: &Value) -> bool {
+    match expected {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        "number" => value.is_number(),
+        "integer" => {
+            value.is_i64() || value.is_u64() || value.as_f64().is_some_and(|f| f.fract() == 0.0)
+        }
+        _ => true,
+    }
+}
+
+fn describe(value: &Value) -> String {
+    match value {
+        Value::Array(_) => "array".to_string(),
+        Value::Object(_) => "object".to_string(),
+        Value::Null => "null".to_string(),
+        Value::Bool(_) => format!("boolean {value}"),
+        Value::Number(_) => format!("number {value}"),
+        Value::String(_) => format!("string {value}"),
+    }
+}
+
+/// RFC 6901 escaping for a pointer segment.
+fn escape(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut prev = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let current = row[j + 1];
+            row[j + 1] = if ca == *cb {
+                prev
+            } else {
+                1 + prev.min(row[j]).min(current)
+            };
+            prev = current;
+        }
+    }
+    row[b.len()]
+}
+
+/// An indented `name: type` outline of a schema for `describe`, nested
+/// objects expanded up to `max_depth` levels.
+pub fn outline(raw: &str, max_depth: usize) -> Result<Vec<String>> {
+    let schema: Value = serde_json::from_str(raw).context("invalid schema in command tree")?;
+    let mut lines = Vec::new();
+    outline_fields(&schema, 0, max_depth, &mut lines);
+    if lines.is_empty() {
+        lines.push(type_name(&schema));
+    }
+    Ok(lines)
+}
+
+/// A response example on one line for `describe`, with objects and arrays
+/// nested deeper than `max_depth` shown as `{…}`/`[…]` and the line cut at
+/// `max_chars`.
+pub fn example(raw: &str, max_depth: usize, max_chars: usize) -> Result<String> {
+    let example: Value = serde_json::from_str(raw).context("invalid example in command tree")?;
+    let line = compact(&example, 0, max_depth);
+    Ok(match line.char_indices().nth(max_chars) {
+        Some((cut, _)) => format!("{}…", &line[..cut]),
+        None => line,
+    })
+}
+
+fn compact(value: &Value, depth: usize, max_depth: usize) -> String {
+    match value {
+        Value::Object(map) if !map.is_empty() && depth >= max_depth => "{…}".to_string(),
+        Value::Array(items) if !items.is_empty() && depth >= max_depth => "[…]".to_string(),
+        Value::Object(map) => {
+            let fields: Vec<String> = map
+                .iter()
+                .map(|(key, v)| {
+                    format!(
+                        "{}:{}",
+                        Value::from(key.as_str()),
+                        compact(v, depth + 1, max_depth)
+                    )
+                })
+                .collect();
+            format!("{{{}}}", fields.join(","))
+        }
+        Value::Array(items) => {
+            let items: Vec<String> = items
+                .iter()
+                .map(|v| compact(v, depth + 1, max_depth))
+                .collect();
+            format!("[{}]", items.join(","))
+        }
+        other => other.to_string(),
+    }
+}
+
+fn outline_fields(schema: &Value, depth: usize, max_depth: usize, out: &mut Vec<String>) {
+    let schema = match schema.get("items") {
+        Some(items) if schema.get("type").and_then(Value::as_str) == Some("array") => items,
+        _ => schema,
+    };
+    let mut fields: Vec<(&String, &Value)> = schema
+        .get("properties")
+        .and_then(Value::as_object)
+        .into_iter()
+        .flatten()
+        .collect();
+    for part in schema
+        .get("allOf")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        fields.extend(
+            part.get("properties")
+                .and_then(Value::as_object)
+                .into_iter()
+                .flatten(),
+        );
+    }
+    for (name, field) in fields {
+        out.push(format!(
+            "{:indent$}{name}: {}",
+            "",
+            type_name(field),
+            indent = depth * 2
+        ));
+        if depth + 1 < max_depth {
+            outline_fields(field, depth + 1, max_depth, out);
+        }
+    }
+}
+
+fn type_name(schema: &Value) -> String {
+    let kind = schema.get("type").and_then(Value::as_str);
+    let mut name = if schema.get("$ref").is_some() {
+        "object (recursive)".to_string()
+    } else if let Some(parts) = schema
+        .get("oneOf")
+        .or_else(|| schema.get("anyOf"))
+        .and_then(Value::as_array)
+    {
+        let parts: Vec<String> = parts.iter().map(type_name).collect();
+        parts.join(" | ")
+    } else {
+        match kind {
+            Some("array") => format!(
+                "array of {}",
+                schema.get("items").map_or("any".to_string(), type_name)
+            ),
+            Some(kind) if kind != "object" => kind.to_string(),
+            _ => match schema.get("additionalProperties") {
+                Some(extra @ Value::Object(_)) if schema.get("properties").is_none() => {
+                    format!("map of {}", type_name(extra))
+                }
+                _ => "object".to_string(),
+            },
+        }
+    };
+    if let Some(format) = schema.get("format").and_then(Value::as_str) {
+        name.push_str(&format!(" ({format})"));
+    }
+    if let Some(values) = schema.get("enum").and_then(Value::as_array) {
+        let values: Vec<String> = values.iter().map(Value::to_string).collect();
+        name.push_str(&format!(", one of {}", values.join(", ")));
+    }
+    if schema.get("nullable") == Some(&Value::Bool(true)) {
+        name.push_str(", nullable");
+    }
+    name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn violations(schema: Value, body: Value) -> Vec<String> {
+        let mut out = Vec::new();
+        validate(&schema, &body, "", 0, Some(&[]), &mut out);
+        out
+    }
+
+    #[test]
+    fn all_of_parts_share_their_properties() {
+        let schema = json!({
+            "allOf": [
+                {"type": "object", "properties": {"a": {"type": "string"}}},
+                {"type": "object", "properties": {"b": {"type": "integer"}}, "required": ["b"]},
+            ]
+        });
+        assert!(violations(schema.clone(), json!({"a": "x", "b": 1})).is_empty());
+        assert_eq!(
+            violations(schema.clone(), json!({"a": "x", "b": 1, "c": true})),
+            ["/c: unknown property"]
+        );
+        assert_eq!(
+            violations(schema, json!({"a": 1, "b": 1})),
+            ["/a: expected string, got number 1"]
+        );
+    }
+
+    #[test]
+    fn project_keeps_only_declared_fields() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "name": {"type": "string"},
+                "labels": {"type": "object", "additionalProperties": {"type": "string"}},
+                "rules": {"type": "array", "items": {"allOf": [
+                    {"properties": {"expr": {"type": "string"}}},
+                    {"properties": {"for": {"type": "string"}}},
+                ]}},
+            }
+        });
+        let body_def = RequestBodyDef {
+            required: true,
+            content_type: "application/json".into(),
+            schema_type: "object".into(),
+            skeleton: None,
+            schema: Some(schema.to_string()),
+            patch_types: None,
+        };
+        let mut value = json!({
+            "id": "1",
+            "name": "x",
+            "labels": {"team": "a"},
+            "rules": [{"expr": "up", "for": "5m", "state": "firing"}],
+        });
+        project(&body_def, &mut value).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "name": "x",
+                "labels": {"team": "a"},
+                "rules": [{"expr": "up", "for": "5m"}],
+            })
+        );
+    }
+
+    #[test]
+    fn unknown_properties_are_flagged_with_a_suggestion() {
+        let schema = json!({"type": "object", "properties": {"title": {"type": "string"}}});
+        assert_eq!(
+            violations(schema, json!({"titel": "x"})),
+            ["/titel: unknown property (did you mean \"title\"?)"]
+        );
+    }
+}
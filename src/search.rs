@@ -0,0 +1,167 @@
+use anyhow::{anyhow, Result};
+use clap::{Arg, ArgAction, Command};
+use serde_json::json;
+
+use crate::command_tree::{CommandTree, Operation};
+
+/// Words too common in summaries to say anything about the operation.
+const STOPWORDS: &[&str] = &[
+    "a", "an", "and", "api", "by", "endpoint", "for", "from", "in", "is", "of", "on", "or", "that",
+    "the", "to", "which", "with",
+];
+
+pub fn command() -> Command {
+    Command::new("search")
+        .about("Find operations by name, path, summary, or description")
+        .arg(
+            Arg::new("term")
+                .value_name("TERM")
+                .required(true)
+                .num_args(1..)
+                .help("Words to look for, e.g. 'pause alert'"),
+        )
+        .arg(
+            Arg::new("limit")
+                .long("limit")
+                .value_name("N")
+                .default_value("10")
+                .help("Show at most N matches"),
+        )
+        .arg(
+            Arg::new("json")
+                .long("json")
+                .action(ArgAction::SetTrue)
+                .help("Emit machine-readable JSON"),
+        )
+}
+
+/// Ranks every operation against the words of the term and prints the best
+/// matches with a command line to run them.
+pub fn handle(tree: &CommandTree, matches: &clap::ArgMatches) -> Result<()> {
+    let words = words(
+        &matches
+            .get_many::<String>("term")
+            .into_iter()
+            .flatten()
+            .cloned()
+            .collect::<Vec<_>>()
+            .join(" "),
+    );
+    if words.is_empty() {
+        return Err(anyhow!("search term has no words to match"));
+    }
+    let limit: usize = matches
+        .get_one::<String>("limit")
+        .map_or("10", String::as_str)
+        .parse()
+        .map_err(|_| anyhow!("invalid --limit"))?;
+
+    let mut hits: Vec<(u32, &str, &Operation)> = tree
+        .resources
+        .iter()
+        .flat_map(|res| res.ops.iter().map(move |op| (res.name.as_str(), op)))
+        .filter_map(|(resource, op)| {
+            let score = score(&words, resource, op);
+            (score > 0).then_some((score, resource, op))
+        })
+        .collect();
+    hits.sort_by(|a, b| {
+        b.0.cmp(&a.0)
+            .then_with(|| a.1.cmp(b.1))
+            .then_with(|| a.2.name.cmp(&b.2.name))
+    });
+    hits.truncate(limit);
+
+    if matches.get_flag("json") {
+        let out: Vec<_> = hits
+            .iter()
+            .map(|(score, resource, op)| {
+                json!({
+                    "resource": resource,
+                    "op": op.name,
+                    "method": op.method,
+                    "path": op.path,
+                    "summary": op.summary,
+                    "score": score,
+                    "invocation": crate::example_invocation(resource, op),
+                })
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&out)?);
+        return Ok(());
+    }
+    if hits.is_empty() {
+        return Err(anyhow!("no operation matches {:?}", words.join(" ")));
+    }
+    for (_, resource, op) in hits {
+        println!("{resource} {}  ({} {})", op.name, op.method, op.path);
+        if let Some(summary) = op.summary.as_deref().filter(|s| !s.trim().is_empty()) {
+            println!("    {}", summary.trim());
+        }
+        println!("    {}", crate::example_invocation(resource, op));
+    }
+    Ok(())
+}
+
+/// Lowercased words of the term, stopwords dropped and a plural `s`
+/// trimmed so "pauses" finds `pause-alert`.
+fn words(term: &str) -> Vec<String> {
+    term.split(|c: char| !c.is_alphanumeric())
+        .map(str::to_lowercase)
+        .filter(|w| !w.is_empty() && !STOPWORDS.contains(&w.as_str()))
+        .map(|w| match w.strip_suffix('s') {
+            Some(stem) if stem.len() >= 3 && !stem.ends_with('s') => stem.to_string(),
+            _ => w,
+        })
+        .collect()
+}
+
+/// Sums, per word, its best match over the operation's fields: names and
+/// paths weigh more than prose, whole words more than substrings, and a
+/// scattered match (`dashbord`) within a word of a name counts for a
+/// little. An operation matching more of the words always ranks above one
+/// matching fewer.
+fn score(words: &[String], resource: &str, op: &Operation) -> u32 {
+    let fields: [(u32, String); 5] = [
+        (8, op.name.to_lowercase()),
+        (6, resource.to_lowercase()),
+        (5, op.path.to_lowercase()),
+        (3, op.summary.as_deref().unwrap_or_default().to_lowercase()),
+        (
+            1,
+            op.description.as_deref().unwrap_or_default().to_lowercase(),
+        ),
+    ];
+    let mut matched = 0;
+    let mut total = 0;
+    for word in words {
+        let best = fields
+            .iter()
+            .enumerate()
+            .map(|(i, (weight, text))| {
+                let tokens = || text.split(|c: char| !c.is_alphanumeric());
+                if tokens().any(|t| t == word || t.strip_suffix('s') == Some(word)) {
+                    weight * 4
+                } else if text.contains(word.as_str()) {
+                    weight * 2
+                } else if i < 2 && word.len() >= 3 && tokens().any(|t| subsequence(word, t)) {
+                    *weight
+                } else {
+                    0
+                }
+            })
+            .max()
+            .unwrap_or(0);
+        if best > 0 {
+            matched += 1;
+            total += best;
+        }
+    }
+    matched * 1000 + total
+}
+
+/// Whether the characters of `needle` appear in `haystack` in order.
+fn subsequence(needle: &str, haystack: &str) -> bool {
+    let mut rest = haystack.chars();
+    needle.chars().all(|c| rest.any(|h| h == c))
+}
@@ -0,0 +1,252 @@
+use anyhow::{anyhow, Context, Result};
+use ring::rand::{SecureRandom, SystemRandom};
+use serde_json::{json, Value};
+use std::{
+    env,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex, OnceLock,
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
+use url::Url;
+
+use crate::capture;
+use crate::http::{Body, ClientOptions, HttpClient};
+
+const TRACES_PATH: &str = "/v1/traces";
+const EXPORT_TIMEOUT_SECS: u64 = 5;
+
+/// Span kinds and status codes of the OTLP data model.
+const KIND_INTERNAL: u8 = 1;
+const KIND_CLIENT: u8 = 3;
+const STATUS_ERROR: u8 = 2;
+
+struct Active {
+    name: String,
+    args: Vec<String>,
+    trace_id: String,
+    root_id: String,
+    started_ns: u64,
+    spans: Mutex<Vec<Value>>,
+    /// Set once the spans are taken for export, so the export request is
+    /// not itself traced.
+    exported: AtomicBool,
+}
+
+static ACTIVE: OnceLock<Active> = OnceLock::new();
+
+/// Whether `--trace-self` or `SIGNOZ_CLI_TRACE` asks for self-tracing.
+pub fn requested(matches: &clap::ArgMatches) -> bool {
+    matches.get_flag("trace-self")
+        || env::var("SIGNOZ_CLI_TRACE")
+            .is_ok_and(|v| !matches!(v.trim(), "" | "0" | "false" | "no" | "off"))
+}
+
+/// Starts the root span of this invocation, named after its subcommands;
+/// HTTP calls become its children until [`finish`] exports them.
+pub fn start(matches: &clap::ArgMatches) -> Result<()> {
+    let mut name = String::from("signoz");
+    let mut current = matches;
+    while let Some((sub, sub_matches)) = current.subcommand() {
+        name.push(' ');
+        name.push_str(sub);
+        current = sub_matches;
+    }
+    let _ = ACTIVE.set(Active {
+        name,
+        args: env::args().skip(1).collect(),
+        trace_id: random_id(16)?,
+        root_id: random_id(8)?,
+        started_ns: now_ns(),
+        spans: Mutex::new(Vec::new()),
+        exported: AtomicBool::new(false),
+    });
+    Ok(())
+}
+
+/// One HTTP attempt in flight, a child of the root span.
+pub struct HttpSpan {
+    span_id: String,
+    started_ns: u64,
+    method: String,
+    url: String,
+}
+
+/// The span for an HTTP attempt about to be sent; `None` when not tracing.
+pub fn http_span(method: &str, url: &str) -> Option<HttpSpan> {
+    let active = ACTIVE.get()?;
+    if active.exported.load(Ordering::SeqCst) {
+        return None;
+    }
+    Some(HttpSpan {
+        span_id: random_id(8).ok()?,
+        started_ns: now_ns(),
+        method: method.to_string(),
+        url: url.to_string(),
+    })
+}
+
+impl HttpSpan {
+    /// W3C trace context for the request, so the server's spans join the
+    /// same trace.
+    pub fn traceparent(&self) -> Option<String> {
+        let active = ACTIVE.get()?;
+        Some(format!("00-{}-{}-01", active.trace_id, self.span_id))
+    }
+
+    /// Ends the span at the response headers; `status` is `None` when no
+    /// response came back.
+    pub fn end(self, status: Option<u16>, resend_count: u32) {
+        let Some(active) = ACTIVE.get() else {
+            return;
+        };
+        let mut attributes = vec![
+            attribute("http.request.method", json!({ "stringValue": self.method })),
+            attribute("url.full", json!({ "stringValue": self.url })),
+        ];
+        if let Some(host) = Url::parse(&self.url)
+            .ok()
+            .and_then(|url| url.host_str().map(str::to_string))
+        {
+            attributes.push(attribute("server.address", json!({ "stringValue": host })));
+        }
+        if let Some(status) = status {
+            attributes.push(attribute(
+                "http.response.status_code",
+                json!({ "intValue": status.to_string() }),
+            ));
+        }
+        if resend_count > 0 {
+            attributes.push(attribute(
+                "http.request.resend_count",
+                json!({ "intValue": resend_count.to_string() }),
+            ));
+        }
+        let span = json!({
+            "traceId": active.trace_id,
+            "spanId": self.span_id,
+            "parentSpanId": active.root_id,
+            "name": self.method,
+            "kind": KIND_CLIENT,
+            "startTimeUnixNano": self.started_ns.to_string(),
+            "endTimeUnixNano": now_ns().to_string(),
+            "attributes": attributes,
+            "status": match status {
+                Some(status) if status < 400 => json!({}),
+                Some(status) => json!({ "code": STATUS_ERROR, "message": format!("http {status}") }),
+                None => json!({ "code": STATUS_ERROR, "message": "no response" }),
+            },
+        });
+        if let Ok(mut spans) = active.spans.lock() {
+            spans.push(span);
+        }
+    }
+}
+
+/// Ends the root span with how the command ended and exports the trace
+/// over OTLP/HTTP. Failures are reported on stderr so they never mask
+/// `result`.
+pub fn finish(result: &Result<()>) {
+    let Some(active) = ACTIVE.get() else {
+        return;
+    };
+    if active.exported.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    if let Err(err) = export(active, result) {
+        eprintln!("warning: trace export: {err:#}");
+    }
+}
+
+fn export(active: &Active, result: &Result<()>) -> Result<()> {
+    let mut spans = std::mem::take(&mut *active.spans.lock().unwrap_or_else(|e| e.into_inner()));
+    spans.push(json!({
+        "traceId": active.trace_id,
+        "spanId": active.root_id,
+        "name": active.name,
+        "kind": KIND_INTERNAL,
+        "startTimeUnixNano": active.started_ns.to_string(),
+        "endTimeUnixNano": now_ns().to_string(),
+        "attributes": [attribute(
+            "process.command_args",
+            json!({ "arrayValue": { "values": capture::redact_args(&active.args)
+                .into_iter()
+                .map(|arg| json!({ "stringValue": arg }))
+                .collect::<Vec<_>>() } }),
+        )],
+        "status": match result {
+            Ok(()) => json!({}),
+            Err(err) => json!({ "code": STATUS_ERROR, "message": format!("{err:#}") }),
+        },
+    }));
+    let service = env::var("OTEL_SERVICE_NAME")
+        .ok()
+        .filter(|v| !v.is_empty())
+        .unwrap_or_else(|| "signoz-cli".to_string());
+    let payload = json!({ "resourceSpans": [{
+        "resource": { "attributes": [
+            attribute("service.name", json!({ "stringValue": service })),
+            attribute("service.version", json!({ "stringValue": env!("CARGO_PKG_VERSION") })),
+        ]},
+        "scopeSpans": [{
+            "scope": { "name": "signoz-cli", "version": env!("CARGO_PKG_VERSION") },
+            "spans": spans,
+        }],
+    }]});
+
+    let endpoint = env::var("OTEL_EXPORTER_OTLP_ENDPOINT")
+        .ok()
+        .filter(|v| !v.is_empty())
+        .unwrap_or_else(|| "http://localhost:4318".to_string());
+    let mut headers = Vec::new();
+    if let Ok(key) = env::var("SIGNOZ_INGESTION_KEY") {
+        headers.push(("signoz-ingestion-key".to_string(), key));
+    }
+    let collector = HttpClient::new(
+        endpoint.trim_end_matches('/').to_string(),
+        None,
+        None,
+        headers,
+        &ClientOptions {
+            timeout_secs: Some(EXPORT_TIMEOUT_SECS),
+            ..ClientOptions::default()
+        },
+    )?;
+    let response = collector
+        .execute(
+            "POST",
+            TRACES_PATH,
+            &[],
+            Some(Body::Json(payload)),
+            Some("application/json"),
+        )
+        .with_context(|| format!("export to {endpoint}"))?;
+    if response.status >= 400 {
+        return Err(anyhow!(
+            "collector rejected the trace: http {}: {}",
+            response.status,
+            response.body
+        ));
+    }
+    Ok(())
+}
+
+fn attribute(key: &str, value: Value) -> Value {
+    json!({ "key": key, "value": value })
+}
+
+fn random_id(len: usize) -> Result<String> {
+    let mut bytes = vec![0u8; len];
+    SystemRandom::new()
+        .fill(&mut bytes)
+        .map_err(|_| anyhow!("no random source"))?;
+    Ok(bytes.iter().map(|b| format!("{b:02x}")).collect())
+}
+
+fn now_ns() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0)
+}
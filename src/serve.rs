@@ -0,0 +1,294 @@
+use anyhow::{Context, Result};
+use clap::{Arg, Command};
+use serde_json::{json, Map, Value};
+use std::{
+    net::{IpAddr, SocketAddr, TcpListener},
+    thread,
+    time::Instant,
+};
+
+use crate::command_tree::CommandTree;
+use crate::error::Failure;
+use crate::http;
+use crate::httpd::{self, Request};
+use crate::{progress, ApiContext};
+
+/// Operation flags that read local files or open an editor; a client sends
+/// the body as the request body instead.
+const LOCAL_FLAGS: [&str; 4] = ["body", "form", "set-file", "edit"];
+
+pub fn command() -> Command {
+    Command::new("serve")
+        .about("Run a local HTTP API that runs operations with this profile's credentials")
+        .long_about(
+            "Serves GET /ops (the operations) and /ops/RESOURCE/OP, which runs the operation \
+             with the query string as its params (?id=42&tag=a&tag=b) and the request body as \
+             its body, and answers with the SigNoz status and body. Read operations take GET \
+             and all others POST. Every request needs 'Authorization: Bearer TOKEN' (a random \
+             token is printed at start unless --require-token sets one), and requests from \
+             browser pages on other origins or to other host names are refused. \
+             GET /healthz answers {\"ok\":true} without a token.",
+        )
+        .arg(
+            Arg::new("listen")
+                .long("listen")
+                .value_name("ADDR")
+                .default_value("127.0.0.1:7777")
+                .help("Address to listen on"),
+        )
+        .arg(
+            Arg::new("require-token")
+                .long("require-token")
+                .value_name("TOKEN")
+                .help("Bearer token clients must send (default: a random one, printed at start)"),
+        )
+}
+
+struct Server<'a> {
+    cli: Command,
+    tree: &'a CommandTree,
+    ctx: &'a ApiContext,
+    token: String,
+    addr: SocketAddr,
+}
+
+/// Serves until interrupted, one thread per connection; the requests share
+/// the context's connection pool and credentials.
+pub fn handle(matches: &clap::ArgMatches, tree: &CommandTree, ctx: &ApiContext) -> Result<()> {
+    let listen = matches
+        .get_one::<String>("listen")
+        .map_or("127.0.0.1:7777", String::as_str);
+    let listener = TcpListener::bind(listen).with_context(|| format!("bind {listen}"))?;
+    let addr = listener.local_addr()?;
+    let token = match matches.get_one::<String>("require-token") {
+        Some(token) => token.clone(),
+        None => {
+            let token = http::random_hex(16)?;
+            eprintln!("Token: {token}");
+            token
+        }
+    };
+    progress::disable();
+    let server = Server {
+        cli: crate::build_cli(tree),
+        tree,
+        ctx,
+        token,
+        addr,
+    };
+    eprintln!(
+        "Serving {} on http://{addr} (GET /ops, /ops/RESOURCE/OP with 'Authorization: Bearer TOKEN'); Ctrl-C to stop.",
+        ctx.base_url
+    );
+    thread::scope(|scope| {
+        let server = &server;
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) => {
+                    scope.spawn(move || {
+                        httpd::serve(stream, |request, peer| server.reply(request, peer))
+                    });
+                }
+                Err(err) => eprintln!("accept: {err}"),
+            }
+        }
+    });
+    Ok(())
+}
+
+impl Server<'_> {
+    fn reply(&self, request: &Request, peer: &str) -> httpd::Reply {
+        let started = Instant::now();
+        let (status, content_type, body) = self.route(request);
+        eprintln!(
+            "{peer}: {} {} -> {status} ({}ms)",
+            request.method,
+            request.path,
+            started.elapsed().as_millis()
+        );
+        (status, content_type, body)
+    }
+
+    fn route(&self, request: &Request) -> httpd::Reply {
+        if request.path == "/healthz" && request.method == "GET" {
+            return reply_json(200, &json!({ "ok": true }));
+        }
+        // Pages in a browser can reach loopback too: refuse other origins,
+        // and host names that only point here by DNS rebinding.
+        if let Some(origin) = request.header("origin") {
+            if !is_loopback_origin(origin) {
+                return error(403, &format!("origin {origin} is not allowed"));
+            }
+        }
+        if !request
+            .header("host")
+            .is_some_and(|host| self.is_own_host(host))
+        {
+            return error(403, "Host must be a loopback address or the listen address");
+        }
+        let sent = request
+            .header("authorization")
+            .and_then(|v| v.strip_prefix("Bearer "));
+        if sent != Some(self.token.as_str()) {
+            return error(401, "missing or wrong bearer token");
+        }
+        let segments: Vec<&str> = request.path.trim_matches('/').split('/').collect();
+        match (request.method.as_str(), segments.as_slice()) {
+            ("GET", ["ops"]) => reply_json(200, &self.ops()),
+            (method @ ("GET" | "POST"), ["ops", resource, op]) => {
+                match self.run(request, method, resource, op) {
+                    Ok(reply) => reply,
+                    Err(err) => {
+                        let status = match err.downcast_ref::<Failure>() {
+                            Some(Failure::Usage(_)) => 400,
+                            _ => 502,
+                        };
+                        error(status, &format!("{err:#}"))
+                    }
+                }
+            }
+            (_, ["ops", ..] | ["healthz"]) => error(405, "use GET or POST"),
+            _ => error(404, "unknown path; see GET /ops"),
+        }
+    }
+
+    /// `localhost`, a loopback address, or the address listened on (any
+    /// address when listening on all of them), with or without the port.
+    fn is_own_host(&self, host: &str) -> bool {
+        let name = match host.rsplit_once(':') {
+            Some((name, port)) if !name.ends_with(':') && port.parse::<u16>().is_ok() => name,
+            _ => host,
+        };
+        let name = name.trim_start_matches('[').trim_end_matches(']');
+        if name.eq_ignore_ascii_case("localhost") {
+            return true;
+        }
+        name.parse::<IpAddr>().is_ok_and(|ip| {
+            ip.is_loopback() || ip == self.addr.ip() || self.addr.ip().is_unspecified()
+        })
+    }
+
+    fn ops(&self) -> Value {
+        let ops: Vec<Value> = self
+            .tree
+            .resources
+            .iter()
+            .flat_map(|resource| {
+                resource.ops.iter().map(|op| {
+                    json!({
+                        "resource": resource.name,
+                        "op": op.name,
+                        "method": op.method,
+                        "path": op.path,
+                        "summary": op.summary,
+                        "route": format!("/ops/{}/{}", resource.name, op.name),
+                    })
+                })
+            })
+            .collect();
+        Value::Array(ops)
+    }
+
+    /// Runs an operation like a `batch` line: the query string becomes its
+    /// params (repeated keys an array) and a non-empty request body its body.
+    /// Only GET runs GET operations and only POST the others, so a link or
+    /// image on a web page cannot change anything.
+    fn run(
+        &self,
+        request: &Request,
+        method: &str,
+        resource: &str,
+        op: &str,
+    ) -> Result<httpd::Reply> {
+        let Some(found) = crate::find_op(self.tree, resource, op) else {
+            return Ok(error(404, &format!("unknown operation {resource} {op}")));
+        };
+        let expected = if found.method == "GET" { "GET" } else { "POST" };
+        if method != expected {
+            return Ok(error(
+                405,
+                &format!(
+                    "{resource} {op} is a {} operation; use {expected}",
+                    found.method
+                ),
+            ));
+        }
+        let mut params = Map::new();
+        for (key, value) in url::form_urlencoded::parse(request.query.as_bytes()) {
+            if LOCAL_FLAGS.contains(&key.as_ref()) {
+                return Err(Failure::Usage(format!(
+                    "\"{key}\" is not accepted here; send the body as the request body"
+                ))
+                .into());
+            }
+            let value = match value.as_ref() {
+                "true" => Value::Bool(true),
+                "false" => Value::Bool(false),
+                _ => Value::String(value.into_owned()),
+            };
+            match params.get_mut(key.as_ref()) {
+                Some(Value::Array(items)) => items.push(value),
+                Some(first) => *first = json!([first.take(), value]),
+                None => {
+                    params.insert(key.into_owned(), value);
+                }
+            }
+        }
+        let mut entry = json!({ "resource": resource, "op": op, "params": params });
+        if !request.body.is_empty() {
+            let body = String::from_utf8(request.body.clone())
+                .map_err(|_| Failure::Usage("request body is not UTF-8".into()))?;
+            // `--body` reads `@path` and `-` locally; JSON never starts so.
+            if body.starts_with('@') || body == "-" {
+                return Err(Failure::Usage("request body must not start with @".into()).into());
+            }
+            let json_body = found
+                .request_body
+                .as_ref()
+                .is_some_and(|b| b.content_type.contains("json"));
+            if json_body {
+                if let Err(err) = serde_json::from_str::<Value>(&body) {
+                    return Err(Failure::Usage(format!("invalid JSON body: {err}")).into());
+                }
+            }
+            entry["body"] = Value::String(body);
+        }
+        let (op, op_matches) = crate::batch::op_matches(&self.cli, self.tree, &entry)
+            .map_err(|err| Failure::Usage(format!("{err:#}")))?;
+        let (response, _) = crate::run_op(self.ctx, op, &op_matches)?;
+        Ok(match &response.body {
+            Value::String(text) if !response.content_type.contains("json") => (
+                response.status,
+                "text/plain; charset=utf-8",
+                text.clone().into_bytes(),
+            ),
+            body => reply_json(response.status, body),
+        })
+    }
+}
+
+/// An `Origin` of a page served from this machine; `null`, which
+/// sandboxed frames send, is not one.
+fn is_loopback_origin(origin: &str) -> bool {
+    let Ok(url) = url::Url::parse(origin) else {
+        return false;
+    };
+    match url.host() {
+        Some(url::Host::Domain(name)) => name.eq_ignore_ascii_case("localhost"),
+        Some(url::Host::Ipv4(ip)) => ip.is_loopback(),
+        Some(url::Host::Ipv6(ip)) => ip.is_loopback(),
+        None => false,
+    }
+}
+
+fn reply_json(status: u16, value: &Value) -> httpd::Reply {
+    (
+        status,
+        "application/json",
+        serde_json::to_vec(value).unwrap_or_default(),
+    )
+}
+
+fn error(status: u16, message: &str) -> httpd::Reply {
+    reply_json(status, &json!({ "error": message }))
+}
@@ -0,0 +1,143 @@
+use anyhow::{Context, Result};
+use clap::{Arg, ArgAction, Command};
+use serde_json::Value;
+use std::{fs, path::PathBuf};
+
+use crate::command_tree::CommandTree;
+use crate::error::{ApiError, Failure};
+use crate::http::Body;
+use crate::output::{self, OutputOptions};
+use crate::{expect, query, template, timeparse, ApiContext};
+
+pub fn command() -> Command {
+    Command::new("snapshot")
+        .about("Run a saved query or template and write the result to a timestamped file, for cron")
+        .long_about(
+            "Reads a template (resource, op, params, body, and vars, as `template run` takes) \
+             or a bare query_range body, sends it, and writes the response to --out with \
+             {date} (YYYY-MM-DD), {time} (HHMMSS), {datetime} (YYYYMMDDTHHMMSSZ), and {unix} \
+             filled in from the current UTC time. The file is written whole or not at all, \
+             nothing is asked, and the exit code tells the outcome as for any command. \
+             Prints the path written.",
+        )
+        .arg(
+            Arg::new("query-file")
+                .long("query-file")
+                .value_name("FILE")
+                .required(true)
+                .help("Template or query_range body (YAML or JSON)"),
+        )
+        .arg(
+            Arg::new("out")
+                .long("out")
+                .value_name("PATTERN")
+                .required(true)
+                .help("File to write, e.g. 'slo/{date}.json'; missing directories are created"),
+        )
+        .arg(
+            Arg::new("var")
+                .long("var")
+                .value_name("NAME=VALUE")
+                .action(ArgAction::Append)
+                .help("Set a template variable (repeatable)"),
+        )
+        .arg(
+            Arg::new("since")
+                .long("since")
+                .value_name("TIME")
+                .help("Set a query_range body's window: 24h, now-1d, RFC 3339, or epoch ms"),
+        )
+        .arg(timeparse::until_arg())
+}
+
+pub fn handle(
+    matches: &clap::ArgMatches,
+    tree: &CommandTree,
+    ctx: &ApiContext,
+    opts: &OutputOptions,
+) -> Result<()> {
+    let path = out_path(
+        matches.get_one::<String>("out").expect("required"),
+        query::now_millis(),
+    )?;
+    let file = matches.get_one::<String>("query-file").expect("required");
+    let raw = fs::read_to_string(file).with_context(|| format!("read {file}"))?;
+    let saved: Value =
+        serde_yaml::from_str(&raw).with_context(|| format!("invalid query file {file}"))?;
+    let mut entry = template::render(&saved, matches.get_many::<String>("var"))
+        .with_context(|| format!("query file {file}"))?;
+
+    let response = if entry.get("resource").is_some() {
+        let cli = crate::build_cli(tree);
+        let (op, op_matches) = crate::batch::op_matches(&cli, tree, &entry)
+            .with_context(|| format!("query file {file}"))?;
+        crate::run_op(ctx, op, &op_matches)?.0
+    } else {
+        if let Some(since) = matches.get_one::<String>("since") {
+            let (start, end) = timeparse::range(since, timeparse::until(matches))?;
+            query::set_time_range(&mut entry, start, end)?;
+        }
+        query::warn_if_heavy(&entry);
+        ctx.execute(
+            "POST",
+            query::QUERY_RANGE_PATH,
+            &[],
+            Some(Body::Json(entry)),
+            Some("application/json"),
+        )?
+    };
+    if response.status >= 400 {
+        return Err(ApiError::from_response(&response).into());
+    }
+    expect::check(&response, opts)?;
+
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(dir).with_context(|| format!("create {}", dir.display()))?;
+    }
+    // Written beside the target and renamed, so a sync job never picks up
+    // half a file.
+    let partial = path.with_file_name(format!(
+        ".{}.partial",
+        path.file_name().unwrap_or_default().to_string_lossy()
+    ));
+    let file_opts = OutputOptions {
+        output_file: Some(partial.clone()),
+        pager: false,
+        color: false,
+        quiet: false,
+        status_only: false,
+        ..opts.clone()
+    };
+    let written = output::print_response(&response, &file_opts).and_then(|()| {
+        fs::rename(&partial, &path).with_context(|| format!("write {}", path.display()))
+    });
+    if let Err(err) = written {
+        let _ = fs::remove_file(&partial);
+        return Err(err);
+    }
+    if !opts.quiet {
+        println!("{}", path.display());
+    }
+    Ok(())
+}
+
+/// The pattern with its time placeholders filled in from `now` (epoch ms,
+/// UTC).
+fn out_path(pattern: &str, now: u64) -> Result<PathBuf> {
+    let stamp = timeparse::format_rfc3339(now);
+    let (date, clock) = (&stamp[..10], stamp[11..19].replace(':', ""));
+    let path = pattern
+        .replace("{date}", date)
+        .replace("{time}", &clock)
+        .replace("{datetime}", &format!("{}T{clock}Z", date.replace('-', "")))
+        .replace("{unix}", &(now / 1000).to_string());
+    if let Some(start) = path.find('{').filter(|&i| path[i..].contains('}')) {
+        let end = start + path[start..].find('}').unwrap_or(0);
+        return Err(Failure::Usage(format!(
+            "unknown placeholder {} in --out (use {{date}}, {{time}}, {{datetime}}, or {{unix}})",
+            &path[start..=end]
+        ))
+        .into());
+    }
+    Ok(PathBuf::from(path))
+}
@@ -0,0 +1,180 @@
+use anyhow::{anyhow, Context, Result};
+use clap::{Arg, Command};
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::{env, fs, path::Path};
+
+use crate::command_tree::{self, generate, CommandTree};
+use crate::error::ApiError;
+use crate::http::HttpClient;
+use crate::query::now_millis;
+use crate::timeparse::format_rfc3339;
+use crate::ApiContext;
+
+const DEFAULT_URL: &str =
+    "https://raw.githubusercontent.com/SigNoz/signoz/main/docs/api/openapi.yml";
+
+/// Added/removed operations listed after a sync before the rest are counted.
+const MAX_LISTED: usize = 20;
+
+pub fn command() -> Command {
+    Command::new("spec")
+        .about("Regenerate the command tree from a SigNoz OpenAPI document")
+        .subcommand_required(true)
+        .subcommand(
+            Command::new("sync")
+                .about("Download the OpenAPI document and use the command tree built from it")
+                .arg(
+                    Arg::new("url")
+                        .long("url")
+                        .value_name("URL|PATH")
+                        .help("OpenAPI document: http(s) URL, path on the instance (e.g. /api/v1/openapi.yml), or local file (default: SIGNOZ_OPENAPI_URL, else the SigNoz repo's docs/api/openapi.yml)"),
+                )
+                .arg(
+                    Arg::new("out")
+                        .long("out")
+                        .value_name("FILE")
+                        .help("Write the tree here instead of the config directory (e.g. schemas/command_tree.json before a build)"),
+                ),
+        )
+        .subcommand(
+            Command::new("reset")
+                .about("Remove the synced command tree and go back to the built-in one"),
+        )
+}
+
+pub fn handle(matches: &clap::ArgMatches, tree: &CommandTree, ctx: &ApiContext) -> Result<()> {
+    match matches.subcommand() {
+        Some(("sync", sub)) => sync(sub, tree, ctx),
+        Some(("reset", _)) => reset(),
+        _ => Err(anyhow!("spec subcommand required")),
+    }
+}
+
+fn sync(matches: &clap::ArgMatches, current: &CommandTree, ctx: &ApiContext) -> Result<()> {
+    let source = matches
+        .get_one::<String>("url")
+        .cloned()
+        .or_else(|| env::var("SIGNOZ_OPENAPI_URL").ok())
+        .unwrap_or_else(|| DEFAULT_URL.to_string());
+    let raw = fetch(&source, ctx)?;
+    let spec: Value = serde_yaml::from_str(&raw)
+        .with_context(|| format!("{source} is not valid YAML or JSON"))?;
+    if !spec.get("paths").is_some_and(Value::is_object) {
+        return Err(anyhow!("{source} is not an OpenAPI document (no paths)"));
+    }
+
+    let stamp = format_rfc3339(now_millis() / 1000 * 1000).replace(".000Z", "Z");
+    let tree = generate::from_openapi(&spec, &current.base_url, stamp, sha256_hex(raw.as_bytes()));
+    let (path, embedded) = match matches.get_one::<String>("out") {
+        Some(out) => (Path::new(out).to_path_buf(), false),
+        None => (command_tree::synced_path()?, true),
+    };
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        fs::create_dir_all(dir).with_context(|| format!("create {}", dir.display()))?;
+    }
+    fs::write(&path, generate::to_json(&tree)?)
+        .with_context(|| format!("write {}", path.display()))?;
+
+    let ops = tree.resources.iter().map(|r| r.ops.len()).sum::<usize>();
+    eprintln!(
+        "Wrote {} ({} resources, {ops} operations) from {source}.",
+        path.display(),
+        tree.resources.len()
+    );
+    let before = op_names(current);
+    let after = op_names(&tree);
+    print_changes("added", after.difference(&before));
+    print_changes("removed", before.difference(&after));
+    if embedded {
+        eprintln!(
+            "signoz uses this tree from now on; `signoz spec reset` returns to the built-in one."
+        );
+    } else {
+        eprintln!(
+            "Rebuild to embed it, or copy it to {} to use it now.",
+            command_tree::synced_path()?.display()
+        );
+    }
+    Ok(())
+}
+
+fn reset() -> Result<()> {
+    let path = command_tree::synced_path()?;
+    if !path.exists() {
+        eprintln!(
+            "No synced command tree at {}; already using the built-in one.",
+            path.display()
+        );
+        return Ok(());
+    }
+    fs::remove_file(&path).with_context(|| format!("remove {}", path.display()))?;
+    eprintln!(
+        "Removed {}; using the built-in command tree.",
+        path.display()
+    );
+    Ok(())
+}
+
+/// A local file, a path on the configured instance (sent with its
+/// credentials), or any other URL (fetched without them).
+fn fetch(source: &str, ctx: &ApiContext) -> Result<String> {
+    if Path::new(source).is_file() {
+        return fs::read_to_string(source).with_context(|| format!("read {source}"));
+    }
+    let response = if source.starts_with('/') || source.starts_with(&ctx.base_url) {
+        ctx.execute("GET", source, &[], None, None)?
+    } else if source.starts_with("http://") || source.starts_with("https://") {
+        HttpClient::new(String::new(), None, None, Vec::new(), &ctx.client_opts)?.execute(
+            "GET",
+            source,
+            &[],
+            None,
+            None,
+        )?
+    } else {
+        return Err(anyhow!(
+            "{source} is neither a file nor an http(s) URL or /path on the instance"
+        ));
+    };
+    if response.status >= 400 {
+        return Err(ApiError::from_response(&response)).with_context(|| format!("fetch {source}"));
+    }
+    Ok(match response.body {
+        Value::String(text) => text,
+        other => other.to_string(),
+    })
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    ring::digest::digest(&ring::digest::SHA256, data)
+        .as_ref()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+fn op_names(tree: &CommandTree) -> BTreeSet<String> {
+    tree.resources
+        .iter()
+        .flat_map(|r| {
+            r.ops
+                .iter()
+                .map(move |op| format!("{} {}", r.name, op.name))
+        })
+        .collect()
+}
+
+fn print_changes<'a>(label: &str, names: impl Iterator<Item = &'a String>) {
+    let names: Vec<&String> = names.collect();
+    if names.is_empty() {
+        return;
+    }
+    eprintln!("{} operation(s) {label}:", names.len());
+    for name in names.iter().take(MAX_LISTED) {
+        eprintln!("  {name}");
+    }
+    if names.len() > MAX_LISTED {
+        eprintln!("  ... and {} more", names.len() - MAX_LISTED);
+    }
+}
@@ -0,0 +1,162 @@
+use anyhow::{anyhow, Context, Result};
+use clap::Command;
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+
+use crate::error::ApiError;
+use crate::output::{self, OutputOptions};
+use crate::query;
+use crate::ApiContext;
+
+const DATABASES: &[(&str, &str)] = &[
+    ("signoz_traces", "traces"),
+    ("signoz_logs", "logs"),
+    ("signoz_metrics", "metrics"),
+    ("signoz_meter", "meter"),
+    ("signoz_analytics", "analytics"),
+];
+
+pub fn command() -> Command {
+    Command::new("storage")
+        .about("ClickHouse storage inspection (self-hosted)")
+        .subcommand_required(true)
+        .arg_required_else_help(true)
+        .subcommand(Command::new("usage").about("Table sizes, part counts, and TTLs per signal"))
+}
+
+pub fn handle(matches: &clap::ArgMatches, ctx: &ApiContext, opts: &OutputOptions) -> Result<()> {
+    match matches.subcommand() {
+        Some(("usage", _)) => handle_usage(ctx, opts),
+        _ => Err(anyhow!("unknown storage command")),
+    }
+}
+
+fn handle_usage(ctx: &ApiContext, opts: &OutputOptions) -> Result<()> {
+    let databases = DATABASES
+        .iter()
+        .map(|(db, _)| format!("'{db}'"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let parts = clickhouse_rows(
+        ctx,
+        &format!(
+            "SELECT database, table, sum(bytes_on_disk) AS bytes, sum(rows) AS rows, \
+             count() AS parts FROM system.parts WHERE active AND database IN ({databases}) \
+             GROUP BY database, table ORDER BY bytes DESC"
+        ),
+    )?;
+    let tables = clickhouse_rows(
+        ctx,
+        &format!(
+            "SELECT database, name AS table, engine_full FROM system.tables \
+             WHERE database IN ({databases})"
+        ),
+    )?;
+
+    let mut ttls: BTreeMap<(String, String), Option<String>> = BTreeMap::new();
+    for row in &tables {
+        ttls.insert(
+            (text(row, "database"), text(row, "table")),
+            ttl_clause(&text(row, "engine_full")),
+        );
+    }
+
+    let mut signals: BTreeMap<&str, Map<String, Value>> = BTreeMap::new();
+    for row in &parts {
+        let database = text(row, "database");
+        let table = text(row, "table");
+        let signal = DATABASES
+            .iter()
+            .find(|(db, _)| *db == database)
+            .map(|(_, signal)| *signal)
+            .unwrap_or("other");
+        let (bytes, rows, part_count) = (
+            number(row, "bytes"),
+            number(row, "rows"),
+            number(row, "parts"),
+        );
+        let entry = signals.entry(signal).or_insert_with(|| {
+            let mut m = Map::new();
+            m.insert("bytes".into(), json!(0u64));
+            m.insert("rows".into(), json!(0u64));
+            m.insert("parts".into(), json!(0u64));
+            m.insert("tables".into(), json!([]));
+            m
+        });
+        for (key, value) in [("bytes", bytes), ("rows", rows), ("parts", part_count)] {
+            let total = entry.get(key).and_then(Value::as_u64).unwrap_or(0) + value;
+            entry.insert(key.into(), json!(total));
+        }
+        let ttl = ttls
+            .get(&(database.clone(), table.clone()))
+            .cloned()
+            .flatten();
+        if let Some(Value::Array(list)) = entry.get_mut("tables") {
+            list.push(json!({
+                "database": database,
+                "table": table,
+                "bytes": bytes,
+                "rows": rows,
+                "parts": part_count,
+                "ttl": ttl,
+            }));
+        }
+    }
+
+    output::print_value(&json!({ "signals": signals }), opts)
+}
+
+fn clickhouse_rows(ctx: &ApiContext, sql: &str) -> Result<Vec<Value>> {
+    let end = query::now_millis();
+    let body = query::query_range_body(
+        end.saturating_sub(60_000),
+        end,
+        "raw",
+        vec![json!({
+            "type": "clickhouse_sql",
+            "spec": { "name": "A", "query": sql, "disabled": false },
+        })],
+    );
+    let response = query::run_query_range(ctx, body)?;
+    if response.status >= 400 {
+        return Err(ApiError::from_response(&response)).context("clickhouse query failed");
+    }
+    let rows = response
+        .body
+        .pointer("/data/data/results/0/rows")
+        .and_then(Value::as_array)
+        .cloned()
+        .unwrap_or_default();
+    // Raw rows wrap their columns in `data`.
+    Ok(rows
+        .into_iter()
+        .map(|row| match row.get("data") {
+            Some(data) if data.is_object() => data.clone(),
+            _ => row,
+        })
+        .collect())
+}
+
+fn text(row: &Value, key: &str) -> String {
+    match row.get(key) {
+        Some(Value::String(s)) => s.clone(),
+        Some(Value::Null) | None => String::new(),
+        Some(other) => other.to_string(),
+    }
+}
+
+/// ClickHouse returns UInt64 aggregates as strings in JSON output.
+fn number(row: &Value, key: &str) -> u64 {
+    match row.get(key) {
+        Some(Value::Number(n)) => n.as_u64().unwrap_or(0),
+        Some(Value::String(s)) => s.parse().unwrap_or(0),
+        _ => 0,
+    }
+}
+
+fn ttl_clause(engine_full: &str) -> Option<String> {
+    let start = engine_full.find(" TTL ")?;
+    let rest = &engine_full[start + 5..];
+    let end = rest.find(" SETTINGS ").unwrap_or(rest.len());
+    Some(rest[..end].trim().to_string())
+}
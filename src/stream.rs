@@ -0,0 +1,314 @@
+use anyhow::{anyhow, Context, Result};
+use serde_json::Value;
+use std::{
+    fmt, fs,
+    io::{self, BufWriter, Write},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+};
+
+use crate::error::Failure;
+use crate::normalize::Normalizer;
+use crate::output::{self, OutputOptions};
+use crate::paginate::ITEM_POINTERS;
+use crate::transform::{self, Condition};
+
+/// `--stream`: where the items of a list response go, one compact JSON
+/// object per line, as they are parsed out of the arriving body.
+pub struct Emitter {
+    out: Mutex<Box<dyn Write + Send>>,
+    conditions: Vec<Condition>,
+    fields: Option<Vec<String>>,
+    normalize: Option<Normalizer>,
+    stable: bool,
+    written: AtomicUsize,
+}
+
+impl Emitter {
+    /// Items go to `--output-file` or stdout; `--normalize`, `--where`,
+    /// `--fields`, and `--stable-output` are applied per item. Options that need every item at once are refused,
+    /// naming `what` asked for streaming.
+    pub fn new(opts: &OutputOptions, what: &str) -> Result<Self> {
+        let conflict = [
+            (!opts.sort.is_empty(), "--sort-by"),
+            (opts.stats.is_some(), "--stats"),
+            (opts.sink.is_some(), "--output"),
+            (opts.render.is_some(), "--render"),
+            (opts.join.is_some(), "--join"),
+        ]
+        .into_iter()
+        .find_map(|(given, flag)| given.then_some(flag));
+        if let Some(flag) = conflict {
+            return Err(Failure::Usage(format!(
+                "{what} writes items as they arrive and cannot be combined with {flag}"
+            ))
+            .into());
+        }
+        let out: Box<dyn Write + Send> = match &opts.output_file {
+            Some(path) => Box::new(
+                fs::File::create(path).with_context(|| format!("create {}", path.display()))?,
+            ),
+            None => Box::new(io::stdout()),
+        };
+        Ok(Self {
+            out: Mutex::new(Box::new(BufWriter::new(out))),
+            conditions: opts.conditions.clone(),
+            fields: opts.fields.clone(),
+            normalize: opts.normalize.clone(),
+            stable: opts.stable,
+            written: AtomicUsize::new(0),
+        })
+    }
+
+    /// Writes the items that pass `--where`, and flushes so they show up
+    /// while the rest of the body is still arriving.
+    pub fn write(&self, items: Vec<Value>) -> Result<()> {
+        let mut batch = Value::Array(items);
+        if let Some(normalizer) = &self.normalize {
+            normalizer.apply(&mut batch);
+        }
+        if !self.conditions.is_empty() {
+            transform::select(&mut batch, &self.conditions, &[])?;
+        }
+        if let Some(fields) = &self.fields {
+            batch = transform::project(&batch, fields);
+        }
+        let mut out = self.out.lock().unwrap_or_else(|e| e.into_inner());
+        for item in batch.as_array().into_iter().flatten() {
+            if self.stable {
+                serde_json::to_writer(&mut *out, &output::stabilize(item))?;
+            } else {
+                serde_json::to_writer(&mut *out, item)?;
+            }
+            out.write_all(b"\n")?;
+            self.written.fetch_add(1, Ordering::SeqCst);
+        }
+        out.flush().context("write streamed items")
+    }
+
+    /// Items written so far, across every response.
+    pub fn written(&self) -> usize {
+        self.written.load(Ordering::SeqCst)
+    }
+}
+
+impl fmt::Debug for Emitter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Emitter")
+            .field("written", &self.written())
+            .finish_non_exhaustive()
+    }
+}
+
+enum Frame {
+    Object { key: Option<String> },
+    Array { index: usize },
+}
+
+/// Splits a JSON body fed in chunks: elements of the first array found at
+/// one of the item pointers are parsed one at a time and handed back, and
+/// the rest of the document is kept with that array left empty. Memory
+/// stays at one item plus the envelope however long the list is.
+#[derive(Default)]
+pub struct Splitter {
+    /// The document outside the item list.
+    rest: Vec<u8>,
+    /// Containers open in `rest`, for the path of the value being read.
+    stack: Vec<Frame>,
+    in_string: bool,
+    escaped: bool,
+    /// Raw bytes of an object key being read.
+    key: Option<Vec<u8>>,
+    /// Set once inside the item list: the nesting depth within the current
+    /// item, and the item's bytes so far.
+    items: Option<(usize, Vec<u8>)>,
+    /// The item list was found (and is done or under way).
+    found: bool,
+}
+
+impl Splitter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Consumes `chunk`, returning the items it completed.
+    pub fn feed(&mut self, chunk: &[u8]) -> Result<Vec<Value>> {
+        let mut done = Vec::new();
+        for &byte in chunk {
+            if self.items.is_some() {
+                self.item_byte(byte, &mut done)?;
+            } else {
+                self.rest_byte(byte);
+            }
+        }
+        Ok(done)
+    }
+
+    /// The document with its item list emptied, and whether it had one
+    /// (without, the document is returned whole).
+    pub fn finish(self) -> Result<(Value, bool)> {
+        if self.found && (self.items.is_some() || !self.stack.is_empty()) {
+            return Err(anyhow!(
+                "response body ended in the middle of the item list"
+            ));
+        }
+        match serde_json::from_slice(&self.rest) {
+            Ok(body) => Ok((body, self.found)),
+            // Not JSON after all: kept as text, as an unstreamed body is.
+            Err(_) if !self.found => Ok((
+                Value::String(String::from_utf8_lossy(&self.rest).into_owned()),
+                false,
+            )),
+            Err(err) => Err(err).context("parse response body"),
+        }
+    }
+
+    /// Like [`finish`](Self::finish), for a body cut off part way: when the
+    /// cut falls inside the item list, the partial item is dropped and the
+    /// document closed, and the list's pointer is returned with it. Any
+    /// other cut leaves the body as text.
+    pub fn cut(self) -> (Value, Option<String>) {
+        if self.items.is_some() {
+            let list = &self.stack[..self.stack.len() - 1];
+            let mut closed = self.rest.clone();
+            closed.push(b']');
+            for frame in list.iter().rev() {
+                closed.push(match frame {
+                    Frame::Object { .. } => b'}',
+                    Frame::Array { .. } => b']',
+                });
+            }
+            if let Ok(body) = serde_json::from_slice(&closed) {
+                return (body, Some(pointer(list)));
+            }
+        }
+        match serde_json::from_slice(&self.rest) {
+            Ok(body) if !self.found => (body, None),
+            _ => (
+                Value::String(String::from_utf8_lossy(&self.rest).into_owned()),
+                None,
+            ),
+        }
+    }
+
+    fn rest_byte(&mut self, byte: u8) {
+        self.rest.push(byte);
+        if self.in_string {
+            if self.escaped {
+                self.escaped = false;
+            } else if byte == b'\\' {
+                self.escaped = true;
+            } else if byte == b'"' {
+                self.in_string = false;
+                if let Some(raw) = self.key.take() {
+                    if let Some(Frame::Object { key }) = self.stack.last_mut() {
+                        *key = Some(decode_key(&raw));
+                    }
+                    return;
+                }
+            }
+            if let Some(raw) = &mut self.key {
+                raw.push(byte);
+            }
+            return;
+        }
+        match byte {
+            b'"' => {
+                self.in_string = true;
+                // A string in an object before its `:` is a key.
+                if matches!(self.stack.last(), Some(Frame::Object { key: None })) {
+                    self.key = Some(Vec::new());
+                }
+            }
+            b',' => match self.stack.last_mut() {
+                Some(Frame::Object { key }) => *key = None,
+                Some(Frame::Array { index }) => *index += 1,
+                None => {}
+            },
+            b'{' => self.stack.push(Frame::Object { key: None }),
+            b'[' => {
+                if !self.found && ITEM_POINTERS.contains(&self.pointer().as_str()) {
+                    self.found = true;
+                    self.items = Some((0, Vec::new()));
+                }
+                self.stack.push(Frame::Array { index: 0 });
+            }
+            b'}' | b']' => {
+                self.stack.pop();
+            }
+            _ => {}
+        }
+    }
+
+    fn item_byte(&mut self, byte: u8, done: &mut Vec<Value>) -> Result<()> {
+        let Some((depth, item)) = &mut self.items else {
+            return Ok(());
+        };
+        if self.in_string {
+            item.push(byte);
+            if self.escaped {
+                self.escaped = false;
+            } else if byte == b'\\' {
+                self.escaped = true;
+            } else if byte == b'"' {
+                self.in_string = false;
+            }
+            return Ok(());
+        }
+        match byte {
+            b',' | b']' if *depth == 0 => {
+                if !item.is_empty() {
+                    done.push(serde_json::from_slice(item).context("parse streamed item")?);
+                    item.clear();
+                }
+                if byte == b']' {
+                    self.items = None;
+                    self.rest.push(b']');
+                    self.stack.pop();
+                }
+            }
+            b if b.is_ascii_whitespace() && *depth == 0 => {}
+            _ => {
+                match byte {
+                    b'"' => self.in_string = true,
+                    b'{' | b'[' => *depth += 1,
+                    b'}' | b']' => *depth = depth.saturating_sub(1),
+                    _ => {}
+                }
+                item.push(byte);
+            }
+        }
+        Ok(())
+    }
+
+    /// JSON pointer of the value starting now.
+    fn pointer(&self) -> String {
+        pointer(&self.stack)
+    }
+}
+
+fn pointer(frames: &[Frame]) -> String {
+    frames
+        .iter()
+        .map(|frame| match frame {
+            Frame::Object { key } => format!(
+                "/{}",
+                key.as_deref()
+                    .unwrap_or_default()
+                    .replace('~', "~0")
+                    .replace('/', "~1")
+            ),
+            Frame::Array { index } => format!("/{index}"),
+        })
+        .collect()
+}
+
+fn decode_key(raw: &[u8]) -> String {
+    if !raw.contains(&b'\\') {
+        return String::from_utf8_lossy(raw).into_owned();
+    }
+    let quoted = [b"\"", raw, b"\""].concat();
+    serde_json::from_slice(&quoted).unwrap_or_else(|_| String::from_utf8_lossy(raw).into_owned())
+}
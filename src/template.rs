@@ -0,0 +1,233 @@
+use anyhow::{anyhow, Context, Result};
+use clap::{Arg, ArgAction, Command};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::fs;
+
+use crate::command_tree::CommandTree;
+use crate::expect;
+use crate::output::{self, OutputOptions};
+use crate::timeparse;
+use crate::ApiContext;
+
+const TIME_UNITS: &[&str] = &["s", "ms", "us", "ns", "rfc3339"];
+
+pub fn command() -> Command {
+    let file = Arg::new("file")
+        .required(true)
+        .value_name("FILE")
+        .help("Template (YAML or JSON): resource, op, params, body, and vars with their defaults");
+    let var = Arg::new("var")
+        .long("var")
+        .value_name("NAME=VALUE")
+        .action(ArgAction::Append)
+        .help("Set a template variable (repeatable); overrides the template's default");
+    Command::new("template")
+        .about("Run saved requests with {{var}} placeholders filled in")
+        .long_about(
+            "A template names an operation like a `signoz batch` line and may use \
+             {{name}} anywhere in params and body. Variables come from --var, then the \
+             template's `vars` defaults (a null default makes the variable required). \
+             A time such as {{now}} or {{now-7d}}, or a variable holding one, can take a \
+             unit: {{now-7d|ns}} (s, ms, us, ns, rfc3339; ms by default). A string that \
+             is just one placeholder becomes a number, boolean, or null when the value \
+             is one.",
+        )
+        .subcommand_required(true)
+        .subcommand(
+            Command::new("run")
+                .about("Fill in the template and send it")
+                .arg(file.clone())
+                .arg(var.clone()),
+        )
+        .subcommand(
+            Command::new("render")
+                .about("Print the filled-in template without sending it")
+                .arg(file)
+                .arg(var),
+        )
+}
+
+pub fn handle(
+    matches: &clap::ArgMatches,
+    tree: &CommandTree,
+    ctx: &ApiContext,
+    opts: &OutputOptions,
+) -> Result<()> {
+    let (name, sub) = matches
+        .subcommand()
+        .ok_or_else(|| anyhow!("template subcommand required"))?;
+    let file = sub.get_one::<String>("file").expect("required");
+    let raw = fs::read_to_string(file).with_context(|| format!("read {file}"))?;
+    let template: Value =
+        serde_yaml::from_str(&raw).with_context(|| format!("invalid template {file}"))?;
+    let entry = render(&template, sub.get_many::<String>("var"))
+        .with_context(|| format!("template {file}"))?;
+
+    if name == "render" {
+        println!("{}", serde_json::to_string_pretty(&entry)?);
+        return Ok(());
+    }
+    let cli = crate::build_cli(tree);
+    let (op, op_matches) =
+        crate::batch::op_matches(&cli, tree, &entry).with_context(|| format!("template {file}"))?;
+    let (response, checkpoint) = crate::run_op(ctx, op, &op_matches)?;
+    output::print_response(&response, opts)?;
+    expect::check(&response, opts)?;
+    if checkpoint.is_some() {
+        return Err(crate::interrupt::Interrupted { checkpoint }.into());
+    }
+    Ok(())
+}
+
+/// The template with `vars` resolved and dropped, ready for
+/// `batch::op_matches`.
+pub fn render<'a>(
+    template: &Value,
+    overrides: Option<impl Iterator<Item = &'a String>>,
+) -> Result<Value> {
+    let Value::Object(fields) = template else {
+        return Err(anyhow!(
+            "expected a mapping with resource, op, params, and body"
+        ));
+    };
+    let vars = vars(fields, overrides)?;
+    let lookup = |name: &str| vars.get(name).cloned();
+
+    let mut missing = Vec::new();
+    let mut entry = Map::new();
+    for (key, value) in fields {
+        if key != "vars" {
+            entry.insert(key.clone(), fill(value, &lookup, &mut missing)?);
+        }
+    }
+    if !missing.is_empty() {
+        missing.sort();
+        missing.dedup();
+        let flags: Vec<String> = missing.iter().map(|m| format!("--var {m}=...")).collect();
+        return Err(anyhow!("missing variable(s); pass {}", flags.join(" ")));
+    }
+    Ok(Value::Object(entry))
+}
+
+/// The `vars` defaults of a template or runbook with `--var` overrides
+/// applied; variables left null are dropped.
+pub fn vars<'a>(
+    fields: &Map<String, Value>,
+    overrides: Option<impl Iterator<Item = &'a String>>,
+) -> Result<BTreeMap<String, String>> {
+    let mut vars: Map<String, Value> = match fields.get("vars") {
+        Some(Value::Object(vars)) => vars.clone(),
+        Some(Value::Null) | None => Map::new(),
+        Some(_) => return Err(anyhow!("\"vars\" must be a mapping of names to defaults")),
+    };
+    for raw in overrides.into_iter().flatten() {
+        let (name, value) = raw
+            .split_once('=')
+            .ok_or_else(|| anyhow!("--var {raw}: expected NAME=VALUE"))?;
+        vars.insert(name.trim().to_string(), Value::String(value.to_string()));
+    }
+    // A null default leaves the variable unset, so using it is an error.
+    Ok(vars
+        .into_iter()
+        .filter_map(|(name, value)| match value {
+            Value::Null => None,
+            Value::String(s) => Some((name, s)),
+            other => Some((name, other.to_string())),
+        })
+        .collect())
+}
+
+/// Fills the placeholders in `value` with what `vars` finds for their
+/// names; names it does not know are added to `missing`.
+pub fn fill(
+    value: &Value,
+    vars: &dyn Fn(&str) -> Option<String>,
+    missing: &mut Vec<String>,
+) -> Result<Value> {
+    Ok(match value {
+        Value::String(s) => {
+            let trimmed = s.trim();
+            let whole = trimmed.starts_with("{{")
+                && trimmed.ends_with("}}")
+                && trimmed.matches("{{").count() == 1;
+            let filled = fill_str(s, vars, missing)?;
+            match serde_json::from_str::<Value>(&filled) {
+                Ok(typed @ (Value::Number(_) | Value::Bool(_) | Value::Null)) if whole => typed,
+                _ => Value::String(filled),
+            }
+        }
+        Value::Array(items) => Value::Array(
+            items
+                .iter()
+                .map(|item| fill(item, vars, missing))
+                .collect::<Result<_>>()?,
+        ),
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(k, v)| Ok((fill_str(k, vars, missing)?, fill(v, vars, missing)?)))
+                .collect::<Result<_>>()?,
+        ),
+        other => other.clone(),
+    })
+}
+
+pub fn fill_str(
+    text: &str,
+    vars: &dyn Fn(&str) -> Option<String>,
+    missing: &mut Vec<String>,
+) -> Result<String> {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        out.push_str(&rest[..start]);
+        let inner = &rest[start + 2..start + 2 + len];
+        out.push_str(&placeholder(inner, vars, missing)?);
+        rest = &rest[start + 4 + len..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// `name` or `name|unit`; a name that is no variable but starts with
+/// `now` is a time, and so is a value that does. `{{.name}}`, SigNoz's own
+/// variable syntax in dashboard and alert queries, is left alone, and so
+/// is a dotted name that is no variable, like the `{{service.name}}` of a
+/// legend.
+fn placeholder(
+    inner: &str,
+    vars: &dyn Fn(&str) -> Option<String>,
+    missing: &mut Vec<String>,
+) -> Result<String> {
+    let (name, unit) = match inner.split_once('|') {
+        Some((name, unit)) => (name.trim(), Some(unit.trim())),
+        None => (inner.trim(), None),
+    };
+    if name.starts_with('.') {
+        return Ok(format!("{{{{{inner}}}}}"));
+    }
+    let value = match vars(name) {
+        Some(value) => value,
+        None if name.starts_with("now") => name.to_string(),
+        None if name.contains('.') => return Ok(format!("{{{{{inner}}}}}")),
+        None => {
+            missing.push(name.to_string());
+            return Ok(String::new());
+        }
+    };
+    let unit = match unit {
+        Some(unit) if TIME_UNITS.contains(&unit) => unit,
+        Some(unit) => {
+            return Err(anyhow!(
+                "{{{{{inner}}}}}: unknown unit {unit} (use {})",
+                TIME_UNITS.join(", ")
+            ))
+        }
+        None if value.starts_with("now") => "ms",
+        None => return Ok(value),
+    };
+    timeparse::normalize_param(&value, unit).with_context(|| format!("{{{{{inner}}}}}"))
+}
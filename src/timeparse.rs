@@ -0,0 +1,326 @@
+use anyhow::{anyhow, Result};
+use clap::{Arg, ArgMatches};
+
+use crate::command_tree::ParamDef;
+use crate::query;
+
+/// Parses `500ms`, `30s`, `15m`, `1h`, `2d`, `1w` into milliseconds.
+pub fn parse_duration_ms(raw: &str) -> Result<u64> {
+    let raw = raw.trim();
+    let split = raw
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(raw.len());
+    let (num, unit) = raw.split_at(split);
+    let value: f64 = num
+        .parse()
+        .map_err(|_| anyhow!("invalid duration: {raw}"))?;
+    let scale = match unit.trim() {
+        "ms" => 1.0,
+        "" | "s" => 1_000.0,
+        "m" => 60_000.0,
+        "h" => 3_600_000.0,
+        "d" => 86_400_000.0,
+        "w" => 604_800_000.0,
+        other => return Err(anyhow!("invalid duration unit `{other}` in {raw}")),
+    };
+    Ok((value * scale) as u64)
+}
+
+/// Parses a point in time into epoch milliseconds: `now`, `now-15m`,
+/// `now+1h`, a bare duration meaning that long ago (`1h`), RFC 3339
+/// (`2024-05-01T10:00:00Z`, offsets and a date alone are accepted), or an
+/// epoch number in seconds, milliseconds, microseconds, or nanoseconds
+/// (told apart by magnitude).
+pub fn parse_time_ms(raw: &str) -> Result<u64> {
+    let raw = raw.trim();
+    let now = query::now_millis();
+    if let Some(rest) = raw.strip_prefix("now") {
+        return match rest.chars().next() {
+            None => Ok(now),
+            Some('-') => Ok(now.saturating_sub(parse_duration_ms(&rest[1..])?)),
+            Some('+') => now
+                .checked_add(parse_duration_ms(&rest[1..])?)
+                .ok_or_else(|| anyhow!("invalid time: {raw} is too far ahead")),
+            _ => Err(anyhow!("invalid time: {raw}")),
+        };
+    }
+    if raw.chars().all(|c| c.is_ascii_digit()) && !raw.is_empty() {
+        let n: u64 = raw.parse().map_err(|_| anyhow!("invalid time: {raw}"))?;
+        return Ok(match n {
+            0..=99_999_999_999 => n * 1000,
+            100_000_000_000..=99_999_999_999_999 => n,
+            100_000_000_000_000..=99_999_999_999_999_999 => n / 1000,
+            _ => n / 1_000_000,
+        });
+    }
+    if raw.len() >= 10 && raw.as_bytes()[4] == b'-' {
+        return Ok(parse_rfc3339_ns(raw)? / 1_000_000);
+    }
+    parse_duration_ms(raw)
+        .map(|ago| now.saturating_sub(ago))
+        .map_err(|_| anyhow!("invalid time `{raw}` (use now-15m, 1h, RFC 3339, or epoch ms)"))
+}
+
+/// `--since` (a time or a duration) and `--until` (default now) as an
+/// epoch-ms `(start, end)` range. A bare duration in `--since` counts back
+/// from the end of the range; bare digits are an epoch time.
+pub fn range(since: &str, until: Option<&str>) -> Result<(u64, u64)> {
+    let end = until
+        .map(parse_time_ms)
+        .transpose()?
+        .unwrap_or_else(query::now_millis);
+    let start = match parse_duration_ms(since) {
+        Ok(window) if !since.trim().chars().all(|c| c.is_ascii_digit()) => {
+            end.saturating_sub(window)
+        }
+        _ => parse_time_ms(since)?,
+    };
+    if start >= end {
+        return Err(anyhow!("--since must be before --until"));
+    }
+    Ok((start, end))
+}
+
+/// The `--until` flag shared by windowed query commands.
+pub fn until_arg() -> Arg {
+    Arg::new("until")
+        .long("until")
+        .value_name("TIME")
+        .help("End of the window: now-5m, RFC 3339, or epoch ms (default now)")
+}
+
+pub fn until(matches: &ArgMatches) -> Option<&str> {
+    matches.get_one::<String>("until").map(String::as_str)
+}
+
+/// The unit a generated parameter's time values are sent in, or `None` if it
+/// is not a time parameter. Trees without recorded units fall back to the
+/// parameter name and schema type (epoch ms for numbers, RFC 3339 otherwise).
+pub fn param_unit(param: &ParamDef) -> Option<&str> {
+    if let Some(unit) = &param.time_unit {
+        return Some(unit);
+    }
+    let named = matches!(
+        param.param_name.to_ascii_lowercase().as_str(),
+        "start" | "end" | "from" | "to"
+    );
+    match param.schema_type.as_str() {
+        _ if !named => None,
+        "integer" | "number" => Some("ms"),
+        _ => Some("rfc3339"),
+    }
+}
+
+/// Converts any accepted time (see `parse_time_ms`) to `unit`, so an epoch
+/// given in the wrong unit cannot be sent off by a factor of 1000.
+pub fn normalize_param(raw: &str, unit: &str) -> Result<String> {
+    let ms = parse_time_ms(raw)?;
+    let scaled = |factor: u64| {
+        ms.checked_mul(factor)
+            .map(|n| n.to_string())
+            .ok_or_else(|| anyhow!("time {raw} is out of range in {unit}"))
+    };
+    match unit {
+        "s" => Ok((ms / 1000).to_string()),
+        "ms" => Ok(ms.to_string()),
+        "us" => scaled(1000),
+        "ns" => scaled(1_000_000),
+        _ => Ok(format_rfc3339(ms)),
+    }
+}
+
+/// RFC 3339 time as epoch nanoseconds (fractions up to 9 digits; years up
+/// to 2262, where epoch nanoseconds run out).
+pub fn parse_rfc3339_ns(raw: &str) -> Result<u64> {
+    let invalid = || anyhow!("invalid RFC 3339 time: {raw}");
+    let num = |s: &str| s.parse::<i64>().map_err(|_| invalid());
+    let (date, time) = match raw.split_once(['T', ' ']) {
+        Some((date, time)) => (date, time),
+        None => (raw, "00:00:00Z"),
+    };
+    let mut ymd = date.splitn(3, '-');
+    let (year, month, day) = (
+        num(ymd.next().ok_or_else(invalid)?)?,
+        num(ymd.next().ok_or_else(invalid)?)?,
+        num(ymd.next().ok_or_else(invalid)?)?,
+    );
+
+    let (clock, offset_secs) = if let Some(clock) = time.strip_suffix(['Z', 'z']) {
+        (clock, 0)
+    } else if let Some(pos) = time.rfind(['+', '-']) {
+        let (clock, offset) = time.split_at(pos);
+        let sign = if offset.starts_with('-') { -1 } else { 1 };
+        let (h, m) = offset[1..].split_once(':').unwrap_or((&offset[1..], "0"));
+        let (h, m) = (num(h)?, num(m)?);
+        if h > 23 || m > 59 {
+            return Err(invalid());
+        }
+        (clock, sign * (h * 3600 + m * 60))
+    } else {
+        (time, 0)
+    };
+    let (clock, fraction) = clock.split_once('.').unwrap_or((clock, ""));
+    let mut hms = clock.splitn(3, ':');
+    let hour = num(hms.next().ok_or_else(invalid)?)?;
+    let minute = num(hms.next().unwrap_or("0"))?;
+    let second = num(hms.next().unwrap_or("0"))?;
+    // Digits only, so the 9-byte cut below cannot split a character.
+    if !fraction.chars().all(|c| c.is_ascii_digit()) {
+        return Err(invalid());
+    }
+    let nanos = if fraction.is_empty() {
+        0
+    } else {
+        num(&format!("{fraction:0<9}")[..9])?
+    };
+    if !(0..=9999).contains(&year)
+        || !(1..=12).contains(&month)
+        || !(1..=31).contains(&day)
+        || !(0..=23).contains(&hour)
+        || !(0..=59).contains(&minute)
+        || !(0..=60).contains(&second)
+    {
+        return Err(invalid());
+    }
+
+    let secs = days_from_civil(year, month, day) * 86_400 + hour * 3600 + minute * 60 + second
+        - offset_secs;
+    secs.checked_mul(1_000_000_000)
+        .and_then(|ns| ns.checked_add(nanos))
+        .and_then(|ns| u64::try_from(ns).ok())
+        .ok_or_else(invalid)
+}
+
+/// An HTTP `Date` header (`Fri, 16 Oct 2026 14:46:52 GMT`) as epoch ms.
+pub fn parse_http_date(raw: &str) -> Option<u64> {
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+    let parts: Vec<&str> = raw.split_whitespace().collect();
+    let [_, day, month, year, clock, "GMT"] = parts[..] else {
+        return None;
+    };
+    let month = MONTHS.iter().position(|m| *m == month)? + 1;
+    let rfc3339 = format!("{year}-{month:02}-{day:0>2}T{clock}Z");
+    parse_rfc3339_ns(&rfc3339).ok().map(|ns| ns / 1_000_000)
+}
+
+/// Epoch ms as `YYYY-MM-DDTHH:MM:SS.mmmZ`.
+pub fn format_rfc3339(ms: u64) -> String {
+    let secs = (ms / 1000) as i64;
+    let (year, month, day) = civil_from_days(secs.div_euclid(86_400));
+    let rem = secs.rem_euclid(86_400);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}.{:03}Z",
+        rem / 3600,
+        rem / 60 % 60,
+        rem % 60,
+        ms % 1000
+    )
+}
+
+// Proleptic Gregorian conversions (H. Hinnant's days_from_civil algorithm).
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    (
+        if month <= 2 {
+            yoe + era * 400 + 1
+        } else {
+            yoe + era * 400
+        },
+        month,
+        day,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_time_ms_accepts_every_form() {
+        let at = 1_714_557_600_000; // 2024-05-01T10:00:00Z
+        assert_eq!(parse_time_ms("2024-05-01T10:00:00Z").unwrap(), at);
+        assert_eq!(parse_time_ms("2024-05-01T12:00:00+02:00").unwrap(), at);
+        assert_eq!(parse_time_ms("2024-05-01T10:00:00.250Z").unwrap(), at + 250);
+        assert_eq!(parse_time_ms("2024-05-01").unwrap(), at - 10 * 3_600_000);
+        assert_eq!(parse_time_ms("1714557600").unwrap(), at);
+        assert_eq!(parse_time_ms("1714557600000").unwrap(), at);
+        assert_eq!(parse_time_ms("1714557600000000").unwrap(), at);
+        assert_eq!(parse_time_ms("1714557600000000000").unwrap(), at);
+
+        let now = query::now_millis();
+        let near = |ms: u64, expected: u64| ms.abs_diff(expected) < 5_000;
+        assert!(near(parse_time_ms("now").unwrap(), now));
+        assert!(near(parse_time_ms("now-15m").unwrap(), now - 900_000));
+        assert!(near(parse_time_ms("now+1h").unwrap(), now + 3_600_000));
+        assert!(near(parse_time_ms("1h").unwrap(), now - 3_600_000));
+    }
+
+    #[test]
+    fn parse_time_ms_rejects_without_panicking() {
+        for raw in [
+            "",
+            "yesterday",
+            "now*5m",
+            "2024-13-01T00:00:00Z",
+            "2024-05-01T24:00:00Z",
+            "2024-05-01T10:00:99Z",
+            "2024-05-01T10:00:00.12345678é",
+            "2024-05-01T10:00:00+99:00",
+            "2263-01-01T00:00:00Z",
+            "99999-01-01T00:00:00Z",
+            "now+99999999999999w",
+        ] {
+            assert!(parse_time_ms(raw).is_err(), "{raw} should be rejected");
+        }
+    }
+
+    #[test]
+    fn normalize_param_rejects_out_of_range_units() {
+        assert_eq!(normalize_param("1714557600", "s").unwrap(), "1714557600");
+        assert_eq!(
+            normalize_param("1714557600", "ns").unwrap(),
+            "1714557600000000000"
+        );
+        assert!(normalize_param("99999999999", "ns").is_err());
+    }
+
+    #[test]
+    fn range_counts_a_bare_duration_back_from_until() {
+        let at = 1_714_557_600_000;
+        assert_eq!(
+            range("1h", Some("2024-05-01T10:00:00Z")).unwrap(),
+            (at - 3_600_000, at)
+        );
+        assert_eq!(
+            range("2024-05-01T09:00:00Z", Some("1714557600000")).unwrap(),
+            (at - 3_600_000, at)
+        );
+        assert_eq!(
+            range("1714554000", Some("1714557600")).unwrap(),
+            (at - 3_600_000, at)
+        );
+        assert!(range("2024-05-01T11:00:00Z", Some("2024-05-01T10:00:00Z")).is_err());
+
+        let (start, end) = range("15m", None).unwrap();
+        assert_eq!(end - start, 900_000);
+    }
+}
@@ -0,0 +1,448 @@
+use anyhow::{anyhow, Context, Result};
+use clap::{Arg, ArgAction, Command};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+
+use crate::error::ApiError;
+use crate::output::{self, OutputOptions};
+use crate::query;
+use crate::timeparse;
+use crate::ApiContext;
+
+pub fn extend(cmd: Command) -> Command {
+    cmd.subcommand(
+        Command::new("show")
+            .about("Fetch every span of a trace (try --render waterfall)")
+            .arg(Arg::new("trace-id").required(true).help("Trace id"))
+            .arg(
+                Arg::new("since")
+                    .long("since")
+                    .value_name("DURATION|TIME")
+                    .help("Window to search for the trace (default 1d)"),
+            )
+            .arg(timeparse::until_arg()),
+    )
+    .subcommand(
+        Command::new("breakdown")
+            .about("Share of a trace's time spent in each service and span kind")
+            .arg(Arg::new("trace-id").required(true).help("Trace id"))
+            .arg(
+                Arg::new("since")
+                    .long("since")
+                    .value_name("DURATION|TIME")
+                    .help("Window to search for the trace (default 1d)"),
+            )
+            .arg(timeparse::until_arg()),
+    )
+    .subcommand(
+        Command::new("search")
+            .about("Find spans by service, operation, and duration; prints trace ids")
+            .arg(
+                Arg::new("service")
+                    .long("service")
+                    .value_name("NAME")
+                    .help("Only spans from this service.name"),
+            )
+            .arg(
+                Arg::new("op")
+                    .long("op")
+                    .value_name("NAME")
+                    .help("Span (operation) name, e.g. 'HTTP GET'"),
+            )
+            .arg(
+                Arg::new("min-duration")
+                    .long("min-duration")
+                    .value_name("DURATION")
+                    .help("Only spans at least this long (e.g. 500ms, 2s)"),
+            )
+            .arg(
+                Arg::new("filter")
+                    .long("filter")
+                    .value_name("EXPR")
+                    .help("Extra filter expression, e.g. \"http.status_code >= 500\""),
+            )
+            .arg(
+                Arg::new("since")
+                    .long("since")
+                    .value_name("DURATION|TIME")
+                    .help(
+                        "How far back (30m, 1h; default 1h) or a start time (RFC 3339, epoch ms)",
+                    ),
+            )
+            .arg(timeparse::until_arg())
+            .arg(
+                Arg::new("limit")
+                    .long("limit")
+                    .value_name("N")
+                    .default_value("50")
+                    .help("Maximum spans to return (slowest first)"),
+            )
+            .arg(
+                Arg::new("print-query")
+                    .long("print-query")
+                    .action(ArgAction::SetTrue)
+                    .help("Print the generated query_range body instead of running it"),
+            ),
+    )
+    .subcommand(
+        Command::new("sampling-report")
+            .about("Estimate effective trace sampling per service")
+            .arg(
+                Arg::new("service")
+                    .long("service")
+                    .value_name("NAME")
+                    .help("Limit the report to one service"),
+            )
+            .arg(
+                Arg::new("since")
+                    .long("since")
+                    .value_name("DURATION|TIME")
+                    .help("Window to analyze (15m, 1h, 1d; default 1h) or a start time"),
+            )
+            .arg(timeparse::until_arg())
+            .arg(
+                Arg::new("metric")
+                    .long("metric")
+                    .value_name("NAME")
+                    .default_value("signoz_calls_total")
+                    .help("Span-metrics counter with unsampled call counts"),
+            ),
+    )
+}
+
+pub fn handle(
+    op: &str,
+    matches: &clap::ArgMatches,
+    ctx: &ApiContext,
+    opts: &OutputOptions,
+) -> Option<Result<()>> {
+    match op {
+        "show" => Some(handle_show(matches, ctx, opts)),
+        "breakdown" => Some(handle_breakdown(matches, ctx, opts)),
+        "search" => Some(handle_search(matches, ctx, opts)),
+        "sampling-report" => Some(handle_sampling_report(matches, ctx, opts)),
+        _ => None,
+    }
+}
+
+fn handle_show(matches: &clap::ArgMatches, ctx: &ApiContext, opts: &OutputOptions) -> Result<()> {
+    output::print_value(&Value::Array(fetch_spans(matches, ctx)?), opts)
+}
+
+/// Self time (a span's duration minus the time covered by its children)
+/// summed per service and per span kind, so concurrent children are not
+/// counted twice against their parent.
+fn handle_breakdown(
+    matches: &clap::ArgMatches,
+    ctx: &ApiContext,
+    opts: &OutputOptions,
+) -> Result<()> {
+    let spans = fetch_spans(matches, ctx)?;
+    let interval = |span: &Value| {
+        let start = span["start_ns"].as_u64()?;
+        let duration = span["duration_ms"].as_f64()? * 1e6;
+        Some((start, start + duration as u64))
+    };
+    let mut children: BTreeMap<&str, Vec<(u64, u64)>> = BTreeMap::new();
+    for span in &spans {
+        if let (Some(parent), Some(range)) = (span["parentSpanID"].as_str(), interval(span)) {
+            children.entry(parent).or_default().push(range);
+        }
+    }
+
+    let mut by_service: BTreeMap<String, (usize, u64)> = BTreeMap::new();
+    let mut by_kind: BTreeMap<String, (usize, u64)> = BTreeMap::new();
+    for span in &spans {
+        let Some((start, end)) = interval(span) else {
+            continue;
+        };
+        let mut covered: Vec<(u64, u64)> = span["spanID"]
+            .as_str()
+            .and_then(|id| children.get(id))
+            .into_iter()
+            .flatten()
+            .map(|&(s, e)| (s.max(start), e.min(end)))
+            .filter(|(s, e)| s < e)
+            .collect();
+        covered.sort_unstable();
+        let (mut busy, mut reach) = (0, start);
+        for (s, e) in covered {
+            busy += e.saturating_sub(s.max(reach));
+            reach = reach.max(e);
+        }
+        let own = (end - start).saturating_sub(busy);
+        let label = |key: &str| span[key].as_str().unwrap_or("unknown").to_string();
+        for (totals, key) in [(&mut by_service, "service"), (&mut by_kind, "kind")] {
+            let entry = totals.entry(label(key)).or_default();
+            entry.0 += 1;
+            entry.1 += own;
+        }
+    }
+
+    let total: u64 = by_service.values().map(|(_, ns)| ns).sum();
+    let mut rows = Vec::new();
+    for (group, totals) in [("service", by_service), ("kind", by_kind)] {
+        let mut totals: Vec<_> = totals.into_iter().collect();
+        totals.sort_by(|a, b| b.1 .1.cmp(&a.1 .1).then_with(|| a.0.cmp(&b.0)));
+        rows.extend(totals.into_iter().map(|(name, (count, ns))| {
+            let percent = match total {
+                0 => 0.0,
+                _ => (ns as f64 * 1000.0 / total as f64).round() / 10.0,
+            };
+            json!({
+                "group": group,
+                "name": name,
+                "spans": count,
+                "self_ms": ns as f64 / 1e6,
+                "percent": percent,
+            })
+        }));
+    }
+    output::print_value(&Value::Array(rows), opts)
+}
+
+/// Spans of one trace in start order as `{traceID, spanID, parentSpanID,
+/// service, operation, kind, start_ns, duration_ms, error}`.
+fn fetch_spans(matches: &clap::ArgMatches, ctx: &ApiContext) -> Result<Vec<Value>> {
+    let trace_id = matches
+        .get_one::<String>("trace-id")
+        .ok_or_else(|| anyhow!("trace id required"))?;
+    let since = matches
+        .get_one::<String>("since")
+        .map_or("1d", String::as_str);
+    let (start, end) = timeparse::range(since, timeparse::until(matches))?;
+    let spec = json!({
+        "filter": { "expression": format!("trace_id = '{}'", trace_id.replace('\'', "\\'")) },
+        "selectFields": [
+            { "name": "trace_id" },
+            { "name": "span_id" },
+            { "name": "parent_span_id" },
+            { "name": "name" },
+            { "name": "kind_string" },
+            { "name": "service.name", "fieldContext": "resource" },
+            { "name": "duration_nano" },
+            { "name": "has_error" },
+        ],
+        "order": [{ "key": { "name": "timestamp" }, "direction": "asc" }],
+        "limit": 10_000,
+    });
+    let body = query::query_range_body(
+        start,
+        end,
+        "raw",
+        vec![query::builder_query("A", "traces", spec)],
+    );
+    let response = query::run_query_range(ctx, body)?;
+    if response.status >= 400 {
+        return Err(ApiError::from_response(&response)).context("traces query failed");
+    }
+    let spans: Vec<Value> = response
+        .body
+        .pointer("/data/data/results/0/rows")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .map(|row| {
+            let data = row.get("data").unwrap_or(row);
+            let field = |keys: &[&str]| keys.iter().find_map(|k| data.get(*k)).cloned();
+            json!({
+                "traceID": field(&["trace_id", "traceID"]),
+                "spanID": field(&["span_id", "spanID"]),
+                "parentSpanID": field(&["parent_span_id", "parentSpanID"]),
+                "service": field(&["service.name", "serviceName"]),
+                "operation": field(&["name"]),
+                "kind": field(&["kind_string", "spanKind"]),
+                "start_ns": row.get("timestamp").or(data.get("timestamp")).and_then(timestamp_ns),
+                "duration_ms": field(&["duration_nano", "durationNano"])
+                    .as_ref()
+                    .and_then(number)
+                    .map(|n| n / 1e6),
+                "error": field(&["has_error", "hasError"]).unwrap_or(Value::Bool(false)),
+            })
+        })
+        .collect();
+    if spans.is_empty() {
+        return Err(anyhow!(
+            "no spans found for trace {trace_id} (widen --since?)"
+        ));
+    }
+    Ok(spans)
+}
+
+fn number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.parse().ok(),
+        _ => None,
+    }
+}
+
+/// Span start as epoch ns from an RFC 3339 string or a ns number.
+fn timestamp_ns(value: &Value) -> Option<u64> {
+    match value {
+        Value::String(s) if s.contains('T') => timeparse::parse_rfc3339_ns(s).ok(),
+        other => number(other).map(|n| n as u64),
+    }
+}
+
+fn handle_search(matches: &clap::ArgMatches, ctx: &ApiContext, opts: &OutputOptions) -> Result<()> {
+    let arg = |name: &str| matches.get_one::<String>(name).map(String::as_str);
+    let quote = |s: &str| format!("'{}'", s.replace('\'', "\\'"));
+    let (start, end) = timeparse::range(
+        ctx.since("traces", matches, "1h"),
+        timeparse::until(matches),
+    )?;
+    let limit: usize = arg("limit")
+        .unwrap_or("50")
+        .parse()
+        .map_err(|_| anyhow!("invalid --limit"))?;
+    let mut clauses = Vec::new();
+    if let Some(service) = arg("service") {
+        clauses.push(format!("service.name = {}", quote(service)));
+    }
+    if let Some(op) = arg("op") {
+        clauses.push(format!("name = {}", quote(op)));
+    }
+    if let Some(min) = arg("min-duration") {
+        let nanos = timeparse::parse_duration_ms(min)?.saturating_mul(1_000_000);
+        clauses.push(format!("duration_nano >= {nanos}"));
+    }
+    if let Some(filter) = arg("filter") {
+        clauses.push(format!("({filter})"));
+    }
+
+    let spec = json!({
+        "filter": { "expression": clauses.join(" AND ") },
+        "selectFields": [
+            { "name": "trace_id" },
+            { "name": "span_id" },
+            { "name": "name" },
+            { "name": "service.name", "fieldContext": "resource" },
+            { "name": "duration_nano" },
+        ],
+        "order": [{ "key": { "name": "duration_nano" }, "direction": "desc" }],
+        "limit": limit,
+    });
+    let body = query::query_range_body(
+        start,
+        end,
+        "raw",
+        vec![query::builder_query("A", "traces", spec)],
+    );
+    if let Some(result) = query::dry_run(matches, &body, opts) {
+        return result;
+    }
+    let response = query::run_query_range(ctx, body)?;
+    if response.status >= 400 {
+        return Err(ApiError::from_response(&response)).context("traces query failed");
+    }
+    let spans: Vec<Value> = response
+        .body
+        .pointer("/data/data/results/0/rows")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .map(|row| {
+            let data = row.get("data").unwrap_or(row);
+            let field = |keys: &[&str]| keys.iter().find_map(|k| data.get(*k)).cloned();
+            let nanos = field(&["duration_nano", "durationNano"])
+                .as_ref()
+                .and_then(number);
+            json!({
+                "traceID": field(&["trace_id", "traceID"]),
+                "spanID": field(&["span_id", "spanID"]),
+                "service": field(&["service.name", "serviceName"]),
+                "operation": field(&["name"]),
+                "duration_ms": nanos.map(|n| n / 1e6),
+                "timestamp": row.get("timestamp").or(data.get("timestamp")),
+            })
+        })
+        .collect();
+    output::print_value(&Value::Array(spans), opts)
+}
+
+/// Stored span counts vs. span-metrics call counts (computed before
+/// sampling) per service; their ratio is the effective sampling rate.
+fn handle_sampling_report(
+    matches: &clap::ArgMatches,
+    ctx: &ApiContext,
+    opts: &OutputOptions,
+) -> Result<()> {
+    let since = ctx.since("traces", matches, "1h");
+    let metric = matches
+        .get_one::<String>("metric")
+        .map(String::as_str)
+        .unwrap_or("signoz_calls_total");
+    let (start, end) = timeparse::range(since, timeparse::until(matches))?;
+    let filter = matches
+        .get_one::<String>("service")
+        .map(|svc| format!("service.name = '{}'", svc.replace('\'', "\\'")))
+        .unwrap_or_default();
+    let group_by = json!([{ "name": "service.name" }]);
+
+    let spans = query::builder_query(
+        "A",
+        "traces",
+        json!({
+            "aggregations": [{ "expression": "count()" }],
+            "groupBy": group_by,
+            "filter": { "expression": filter },
+        }),
+    );
+    let calls = query::builder_query(
+        "A",
+        "metrics",
+        json!({
+            "aggregations": [{
+                "metricName": metric,
+                "timeAggregation": "increase",
+                "spaceAggregation": "sum",
+            }],
+            "groupBy": group_by,
+            "filter": { "expression": filter },
+        }),
+    );
+
+    let span_counts = scalar_by_service(ctx, start, end, spans)?;
+    let call_counts = scalar_by_service(ctx, start, end, calls)?;
+
+    let mut services: Vec<&String> = span_counts.keys().chain(call_counts.keys()).collect();
+    services.sort();
+    services.dedup();
+    let rows: Vec<Value> = services
+        .into_iter()
+        .map(|svc| {
+            let spans = span_counts.get(svc).copied().unwrap_or(0.0);
+            let calls = call_counts.get(svc).copied();
+            let rate = calls.filter(|c| *c > 0.0).map(|c| (spans / c).min(1.0));
+            json!({
+                "service": svc,
+                "spans": spans,
+                "requests": calls,
+                "sampling_rate": rate,
+            })
+        })
+        .collect();
+
+    output::print_value(
+        &json!({ "since": since, "start": start, "end": end, "metric": metric, "services": rows }),
+        opts,
+    )
+}
+
+fn scalar_by_service(
+    ctx: &ApiContext,
+    start: u64,
+    end: u64,
+    query: Value,
+) -> Result<BTreeMap<String, f64>> {
+    let body = query::query_range_body(start, end, "scalar", vec![query]);
+    let response = query::run_query_range(ctx, body)?;
+    if response.status >= 400 {
+        return Err(ApiError::from_response(&response)).context("query_range failed");
+    }
+    Ok(query::scalar_rows(&response.body)
+        .into_iter()
+        .filter_map(|(groups, value)| Some((groups.into_iter().next()?, value.unwrap_or(0.0))))
+        .collect())
+}
@@ -0,0 +1,346 @@
+use anyhow::{anyhow, Context, Result};
+use serde_json::{json, Map, Value};
+use std::{cmp::Ordering, fs};
+
+use crate::paginate::items_pointer;
+
+/// `--join FILE on KEY`: left-joins the current response's items with the
+/// items of a previously saved output. `KEY` may be `left=right` when the
+/// two sides name the field differently; both accept dotted paths.
+#[derive(Clone, Debug)]
+pub struct JoinSpec {
+    other: Value,
+    left_key: String,
+    right_key: String,
+}
+
+impl JoinSpec {
+    pub fn from_args(values: &[String]) -> Result<Self> {
+        let [file, on, key] = values else {
+            return Err(anyhow!("--join expects: FILE on KEY"));
+        };
+        if on != "on" {
+            return Err(anyhow!("--join expects: FILE on KEY (got `{on}`)"));
+        }
+        let raw = fs::read_to_string(file).with_context(|| format!("read {file}"))?;
+        let other =
+            serde_json::from_str(&raw).with_context(|| format!("invalid JSON in {file}"))?;
+        let (left_key, right_key) = match key.split_once('=') {
+            Some((l, r)) => (l.to_string(), r.to_string()),
+            None => (key.clone(), key.clone()),
+        };
+        Ok(Self {
+            other,
+            left_key,
+            right_key,
+        })
+    }
+
+    /// Each left item is merged with every right item sharing its key (left
+    /// fields win); unmatched left items pass through unchanged.
+    pub fn apply(&self, value: &mut Value) -> Result<()> {
+        let right = self
+            .other
+            .pointer(items_pointer(&self.other).unwrap_or(""))
+            .and_then(Value::as_array)
+            .ok_or_else(|| anyhow!("join file has no item list"))?;
+        let pointer =
+            items_pointer(value).ok_or_else(|| anyhow!("response has no item list to join"))?;
+        let Some(Value::Array(left)) = value.pointer_mut(pointer) else {
+            return Ok(());
+        };
+
+        let mut joined = Vec::with_capacity(left.len());
+        for item in left.drain(..) {
+            let key = lookup(&item, &self.left_key).cloned();
+            let matches: Vec<&Value> = match &key {
+                Some(key) if !key.is_null() => right
+                    .iter()
+                    .filter(|r| lookup(r, &self.right_key) == Some(key))
+                    .collect(),
+                _ => Vec::new(),
+            };
+            if matches.is_empty() {
+                joined.push(item);
+                continue;
+            }
+            for other in matches {
+                joined.push(merge(&item, other));
+            }
+        }
+        *left = joined;
+        Ok(())
+    }
+}
+
+/// `--stats FIELD`: count, min, max, mean, and nearest-rank percentiles of a
+/// numeric field (numbers or numeric strings) across the value's items.
+/// Items without the field are counted as `missing`.
+pub fn stats(value: &Value, field: &str) -> Result<Value> {
+    let items = value
+        .pointer(items_pointer(value).unwrap_or(""))
+        .and_then(Value::as_array)
+        .ok_or_else(|| anyhow!("--stats needs a response with an item list"))?;
+    let mut numbers: Vec<f64> = items
+        .iter()
+        .filter_map(|item| match lookup(item, field)? {
+            Value::Number(n) => n.as_f64(),
+            Value::String(s) => s.trim().parse().ok(),
+            _ => None,
+        })
+        .filter(|n: &f64| n.is_finite())
+        .collect();
+    let missing = items.len() - numbers.len();
+    if numbers.is_empty() {
+        return Ok(json!({ "field": field, "count": 0, "missing": missing }));
+    }
+    numbers.sort_by(f64::total_cmp);
+    let percentile = |p: f64| {
+        let rank = (p / 100.0 * numbers.len() as f64).ceil() as usize;
+        numbers[rank.clamp(1, numbers.len()) - 1]
+    };
+    Ok(json!({
+        "field": field,
+        "count": numbers.len(),
+        "missing": missing,
+        "min": numbers[0],
+        "max": numbers[numbers.len() - 1],
+        "avg": numbers.iter().sum::<f64>() / numbers.len() as f64,
+        "p50": percentile(50.0),
+        "p95": percentile(95.0),
+        "p99": percentile(99.0),
+    }))
+}
+
+const WHERE_OPERATORS: &[&str] = &[
+    "==",
+    "!=",
+    ">=",
+    "<=",
+    "=",
+    ">",
+    "<",
+    "~",
+    " startsWith ",
+    " endsWith ",
+];
+
+/// `--where PATH<op>VALUE`: a condition on each item. `=`/`==` and `!=`
+/// compare as numbers when both sides are numeric, else as text; `<`, `>`,
+/// `<=`, `>=` order the same way (so RFC 3339 times work); `~` is a
+/// case-insensitive substring match, and `PATH startsWith VALUE` and
+/// `PATH endsWith VALUE` match the text's ends exactly. An item without the
+/// field only passes `!=`.
+#[derive(Clone, Debug)]
+pub struct Condition {
+    path: String,
+    op: &'static str,
+    value: String,
+}
+
+impl Condition {
+    pub fn parse(raw: &str) -> Result<Self> {
+        let (at, op) = WHERE_OPERATORS
+            .iter()
+            .filter_map(|op| raw.find(op).map(|at| (at, *op)))
+            .min_by_key(|(at, op)| (*at, std::cmp::Reverse(op.len())))
+            .ok_or_else(|| {
+                anyhow!("--where expects PATH=VALUE (or !=, <, >, <=, >=, ~, startsWith, endsWith): {raw}")
+            })?;
+        let path = raw[..at].trim();
+        if path.is_empty() {
+            return Err(anyhow!(
+                "--where has no field before `{}`: {raw}",
+                op.trim()
+            ));
+        }
+        let value = raw[at + op.len()..].trim();
+        let value = ['"', '\'']
+            .iter()
+            .find_map(|q| value.strip_prefix(*q)?.strip_suffix(*q))
+            .unwrap_or(value);
+        Ok(Self {
+            path: path.to_string(),
+            op,
+            value: value.to_string(),
+        })
+    }
+
+    fn holds(&self, item: &Value) -> bool {
+        let Some(found) = lookup(item, &self.path) else {
+            return self.op == "!=";
+        };
+        let text = match found {
+            Value::String(s) => s.clone(),
+            other => other.to_string(),
+        };
+        match self.op.trim() {
+            "~" => return text.to_lowercase().contains(&self.value.to_lowercase()),
+            "startsWith" => return text.starts_with(&self.value),
+            "endsWith" => return text.ends_with(&self.value),
+            _ => {}
+        }
+        let ordering = match (text.trim().parse::<f64>(), self.value.parse::<f64>()) {
+            (Ok(a), Ok(b)) => a.partial_cmp(&b),
+            _ => Some(text.as_str().cmp(self.value.as_str())),
+        };
+        let Some(ordering) = ordering else {
+            return false;
+        };
+        match self.op {
+            "=" | "==" => ordering.is_eq(),
+            "!=" => ordering.is_ne(),
+            ">" => ordering.is_gt(),
+            "<" => ordering.is_lt(),
+            ">=" => ordering.is_ge(),
+            _ => ordering.is_le(),
+        }
+    }
+}
+
+/// `--sort-by PATH[:asc|:desc]`; items missing the field sort last.
+#[derive(Clone, Debug)]
+pub struct SortKey {
+    path: String,
+    descending: bool,
+}
+
+impl SortKey {
+    pub fn parse(raw: &str) -> Result<Self> {
+        let (path, descending) = match raw.rsplit_once(':') {
+            Some((path, "desc")) => (path, true),
+            Some((path, "asc")) => (path, false),
+            Some((_, order)) => {
+                return Err(anyhow!(
+                    "--sort-by order must be asc or desc, not `{order}`"
+                ))
+            }
+            None => (raw, false),
+        };
+        let path = path.trim();
+        if path.is_empty() {
+            return Err(anyhow!("--sort-by needs a field"));
+        }
+        Ok(Self {
+            path: path.to_string(),
+            descending,
+        })
+    }
+
+    fn compare(&self, a: &Value, b: &Value) -> Ordering {
+        let (a, b) = (lookup(a, &self.path), lookup(b, &self.path));
+        let ordering = match (a, b) {
+            (None | Some(Value::Null), None | Some(Value::Null)) => return Ordering::Equal,
+            (None | Some(Value::Null), _) => return Ordering::Greater,
+            (_, None | Some(Value::Null)) => return Ordering::Less,
+            (Some(Value::Number(a)), Some(Value::Number(b))) => a
+                .as_f64()
+                .zip(b.as_f64())
+                .and_then(|(a, b)| a.partial_cmp(&b))
+                .unwrap_or(Ordering::Equal),
+            (Some(Value::String(a)), Some(Value::String(b))) => a.cmp(b),
+            (Some(a), Some(b)) => a.to_string().cmp(&b.to_string()),
+        };
+        if self.descending {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    }
+}
+
+/// Keeps the items meeting every condition, then orders them by the sort
+/// keys in turn (stable, so ties keep the server's order).
+pub fn select(value: &mut Value, conditions: &[Condition], sort: &[SortKey]) -> Result<()> {
+    let pointer = items_pointer(value)
+        .ok_or_else(|| anyhow!("--where/--sort-by need a response with an item list"))?;
+    let Some(Value::Array(items)) = value.pointer_mut(pointer) else {
+        return Ok(());
+    };
+    items.retain(|item| conditions.iter().all(|c| c.holds(item)));
+    items.sort_by(|a, b| {
+        sort.iter()
+            .map(|key| key.compare(a, b))
+            .find(|o| o.is_ne())
+            .unwrap_or(Ordering::Equal)
+    });
+    Ok(())
+}
+
+/// `--fields a,b.c`: keeps only those dotted paths of each item, nested as
+/// they were. Without an item list, the `data` object of an API envelope
+/// (or else the value itself) is projected; missing paths are left out.
+pub fn project(value: &Value, fields: &[String]) -> Value {
+    let pick = |item: &Value| -> Value {
+        if !item.is_object() {
+            return item.clone();
+        }
+        let mut out = Value::Object(Map::new());
+        for field in fields {
+            if let Some(found) = lookup(item, field) {
+                insert_path(&mut out, field, found.clone());
+            }
+        }
+        out
+    };
+    let mut value = value.clone();
+    match items_pointer(&value) {
+        Some(pointer) => {
+            if let Some(Value::Array(items)) = value.pointer_mut(pointer) {
+                for item in items.iter_mut() {
+                    *item = pick(item);
+                }
+            }
+        }
+        None => match value.get_mut("data") {
+            Some(data @ Value::Object(_)) => *data = pick(data),
+            _ => value = pick(&value),
+        },
+    }
+    value
+}
+
+fn insert_path(out: &mut Value, path: &str, leaf: Value) {
+    let mut cur = out;
+    let mut parts = path.split('.').peekable();
+    while let Some(part) = parts.next() {
+        let Value::Object(map) = cur else {
+            return;
+        };
+        if parts.peek().is_none() {
+            map.insert(part.to_string(), leaf);
+            return;
+        }
+        cur = map
+            .entry(part.to_string())
+            .or_insert_with(|| Value::Object(Map::new()));
+    }
+}
+
+/// Dotted-path lookup that also looks inside a row's `data` object, where
+/// query_range raw rows keep their columns.
+pub fn lookup<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    lookup_direct(value, path)
+        .or_else(|| value.get("data").and_then(|data| lookup_direct(data, path)))
+}
+
+fn lookup_direct<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.').try_fold(value, |cur, part| cur.get(part))
+}
+
+fn merge(left: &Value, right: &Value) -> Value {
+    match (left, right) {
+        (Value::Object(l), Value::Object(r)) => {
+            let mut out: Map<String, Value> = r.clone();
+            for (k, v) in l {
+                let merged = match out.get(k) {
+                    Some(existing) => merge(v, existing),
+                    None => v.clone(),
+                };
+                out.insert(k.clone(), merged);
+            }
+            Value::Object(out)
+        }
+        _ => left.clone(),
+    }
+}
@@ -0,0 +1,242 @@
+use anyhow::{anyhow, Context, Result};
+use clap::{Arg, ArgAction, Command};
+use serde_json::{json, Value};
+use std::fs;
+
+use crate::apply;
+use crate::error::ApiError;
+use crate::http::Body;
+use crate::output::{self, OutputOptions};
+use crate::ApiContext;
+
+const USERS_PATH: &str = "/api/v1/user";
+const INVITE_PATH: &str = "/api/v1/invite";
+const BULK_INVITE_PATH: &str = "/api/v1/invite/bulk";
+const ROLES: [&str; 3] = ["ADMIN", "EDITOR", "VIEWER"];
+
+pub fn extend(cmd: Command) -> Command {
+    let auto_approve = || {
+        Arg::new("auto-approve")
+            .long("auto-approve")
+            .action(ArgAction::SetTrue)
+            .help("Skip the confirmation prompt")
+    };
+    cmd.subcommand(
+        Command::new("invite")
+            .about("Invite one or more users by email")
+            .arg(
+                Arg::new("email")
+                    .long("email")
+                    .value_name("EMAIL")
+                    .action(ArgAction::Append)
+                    .value_delimiter(',')
+                    .required_unless_present("from-file")
+                    .help("Email to invite (repeatable or comma-separated)"),
+            )
+            .arg(
+                Arg::new("from-file")
+                    .long("from-file")
+                    .value_name("FILE")
+                    .help("One invite per line: email[,role[,name]]"),
+            )
+            .arg(
+                Arg::new("role")
+                    .long("role")
+                    .value_name("ROLE")
+                    .default_value("viewer")
+                    .help("admin, editor, or viewer (default for lines without a role)"),
+            )
+            .arg(
+                Arg::new("name")
+                    .long("name")
+                    .value_name("NAME")
+                    .help("Display name (single --email only)"),
+            )
+            .arg(
+                Arg::new("frontend-url")
+                    .long("frontend-url")
+                    .value_name("URL")
+                    .help("Base of the invite link (default: the base URL)"),
+            )
+            .arg(auto_approve()),
+    )
+    .subcommand(
+        Command::new("deactivate")
+            .about("Remove users' access, by id or email")
+            .arg(
+                Arg::new("user")
+                    .required(true)
+                    .num_args(1..)
+                    .help("User id or email"),
+            )
+            .arg(auto_approve()),
+    )
+}
+
+pub fn handle(
+    op: &str,
+    matches: &clap::ArgMatches,
+    ctx: &ApiContext,
+    opts: &OutputOptions,
+) -> Option<Result<()>> {
+    match op {
+        "invite" => Some(handle_invite(matches, ctx, opts)),
+        "deactivate" => Some(handle_deactivate(matches, ctx, opts)),
+        _ => None,
+    }
+}
+
+/// One invite goes through the single-invite endpoint, several through the
+/// bulk one after a confirmation listing them.
+fn handle_invite(matches: &clap::ArgMatches, ctx: &ApiContext, opts: &OutputOptions) -> Result<()> {
+    let default_role = role(
+        matches
+            .get_one::<String>("role")
+            .map_or("viewer", String::as_str),
+    )?;
+    let frontend = matches
+        .get_one::<String>("frontend-url")
+        .unwrap_or(&ctx.base_url)
+        .trim_end_matches('/')
+        .to_string();
+    let invite = |email: &str, role: &str, name: Option<&str>| {
+        json!({
+            "email": email.trim(),
+            "role": role,
+            "name": name.unwrap_or_default(),
+            "frontendBaseUrl": frontend,
+        })
+    };
+
+    let mut invites = Vec::new();
+    let name = matches.get_one::<String>("name").map(String::as_str);
+    for email in matches.get_many::<String>("email").into_iter().flatten() {
+        invites.push(invite(email, default_role, name));
+    }
+    if let Some(path) = matches.get_one::<String>("from-file") {
+        let raw = fs::read_to_string(path).with_context(|| format!("read {path}"))?;
+        for (i, line) in raw.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut fields = line.split(',').map(str::trim);
+            let email = fields.next().unwrap_or_default();
+            let line_role = match fields.next().filter(|r| !r.is_empty()) {
+                Some(r) => role(r).with_context(|| format!("{path}:{}", i + 1))?,
+                None => default_role,
+            };
+            invites.push(invite(email, line_role, fields.next()));
+        }
+    }
+    if let Some(bad) = invites
+        .iter()
+        .find(|i| !i["email"].as_str().unwrap_or_default().contains('@'))
+    {
+        return Err(anyhow!("invalid email: {}", bad["email"]));
+    }
+    if name.is_some() && invites.len() > 1 {
+        return Err(anyhow!("--name only applies to a single invite"));
+    }
+
+    let (path, body) = match invites.as_slice() {
+        [] => return Err(anyhow!("nothing to invite")),
+        [single] => (INVITE_PATH, single.clone()),
+        _ => {
+            for invite in &invites {
+                println!(
+                    "  + invite {} as {}",
+                    invite["email"].as_str().unwrap_or_default(),
+                    invite["role"].as_str().unwrap_or_default()
+                );
+            }
+            println!("\nPlan: {} to invite.", invites.len());
+            if !matches.get_flag("auto-approve") {
+                apply::confirm()?;
+            }
+            (BULK_INVITE_PATH, json!(invites))
+        }
+    };
+    let response = ctx.execute(
+        "POST",
+        path,
+        &[],
+        Some(Body::Json(body)),
+        Some("application/json"),
+    )?;
+    if response.status >= 400 {
+        return Err(ApiError::from_response(&response)).context("invite failed");
+    }
+    output::print_value(
+        &json!({
+            "invited": invites.iter().map(|i| &i["email"]).collect::<Vec<_>>(),
+            "response": response.body.get("data").unwrap_or(&response.body),
+        }),
+        opts,
+    )
+}
+
+fn handle_deactivate(
+    matches: &clap::ArgMatches,
+    ctx: &ApiContext,
+    opts: &OutputOptions,
+) -> Result<()> {
+    let response = ctx.execute("GET", USERS_PATH, &[], None, None)?;
+    if response.status >= 400 {
+        return Err(ApiError::from_response(&response)).context("list users failed");
+    }
+    let users = response
+        .body
+        .get("data")
+        .and_then(Value::as_array)
+        .cloned()
+        .unwrap_or_default();
+    let text = |user: &Value, key: &str| {
+        user.get(key)
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string()
+    };
+    let mut targets = Vec::new();
+    for wanted in matches.get_many::<String>("user").into_iter().flatten() {
+        let user = users
+            .iter()
+            .find(|u| text(u, "id") == *wanted || text(u, "email").eq_ignore_ascii_case(wanted))
+            .ok_or_else(|| anyhow!("no user with id or email {wanted}"))?;
+        targets.push(user);
+    }
+
+    for user in &targets {
+        println!(
+            "  - deactivate {} ({}, {})",
+            text(user, "email"),
+            text(user, "role"),
+            text(user, "id")
+        );
+    }
+    println!("\nPlan: {} to deactivate.", targets.len());
+    if !matches.get_flag("auto-approve") {
+        apply::confirm()?;
+    }
+    let mut done = Vec::new();
+    for user in targets {
+        let id = text(user, "id");
+        let path = format!("{USERS_PATH}/{}", urlencoding::encode(&id));
+        let response = ctx.execute("DELETE", &path, &[], None, None)?;
+        if response.status >= 400 {
+            output::print_value(&json!({ "deactivated": done }), opts)?;
+            return Err(ApiError::from_response(&response))
+                .with_context(|| format!("deactivate {} failed", text(user, "email")));
+        }
+        done.push(json!({ "id": id, "email": text(user, "email") }));
+    }
+    output::print_value(&json!({ "deactivated": done }), opts)
+}
+
+fn role(raw: &str) -> Result<&'static str> {
+    let upper = raw.trim().to_uppercase();
+    ROLES
+        .into_iter()
+        .find(|r| *r == upper)
+        .ok_or_else(|| anyhow!("invalid role {raw}; expected admin, editor, or viewer"))
+}
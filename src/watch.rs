@@ -0,0 +1,175 @@
+use anyhow::{anyhow, Result};
+use serde_json::{json, Value};
+use std::{
+    io::{self, IsTerminal, Write},
+    time::Duration,
+};
+
+use crate::error::{self, EXIT_AUTH, EXIT_USAGE};
+use crate::http::HttpResponse;
+use crate::interrupt;
+use crate::output::{self, OutputOptions};
+use crate::query;
+
+#[derive(Clone, Copy, Debug)]
+pub struct WatchOptions {
+    interval: Duration,
+    diff: bool,
+}
+
+/// `--watch SECS` / `--watch-diff`; `--watch-diff` alone polls every 10s.
+pub fn options(matches: &clap::ArgMatches) -> Result<Option<WatchOptions>> {
+    let diff = matches.get_flag("watch-diff");
+    let secs = match matches.get_one::<String>("watch") {
+        Some(raw) => raw
+            .parse::<u64>()
+            .map_err(|_| anyhow!("invalid --watch: {raw}"))?,
+        None if diff => 10,
+        None => return Ok(None),
+    };
+    Ok(Some(WatchOptions {
+        interval: Duration::from_secs(secs.max(1)),
+        diff,
+    }))
+}
+
+/// Re-runs `fetch` until Ctrl-C. Without `--watch-diff` each result replaces
+/// the screen (or is appended when stdout is not a terminal); with it, the
+/// first result is printed in full and later ones only as structural
+/// changes. Request errors are reported and polling continues.
+pub fn run<F>(watch: &WatchOptions, label: &str, opts: &OutputOptions, mut fetch: F) -> Result<()>
+where
+    F: FnMut() -> Result<HttpResponse>,
+{
+    interrupt::graceful();
+    let clear = !watch.diff && io::stdout().is_terminal();
+    let mut previous: Option<Value> = None;
+    loop {
+        match fetch().and_then(|response| output::response_value(&response, opts)) {
+            Ok(value) => {
+                match (&previous, watch.diff) {
+                    (Some(prev), true) => {
+                        let mut changes = Vec::new();
+                        diff(prev, &value, &mut String::new(), &mut changes);
+                        if !changes.is_empty() {
+                            output::print_value(
+                                &json!({ "at": query::now_millis(), "changes": changes }),
+                                opts,
+                            )?;
+                        }
+                    }
+                    _ => {
+                        if clear {
+                            print!("\x1b[2J\x1b[H");
+                            println!(
+                                "Every {}s: signoz {label}    {}\n",
+                                watch.interval.as_secs(),
+                                clock()
+                            );
+                        }
+                        output::print_value(&value, opts)?;
+                    }
+                }
+                let _ = io::stdout().flush();
+                previous = Some(value);
+            }
+            Err(err) => eprintln!("warning: {err:#}"),
+        }
+        if !interrupt::sleep(watch.interval) {
+            return Err(interrupt::Interrupted { checkpoint: None }.into());
+        }
+    }
+}
+
+/// Failed polls in a row for loops that run until Ctrl-C (`alerts watch`,
+/// `logs tail --follow`): a transient failure is reported and waited out
+/// with a growing delay, while an auth or usage error, or too many
+/// failures in a row, ends the loop.
+pub struct PollFailures {
+    interval: Duration,
+    count: u32,
+}
+
+impl PollFailures {
+    const LIMIT: u32 = 10;
+    const MAX_DELAY: Duration = Duration::from_secs(300);
+
+    pub fn new(interval: Duration) -> Self {
+        Self { interval, count: 0 }
+    }
+
+    pub fn succeeded(&mut self) {
+        self.count = 0;
+    }
+
+    /// How long to wait before polling again, or the error to stop with.
+    pub fn failed(&mut self, err: anyhow::Error) -> Result<Duration> {
+        if matches!(error::exit_code(&err), EXIT_AUTH | EXIT_USAGE) {
+            return Err(err);
+        }
+        self.count += 1;
+        if self.count >= Self::LIMIT {
+            return Err(err.context(format!("{} polls in a row failed", self.count)));
+        }
+        let delay = self
+            .interval
+            .saturating_mul(1 << (self.count - 1).min(8))
+            .min(Self::MAX_DELAY.max(self.interval));
+        eprintln!(
+            "warning: {err:#}; retrying in {:.1}s ({}/{})",
+            delay.as_secs_f64(),
+            self.count,
+            Self::LIMIT
+        );
+        Ok(delay)
+    }
+}
+
+/// Collects `{path, from, to}` entries (JSON pointer paths) for every leaf
+/// that was added, removed, or changed between `old` and `new`.
+pub fn diff(old: &Value, new: &Value, path: &mut String, out: &mut Vec<Value>) {
+    match (old, new) {
+        (Value::Object(a), Value::Object(b)) => {
+            for (key, before) in a {
+                let len = path.len();
+                path.push('/');
+                path.push_str(&key.replace('~', "~0").replace('/', "~1"));
+                match b.get(key) {
+                    Some(after) => diff(before, after, path, out),
+                    None => out.push(json!({ "path": path, "from": before })),
+                }
+                path.truncate(len);
+            }
+            for (key, after) in b.iter().filter(|(k, _)| !a.contains_key(*k)) {
+                let key = key.replace('~', "~0").replace('/', "~1");
+                out.push(json!({ "path": format!("{path}/{key}"), "to": after }));
+            }
+        }
+        (Value::Array(a), Value::Array(b)) => {
+            for i in 0..a.len().max(b.len()) {
+                let len = path.len();
+                path.push_str(&format!("/{i}"));
+                match (a.get(i), b.get(i)) {
+                    (Some(before), Some(after)) => diff(before, after, path, out),
+                    (Some(before), None) => out.push(json!({ "path": path, "from": before })),
+                    (None, Some(after)) => out.push(json!({ "path": path, "to": after })),
+                    (None, None) => {}
+                }
+                path.truncate(len);
+            }
+        }
+        _ if old != new => out.push(json!({ "path": path, "from": old, "to": new })),
+        _ => {}
+    }
+}
+
+/// Wall-clock `HH:MM:SS UTC` for the watch header.
+fn clock() -> String {
+    let secs = query::now_millis() / 1000 % 86_400;
+    format!(
+        "{:02}:{:02}:{:02} UTC",
+        secs / 3600,
+        secs / 60 % 60,
+        secs % 60
+    )
+}
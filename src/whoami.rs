@@ -0,0 +1,118 @@
+use anyhow::{Context, Result};
+use clap::Command;
+use serde_json::{json, Value};
+
+use crate::auth::AuthMode;
+use crate::error::{ApiError, Failure};
+use crate::output::{self, OutputOptions};
+use crate::ApiContext;
+
+const ME_PATH: &str = "/api/v1/user/me";
+const ORG_PATH: &str = "/api/v2/orgs/me";
+/// Lists API keys with their tokens; needs the ADMIN role.
+const KEYS_PATH: &str = "/api/v1/pats";
+
+pub fn command() -> Command {
+    Command::new("whoami")
+        .about("Show the user, org, and API key the current credentials resolve to, and which auth mode was used")
+}
+
+/// Mirrors `ApiContext::execute_with`, but records which credential
+/// actually got through so auto mode's api-key-then-token fallback is
+/// visible.
+pub fn handle(ctx: &ApiContext, opts: &OutputOptions) -> Result<()> {
+    let mut attempts = Vec::new();
+    if ctx.auth_mode != AuthMode::Token {
+        if let Some(key) = &ctx.api_key {
+            attempts.push(("api-key", ctx.client(Some(key), None)?));
+        }
+    }
+    if ctx.auth_mode != AuthMode::ApiKey {
+        if let Some(token) = &ctx.token {
+            attempts.push(("token", ctx.client(None, Some(token))?));
+        }
+    }
+    if attempts.is_empty() {
+        return Err(Failure::Auth(format!(
+            "no credentials for {}; set --api-key/SIGNOZ_API_KEY or run `signoz login`",
+            ctx.base_url
+        ))
+        .into());
+    }
+
+    let mut rejected = Vec::new();
+    for (used, client) in attempts {
+        let response = client.execute("GET", ME_PATH, &[], None, None)?;
+        if matches!(response.status, 401 | 403) {
+            rejected.push(format!("{used}: http {}", response.status));
+            continue;
+        }
+        if response.status >= 400 {
+            return Err(ApiError::from_response(&response)).context("get current user failed");
+        }
+        let user = data(response.body);
+        let org = client
+            .execute("GET", ORG_PATH, &[], None, None)
+            .ok()
+            .filter(|r| r.status < 400)
+            .map_or(Value::Null, |r| data(r.body));
+
+        let mut out = json!({
+            "base_url": ctx.base_url,
+            "auth": used,
+            "rejected": rejected,
+            "user": pick(&user, &["id", "email", "displayName", "role"]),
+            "org": pick(&org, &["id", "displayName", "name"]),
+        });
+        if out["org"].is_null() {
+            out["org"] = json!({ "id": user.get("orgId") });
+        }
+        if used == "api-key" {
+            out["api_key"] = api_key_details(&client, ctx.api_key.as_deref().unwrap_or_default());
+        }
+        return output::print_value(&out, opts);
+    }
+    Err(Failure::Auth(format!(
+        "credentials rejected by {} ({})",
+        ctx.base_url,
+        rejected.join(", ")
+    ))
+    .into())
+}
+
+/// Name, role, and expiry of the key in use, found by matching its token in
+/// the key list. Non-admin keys cannot list keys, so this is best effort.
+fn api_key_details(client: &crate::http::HttpClient, key: &str) -> Value {
+    let response = match client.execute("GET", KEYS_PATH, &[], None, None) {
+        Ok(response) if response.status < 400 => response,
+        Ok(response) => {
+            return json!({ "error": format!("listing keys needs ADMIN: http {}", response.status) })
+        }
+        Err(err) => return json!({ "error": format!("{err:#}") }),
+    };
+    data(response.body)
+        .as_array()
+        .into_iter()
+        .flatten()
+        .find(|k| k.get("token").and_then(Value::as_str) == Some(key))
+        .map_or(json!({ "error": "key not found in the key list" }), |k| {
+            pick(k, &["id", "name", "role", "expiresAt", "lastUsed"])
+        })
+}
+
+fn data(mut body: Value) -> Value {
+    match body.get_mut("data") {
+        Some(data) => data.take(),
+        None => body,
+    }
+}
+
+fn pick(value: &Value, keys: &[&str]) -> Value {
+    if !value.is_object() {
+        return Value::Null;
+    }
+    keys.iter()
+        .filter_map(|k| Some((k.to_string(), value.get(*k)?.clone())))
+        .collect::<serde_json::Map<_, _>>()
+        .into()
+}